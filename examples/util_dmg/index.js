@@ -1,11 +1,13 @@
-var {parseEvent} = require('@laihoe/demoparser2');
+var {parseUtilityEffects} = require('@laihoe/demoparser2');
 
 const filePath = "path/to/demo.dem"
 
 
-let events = parseEvent(filePath, "player_hurt")
-let heDmg = events.filter(e => e.weapon == "hegrenade")
-let molotovDmg = events.filter(e => e.weapon == "molotov" || e.weapon == "inferno")
+let effects = parseUtilityEffects(filePath)
+let heDmg = effects.filter(e => e.grenade_type == "he_grenade")
+let molotovDmg = effects.filter(e => e.grenade_type == "molotov" || e.grenade_type == "incendiary_grenade")
+let flashes = effects.filter(e => e.grenade_type == "flashbang")
 
 console.log(heDmg)
-console.log(molotovDmg)
\ No newline at end of file
+console.log(molotovDmg)
+console.log(flashes)
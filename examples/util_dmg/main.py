@@ -1,12 +1,13 @@
 from demoparser2 import DemoParser
-import glob
 
 
 parser = DemoParser("path_to_demo.dem")
-df = parser.parse_event("player_hurt")
+df = parser.parse_utility_effects()
 
-he_dmg = df[df["weapon"] == "hegrenade"]
-molotov_dmg = df[(df["weapon"] == "molotov") | (df["weapon"] == "inferno")]
+he_dmg = df[df["grenade_type"] == "he_grenade"]
+molotov_dmg = df[df["grenade_type"].isin(["molotov", "incendiary_grenade"])]
+flashes = df[df["grenade_type"] == "flashbang"]
 
-print(he_dmg)
-print(molotov_dmg)
+print(he_dmg[["thrower_name", "enemy_damage", "team_damage"]])
+print(molotov_dmg[["thrower_name", "enemy_damage", "team_damage"]])
+print(flashes[["thrower_name", "enemies_flashed", "teammates_flashed", "kills_on_flashed"]])
@@ -6,10 +6,24 @@
 export function parseVoice(pathOrBuf: string | Buffer): Record<string, Array<number>>
 export function listGameEvents(pathOrBuf: string | Buffer): any
 export function parseGrenades(pathOrBuf: string | Buffer): any
+export function parseMoneyLedger(pathOrBuf: string | Buffer): any
+export function parseGrenadeLifecycles(pathOrBuf: string | Buffer, trajectoryStep?: number | undefined | null): any
+export function parseUtilityEffects(pathOrBuf: string | Buffer): any
+export function parseInfernos(pathOrBuf: string | Buffer): any
+export function parseBomb(pathOrBuf: string | Buffer): any
+export function parseVisibility(pathOrBuf: string | Buffer, geometryPath: string, pairs?: Array<Array<string>> | undefined | null, wantedTicks?: Array<number> | undefined | null): any
+export function parseSpotting(pathOrBuf: string | Buffer): any
+export function parseFov(pathOrBuf: string | Buffer, wantedTicks?: Array<number> | undefined | null, fov?: number | undefined | null): any
+export function parseShots(pathOrBuf: string | Buffer): any
+export function parseSprays(pathOrBuf: string | Buffer): any
+export function parseUsercmds(pathOrBuf: string | Buffer): any
+export function parseMovement(pathOrBuf: string | Buffer): any
+export function parseEntities(pathOrBuf: string | Buffer, classNames: Array<string>, props: Array<string>, wantedTicks?: Array<number> | undefined | null): any
+export function parseWeapons(pathOrBuf: string | Buffer, wantedTicks?: Array<number> | undefined | null): any
 export function parseHeader(pathOrBuf: string | Buffer): any
-export function parseEvent(pathOrBuf: string | Buffer, eventName: string, playerExtra?: Array<string> | undefined | null, otherExtra?: Array<string> | undefined | null): any
-export function parseEvents(pathOrBuf: string | Buffer, eventNames?: Array<string> | undefined | null, playerExtra?: Array<string> | undefined | null, otherExtra?: Array<string> | undefined | null): any
-export function parseTicks(pathOrBuf: string | Buffer, wantedProps: Array<string>, wantedTicks?: Array<number> | undefined | null, wantedPlayers?: Array<string> | undefined | null, structOfArrays?: boolean | undefined | null, orderBySteamid?: boolean | undefined | null, propStates?: Array<WantedPropState> | undefined | null): any
+export function parseEvent(pathOrBuf: string | Buffer, eventName: string, playerExtra?: Array<string> | undefined | null, otherExtra?: Array<string> | undefined | null, navPath?: string | undefined | null, killContext?: boolean | undefined | null, entityClasses?: Array<string> | undefined | null, propChanges?: Array<string> | undefined | null): any
+export function parseEvents(pathOrBuf: string | Buffer, eventNames?: Array<string> | undefined | null, playerExtra?: Array<string> | undefined | null, otherExtra?: Array<string> | undefined | null, navPath?: string | undefined | null, killContext?: boolean | undefined | null, entityClasses?: Array<string> | undefined | null, propChanges?: Array<string> | undefined | null): any
+export function parseTicks(pathOrBuf: string | Buffer, wantedProps: Array<string>, wantedTicks?: Array<number> | undefined | null, wantedPlayers?: Array<string> | undefined | null, structOfArrays?: boolean | undefined | null, orderBySteamid?: boolean | undefined | null, propStates?: string | Array<WantedPropState> | undefined | null, overviewPath?: string | undefined | null, navPath?: string | undefined | null): any
 export function parsePlayerInfo(pathOrBuf: string | Buffer): any
 export function parsePlayerSkins(pathOrBuf: string | Buffer): any
 export declare class JsVariant { }
@@ -310,13 +310,27 @@ if (!nativeBinding) {
   throw new Error(`Failed to load native binding`)
 }
 
-const { JsVariant, WantedPropState, parseVoice, listGameEvents, parseGrenades, parseHeader, parseEvent, parseEvents, parseTicks, parsePlayerInfo, parsePlayerSkins } = nativeBinding
+const { JsVariant, WantedPropState, parseVoice, listGameEvents, parseGrenades, parseMoneyLedger, parseGrenadeLifecycles, parseUtilityEffects, parseInfernos, parseBomb, parseVisibility, parseSpotting, parseFov, parseShots, parseSprays, parseUsercmds, parseMovement, parseEntities, parseWeapons, parseHeader, parseEvent, parseEvents, parseTicks, parsePlayerInfo, parsePlayerSkins } = nativeBinding
 
 module.exports.JsVariant = JsVariant
 module.exports.WantedPropState = WantedPropState
 module.exports.parseVoice = parseVoice
 module.exports.listGameEvents = listGameEvents
 module.exports.parseGrenades = parseGrenades
+module.exports.parseMoneyLedger = parseMoneyLedger
+module.exports.parseGrenadeLifecycles = parseGrenadeLifecycles
+module.exports.parseUtilityEffects = parseUtilityEffects
+module.exports.parseInfernos = parseInfernos
+module.exports.parseBomb = parseBomb
+module.exports.parseVisibility = parseVisibility
+module.exports.parseSpotting = parseSpotting
+module.exports.parseFov = parseFov
+module.exports.parseShots = parseShots
+module.exports.parseSprays = parseSprays
+module.exports.parseUsercmds = parseUsercmds
+module.exports.parseMovement = parseMovement
+module.exports.parseEntities = parseEntities
+module.exports.parseWeapons = parseWeapons
 module.exports.parseHeader = parseHeader
 module.exports.parseEvent = parseEvent
 module.exports.parseEvents = parseEvents
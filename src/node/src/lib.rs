@@ -8,11 +8,17 @@ use napi::bindgen_prelude::*;
 use napi::Either;
 use napi::JsBigInt;
 use napi::JsUnknown;
-use parser::first_pass::parser_settings::rm_map_user_friendly_names;
+use parser::first_pass::parser_settings::rm_filter_user_friendly_names;
 use parser::first_pass::parser_settings::rm_user_friendly_names;
+use parser::first_pass::parser_settings::ParallelismSettings;
 use parser::first_pass::parser_settings::ParserInputs;
+use parser::first_pass::prop_filter::PropFilter;
+use parser::maps::geometry::TriangleMesh;
+use parser::maps::nav::NavMesh;
+use parser::maps::overview::MapOverview;
 use parser::parse_demo::DemoOutput;
 use parser::parse_demo::Parser;
+use parser::second_pass::fov::DEFAULT_FOV;
 use parser::second_pass::parser_settings::create_huffman_lookup_table;
 use parser::second_pass::variants::soa_to_aos;
 use parser::second_pass::variants::BytesVariant;
@@ -24,6 +30,7 @@ use std::collections::HashMap;
 use std::fs::File;
 use std::hash::RandomState;
 use std::result::Result;
+use std::sync::Arc;
 
 #[napi]
 #[derive(Clone)]
@@ -148,7 +155,7 @@ pub fn parse_voice(path_or_buf: Either<String, Buffer>) -> napi::Result<HashMap<
     wanted_other_props: vec![],
     wanted_events: vec![],
     wanted_ticks: vec![],
-    wanted_prop_states: AHashMap::default(),
+    wanted_prop_states: PropFilter::default(),
     real_name_to_og_name: AHashMap::default(),
     parse_ents: false,
     parse_projectiles: false,
@@ -157,6 +164,26 @@ pub fn parse_voice(path_or_buf: Either<String, Buffer>) -> napi::Result<HashMap<
     only_convars: false,
     huffman_lookup_table: &vec![],
     order_by_steamid: false,
+    change_log: false,
+    parse_money_ledger: false,
+    parse_bomb: false,
+    parse_spotting: false,
+    parse_kill_context: false,
+    parse_shots: false,
+    parse_sprays: false,
+    parse_usercmds: false,
+    parse_movement: false,
+    parse_weapons: false,
+    wanted_entity_classes: vec![],
+    wanted_entity_props: vec![],
+    entity_event_classes: vec![],
+    wanted_prop_changes: vec![],
+    map_overview: None,
+    nav_mesh: None,
+    map_geometry: None,
+    visibility_pairs: None,
+    fov: None,
+    parallelism: ParallelismSettings::default(),
   };
   let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
   let output = parse_demo(bytes, &mut parser)?;
@@ -181,7 +208,7 @@ pub fn list_game_events(path_or_buf: Either<String, Buffer>) -> napi::Result<Val
     real_name_to_og_name: AHashMap::default(),
     wanted_player_props: vec![],
     wanted_other_props: vec![],
-    wanted_prop_states: AHashMap::default(),
+    wanted_prop_states: PropFilter::default(),
     wanted_events: vec!["all".to_string()],
     parse_ents: false,
     wanted_ticks: vec![],
@@ -191,6 +218,26 @@ pub fn list_game_events(path_or_buf: Either<String, Buffer>) -> napi::Result<Val
     only_convars: false,
     huffman_lookup_table: &huf,
     order_by_steamid: false,
+    change_log: false,
+    parse_money_ledger: false,
+    parse_bomb: false,
+    parse_spotting: false,
+    parse_kill_context: false,
+    parse_shots: false,
+    parse_sprays: false,
+    parse_usercmds: false,
+    parse_movement: false,
+    parse_weapons: false,
+    wanted_entity_classes: vec![],
+    wanted_entity_props: vec![],
+    entity_event_classes: vec![],
+    wanted_prop_changes: vec![],
+    map_overview: None,
+    nav_mesh: None,
+    map_geometry: None,
+    visibility_pairs: None,
+    fov: None,
+    parallelism: ParallelismSettings::default(),
   };
   let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
   let output = parse_demo(bytes, &mut parser)?;
@@ -214,7 +261,7 @@ pub fn parse_grenades(path_or_buf: Either<String, Buffer>) -> napi::Result<Value
     wanted_player_props: vec![],
     wanted_other_props: vec![],
     wanted_events: vec![],
-    wanted_prop_states: AHashMap::default(),
+    wanted_prop_states: PropFilter::default(),
     parse_ents: true,
     wanted_ticks: vec![],
     parse_projectiles: true,
@@ -223,6 +270,26 @@ pub fn parse_grenades(path_or_buf: Either<String, Buffer>) -> napi::Result<Value
     only_convars: false,
     huffman_lookup_table: &huf,
     order_by_steamid: false,
+    change_log: false,
+    parse_money_ledger: false,
+    parse_bomb: false,
+    parse_spotting: false,
+    parse_kill_context: false,
+    parse_shots: false,
+    parse_sprays: false,
+    parse_usercmds: false,
+    parse_movement: false,
+    parse_weapons: false,
+    wanted_entity_classes: vec![],
+    wanted_entity_props: vec![],
+    entity_event_classes: vec![],
+    wanted_prop_changes: vec![],
+    map_overview: None,
+    nav_mesh: None,
+    map_geometry: None,
+    visibility_pairs: None,
+    fov: None,
+    parallelism: ParallelismSettings::default(),
   };
   let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
   let output = parse_demo(bytes, &mut parser)?;
@@ -234,6 +301,747 @@ pub fn parse_grenades(path_or_buf: Either<String, Buffer>) -> napi::Result<Value
   Ok(s)
 }
 #[napi]
+pub fn parse_money_ledger(path_or_buf: Either<String, Buffer>) -> napi::Result<Value> {
+  let bytes = resolve_byte_type(path_or_buf)?;
+  let huf = create_huffman_lookup_table();
+
+  let settings = ParserInputs {
+    wanted_players: vec![],
+    real_name_to_og_name: AHashMap::default(),
+    wanted_player_props: vec![],
+    wanted_other_props: vec![],
+    wanted_events: vec![],
+    wanted_prop_states: PropFilter::default(),
+    parse_ents: true,
+    wanted_ticks: vec![],
+    parse_projectiles: false,
+    only_header: true,
+    count_props: false,
+    only_convars: false,
+    huffman_lookup_table: &huf,
+    order_by_steamid: false,
+    change_log: false,
+    parse_money_ledger: true,
+    parse_bomb: false,
+    parse_spotting: false,
+    parse_kill_context: false,
+    parse_shots: false,
+    parse_sprays: false,
+    parse_usercmds: false,
+    parse_movement: false,
+    parse_weapons: false,
+    wanted_entity_classes: vec![],
+    wanted_entity_props: vec![],
+    entity_event_classes: vec![],
+    wanted_prop_changes: vec![],
+    map_overview: None,
+    nav_mesh: None,
+    map_geometry: None,
+    visibility_pairs: None,
+    fov: None,
+    parallelism: ParallelismSettings::default(),
+  };
+  let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
+  let output = parse_demo(bytes, &mut parser)?;
+
+  let s = match serde_json::to_value(&output.money_ledger) {
+    Ok(s) => s,
+    Err(e) => return Err(Error::new(Status::InvalidArg, format!("{}", e).to_owned())),
+  };
+  Ok(s)
+}
+#[napi]
+pub fn parse_grenade_lifecycles(path_or_buf: Either<String, Buffer>, trajectory_step: Option<u32>) -> napi::Result<Value> {
+  let bytes = resolve_byte_type(path_or_buf)?;
+  let huf = create_huffman_lookup_table();
+
+  let settings = ParserInputs {
+    wanted_players: vec![],
+    real_name_to_og_name: AHashMap::default(),
+    wanted_player_props: vec![],
+    wanted_other_props: vec![],
+    wanted_events: vec![],
+    wanted_prop_states: PropFilter::default(),
+    parse_ents: true,
+    wanted_ticks: vec![],
+    parse_projectiles: true,
+    only_header: true,
+    count_props: false,
+    only_convars: false,
+    huffman_lookup_table: &huf,
+    order_by_steamid: false,
+    change_log: false,
+    parse_money_ledger: false,
+    parse_bomb: false,
+    parse_spotting: false,
+    parse_kill_context: false,
+    parse_shots: false,
+    parse_sprays: false,
+    parse_usercmds: false,
+    parse_movement: false,
+    parse_weapons: false,
+    wanted_entity_classes: vec![],
+    wanted_entity_props: vec![],
+    entity_event_classes: vec![],
+    wanted_prop_changes: vec![],
+    map_overview: None,
+    nav_mesh: None,
+    map_geometry: None,
+    visibility_pairs: None,
+    fov: None,
+    parallelism: ParallelismSettings::default(),
+  };
+  let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
+  let mut output = parse_demo(bytes, &mut parser)?;
+
+  if let Some(step) = trajectory_step {
+    for grenade in output.grenade_lifecycles.iter_mut() {
+      grenade.trajectory = grenade.downsampled_trajectory(step as usize);
+    }
+  }
+  let s = match serde_json::to_value(&output.grenade_lifecycles) {
+    Ok(s) => s,
+    Err(e) => return Err(Error::new(Status::InvalidArg, format!("{}", e).to_owned())),
+  };
+  Ok(s)
+}
+#[napi]
+pub fn parse_utility_effects(path_or_buf: Either<String, Buffer>) -> napi::Result<Value> {
+  let bytes = resolve_byte_type(path_or_buf)?;
+  let huf = create_huffman_lookup_table();
+
+  let settings = ParserInputs {
+    wanted_players: vec![],
+    real_name_to_og_name: AHashMap::default(),
+    wanted_player_props: vec![],
+    wanted_other_props: vec![],
+    wanted_events: vec![],
+    wanted_prop_states: PropFilter::default(),
+    parse_ents: true,
+    wanted_ticks: vec![],
+    parse_projectiles: true,
+    only_header: true,
+    count_props: false,
+    only_convars: false,
+    huffman_lookup_table: &huf,
+    order_by_steamid: false,
+    change_log: false,
+    parse_money_ledger: false,
+    parse_bomb: false,
+    parse_spotting: false,
+    parse_kill_context: false,
+    parse_shots: false,
+    parse_sprays: false,
+    parse_usercmds: false,
+    parse_movement: false,
+    parse_weapons: false,
+    wanted_entity_classes: vec![],
+    wanted_entity_props: vec![],
+    entity_event_classes: vec![],
+    wanted_prop_changes: vec![],
+    map_overview: None,
+    nav_mesh: None,
+    map_geometry: None,
+    visibility_pairs: None,
+    fov: None,
+    parallelism: ParallelismSettings::default(),
+  };
+  let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
+  let output = parse_demo(bytes, &mut parser)?;
+
+  let s = match serde_json::to_value(&output.utility_effects) {
+    Ok(s) => s,
+    Err(e) => return Err(Error::new(Status::InvalidArg, format!("{}", e).to_owned())),
+  };
+  Ok(s)
+}
+#[napi]
+pub fn parse_infernos(path_or_buf: Either<String, Buffer>) -> napi::Result<Value> {
+  let bytes = resolve_byte_type(path_or_buf)?;
+  let huf = create_huffman_lookup_table();
+
+  let settings = ParserInputs {
+    wanted_players: vec![],
+    real_name_to_og_name: AHashMap::default(),
+    wanted_player_props: vec![],
+    wanted_other_props: vec![],
+    wanted_events: vec![],
+    wanted_prop_states: PropFilter::default(),
+    parse_ents: true,
+    wanted_ticks: vec![],
+    parse_projectiles: true,
+    only_header: true,
+    count_props: false,
+    only_convars: false,
+    huffman_lookup_table: &huf,
+    order_by_steamid: false,
+    change_log: false,
+    parse_money_ledger: false,
+    parse_bomb: false,
+    parse_spotting: false,
+    parse_kill_context: false,
+    parse_shots: false,
+    parse_sprays: false,
+    parse_usercmds: false,
+    parse_movement: false,
+    parse_weapons: false,
+    wanted_entity_classes: vec![],
+    wanted_entity_props: vec![],
+    entity_event_classes: vec![],
+    wanted_prop_changes: vec![],
+    map_overview: None,
+    nav_mesh: None,
+    map_geometry: None,
+    visibility_pairs: None,
+    fov: None,
+    parallelism: ParallelismSettings::default(),
+  };
+  let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
+  let output = parse_demo(bytes, &mut parser)?;
+
+  let s = match serde_json::to_value(&output.inferno_fires) {
+    Ok(s) => s,
+    Err(e) => return Err(Error::new(Status::InvalidArg, format!("{}", e).to_owned())),
+  };
+  Ok(s)
+}
+#[napi]
+pub fn parse_bomb(path_or_buf: Either<String, Buffer>) -> napi::Result<Value> {
+  let bytes = resolve_byte_type(path_or_buf)?;
+  let huf = create_huffman_lookup_table();
+
+  let settings = ParserInputs {
+    wanted_players: vec![],
+    real_name_to_og_name: AHashMap::default(),
+    wanted_player_props: vec![],
+    wanted_other_props: vec![],
+    wanted_events: vec![],
+    wanted_prop_states: PropFilter::default(),
+    parse_ents: true,
+    wanted_ticks: vec![],
+    parse_projectiles: false,
+    only_header: true,
+    count_props: false,
+    only_convars: false,
+    huffman_lookup_table: &huf,
+    order_by_steamid: false,
+    change_log: false,
+    parse_money_ledger: false,
+    parse_bomb: true,
+    parse_spotting: false,
+    parse_kill_context: false,
+    parse_shots: false,
+    parse_sprays: false,
+    parse_usercmds: false,
+    parse_movement: false,
+    parse_weapons: false,
+    wanted_entity_classes: vec![],
+    wanted_entity_props: vec![],
+    entity_event_classes: vec![],
+    wanted_prop_changes: vec![],
+    map_overview: None,
+    nav_mesh: None,
+    map_geometry: None,
+    visibility_pairs: None,
+    fov: None,
+    parallelism: ParallelismSettings::default(),
+  };
+  let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
+  let output = parse_demo(bytes, &mut parser)?;
+
+  let s = match serde_json::to_value(&output.bomb_timelines) {
+    Ok(s) => s,
+    Err(e) => return Err(Error::new(Status::InvalidArg, format!("{}", e).to_owned())),
+  };
+  Ok(s)
+}
+#[napi]
+pub fn parse_visibility(
+  path_or_buf: Either<String, Buffer>,
+  geometry_path: String,
+  pairs: Option<Vec<Vec<String>>>,
+  wanted_ticks: Option<Vec<i32>>,
+) -> napi::Result<Value> {
+  let geometry = match TriangleMesh::from_file(&geometry_path) {
+    Ok(geometry) => geometry,
+    Err(e) => return Err(Error::new(Status::InvalidArg, format!("{}", e).to_owned())),
+  };
+  let mut visibility_pairs = vec![];
+  for pair in pairs.unwrap_or_default() {
+    match pair.as_slice() {
+      [observer, target] => visibility_pairs.push((observer.parse::<u64>().unwrap_or(0), target.parse::<u64>().unwrap_or(0))),
+      _ => return Err(Error::new(Status::InvalidArg, "pairs must be [observerSteamid, targetSteamid]")),
+    }
+  }
+  let bytes = resolve_byte_type(path_or_buf)?;
+  let huf = create_huffman_lookup_table();
+
+  let settings = ParserInputs {
+    wanted_players: vec![],
+    real_name_to_og_name: AHashMap::default(),
+    wanted_player_props: vec![],
+    wanted_other_props: vec![],
+    wanted_events: vec![],
+    wanted_prop_states: PropFilter::default(),
+    parse_ents: true,
+    wanted_ticks: wanted_ticks.unwrap_or_default(),
+    parse_projectiles: false,
+    only_header: true,
+    count_props: false,
+    only_convars: false,
+    huffman_lookup_table: &huf,
+    order_by_steamid: false,
+    change_log: false,
+    parse_money_ledger: false,
+    parse_bomb: false,
+    parse_spotting: false,
+    parse_kill_context: false,
+    parse_shots: false,
+    parse_sprays: false,
+    parse_usercmds: false,
+    parse_movement: false,
+    parse_weapons: false,
+    wanted_entity_classes: vec![],
+    wanted_entity_props: vec![],
+    entity_event_classes: vec![],
+    wanted_prop_changes: vec![],
+    map_overview: None,
+    nav_mesh: None,
+    map_geometry: Some(Arc::new(geometry)),
+    visibility_pairs: Some(visibility_pairs),
+    fov: None,
+    parallelism: ParallelismSettings::default(),
+  };
+  let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
+  let output = parse_demo(bytes, &mut parser)?;
+
+  let s = match serde_json::to_value(&output.visibility) {
+    Ok(s) => s,
+    Err(e) => return Err(Error::new(Status::InvalidArg, format!("{}", e).to_owned())),
+  };
+  Ok(s)
+}
+#[napi]
+pub fn parse_spotting(path_or_buf: Either<String, Buffer>) -> napi::Result<Value> {
+  let bytes = resolve_byte_type(path_or_buf)?;
+  let huf = create_huffman_lookup_table();
+
+  let settings = ParserInputs {
+    wanted_players: vec![],
+    real_name_to_og_name: AHashMap::default(),
+    wanted_player_props: vec![],
+    wanted_other_props: vec![],
+    wanted_events: vec![],
+    wanted_prop_states: PropFilter::default(),
+    parse_ents: true,
+    wanted_ticks: vec![],
+    parse_projectiles: false,
+    only_header: true,
+    count_props: false,
+    only_convars: false,
+    huffman_lookup_table: &huf,
+    order_by_steamid: false,
+    change_log: false,
+    parse_money_ledger: false,
+    parse_bomb: false,
+    parse_spotting: true,
+    parse_kill_context: false,
+    parse_shots: false,
+    parse_sprays: false,
+    parse_usercmds: false,
+    parse_movement: false,
+    parse_weapons: false,
+    wanted_entity_classes: vec![],
+    wanted_entity_props: vec![],
+    entity_event_classes: vec![],
+    wanted_prop_changes: vec![],
+    map_overview: None,
+    nav_mesh: None,
+    map_geometry: None,
+    visibility_pairs: None,
+    fov: None,
+    parallelism: ParallelismSettings::default(),
+  };
+  let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
+  let output = parse_demo(bytes, &mut parser)?;
+
+  let s = match serde_json::to_value(&serde_json::json!({
+    "edges": output.spotting_edges,
+    "rounds": output.spotting_rounds,
+  })) {
+    Ok(s) => s,
+    Err(e) => return Err(Error::new(Status::InvalidArg, format!("{}", e).to_owned())),
+  };
+  Ok(s)
+}
+#[napi]
+pub fn parse_fov(path_or_buf: Either<String, Buffer>, wanted_ticks: Option<Vec<i32>>, fov: Option<f64>) -> napi::Result<Value> {
+  let bytes = resolve_byte_type(path_or_buf)?;
+  let huf = create_huffman_lookup_table();
+
+  let settings = ParserInputs {
+    wanted_players: vec![],
+    real_name_to_og_name: AHashMap::default(),
+    wanted_player_props: vec![],
+    wanted_other_props: vec![],
+    wanted_events: vec![],
+    wanted_prop_states: PropFilter::default(),
+    parse_ents: true,
+    wanted_ticks: wanted_ticks.unwrap_or_default(),
+    parse_projectiles: false,
+    only_header: true,
+    count_props: false,
+    only_convars: false,
+    huffman_lookup_table: &huf,
+    order_by_steamid: false,
+    change_log: false,
+    parse_money_ledger: false,
+    parse_bomb: false,
+    parse_spotting: false,
+    parse_kill_context: false,
+    parse_shots: false,
+    parse_sprays: false,
+    parse_usercmds: false,
+    parse_movement: false,
+    parse_weapons: false,
+    wanted_entity_classes: vec![],
+    wanted_entity_props: vec![],
+    entity_event_classes: vec![],
+    wanted_prop_changes: vec![],
+    map_overview: None,
+    nav_mesh: None,
+    map_geometry: None,
+    visibility_pairs: None,
+    fov: Some(fov.map(|f| f as f32).unwrap_or(DEFAULT_FOV)),
+    parallelism: ParallelismSettings::default(),
+  };
+  let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
+  let output = parse_demo(bytes, &mut parser)?;
+
+  let s = match serde_json::to_value(&serde_json::json!({
+    "fov": output.fov,
+    "crosshair_placement": output.crosshair_placement,
+  })) {
+    Ok(s) => s,
+    Err(e) => return Err(Error::new(Status::InvalidArg, format!("{}", e).to_owned())),
+  };
+  Ok(s)
+}
+#[napi]
+pub fn parse_shots(path_or_buf: Either<String, Buffer>) -> napi::Result<Value> {
+  let bytes = resolve_byte_type(path_or_buf)?;
+  let huf = create_huffman_lookup_table();
+
+  let settings = ParserInputs {
+    wanted_players: vec![],
+    real_name_to_og_name: AHashMap::default(),
+    wanted_player_props: vec![],
+    wanted_other_props: vec![],
+    wanted_events: vec![],
+    wanted_prop_states: PropFilter::default(),
+    parse_ents: true,
+    wanted_ticks: vec![],
+    parse_projectiles: false,
+    only_header: true,
+    count_props: false,
+    only_convars: false,
+    huffman_lookup_table: &huf,
+    order_by_steamid: false,
+    change_log: false,
+    parse_money_ledger: false,
+    parse_bomb: false,
+    parse_spotting: false,
+    parse_kill_context: false,
+    parse_shots: true,
+    parse_sprays: false,
+    parse_usercmds: false,
+    parse_movement: false,
+    parse_weapons: false,
+    wanted_entity_classes: vec![],
+    wanted_entity_props: vec![],
+    entity_event_classes: vec![],
+    wanted_prop_changes: vec![],
+    map_overview: None,
+    nav_mesh: None,
+    map_geometry: None,
+    visibility_pairs: None,
+    fov: None,
+    parallelism: ParallelismSettings::default(),
+  };
+  let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
+  let output = parse_demo(bytes, &mut parser)?;
+
+  let s = match serde_json::to_value(&serde_json::json!({
+    "shots": output.shots,
+    "accuracy": output.accuracy,
+  })) {
+    Ok(s) => s,
+    Err(e) => return Err(Error::new(Status::InvalidArg, format!("{}", e).to_owned())),
+  };
+  Ok(s)
+}
+#[napi]
+pub fn parse_sprays(path_or_buf: Either<String, Buffer>) -> napi::Result<Value> {
+  let bytes = resolve_byte_type(path_or_buf)?;
+  let huf = create_huffman_lookup_table();
+
+  let settings = ParserInputs {
+    wanted_players: vec![],
+    real_name_to_og_name: AHashMap::default(),
+    wanted_player_props: vec![],
+    wanted_other_props: vec![],
+    wanted_events: vec![],
+    wanted_prop_states: PropFilter::default(),
+    parse_ents: true,
+    wanted_ticks: vec![],
+    parse_projectiles: false,
+    only_header: true,
+    count_props: false,
+    only_convars: false,
+    huffman_lookup_table: &huf,
+    order_by_steamid: false,
+    change_log: false,
+    parse_money_ledger: false,
+    parse_bomb: false,
+    parse_spotting: false,
+    parse_kill_context: false,
+    parse_shots: false,
+    parse_sprays: true,
+    parse_usercmds: false,
+    parse_movement: false,
+    parse_weapons: false,
+    wanted_entity_classes: vec![],
+    wanted_entity_props: vec![],
+    entity_event_classes: vec![],
+    wanted_prop_changes: vec![],
+    map_overview: None,
+    nav_mesh: None,
+    map_geometry: None,
+    visibility_pairs: None,
+    fov: None,
+    parallelism: ParallelismSettings::default(),
+  };
+  let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
+  let output = parse_demo(bytes, &mut parser)?;
+
+  let s = match serde_json::to_value(&serde_json::json!({
+    "sprays": output.sprays,
+    "bullets": output.spray_bullets,
+  })) {
+    Ok(s) => s,
+    Err(e) => return Err(Error::new(Status::InvalidArg, format!("{}", e).to_owned())),
+  };
+  Ok(s)
+}
+#[napi]
+pub fn parse_usercmds(path_or_buf: Either<String, Buffer>) -> napi::Result<Value> {
+  let bytes = resolve_byte_type(path_or_buf)?;
+  let huf = create_huffman_lookup_table();
+
+  let settings = ParserInputs {
+    wanted_players: vec![],
+    real_name_to_og_name: AHashMap::default(),
+    wanted_player_props: vec![],
+    wanted_other_props: vec![],
+    wanted_events: vec![],
+    wanted_prop_states: PropFilter::default(),
+    parse_ents: true,
+    wanted_ticks: vec![],
+    parse_projectiles: false,
+    only_header: true,
+    count_props: false,
+    only_convars: false,
+    huffman_lookup_table: &huf,
+    order_by_steamid: false,
+    change_log: false,
+    parse_money_ledger: false,
+    parse_bomb: false,
+    parse_spotting: false,
+    parse_kill_context: false,
+    parse_shots: false,
+    parse_sprays: false,
+    parse_usercmds: true,
+    parse_movement: false,
+    parse_weapons: false,
+    wanted_entity_classes: vec![],
+    wanted_entity_props: vec![],
+    entity_event_classes: vec![],
+    wanted_prop_changes: vec![],
+    map_overview: None,
+    nav_mesh: None,
+    map_geometry: None,
+    visibility_pairs: None,
+    fov: None,
+    parallelism: ParallelismSettings::default(),
+  };
+  let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
+  let output = parse_demo(bytes, &mut parser)?;
+
+  let s = match serde_json::to_value(&output.usercmds) {
+    Ok(s) => s,
+    Err(e) => return Err(Error::new(Status::InvalidArg, format!("{}", e).to_owned())),
+  };
+  Ok(s)
+}
+#[napi]
+pub fn parse_movement(path_or_buf: Either<String, Buffer>) -> napi::Result<Value> {
+  let bytes = resolve_byte_type(path_or_buf)?;
+  let huf = create_huffman_lookup_table();
+
+  let settings = ParserInputs {
+    wanted_players: vec![],
+    real_name_to_og_name: AHashMap::default(),
+    wanted_player_props: vec![],
+    wanted_other_props: vec![],
+    wanted_events: vec![],
+    wanted_prop_states: PropFilter::default(),
+    parse_ents: true,
+    wanted_ticks: vec![],
+    parse_projectiles: false,
+    only_header: true,
+    count_props: false,
+    only_convars: false,
+    huffman_lookup_table: &huf,
+    order_by_steamid: false,
+    change_log: false,
+    parse_money_ledger: false,
+    parse_bomb: false,
+    parse_spotting: false,
+    parse_kill_context: false,
+    parse_shots: false,
+    parse_sprays: false,
+    parse_usercmds: false,
+    parse_movement: true,
+    parse_weapons: false,
+    wanted_entity_classes: vec![],
+    wanted_entity_props: vec![],
+    entity_event_classes: vec![],
+    wanted_prop_changes: vec![],
+    map_overview: None,
+    nav_mesh: None,
+    map_geometry: None,
+    visibility_pairs: None,
+    fov: None,
+    parallelism: ParallelismSettings::default(),
+  };
+  let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
+  let output = parse_demo(bytes, &mut parser)?;
+
+  let s = match serde_json::to_value(&output.movement) {
+    Ok(s) => s,
+    Err(e) => return Err(Error::new(Status::InvalidArg, format!("{}", e).to_owned())),
+  };
+  Ok(s)
+}
+#[napi]
+pub fn parse_entities(
+  path_or_buf: Either<String, Buffer>,
+  class_names: Vec<String>,
+  props: Vec<String>,
+  wanted_ticks: Option<Vec<i32>>,
+) -> napi::Result<Value> {
+  let bytes = resolve_byte_type(path_or_buf)?;
+  let huf = create_huffman_lookup_table();
+
+  let settings = ParserInputs {
+    wanted_players: vec![],
+    real_name_to_og_name: AHashMap::default(),
+    wanted_player_props: vec![],
+    wanted_other_props: vec![],
+    wanted_events: vec![],
+    wanted_prop_states: PropFilter::default(),
+    parse_ents: true,
+    wanted_ticks: wanted_ticks.unwrap_or_default(),
+    parse_projectiles: false,
+    only_header: true,
+    count_props: false,
+    only_convars: false,
+    huffman_lookup_table: &huf,
+    order_by_steamid: false,
+    change_log: false,
+    parse_money_ledger: false,
+    parse_bomb: false,
+    parse_spotting: false,
+    parse_kill_context: false,
+    parse_shots: false,
+    parse_sprays: false,
+    parse_usercmds: false,
+    parse_movement: false,
+    parse_weapons: false,
+    wanted_entity_classes: class_names,
+    wanted_entity_props: props,
+    entity_event_classes: vec![],
+    wanted_prop_changes: vec![],
+    map_overview: None,
+    nav_mesh: None,
+    map_geometry: None,
+    visibility_pairs: None,
+    fov: None,
+    parallelism: ParallelismSettings::default(),
+  };
+  let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
+  let output = parse_demo(bytes, &mut parser)?;
+
+  let s = match serde_json::to_value(&output.entities) {
+    Ok(s) => s,
+    Err(e) => return Err(Error::new(Status::InvalidArg, format!("{}", e).to_owned())),
+  };
+  Ok(s)
+}
+#[napi]
+pub fn parse_weapons(path_or_buf: Either<String, Buffer>, wanted_ticks: Option<Vec<i32>>) -> napi::Result<Value> {
+  let bytes = resolve_byte_type(path_or_buf)?;
+  let huf = create_huffman_lookup_table();
+
+  let settings = ParserInputs {
+    wanted_players: vec![],
+    real_name_to_og_name: AHashMap::default(),
+    wanted_player_props: vec![],
+    wanted_other_props: vec![],
+    wanted_events: vec![],
+    wanted_prop_states: PropFilter::default(),
+    parse_ents: true,
+    wanted_ticks: wanted_ticks.unwrap_or_default(),
+    parse_projectiles: false,
+    only_header: true,
+    count_props: false,
+    only_convars: false,
+    huffman_lookup_table: &huf,
+    order_by_steamid: false,
+    change_log: false,
+    parse_money_ledger: false,
+    parse_bomb: false,
+    parse_spotting: false,
+    parse_kill_context: false,
+    parse_shots: false,
+    parse_sprays: false,
+    parse_usercmds: false,
+    parse_movement: false,
+    parse_weapons: true,
+    wanted_entity_classes: vec![],
+    wanted_entity_props: vec![],
+    entity_event_classes: vec![],
+    wanted_prop_changes: vec![],
+    map_overview: None,
+    nav_mesh: None,
+    map_geometry: None,
+    visibility_pairs: None,
+    fov: None,
+    parallelism: ParallelismSettings::default(),
+  };
+  let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
+  let output = parse_demo(bytes, &mut parser)?;
+
+  let s = match serde_json::to_value(&serde_json::json!({
+    "weapons": output.weapons,
+    "ground_weapons": output.ground_weapons,
+  })) {
+    Ok(s) => s,
+    Err(e) => return Err(Error::new(Status::InvalidArg, format!("{}", e).to_owned())),
+  };
+  Ok(s)
+}
+#[napi]
 pub fn parse_header(path_or_buf: Either<String, Buffer>) -> napi::Result<Value> {
   let bytes = resolve_byte_type(path_or_buf)?;
   let huf = create_huffman_lookup_table();
@@ -243,7 +1051,7 @@ pub fn parse_header(path_or_buf: Either<String, Buffer>) -> napi::Result<Value>
     wanted_players: vec![],
     wanted_player_props: vec![],
     wanted_other_props: vec![],
-    wanted_prop_states: AHashMap::default(),
+    wanted_prop_states: PropFilter::default(),
     wanted_events: vec![],
     parse_ents: false,
     wanted_ticks: vec![],
@@ -253,6 +1061,26 @@ pub fn parse_header(path_or_buf: Either<String, Buffer>) -> napi::Result<Value>
     only_convars: false,
     huffman_lookup_table: &huf,
     order_by_steamid: false,
+    change_log: false,
+    parse_money_ledger: false,
+    parse_bomb: false,
+    parse_spotting: false,
+    parse_kill_context: false,
+    parse_shots: false,
+    parse_sprays: false,
+    parse_usercmds: false,
+    parse_movement: false,
+    parse_weapons: false,
+    wanted_entity_classes: vec![],
+    wanted_entity_props: vec![],
+    entity_event_classes: vec![],
+    wanted_prop_changes: vec![],
+    map_overview: None,
+    nav_mesh: None,
+    map_geometry: None,
+    visibility_pairs: None,
+    fov: None,
+    parallelism: ParallelismSettings::default(),
   };
   let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
   let output = parse_demo(bytes, &mut parser)?;
@@ -274,6 +1102,10 @@ pub fn parse_event(
   event_name: String,
   player_extra: Option<Vec<String>>,
   other_extra: Option<Vec<String>>,
+  nav_path: Option<String>,
+  kill_context: Option<bool>,
+  entity_classes: Option<Vec<String>>,
+  prop_changes: Option<Vec<String>>,
 ) -> napi::Result<Value> {
   let player_props = match player_extra {
     Some(p) => p,
@@ -303,12 +1135,17 @@ pub fn parse_event(
   let bytes = resolve_byte_type(path_or_buf)?;
   let huf = create_huffman_lookup_table();
 
+  let nav_mesh = match nav_path.map(|path| NavMesh::from_nav_file(&path)).transpose() {
+    Ok(nav_mesh) => nav_mesh.map(Arc::new),
+    Err(e) => return Err(Error::new(Status::InvalidArg, format!("{}", e).to_owned())),
+  };
+
   let settings = ParserInputs {
     real_name_to_og_name: real_name_to_og_name,
     wanted_players: vec![],
     wanted_player_props: real_names_player.clone(),
     wanted_other_props: real_other_props,
-    wanted_prop_states: AHashMap::default(),
+    wanted_prop_states: PropFilter::default(),
     wanted_events: vec![event_name],
     parse_ents: true,
     wanted_ticks: vec![],
@@ -318,6 +1155,26 @@ pub fn parse_event(
     only_convars: false,
     huffman_lookup_table: &huf,
     order_by_steamid: false,
+    change_log: false,
+    parse_money_ledger: false,
+    parse_bomb: false,
+    parse_spotting: false,
+    parse_kill_context: kill_context.unwrap_or(false),
+    parse_shots: false,
+    parse_sprays: false,
+    parse_usercmds: false,
+    parse_movement: false,
+    parse_weapons: false,
+    wanted_entity_classes: vec![],
+    wanted_entity_props: vec![],
+    entity_event_classes: entity_classes.unwrap_or_default(),
+    wanted_prop_changes: prop_changes.unwrap_or_default(),
+    map_overview: None,
+    nav_mesh: nav_mesh,
+    map_geometry: None,
+    visibility_pairs: None,
+    fov: None,
+    parallelism: ParallelismSettings::default(),
   };
   let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
   let output = parse_demo(bytes, &mut parser)?;
@@ -333,6 +1190,10 @@ pub fn parse_events(
   event_names: Option<Vec<String>>,
   player_extra: Option<Vec<String>>,
   other_extra: Option<Vec<String>>,
+  nav_path: Option<String>,
+  kill_context: Option<bool>,
+  entity_classes: Option<Vec<String>>,
+  prop_changes: Option<Vec<String>>,
 ) -> napi::Result<Value> {
   let event_names = match event_names {
     None => return Err(Error::new(Status::InvalidArg, "No events provided!")),
@@ -366,12 +1227,17 @@ pub fn parse_events(
   let bytes = resolve_byte_type(path_or_buf)?;
   let huf = create_huffman_lookup_table();
 
+  let nav_mesh = match nav_path.map(|path| NavMesh::from_nav_file(&path)).transpose() {
+    Ok(nav_mesh) => nav_mesh.map(Arc::new),
+    Err(e) => return Err(Error::new(Status::InvalidArg, format!("{}", e).to_owned())),
+  };
+
   let settings = ParserInputs {
     real_name_to_og_name: real_name_to_og_name,
     wanted_players: vec![],
     wanted_player_props: real_names_player.clone(),
     wanted_other_props: real_other_props.clone(),
-    wanted_prop_states: AHashMap::default(),
+    wanted_prop_states: PropFilter::default(),
     wanted_events: event_names,
     parse_ents: true,
     wanted_ticks: vec![],
@@ -381,6 +1247,26 @@ pub fn parse_events(
     only_convars: false,
     huffman_lookup_table: &huf,
     order_by_steamid: false,
+    change_log: false,
+    parse_money_ledger: false,
+    parse_bomb: false,
+    parse_spotting: false,
+    parse_kill_context: kill_context.unwrap_or(false),
+    parse_shots: false,
+    parse_sprays: false,
+    parse_usercmds: false,
+    parse_movement: false,
+    parse_weapons: false,
+    wanted_entity_classes: vec![],
+    wanted_entity_props: vec![],
+    entity_event_classes: entity_classes.unwrap_or_default(),
+    wanted_prop_changes: prop_changes.unwrap_or_default(),
+    map_overview: None,
+    nav_mesh: nav_mesh,
+    map_geometry: None,
+    visibility_pairs: None,
+    fov: None,
+    parallelism: ParallelismSettings::default(),
   };
   let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
   let output = parse_demo(bytes, &mut parser)?;
@@ -399,7 +1285,9 @@ pub fn parse_ticks(
   wanted_players: Option<Vec<String>>,
   struct_of_arrays: Option<bool>,
   order_by_steamid: Option<bool>,
-  prop_states: Option<Vec<WantedPropState>>,
+  prop_states: Option<Either<String, Vec<WantedPropState>>>,
+  overview_path: Option<String>,
+  nav_path: Option<String>,
 ) -> napi::Result<Value> {
   let mut real_names = match rm_user_friendly_names(&wanted_props) {
     Ok(names) => names,
@@ -409,13 +1297,22 @@ pub fn parse_ticks(
     Some(v) => v.iter().map(|x| x.parse::<u64>().unwrap_or(0)).collect(),
     None => vec![],
   };
-  let wanted_prop_states: AHashMap<String, Variant> = prop_states
-    .unwrap_or_default()
-    .into_iter()
-    .map(|prop| (prop.prop.clone(), prop.state.0.clone()))
-    .collect();
+  // Either a filter expression or the old list of props that have to equal their state
+  let wanted_prop_states = match prop_states {
+    None => PropFilter::default(),
+    Some(Either::A(expression)) => match PropFilter::parse(&expression) {
+      Ok(filter) => filter,
+      Err(e) => return Err(Error::new(Status::InvalidArg, format!("{}", e).to_owned())),
+    },
+    Some(Either::B(states)) => PropFilter::from(
+      states
+        .into_iter()
+        .map(|prop| (prop.prop, prop.state.0))
+        .collect::<AHashMap<String, Variant>>(),
+    ),
+  };
 
-  let real_wanted_prop_states = rm_map_user_friendly_names(&wanted_prop_states);
+  let real_wanted_prop_states = rm_filter_user_friendly_names(&wanted_prop_states);
   let real_wanted_prop_states = match real_wanted_prop_states {
     Ok(real_wanted_prop_states) => real_wanted_prop_states,
     Err(e) => return Err(Error::new(Status::InvalidArg, format!("{}", e).to_owned())),
@@ -429,10 +1326,11 @@ pub fn parse_ticks(
     real_name_to_og_name.insert(real_name.clone(), user_friendly_name.clone());
   }
   for (real_name, user_friendly_name) in real_wanted_prop_states
-    .keys()
-    .zip(wanted_prop_states.keys())
+    .prop_names()
+    .into_iter()
+    .zip(wanted_prop_states.prop_names())
   {
-    real_name_to_og_name.insert(real_name.clone(), user_friendly_name.clone());
+    real_name_to_og_name.insert(real_name.to_string(), user_friendly_name.to_string());
   }
 
   let wanted_ticks = match wanted_ticks {
@@ -443,6 +1341,18 @@ pub fn parse_ticks(
     Some(true) => true,
     _ => false,
   };
+  let map_overview = match overview_path {
+    Some(path) => match MapOverview::from_overview_file(&path) {
+      Ok(overview) => Some(overview),
+      Err(e) => return Err(Error::new(Status::InvalidArg, format!("{}", e).to_owned())),
+    },
+    None => None,
+  };
+
+  let nav_mesh = match nav_path.map(|path| NavMesh::from_nav_file(&path)).transpose() {
+    Ok(nav_mesh) => nav_mesh.map(Arc::new),
+    Err(e) => return Err(Error::new(Status::InvalidArg, format!("{}", e).to_owned())),
+  };
 
   let settings = ParserInputs {
     real_name_to_og_name: real_name_to_og_name,
@@ -459,6 +1369,26 @@ pub fn parse_ticks(
     only_convars: false,
     huffman_lookup_table: &huf,
     order_by_steamid: order_by_steamid,
+    change_log: false,
+    parse_money_ledger: false,
+    parse_bomb: false,
+    parse_spotting: false,
+    parse_kill_context: false,
+    parse_shots: false,
+    parse_sprays: false,
+    parse_usercmds: false,
+    parse_movement: false,
+    parse_weapons: false,
+    wanted_entity_classes: vec![],
+    wanted_entity_props: vec![],
+    entity_event_classes: vec![],
+    wanted_prop_changes: vec![],
+    map_overview: map_overview,
+    nav_mesh: nav_mesh,
+    map_geometry: None,
+    visibility_pairs: None,
+    fov: None,
+    parallelism: ParallelismSettings::default(),
   };
 
   let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
@@ -522,7 +1452,7 @@ pub fn parse_player_info(path_or_buf: Either<String, Buffer>) -> napi::Result<Va
     real_name_to_og_name: AHashMap::default(),
     wanted_player_props: vec![],
     wanted_other_props: vec![],
-    wanted_prop_states: AHashMap::default(),
+    wanted_prop_states: PropFilter::default(),
     wanted_events: vec![],
     parse_ents: false,
     wanted_ticks: vec![],
@@ -532,6 +1462,26 @@ pub fn parse_player_info(path_or_buf: Either<String, Buffer>) -> napi::Result<Va
     only_convars: false,
     huffman_lookup_table: &huf,
     order_by_steamid: false,
+    change_log: false,
+    parse_money_ledger: false,
+    parse_bomb: false,
+    parse_spotting: false,
+    parse_kill_context: false,
+    parse_shots: false,
+    parse_sprays: false,
+    parse_usercmds: false,
+    parse_movement: false,
+    parse_weapons: false,
+    wanted_entity_classes: vec![],
+    wanted_entity_props: vec![],
+    entity_event_classes: vec![],
+    wanted_prop_changes: vec![],
+    map_overview: None,
+    nav_mesh: None,
+    map_geometry: None,
+    visibility_pairs: None,
+    fov: None,
+    parallelism: ParallelismSettings::default(),
   };
   let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
   let output = parse_demo(bytes, &mut parser)?;
@@ -552,7 +1502,7 @@ pub fn parse_player_skins(path_or_buf: Either<String, Buffer>) -> napi::Result<V
     real_name_to_og_name: AHashMap::default(),
     wanted_player_props: vec![],
     wanted_other_props: vec![],
-    wanted_prop_states: AHashMap::default(),
+    wanted_prop_states: PropFilter::default(),
     wanted_events: vec![],
     parse_ents: true,
     wanted_ticks: vec![],
@@ -562,6 +1512,26 @@ pub fn parse_player_skins(path_or_buf: Either<String, Buffer>) -> napi::Result<V
     only_convars: false,
     huffman_lookup_table: &huf,
     order_by_steamid: false,
+    change_log: false,
+    parse_money_ledger: false,
+    parse_bomb: false,
+    parse_spotting: false,
+    parse_kill_context: false,
+    parse_shots: false,
+    parse_sprays: false,
+    parse_usercmds: false,
+    parse_movement: false,
+    parse_weapons: false,
+    wanted_entity_classes: vec![],
+    wanted_entity_props: vec![],
+    entity_event_classes: vec![],
+    wanted_prop_changes: vec![],
+    map_overview: None,
+    nav_mesh: None,
+    map_geometry: None,
+    visibility_pairs: None,
+    fov: None,
+    parallelism: ParallelismSettings::default(),
   };
   let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
   let output = parse_demo(bytes, &mut parser)?;
@@ -1,7 +1,9 @@
 #![allow(non_snake_case)]
 
+use crate::first_pass::parser_settings::ParallelismSettings;
 use crate::first_pass::parser_settings::ParserInputs;
 use crate::first_pass::prop_controller::PropController;
+use crate::first_pass::prop_filter::PropFilter;
 use crate::first_pass::prop_controller::*;
 use crate::parse_demo::DemoOutput;
 use crate::parse_demo::Parser;
@@ -321,13 +323,33 @@ pub fn _create_ge_tests() {
         parse_ents: true,
         wanted_players: vec![],
         wanted_ticks: (0..5).into_iter().map(|x| x * 10000).collect_vec(),
-        wanted_prop_states: AHashMap::default(),
+        wanted_prop_states: PropFilter::default(),
         parse_projectiles: true,
         only_header: false,
         count_props: false,
         only_convars: false,
         huffman_lookup_table: &huf,
         order_by_steamid: false,
+        change_log: false,
+        parse_money_ledger: false,
+        parse_bomb: false,
+        parse_spotting: false,
+        parse_kill_context: false,
+        parse_shots: false,
+        parse_sprays: false,
+        parse_usercmds: false,
+        parse_movement: false,
+        parse_weapons: false,
+        wanted_entity_classes: vec![],
+        wanted_entity_props: vec![],
+        entity_event_classes: vec![],
+        wanted_prop_changes: vec![],
+        map_overview: None,
+        nav_mesh: None,
+        map_geometry: None,
+        visibility_pairs: None,
+        fov: None,
+        parallelism: ParallelismSettings::default(),
     };
 
     let mut ds = Parser::new(settings, crate::parse_demo::ParsingMode::ForceMultiThreaded);
@@ -723,13 +745,33 @@ pub fn _create_tests() {
         parse_ents: true,
         wanted_players: vec![],
         wanted_ticks: (0..5).into_iter().map(|x| x * 10000).collect_vec(),
-        wanted_prop_states: AHashMap::default(),
+        wanted_prop_states: PropFilter::default(),
         parse_projectiles: true,
         only_header: false,
         count_props: false,
         only_convars: false,
         huffman_lookup_table: &huf,
         order_by_steamid: false,
+        change_log: false,
+        parse_money_ledger: false,
+        parse_bomb: false,
+        parse_spotting: false,
+        parse_kill_context: false,
+        parse_shots: false,
+        parse_sprays: false,
+        parse_usercmds: false,
+        parse_movement: false,
+        parse_weapons: false,
+        wanted_entity_classes: vec![],
+        wanted_entity_props: vec![],
+        entity_event_classes: vec![],
+        wanted_prop_changes: vec![],
+        map_overview: None,
+        nav_mesh: None,
+        map_geometry: None,
+        visibility_pairs: None,
+        fov: None,
+        parallelism: ParallelismSettings::default(),
     };
 
     let mut ds = Parser::new(settings, crate::parse_demo::ParsingMode::ForceMultiThreaded);
@@ -1111,13 +1153,33 @@ fn create_data() -> (DemoOutput, PropController, BTreeMap<String, Vec<GameEvent>
         parse_ents: true,
         wanted_players: vec![],
         wanted_ticks: (0..5).into_iter().map(|x| x * 10000).collect_vec(),
-        wanted_prop_states: AHashMap::default(),
+        wanted_prop_states: PropFilter::default(),
         parse_projectiles: true,
         only_header: false,
         count_props: false,
         only_convars: false,
         huffman_lookup_table: &huf,
         order_by_steamid: false,
+        change_log: false,
+        parse_money_ledger: false,
+        parse_bomb: false,
+        parse_spotting: false,
+        parse_kill_context: false,
+        parse_shots: false,
+        parse_sprays: false,
+        parse_usercmds: false,
+        parse_movement: false,
+        parse_weapons: false,
+        wanted_entity_classes: vec![],
+        wanted_entity_props: vec![],
+        entity_event_classes: vec![],
+        wanted_prop_changes: vec![],
+        map_overview: None,
+        nav_mesh: None,
+        map_geometry: None,
+        visibility_pairs: None,
+        fov: None,
+        parallelism: ParallelismSettings::default(),
     };
 
     let mut ds = Parser::new(settings, crate::parse_demo::ParsingMode::ForceMultiThreaded);
@@ -1136,13 +1198,33 @@ fn create_data() -> (DemoOutput, PropController, BTreeMap<String, Vec<GameEvent>
         parse_ents: true,
         wanted_players: vec![],
         wanted_ticks: (0..5).into_iter().map(|x| x * 10000).collect_vec(),
-        wanted_prop_states: AHashMap::default(),
+        wanted_prop_states: PropFilter::default(),
         parse_projectiles: true,
         only_header: false,
         count_props: false,
         only_convars: false,
         huffman_lookup_table: &huf,
         order_by_steamid: false,
+        change_log: false,
+        parse_money_ledger: false,
+        parse_bomb: false,
+        parse_spotting: false,
+        parse_kill_context: false,
+        parse_shots: false,
+        parse_sprays: false,
+        parse_usercmds: false,
+        parse_movement: false,
+        parse_weapons: false,
+        wanted_entity_classes: vec![],
+        wanted_entity_props: vec![],
+        entity_event_classes: vec![],
+        wanted_prop_changes: vec![],
+        map_overview: None,
+        nav_mesh: None,
+        map_geometry: None,
+        visibility_pairs: None,
+        fov: None,
+        parallelism: ParallelismSettings::default(),
     };
     let mut ds = Parser::new(settings, crate::parse_demo::ParsingMode::ForceMultiThreaded);
     let file = File::open("test_demo.dem".to_string()).unwrap();
@@ -1208,8 +1290,10 @@ fn create_data() -> (DemoOutput, PropController, BTreeMap<String, Vec<GameEvent>
 #[cfg(test)]
 mod tests {
     use crate::e2e_test::create_data;
+    use crate::first_pass::parser_settings::ParallelismSettings;
     use crate::first_pass::parser_settings::ParserInputs;
     use crate::first_pass::prop_controller::PropController;
+    use crate::first_pass::prop_filter::PropFilter;
     use crate::first_pass::prop_controller::PITCH_ID;
     use crate::first_pass::prop_controller::PLAYER_Y_ID;
     use crate::first_pass::prop_controller::WEAPON_NAME_ID;
@@ -1249,13 +1333,33 @@ mod tests {
             wanted_other_props: vec![],
             parse_ents: true,
             wanted_ticks: vec![],
-            wanted_prop_states: AHashMap::default(),
+            wanted_prop_states: PropFilter::default(),
             parse_projectiles: true,
             only_header: false,
             count_props: false,
             only_convars: false,
             huffman_lookup_table: &huf,
             order_by_steamid: false,
+            change_log: false,
+            parse_money_ledger: false,
+            parse_bomb: false,
+            parse_spotting: false,
+            parse_kill_context: false,
+            parse_shots: false,
+            parse_sprays: false,
+            parse_usercmds: false,
+            parse_movement: false,
+            parse_weapons: false,
+            wanted_entity_classes: vec![],
+            wanted_entity_props: vec![],
+            entity_event_classes: vec![],
+            wanted_prop_changes: vec![],
+            map_overview: None,
+            nav_mesh: None,
+            map_geometry: None,
+            visibility_pairs: None,
+            fov: None,
+            parallelism: ParallelismSettings::default(),
         };
 
         let mut wanted_prop_states: AHashMap<std::string::String, Variant> = AHashMap::default();
@@ -1271,13 +1375,33 @@ mod tests {
             wanted_other_props: vec![],
             parse_ents: true,
             wanted_ticks: vec![],
-            wanted_prop_states: wanted_prop_states,
+            wanted_prop_states: wanted_prop_states.into(),
             parse_projectiles: true,
             only_header: false,
             count_props: false,
             only_convars: false,
             huffman_lookup_table: &huf2,
             order_by_steamid: false,
+            change_log: false,
+            parse_money_ledger: false,
+            parse_bomb: false,
+            parse_spotting: false,
+            parse_kill_context: false,
+            parse_shots: false,
+            parse_sprays: false,
+            parse_usercmds: false,
+            parse_movement: false,
+            parse_weapons: false,
+            wanted_entity_classes: vec![],
+            wanted_entity_props: vec![],
+            entity_event_classes: vec![],
+            wanted_prop_changes: vec![],
+            map_overview: None,
+            nav_mesh: None,
+            map_geometry: None,
+            visibility_pairs: None,
+            fov: None,
+            parallelism: ParallelismSettings::default(),
         };
 
         let mut ds = Parser::new(settings, crate::parse_demo::ParsingMode::ForceMultiThreaded);
@@ -1351,13 +1475,33 @@ mod tests {
             wanted_other_props: vec!["CCSTeam.m_iScore".to_string()],
             parse_ents: true,
             wanted_ticks: vec![10000, 10001],
-            wanted_prop_states: AHashMap::default(),
+            wanted_prop_states: PropFilter::default(),
             parse_projectiles: true,
             only_header: false,
             count_props: false,
             only_convars: false,
             huffman_lookup_table: &huf,
             order_by_steamid: false,
+            change_log: false,
+            parse_money_ledger: false,
+            parse_bomb: false,
+            parse_spotting: false,
+            parse_kill_context: false,
+            parse_shots: false,
+            parse_sprays: false,
+            parse_usercmds: false,
+            parse_movement: false,
+            parse_weapons: false,
+            wanted_entity_classes: vec![],
+            wanted_entity_props: vec![],
+            entity_event_classes: vec![],
+            wanted_prop_changes: vec![],
+            map_overview: None,
+            nav_mesh: None,
+            map_geometry: None,
+            visibility_pairs: None,
+            fov: None,
+            parallelism: ParallelismSettings::default(),
         };
 
         let mut ds = Parser::new(settings, crate::parse_demo::ParsingMode::ForceMultiThreaded);
@@ -1370,6 +1514,173 @@ mod tests {
         assert_eq!(steamids.data, Some(VarVec::U64(vec![Some(76561198244754626), Some(76561198244754626)])));
     }
     #[test]
+    fn test_grenade_lifecycles_with_events() {
+        let huf = create_huffman_lookup_table();
+        // Asking for events and ticks must not drop trajectory points or fire cells
+        let settings = |events: bool| ParserInputs {
+            wanted_players: vec![],
+            real_name_to_og_name: AHashMap::default(),
+            wanted_player_props: vec!["X".to_string()],
+            wanted_events: if events { vec!["all".to_string()] } else { vec![] },
+            wanted_other_props: vec![],
+            parse_ents: true,
+            wanted_ticks: if events { vec![10000] } else { vec![] },
+            wanted_prop_states: PropFilter::default(),
+            parse_projectiles: true,
+            only_header: false,
+            count_props: false,
+            only_convars: false,
+            huffman_lookup_table: &huf,
+            order_by_steamid: false,
+            change_log: false,
+            parse_money_ledger: false,
+            parse_bomb: false,
+            parse_spotting: false,
+            parse_kill_context: false,
+            parse_shots: false,
+            parse_sprays: false,
+            parse_usercmds: false,
+            parse_movement: false,
+            parse_weapons: false,
+            wanted_entity_classes: vec![],
+            wanted_entity_props: vec![],
+            entity_event_classes: vec![],
+            wanted_prop_changes: vec![],
+            map_overview: None,
+            nav_mesh: None,
+            map_geometry: None,
+            visibility_pairs: None,
+            fov: None,
+            parallelism: ParallelismSettings::default(),
+        };
+
+        let file = File::open("test_demo.dem").unwrap();
+        let mmap = unsafe { MmapOptions::new().map(&file).unwrap() };
+        let with_events = Parser::new(settings(true), crate::parse_demo::ParsingMode::Normal).parse_demo(&mmap).unwrap();
+        let without_events = Parser::new(settings(false), crate::parse_demo::ParsingMode::Normal).parse_demo(&mmap).unwrap();
+
+        assert!(!with_events.game_events.is_empty());
+        assert!(!with_events.grenade_lifecycles.is_empty());
+        assert_eq!(
+            format!("{:?}", with_events.grenade_lifecycles),
+            format!("{:?}", without_events.grenade_lifecycles)
+        );
+        assert_eq!(format!("{:?}", with_events.inferno_fires), format!("{:?}", without_events.inferno_fires));
+    }
+    #[test]
+    fn test_single_and_multi_threaded_are_equal() {
+        let huf = create_huffman_lookup_table();
+        let huf2 = create_huffman_lookup_table();
+        let wanted_props: Vec<std::string::String> = itertools::Itertools::unique(crate::maps::FRIENDLY_NAMES_MAPPING.values().map(|x| x.to_string())).collect();
+        let real_name_to_og_name: AHashMap<std::string::String, std::string::String> = crate::maps::FRIENDLY_NAMES_MAPPING
+            .entries()
+            .map(|(friendly, real)| (real.to_string(), friendly.to_string()))
+            .collect();
+        // Sparse enough to fit in memory, dense enough to land on chunk boundaries
+        let wanted_ticks: Vec<i32> = (0..200000).step_by(16).collect();
+        // Events and the per player frames skip the dense output so they get their own parse. Kill
+        // context is only added to events
+        let settings = |huf, events: bool| ParserInputs {
+            wanted_players: vec![],
+            real_name_to_og_name: real_name_to_og_name.clone(),
+            wanted_player_props: wanted_props.clone(),
+            wanted_events: if events { vec!["all".to_string()] } else { vec![] },
+            wanted_other_props: vec![],
+            parse_ents: true,
+            wanted_ticks: wanted_ticks.clone(),
+            wanted_prop_states: PropFilter::default(),
+            parse_projectiles: !events,
+            only_header: false,
+            count_props: false,
+            only_convars: false,
+            huffman_lookup_table: huf,
+            order_by_steamid: events,
+            change_log: false,
+            parse_money_ledger: !events,
+            parse_bomb: !events,
+            parse_spotting: !events,
+            parse_kill_context: true,
+            parse_shots: !events,
+            parse_sprays: !events,
+            parse_usercmds: !events,
+            parse_movement: !events,
+            parse_weapons: !events,
+            wanted_entity_classes: vec![],
+            wanted_entity_props: vec![],
+            entity_event_classes: vec![],
+            wanted_prop_changes: vec![],
+            map_overview: None,
+            nav_mesh: None,
+            map_geometry: None,
+            visibility_pairs: None,
+            fov: None,
+            parallelism: ParallelismSettings::default(),
+        };
+
+        let file = File::open("test_demo.dem").unwrap();
+        let mmap = unsafe { MmapOptions::new().map(&file).unwrap() };
+        let single = Parser::new(settings(&huf, false), crate::parse_demo::ParsingMode::ForceSingleThreaded)
+            .parse_demo(&mmap)
+            .unwrap();
+        let multi = Parser::new(settings(&huf2, false), crate::parse_demo::ParsingMode::ForceMultiThreaded)
+            .parse_demo(&mmap)
+            .unwrap();
+
+        for prop_info in &single.prop_controller.prop_infos {
+            assert_eq!(
+                single.df.get(&prop_info.id).map(|c| &c.data),
+                multi.df.get(&prop_info.id).map(|c| &c.data),
+                "{} differs between single and multi threaded parsing",
+                prop_info.prop_friendly_name
+            );
+        }
+        let records = [
+            ("projectiles", format!("{:?}", single.projectiles), format!("{:?}", multi.projectiles)),
+            ("money_ledger", format!("{:?}", single.money_ledger), format!("{:?}", multi.money_ledger)),
+            ("grenade_lifecycles", format!("{:?}", single.grenade_lifecycles), format!("{:?}", multi.grenade_lifecycles)),
+            ("utility_effects", format!("{:?}", single.utility_effects), format!("{:?}", multi.utility_effects)),
+            ("inferno_fires", format!("{:?}", single.inferno_fires), format!("{:?}", multi.inferno_fires)),
+            ("bomb_timelines", format!("{:?}", single.bomb_timelines), format!("{:?}", multi.bomb_timelines)),
+            ("spotting_edges", format!("{:?}", single.spotting_edges), format!("{:?}", multi.spotting_edges)),
+            ("spotting_rounds", format!("{:?}", single.spotting_rounds), format!("{:?}", multi.spotting_rounds)),
+            ("shots", format!("{:?}", single.shots), format!("{:?}", multi.shots)),
+            ("accuracy", format!("{:?}", single.accuracy), format!("{:?}", multi.accuracy)),
+            ("sprays", format!("{:?}", single.sprays), format!("{:?}", multi.sprays)),
+            ("spray_bullets", format!("{:?}", single.spray_bullets), format!("{:?}", multi.spray_bullets)),
+            ("usercmds", format!("{:?}", single.usercmds), format!("{:?}", multi.usercmds)),
+            ("movement", format!("{:?}", single.movement), format!("{:?}", multi.movement)),
+            ("weapons", format!("{:?}", single.weapons), format!("{:?}", multi.weapons)),
+            ("ground_weapons", format!("{:?}", single.ground_weapons), format!("{:?}", multi.ground_weapons)),
+        ];
+        for (name, single_records, multi_records) in records {
+            assert_eq!(single_records, multi_records, "{} differ between single and multi threaded parsing", name);
+        }
+
+        let single = Parser::new(settings(&huf, true), crate::parse_demo::ParsingMode::ForceSingleThreaded)
+            .parse_demo(&mmap)
+            .unwrap();
+        let multi = Parser::new(settings(&huf2, true), crate::parse_demo::ParsingMode::ForceMultiThreaded)
+            .parse_demo(&mmap)
+            .unwrap();
+
+        assert_eq!(single.game_events.len(), multi.game_events.len());
+        for (single_event, multi_event) in single.game_events.iter().zip(&multi.game_events) {
+            assert_eq!(single_event, multi_event);
+        }
+        assert_eq!(single.df_per_player.len(), multi.df_per_player.len());
+        for (steamid, df) in &single.df_per_player {
+            for prop_info in &single.prop_controller.prop_infos {
+                assert_eq!(
+                    df.get(&prop_info.id).map(|c| &c.data),
+                    multi.df_per_player.get(steamid).and_then(|df| df.get(&prop_info.id)).map(|c| &c.data),
+                    "{} of {} differs between single and multi threaded parsing",
+                    prop_info.prop_friendly_name,
+                    steamid
+                );
+            }
+        }
+    }
+    #[test]
     fn CBodyComponentBaseAnimGraph_m_nNewSequenceParity() {
         let prop = (
             "CBodyComponentBaseAnimGraph.m_nNewSequenceParity",
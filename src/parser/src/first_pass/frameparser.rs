@@ -126,8 +126,8 @@ impl FrameParser {
         v
     }
 
-    pub fn par_start(&mut self, demo_bytes: &[u8], sender: Sender<StartEndOffset>) -> Result<(), DemoParserError> {
-        let start_pos = FrameParser::split_file_into_n_chunks(demo_bytes.len(), 12);
+    pub fn par_start(&mut self, demo_bytes: &[u8], num_chunks: usize, sender: Sender<StartEndOffset>) -> Result<(), DemoParserError> {
+        let start_pos = FrameParser::split_file_into_n_chunks(demo_bytes.len(), num_chunks);
         let both: Vec<Vec<StartEndOffset>> = start_pos
             .par_iter()
             .map(|(start, end)| {
@@ -3,6 +3,7 @@ pub mod frameparser;
 pub mod parser;
 pub mod parser_settings;
 pub mod prop_controller;
+pub mod prop_filter;
 pub mod read_bits;
 pub mod sendtables;
 pub mod stringtables;
@@ -22,6 +22,7 @@ use csgoproto::demo::EDemoCommands::*;
 use csgoproto::demo::{CDemoClassInfo, CDemoFileHeader};
 use csgoproto::netmessages::csvcmsg_game_event_list::Descriptor_t;
 use csgoproto::netmessages::CSVCMsg_GameEventList;
+use csgoproto::netmessages::CSVCMsg_ServerInfo;
 use protobuf::Message;
 use snap::raw::decompress_len;
 use snap::raw::Decoder as SnapDecoder;
@@ -64,6 +65,7 @@ pub struct FirstPassOutput<'a> {
     pub added_temp_props: Vec<String>,
     pub wanted_players: AHashSet<u64>,
     pub header: AHashMap<String, String>,
+    pub pov_player_slot: Option<i32>,
     pub order_by_steamid: bool,
 }
 #[derive(Debug)]
@@ -188,6 +190,7 @@ impl<'a> FirstPassParser<'a> {
         Ok(FirstPassOutput {
             order_by_steamid: self.order_by_steamid,
             header: self.header.clone(),
+            pov_player_slot: self.pov_player_slot,
             fullpacket_offsets: self.fullpacket_offsets.clone(),
             settings: &self.settings,
             baselines: self.baselines.clone(),
@@ -271,12 +274,27 @@ impl<'a> FirstPassParser<'a> {
                 svc_CreateStringTable => self.parse_create_stringtable(&msg_bytes),
                 svc_UpdateStringTable => self.update_string_table(&msg_bytes),
                 svc_ClearAllStringTables => self.clear_stringtables(),
+                svc_ServerInfo => self.parse_server_info(&msg_bytes),
                 _ => Ok(()),
             };
             ok?
         }
         Ok(())
     }
+    // GOTV demos are recorded by the server, POV demos by a player's client
+    fn parse_server_info(&mut self, bytes: &[u8]) -> Result<(), DemoParserError> {
+        let server_info: CSVCMsg_ServerInfo = match Message::parse_from_bytes(bytes) {
+            Ok(info) => info,
+            Err(_) => return Err(DemoParserError::MalformedMessage),
+        };
+        let demo_kind = if server_info.is_hltv() { "gotv" } else { "pov" };
+        self.header.insert("demo_kind".to_string(), demo_kind.to_string());
+        self.pov_player_slot = match server_info.is_hltv() {
+            true => None,
+            false => Some(server_info.player_slot()),
+        };
+        Ok(())
+    }
     fn clear_stringtables(&mut self) -> Result<(), DemoParserError> {
         self.string_tables = vec![];
         Ok(())
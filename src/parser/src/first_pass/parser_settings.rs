@@ -2,8 +2,12 @@ use super::sendtables::Serializer;
 use super::stringtables::StringTable;
 use crate::first_pass::prop_controller::PropController;
 use crate::first_pass::prop_controller::PropInfo;
+use crate::first_pass::prop_filter::PropFilter;
 use crate::first_pass::read_bits::DemoParserError;
 use crate::first_pass::stringtables::UserInfo;
+use crate::maps::geometry::MapGeometry;
+use crate::maps::nav::NavMesh;
+use crate::maps::overview::MapOverview;
 use crate::maps::FRIENDLY_NAMES_MAPPING;
 use crate::maps::NON_MULTITHREADABLE_PROPS;
 use crate::second_pass::decoder::QfMapper;
@@ -18,9 +22,12 @@ use csgoproto::demo::CDemoSendTables;
 use csgoproto::netmessages::csvcmsg_game_event_list::Descriptor_t;
 use memmap2::Mmap;
 use memmap2::MmapOptions;
+use rayon::ThreadPool;
+use rayon::ThreadPoolBuilder;
 use std::collections::BTreeMap;
 use std::fs::File;
 use std::sync::Arc;
+use std::time::Duration;
 
 #[derive(Debug, Clone)]
 pub struct ParserInputs<'a> {
@@ -28,7 +35,7 @@ pub struct ParserInputs<'a> {
     pub wanted_players: Vec<u64>,
     pub wanted_player_props: Vec<String>,
     pub wanted_other_props: Vec<String>,
-    pub wanted_prop_states: AHashMap<String, Variant>,
+    pub wanted_prop_states: PropFilter,
     pub wanted_ticks: Vec<i32>,
     pub wanted_events: Vec<String>,
     pub parse_ents: bool,
@@ -38,6 +45,90 @@ pub struct ParserInputs<'a> {
     pub only_convars: bool,
     pub huffman_lookup_table: &'a Vec<(u8, u8)>,
     pub order_by_steamid: bool,
+    pub change_log: bool,
+    pub parse_money_ledger: bool,
+    pub parse_bomb: bool,
+    pub parse_spotting: bool,
+    // Adds distance, speed, angles and more to player_death events
+    pub parse_kill_context: bool,
+    pub parse_shots: bool,
+    // Groups rifle shots into sprays, also enables parse_shots and usercmd parsing
+    pub parse_sprays: bool,
+    // Every usercmd as its own row instead of sampled at wanted ticks
+    pub parse_usercmds: bool,
+    // Jumps, bhops, counter-strafes, crouch-peeks and ladders derived from per tick player state
+    pub parse_movement: bool,
+    // Weapon provenance (purchaser, owners, kills) and weapons lying on the ground at wanted ticks
+    pub parse_weapons: bool,
+    // Rows for every entity of these classes (CChicken, CPlantedC4...), empty means none
+    pub wanted_entity_classes: Vec<String>,
+    // Props for those rows, relative to the class ("m_iHealth") or full names. "X", "Y" and "Z" are positions
+    pub wanted_entity_props: Vec<String>,
+    // Classes the opt-in entity_created and entity_deleted events are emitted for, empty means all
+    pub entity_event_classes: Vec<String>,
+    // Props that emit a prop_changed event every time their value changes
+    pub wanted_prop_changes: Vec<String>,
+    // Overrides the built-in overview picked from the map name in the header
+    pub map_overview: Option<MapOverview>,
+    pub nav_mesh: Option<Arc<NavMesh>>,
+    pub map_geometry: Option<Arc<dyn MapGeometry>>,
+    // (observer, target) steamids to compute visibility for, empty means all pairs
+    pub visibility_pairs: Option<Vec<(u64, u64)>>,
+    // Horizontal fov in degrees, enables per pair fov records and crosshair placement
+    pub fov: Option<f32>,
+    pub parallelism: ParallelismSettings,
+}
+
+#[derive(Debug, Clone)]
+pub struct ParallelismSettings {
+    // All parallel work runs on this pool instead of the global rayon pool
+    pub thread_pool: Option<Arc<ThreadPool>>,
+    // Builds a pool of this size for the parse, ignored if thread_pool is set
+    pub num_threads: Option<usize>,
+    // How the frame scanner splits the file while looking for fullpackets
+    pub chunking: Chunking,
+    // How long to wait for the next chunk from the frame scanner before falling back
+    pub fallback_timeout: Duration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Chunking {
+    Count(usize),
+    // Bytes per chunk
+    TargetSize(usize),
+}
+
+impl Default for ParallelismSettings {
+    fn default() -> Self {
+        ParallelismSettings {
+            thread_pool: None,
+            num_threads: None,
+            chunking: Chunking::Count(12),
+            fallback_timeout: Duration::from_secs(3),
+        }
+    }
+}
+
+impl ParallelismSettings {
+    // None means the global pool
+    pub fn build_pool(&self) -> Result<Option<Arc<ThreadPool>>, DemoParserError> {
+        if let Some(pool) = &self.thread_pool {
+            return Ok(Some(pool.clone()));
+        }
+        match self.num_threads {
+            Some(num_threads) => match ThreadPoolBuilder::new().num_threads(num_threads).build() {
+                Ok(pool) => Ok(Some(Arc::new(pool))),
+                Err(e) => Err(DemoParserError::ThreadPoolBuildError(e.to_string())),
+            },
+            None => Ok(None),
+        }
+    }
+    pub fn num_chunks(&self, demo_len: usize) -> usize {
+        match self.chunking {
+            Chunking::Count(n) => n.max(1),
+            Chunking::TargetSize(size) => demo_len.div_ceil(size.max(1)).max(1),
+        }
+    }
 }
 
 pub struct FirstPassParser<'a> {
@@ -65,7 +156,7 @@ pub struct FirstPassParser<'a> {
     pub wanted_players: AHashSet<u64, RandomState>,
     pub wanted_ticks: AHashSet<i32, RandomState>,
     pub wanted_other_props: Vec<String>,
-    pub wanted_prop_states: AHashMap<String, Variant>,
+    pub wanted_prop_states: PropFilter,
     pub wanted_events: Vec<String>,
     pub parse_entities: bool,
     pub parse_projectiles: bool,
@@ -76,6 +167,8 @@ pub struct FirstPassParser<'a> {
     pub only_header: bool,
     pub prop_infos: Vec<PropInfo>,
     pub header: AHashMap<String, String>,
+    // Slot of the player who recorded the demo, None for GOTV demos
+    pub pov_player_slot: Option<i32>,
     pub is_multithreadable: bool,
     pub needs_velocity: bool,
     pub sendtable_message: Option<CDemoSendTables>,
@@ -99,6 +192,7 @@ impl<'a> FirstPassParser<'a> {
             added_temp_props: vec![],
             is_multithreadable: check_multithreadability(&inputs.wanted_player_props),
             stringtable_players: BTreeMap::default(),
+            pov_player_slot: None,
             only_header: inputs.only_header,
             ge_list_set: false,
             cls_by_id_set: false,
@@ -145,6 +239,7 @@ impl<'a> FirstPassParser<'a> {
         }
     }
 }
+// False if chunks can't start straight from a fullpacket and need the state of the chunks before them
 pub fn check_multithreadability(player_props: &[String]) -> bool {
     for name in player_props {
         if NON_MULTITHREADABLE_PROPS.contains(name) {
@@ -176,6 +271,13 @@ pub fn rm_map_user_friendly_names(map: &AHashMap<String, Variant>) -> Result<AHa
     Ok(real_names_map)
 }
 
+pub fn rm_filter_user_friendly_names(filter: &PropFilter) -> Result<PropFilter, DemoParserError> {
+    filter.map_prop_names(&mut |name| match FRIENDLY_NAMES_MAPPING.get(name) {
+        Some(real_name) => Ok(real_name.to_string()),
+        None => Err(DemoParserError::UnknownPropName(name.to_string())),
+    })
+}
+
 pub fn create_mmap(path: String) -> Result<Mmap, DemoParserError> {
     let file = match File::open(path) {
         Err(e) => return Err(DemoParserError::FileNotFound(format!("{}", e))),
@@ -1,3 +1,4 @@
+use crate::first_pass::prop_filter::PropFilter;
 use crate::first_pass::sendtables::Field;
 use crate::first_pass::sendtables::Serializer;
 use crate::first_pass::sendtables::ValueField;
@@ -6,10 +7,11 @@ use crate::maps::CUSTOM_PLAYER_PROP_IDS;
 use crate::maps::TYPEHM;
 use crate::second_pass::collect_data::PropType;
 use crate::second_pass::parser_settings::SpecialIDs;
-use crate::second_pass::variants::Variant;
 use ahash::AHashMap;
 
 pub const PLAYER_ENTITY_HANDLE_MISSING: i32 = 2047;
+// CS2 servers run at 64 ticks per second
+pub const TICKRATE: f32 = 64.0;
 pub const SPECTATOR_TEAM_NUM: u32 = 1;
 pub const BUTTONS_BASEID: u32 = 100000;
 pub const NORMAL_PROP_BASEID: u32 = 1000;
@@ -49,6 +51,10 @@ pub const ITEM_PURCHASE_COST: u32 = 400000000;
 pub const ITEM_PURCHASE_HANDLE: u32 = 500000000;
 pub const ITEM_PURCHASE_NEW_DEF_IDX: u32 = 600000000;
 pub const FLATTENED_VEC_MAX_LEN: u32 = 100000;
+pub const INFERNO_FIRE_X_DELTA: u32 = 700000000;
+pub const INFERNO_FIRE_Y_DELTA: u32 = 710000000;
+pub const INFERNO_FIRE_Z_DELTA: u32 = 720000000;
+pub const INFERNO_FIRE_IS_BURNING: u32 = 730000000;
 
 pub const USERCMD_VIEWANGLE_X: u32 = 100000022;
 pub const USERCMD_VIEWANGLE_Y: u32 = 100000023;
@@ -63,15 +69,26 @@ pub const USERCMD_BUTTONSTATE_3: u32 = 100000031;
 pub const USERCMD_CONSUMED_SERVER_ANGLE_CHANGES: u32 = 100000032;
 pub const USERCMD_LEFTMOVE: u32 = 100000033;
 pub const USERCMD_WEAPON_SELECT: u32 = 100000034;
-pub const USERCMD_SUBTICK_MOVE_ANALOG_FORWARD_DELTA: u32 = 100000035;
-pub const USERCMD_SUBTICK_MOVE_ANALOG_LEFT_DELTA: u32 = 100000036;
-pub const USERCMD_SUBTICK_MOVE_BUTTON: u32 = 100000037;
-pub const USERCMD_SUBTICK_MOVE_WHEN: u32 = 100000038;
+pub const USERCMD_SUBTICK_MOVES: u32 = 100000035;
 pub const USERCMD_SUBTICK_LEFT_HAND_DESIRED: u32 = 100000039;
 
 pub const USERCMD_ATTACK_START_HISTORY_INDEX_1: u32 = 100000040;
 pub const USERCMD_ATTACK_START_HISTORY_INDEX_2: u32 = 100000041;
 pub const USERCMD_ATTACK_START_HISTORY_INDEX_3: u32 = 100000042;
+pub const IN_SMOKE_ID: u32 = 100000043;
+pub const RADAR_X_ID: u32 = 100000044;
+pub const RADAR_Y_ID: u32 = 100000045;
+pub const RADAR_LEVEL_ID: u32 = 100000046;
+pub const NAV_AREA_ID_ID: u32 = 100000047;
+pub const NAV_PLACE_ID: u32 = 100000048;
+pub const CLOSEST_ENEMY_ANGLE_ID: u32 = 100000049;
+pub const ENEMIES_IN_FOV_ID: u32 = 100000050;
+pub const USERCMD_LEGACY_COMMAND_NUMBER: u32 = 100000051;
+pub const USERCMD_CLIENT_TICK: u32 = 100000052;
+pub const USERCMD_UPMOVE: u32 = 100000053;
+pub const USERCMD_RANDOM_SEED: u32 = 100000054;
+pub const USERCMD_CMD_FLAGS: u32 = 100000055;
+pub const USERCMD_MOVE_CRC: u32 = 100000056;
 
 pub const USERCMD_INPUT_HISTORY_BASEID: u32 = 100001000;
 pub const INPUT_HISTORY_X_OFFSET: u32 = 0;
@@ -97,8 +114,9 @@ pub struct PropController {
     pub event_with_velocity: bool,
     pub needs_velocity: bool,
     pub path_to_name: AHashMap<[i32; 7], String>,
-    pub wanted_prop_states: AHashMap<String, Variant>,
-    pub wanted_prop_state_infos: Vec<WantedPropStateInfo>,
+    pub wanted_prop_states: PropFilter,
+    // Keyed by prop name, the prop_states filter looks these up for every player on every tick
+    pub wanted_prop_state_infos: AHashMap<String, WantedPropStateInfo>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -110,10 +128,10 @@ pub struct PropInfo {
     pub is_player_prop: bool,
 }
 
+// Prop read by the prop_states filter
 #[derive(Debug, Clone, PartialEq)]
 pub struct WantedPropStateInfo {
     pub base: PropInfo,
-    pub wanted_prop_state: Variant,
 }
 
 pub enum PropCollectionType {
@@ -126,7 +144,7 @@ impl PropController {
     pub fn new(
         wanted_player_props: Vec<String>,
         wanted_other_props: Vec<String>,
-        wanted_prop_states: AHashMap<String, Variant>,
+        wanted_prop_states: PropFilter,
         real_name_to_og_name: AHashMap<String, String>,
         needs_velocty: bool,
         wanted_events: &[String],
@@ -146,10 +164,14 @@ impl PropController {
             path_to_name: AHashMap::default(),
             needs_velocity: needs_velocty,
             wanted_prop_states,
-            wanted_prop_state_infos: vec![],
+            wanted_prop_state_infos: AHashMap::default(),
         }
     }
 
+    fn insert_prop_state_info(&mut self, info: WantedPropStateInfo) {
+        self.wanted_prop_state_infos.entry(info.base.prop_name.clone()).or_insert(info);
+    }
+
     pub fn set_custom_propinfos(&mut self) {
         let button_names = BUTTONMAP.keys();
         let mut someid = BUTTONS_BASEID;
@@ -165,8 +187,8 @@ impl PropController {
                 });
                 someid += 1;
             }
-            if let Some(wanted_state) = self.wanted_prop_states.get(&(bn.to_string())) {
-                self.wanted_prop_state_infos.push(WantedPropStateInfo {
+            if self.wanted_prop_states.references(bn) {
+                self.insert_prop_state_info(WantedPropStateInfo {
                     base: PropInfo {
                         id: someid2,
                         prop_type: PropType::Button,
@@ -174,7 +196,6 @@ impl PropController {
                         prop_friendly_name: bn.to_string(),
                         is_player_prop: true,
                     },
-                    wanted_prop_state: wanted_state.clone(),
                 });
                 someid2 += 1;
             }
@@ -194,8 +215,8 @@ impl PropController {
                     is_player_prop: true,
                 })
             }
-            if let Some(wanted_state) = self.wanted_prop_states.get(&(custom_prop_name.to_string())) {
-                self.wanted_prop_state_infos.push(WantedPropStateInfo {
+            if self.wanted_prop_states.references(custom_prop_name) {
+                self.insert_prop_state_info(WantedPropStateInfo {
                     base: PropInfo {
                         id: *custom_prop_id,
                         prop_type: *TYPEHM.get(&custom_prop_name).unwrap_or(&PropType::Custom),
@@ -207,7 +228,6 @@ impl PropController {
                             .to_string(),
                         is_player_prop: true,
                     },
-                    wanted_prop_state: wanted_state.clone(),
                 })
             }
         }
@@ -221,8 +241,8 @@ impl PropController {
                 is_player_prop: true,
             });
         }
-        if let Some(wanted_state) = self.wanted_prop_states.get(&("game_time".to_string())) {
-            self.wanted_prop_state_infos.push(WantedPropStateInfo {
+        if self.wanted_prop_states.references("game_time") {
+            self.insert_prop_state_info(WantedPropStateInfo {
                 base: PropInfo {
                     id: GAME_TIME_ID,
                     prop_type: PropType::GameTime,
@@ -230,7 +250,6 @@ impl PropController {
                     prop_friendly_name: "game_time".to_string(),
                     is_player_prop: true,
                 },
-                wanted_prop_state: wanted_state.clone(),
             });
         }
         // Can also be non-player prop
@@ -316,8 +335,8 @@ impl PropController {
                     is_player_prop: false,
                 })
             }
-            if let Some(wanted_state) = self.wanted_prop_states.get(&prop_name.to_string()) {
-                self.wanted_prop_state_infos.push(WantedPropStateInfo {
+            if self.wanted_prop_states.references(prop_name) {
+                self.insert_prop_state_info(WantedPropStateInfo {
                     base: PropInfo {
                         id: f.prop_id as u32,
                         prop_type: *prop_type,
@@ -329,7 +348,6 @@ impl PropController {
                             .to_string(),
                         is_player_prop: true,
                     },
-                    wanted_prop_state: wanted_state.clone(),
                 });
             }
         }
@@ -390,15 +408,44 @@ impl PropController {
         if prop_name.contains("CEconItemAttribute.m_iRawValue32") {
             f.prop_id = WEAPON_SKIN_ID as u32;
         }
+        if split_at_dot[0] == "CInferno" {
+            self.set_inferno_special_ids(&prop_name, f.prop_id);
+            // Fixed size arrays with one entry per fire, flattened in get_propinfo
+            match prop_name.as_str() {
+                "m_fireXDelta" => f.prop_id = INFERNO_FIRE_X_DELTA,
+                "m_fireYDelta" => f.prop_id = INFERNO_FIRE_Y_DELTA,
+                "m_fireZDelta" => f.prop_id = INFERNO_FIRE_Z_DELTA,
+                "m_bFireIsBurning" => f.prop_id = INFERNO_FIRE_IS_BURNING,
+                _ => {}
+            }
+        }
         self.id += 1;
     }
 
+    fn set_inferno_special_ids(&mut self, name: &str, id: u32) {
+        // Body component name differs between entity types so only match on the end
+        match name.rsplit('.').next() {
+            Some("m_fireCount") => self.special_ids.inferno_fire_count = Some(id),
+            Some("m_cellX") => self.special_ids.inferno_cell_x = Some(id),
+            Some("m_cellY") => self.special_ids.inferno_cell_y = Some(id),
+            Some("m_cellZ") => self.special_ids.inferno_cell_z = Some(id),
+            Some("m_vecX") => self.special_ids.inferno_vec_x = Some(id),
+            Some("m_vecY") => self.special_ids.inferno_vec_y = Some(id),
+            Some("m_vecZ") => self.special_ids.inferno_vec_z = Some(id),
+            _ => {}
+        }
+    }
     fn set_special_ids(&mut self, name: &str, is_grenade_or_weapon: bool, id: u32) {
         if is_grenade_or_weapon {
             match name {
                 "m_bIsIncGrenade" => self.special_ids.is_incendiary_grenade = Some(id),
                 "m_hOwnerEntity" => self.special_ids.h_owner_entity = Some(id),
                 "m_nOwnerId" => self.special_ids.grenade_owner_id = Some(id),
+                "m_nBounces" => self.special_ids.grenade_bounces = Some(id),
+                "m_flC4Blow" => self.special_ids.c4_blow = Some(id),
+                "m_flDefuseCountDown" => self.special_ids.c4_defuse_countdown = Some(id),
+                "m_nBombSite" => self.special_ids.c4_bomb_site = Some(id),
+                "m_bDidSmokeEffect" => self.special_ids.did_smoke_effect = Some(id),
                 "CBodyComponentBaseAnimGraph.m_vecX" => self.special_ids.m_vec_x_grenade = Some(id),
                 "CBodyComponentBaseAnimGraph.m_vecY" => self.special_ids.m_vec_y_grenade = Some(id),
                 "CBodyComponentBaseAnimGraph.m_vecZ" => self.special_ids.m_vec_z_grenade = Some(id),
@@ -409,6 +456,9 @@ impl PropController {
                 "m_OriginalOwnerXuidLow" => self.special_ids.orig_own_low = Some(id),
                 "m_OriginalOwnerXuidHigh" => self.special_ids.orig_own_high = Some(id),
                 "m_szCustomName" => self.special_ids.custom_name = Some(id),
+                "m_iClip1" => self.special_ids.weapon_clip1 = Some(id),
+                "m_flRecoilIndex" => self.special_ids.recoil_index = Some(id),
+                "m_fAccuracyPenalty" => self.special_ids.accuracy_penalty = Some(id),
                 _ => {}
             };
         } else {
@@ -430,6 +480,10 @@ impl PropController {
                 "CCSPlayerPawn.CBodyComponentBaseAnimGraph.m_vecX" => self.special_ids.cell_x_offset_player = Some(id),
                 "CCSPlayerPawn.CBodyComponentBaseAnimGraph.m_cellY" => self.special_ids.cell_y_player = Some(id),
                 "CCSPlayerPawn.m_angEyeAngles" => self.special_ids.eye_angles = Some(id),
+                "CCSPlayerPawn.m_aimPunchAngle" => self.special_ids.aim_punch_angle = Some(id),
+                "CCSPlayerPawn.m_bSpottedByMask" => self.special_ids.spotted_by_mask = Some(id),
+                "CCSPlayerPawn.CCSPlayer_MovementServices.m_bDucked" => self.special_ids.ducked = Some(id),
+                "CCSPlayerPawn.CNetworkViewOffsetVector.m_vecZ" => self.special_ids.view_offset_z = Some(id),
                 "CCSPlayerPawn.CBodyComponentBaseAnimGraph.m_vecY" => self.special_ids.cell_y_offset_player = Some(id),
                 "CCSPlayerPawn.CBodyComponentBaseAnimGraph.m_cellZ" => self.special_ids.cell_z_player = Some(id),
                 "CCSPlayerPawn.CBodyComponentBaseAnimGraph.m_vecZ" => self.special_ids.cell_z_offset_player = Some(id),
@@ -438,6 +492,8 @@ impl PropController {
                 "CCSPlayerController.m_nPawnCharacterDefIndex" => self.special_ids.agent_skin_idx = Some(id),
                 "CCSPlayerPawn.m_bInBuyZone" => self.special_ids.in_buy_zone = Some(id),
                 "CCSPlayerPawn.m_hGroundEntity" => self.special_ids.is_airborn = Some(id),
+                "CCSPlayerPawn.m_MoveType" => self.special_ids.move_type = Some(id),
+                "CCSPlayerController.CCSPlayerController_InGameMoneyServices.m_iAccount" => self.special_ids.account = Some(id),
                 _ => {}
             };
         }
@@ -0,0 +1,423 @@
+use crate::first_pass::read_bits::DemoParserError;
+use crate::second_pass::variants::Variant;
+use ahash::AHashMap;
+use std::cmp::Ordering;
+
+/*
+Filter that decides which rows parse_ticks keeps, evaluated per player before the row is collected.
+Parsed from expressions like:
+
+    is_alive == true AND health < 30 AND is_warmup_period == false
+    (team_num IN (2, 3) OR balance BETWEEN 1000 AND 4000) AND NOT active_weapon_name IS NULL
+
+Props can be player props or rules props. A prop missing from the player makes every comparison
+false, only IS NULL matches it.
+*/
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropFilter {
+    // Empty And matches every row
+    And(Vec<PropFilter>),
+    Or(Vec<PropFilter>),
+    Not(Box<PropFilter>),
+    Compare { prop: String, op: CompareOp, value: Variant },
+    Between { prop: String, low: Variant, high: Variant },
+    In { prop: String, values: Vec<Variant> },
+    IsNull { prop: String },
+}
+
+impl Default for PropFilter {
+    fn default() -> Self {
+        PropFilter::And(vec![])
+    }
+}
+
+// The old prop_states form: every prop has to equal its value
+impl From<AHashMap<String, Variant>> for PropFilter {
+    fn from(states: AHashMap<String, Variant>) -> Self {
+        PropFilter::And(
+            states
+                .into_iter()
+                .map(|(prop, value)| PropFilter::Compare {
+                    prop,
+                    op: CompareOp::Eq,
+                    value,
+                })
+                .collect(),
+        )
+    }
+}
+
+impl PropFilter {
+    pub fn parse(expression: &str) -> Result<PropFilter, DemoParserError> {
+        let tokens = tokenize(expression)?;
+        let mut parser = FilterParser { tokens, pos: 0 };
+        let filter = parser.parse_or()?;
+        match parser.tokens.get(parser.pos) {
+            None => Ok(filter),
+            Some(token) => Err(DemoParserError::InvalidPropFilter(format!("unexpected {:?} in {expression}", token))),
+        }
+    }
+    pub fn is_empty(&self) -> bool {
+        matches!(self, PropFilter::And(filters) if filters.is_empty())
+    }
+    pub fn references(&self, name: &str) -> bool {
+        self.prop_names().contains(&name)
+    }
+    pub fn prop_names(&self) -> Vec<&str> {
+        match self {
+            PropFilter::And(filters) | PropFilter::Or(filters) => filters.iter().flat_map(|f| f.prop_names()).collect(),
+            PropFilter::Not(filter) => filter.prop_names(),
+            PropFilter::Compare { prop, .. } | PropFilter::Between { prop, .. } | PropFilter::In { prop, .. } | PropFilter::IsNull { prop } => {
+                vec![prop.as_str()]
+            }
+        }
+    }
+    // Same filter with every prop name passed through f, used for swapping friendly names to real ones
+    pub fn map_prop_names<F>(&self, f: &mut F) -> Result<PropFilter, DemoParserError>
+    where
+        F: FnMut(&str) -> Result<String, DemoParserError>,
+    {
+        Ok(match self {
+            PropFilter::And(filters) => PropFilter::And(filters.iter().map(|x| x.map_prop_names(f)).collect::<Result<_, _>>()?),
+            PropFilter::Or(filters) => PropFilter::Or(filters.iter().map(|x| x.map_prop_names(f)).collect::<Result<_, _>>()?),
+            PropFilter::Not(filter) => PropFilter::Not(Box::new(filter.map_prop_names(f)?)),
+            PropFilter::Compare { prop, op, value } => PropFilter::Compare {
+                prop: f(prop)?,
+                op: *op,
+                value: value.clone(),
+            },
+            PropFilter::Between { prop, low, high } => PropFilter::Between {
+                prop: f(prop)?,
+                low: low.clone(),
+                high: high.clone(),
+            },
+            PropFilter::In { prop, values } => PropFilter::In {
+                prop: f(prop)?,
+                values: values.clone(),
+            },
+            PropFilter::IsNull { prop } => PropFilter::IsNull { prop: f(prop)? },
+        })
+    }
+    pub fn matches<F>(&self, value_of: &F) -> bool
+    where
+        F: Fn(&str) -> Option<Variant>,
+    {
+        match self {
+            PropFilter::And(filters) => filters.iter().all(|f| f.matches(value_of)),
+            PropFilter::Or(filters) => filters.iter().any(|f| f.matches(value_of)),
+            PropFilter::Not(filter) => !filter.matches(value_of),
+            PropFilter::Compare { prop, op, value } => match value_of(prop) {
+                None => false,
+                Some(prop_value) => {
+                    let ordering = compare_variants(&prop_value, value);
+                    match op {
+                        CompareOp::Eq => ordering == Some(Ordering::Equal),
+                        CompareOp::Ne => ordering != Some(Ordering::Equal),
+                        CompareOp::Lt => ordering == Some(Ordering::Less),
+                        CompareOp::Le => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+                        CompareOp::Gt => ordering == Some(Ordering::Greater),
+                        CompareOp::Ge => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+                    }
+                }
+            },
+            PropFilter::Between { prop, low, high } => match value_of(prop) {
+                None => false,
+                Some(prop_value) => {
+                    matches!(compare_variants(&prop_value, low), Some(Ordering::Greater | Ordering::Equal))
+                        && matches!(compare_variants(&prop_value, high), Some(Ordering::Less | Ordering::Equal))
+                }
+            },
+            PropFilter::In { prop, values } => match value_of(prop) {
+                None => false,
+                Some(prop_value) => values.iter().any(|v| compare_variants(&prop_value, v) == Some(Ordering::Equal)),
+            },
+            PropFilter::IsNull { prop } => value_of(prop).is_none(),
+        }
+    }
+}
+
+fn as_integer(v: &Variant) -> Option<i128> {
+    match v {
+        Variant::Bool(b) => Some(*b as i128),
+        Variant::I32(x) => Some(*x as i128),
+        Variant::U32(x) => Some(*x as i128),
+        Variant::U64(x) => Some(*x as i128),
+        Variant::I16(x) => Some(*x as i128),
+        Variant::U8(x) => Some(*x as i128),
+        _ => None,
+    }
+}
+
+fn as_float(v: &Variant) -> Option<f64> {
+    match v {
+        Variant::F32(x) => Some(*x as f64),
+        _ => as_integer(v).map(|x| x as f64),
+    }
+}
+
+// Numbers compare by value whatever their width so health < 30 works on any prop type
+fn compare_variants(a: &Variant, b: &Variant) -> Option<Ordering> {
+    if let (Some(a), Some(b)) = (as_integer(a), as_integer(b)) {
+        return Some(a.cmp(&b));
+    }
+    if let (Some(a), Some(b)) = (as_float(a), as_float(b)) {
+        return a.partial_cmp(&b);
+    }
+    match (a, b) {
+        (Variant::String(a), Variant::String(b)) => Some(a.cmp(b)),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Number(String),
+    Str(String),
+    Op(CompareOp),
+    LParen,
+    RParen,
+    Comma,
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>, DemoParserError> {
+    let chars: Vec<char> = expression.chars().collect();
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        match c {
+            _ if c.is_whitespace() => i += 1,
+            '(' => {
+                tokens.push(Token::LParen);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token::RParen);
+                i += 1;
+            }
+            ',' => {
+                tokens.push(Token::Comma);
+                i += 1;
+            }
+            '=' | '!' | '<' | '>' => {
+                let (op, len) = match (c, next) {
+                    ('=', Some('=')) => (CompareOp::Eq, 2),
+                    ('=', _) => (CompareOp::Eq, 1),
+                    ('!', Some('=')) => (CompareOp::Ne, 2),
+                    ('<', Some('=')) => (CompareOp::Le, 2),
+                    ('<', Some('>')) => (CompareOp::Ne, 2),
+                    ('<', _) => (CompareOp::Lt, 1),
+                    ('>', Some('=')) => (CompareOp::Ge, 2),
+                    ('>', _) => (CompareOp::Gt, 1),
+                    _ => return Err(DemoParserError::InvalidPropFilter(format!("unexpected '{c}' in {expression}"))),
+                };
+                tokens.push(Token::Op(op));
+                i += len;
+            }
+            '\'' | '"' => {
+                let end = match chars[i + 1..].iter().position(|x| *x == c) {
+                    Some(end) => i + 1 + end,
+                    None => return Err(DemoParserError::InvalidPropFilter(format!("unclosed string in {expression}"))),
+                };
+                tokens.push(Token::Str(chars[i + 1..end].iter().collect()));
+                i = end + 1;
+            }
+            _ if c.is_ascii_digit() || (c == '-' && next.is_some_and(|n| n.is_ascii_digit() || n == '.')) || c == '.' => {
+                let start = i;
+                i += 1;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.' || chars[i] == 'e') {
+                    i += 1;
+                }
+                tokens.push(Token::Number(chars[start..i].iter().collect()));
+            }
+            _ if c.is_alphanumeric() || c == '_' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '.') {
+                    i += 1;
+                }
+                tokens.push(Token::Word(chars[start..i].iter().collect()));
+            }
+            _ => return Err(DemoParserError::InvalidPropFilter(format!("unexpected '{c}' in {expression}"))),
+        }
+    }
+    Ok(tokens)
+}
+
+struct FilterParser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl FilterParser {
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.tokens.get(self.pos), Some(Token::Word(w)) if w.eq_ignore_ascii_case(keyword))
+    }
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let found = self.peek_keyword(keyword);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+    fn expect(&mut self, token: Token) -> Result<(), DemoParserError> {
+        match self.tokens.get(self.pos) {
+            Some(t) if *t == token => {
+                self.pos += 1;
+                Ok(())
+            }
+            t => Err(DemoParserError::InvalidPropFilter(format!("expected {:?}, got {:?}", token, t))),
+        }
+    }
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), DemoParserError> {
+        match self.eat_keyword(keyword) {
+            true => Ok(()),
+            false => Err(DemoParserError::InvalidPropFilter(format!(
+                "expected {keyword}, got {:?}",
+                self.tokens.get(self.pos)
+            ))),
+        }
+    }
+    fn parse_or(&mut self) -> Result<PropFilter, DemoParserError> {
+        let mut filters = vec![self.parse_and()?];
+        while self.eat_keyword("or") {
+            filters.push(self.parse_and()?);
+        }
+        Ok(match filters.len() {
+            1 => filters.remove(0),
+            _ => PropFilter::Or(filters),
+        })
+    }
+    fn parse_and(&mut self) -> Result<PropFilter, DemoParserError> {
+        let mut filters = vec![self.parse_not()?];
+        while self.eat_keyword("and") {
+            filters.push(self.parse_not()?);
+        }
+        Ok(match filters.len() {
+            1 => filters.remove(0),
+            _ => PropFilter::And(filters),
+        })
+    }
+    fn parse_not(&mut self) -> Result<PropFilter, DemoParserError> {
+        if self.eat_keyword("not") {
+            return Ok(PropFilter::Not(Box::new(self.parse_not()?)));
+        }
+        if self.tokens.get(self.pos) == Some(&Token::LParen) {
+            self.pos += 1;
+            let filter = self.parse_or()?;
+            self.expect(Token::RParen)?;
+            return Ok(filter);
+        }
+        self.parse_predicate()
+    }
+    fn parse_predicate(&mut self) -> Result<PropFilter, DemoParserError> {
+        let prop = match self.tokens.get(self.pos) {
+            Some(Token::Word(w)) => w.clone(),
+            t => return Err(DemoParserError::InvalidPropFilter(format!("expected prop name, got {:?}", t))),
+        };
+        self.pos += 1;
+        if let Some(Token::Op(op)) = self.tokens.get(self.pos) {
+            let op = *op;
+            self.pos += 1;
+            let value = self.parse_value()?;
+            return Ok(PropFilter::Compare { prop, op, value });
+        }
+        if self.eat_keyword("between") {
+            let low = self.parse_value()?;
+            self.expect_keyword("and")?;
+            let high = self.parse_value()?;
+            return Ok(PropFilter::Between { prop, low, high });
+        }
+        if self.eat_keyword("is") {
+            let negated = self.eat_keyword("not");
+            self.expect_keyword("null")?;
+            let filter = PropFilter::IsNull { prop };
+            return Ok(match negated {
+                true => PropFilter::Not(Box::new(filter)),
+                false => filter,
+            });
+        }
+        let negated = self.eat_keyword("not");
+        if self.eat_keyword("in") {
+            self.expect(Token::LParen)?;
+            let mut values = vec![self.parse_value()?];
+            while self.tokens.get(self.pos) == Some(&Token::Comma) {
+                self.pos += 1;
+                values.push(self.parse_value()?);
+            }
+            self.expect(Token::RParen)?;
+            let filter = PropFilter::In { prop, values };
+            return Ok(match negated {
+                true => PropFilter::Not(Box::new(filter)),
+                false => filter,
+            });
+        }
+        Err(DemoParserError::InvalidPropFilter(format!(
+            "expected comparison after {prop}, got {:?}",
+            self.tokens.get(self.pos)
+        )))
+    }
+    fn parse_value(&mut self) -> Result<Variant, DemoParserError> {
+        let value = match self.tokens.get(self.pos) {
+            Some(Token::Str(s)) => Variant::String(s.clone()),
+            Some(Token::Word(w)) if w.eq_ignore_ascii_case("true") => Variant::Bool(true),
+            Some(Token::Word(w)) if w.eq_ignore_ascii_case("false") => Variant::Bool(false),
+            Some(Token::Number(n)) => {
+                if let Ok(x) = n.parse::<i32>() {
+                    Variant::I32(x)
+                } else if let Ok(x) = n.parse::<u64>() {
+                    Variant::U64(x)
+                } else if let Ok(x) = n.parse::<f32>() {
+                    Variant::F32(x)
+                } else {
+                    return Err(DemoParserError::InvalidPropFilter(format!("invalid number {n}")));
+                }
+            }
+            t => return Err(DemoParserError::InvalidPropFilter(format!("expected value, got {:?}", t))),
+        };
+        self.pos += 1;
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prop_filter() {
+        let filter = PropFilter::parse("is_alive == true AND health < 30 AND (team_num IN (2, 3) OR name IS NULL) AND balance BETWEEN 100 and 4000.5").unwrap();
+        assert_eq!(filter.prop_names(), vec!["is_alive", "health", "team_num", "name", "balance"]);
+        let row = |health: i32, team: u32| {
+            move |name: &str| match name {
+                "is_alive" => Some(Variant::Bool(true)),
+                "health" => Some(Variant::I32(health)),
+                "team_num" => Some(Variant::U32(team)),
+                "name" => Some(Variant::String("player".to_string())),
+                "balance" => Some(Variant::I32(800)),
+                _ => None,
+            }
+        };
+        assert!(filter.matches(&row(20, 2)));
+        assert!(!filter.matches(&row(30, 2)));
+        assert!(!filter.matches(&row(20, 1)));
+
+        let filter = PropFilter::parse("NOT name != 'player' AND active_weapon IS NULL AND team_num NOT IN (1)").unwrap();
+        assert!(filter.matches(&row(20, 2)));
+        assert!(PropFilter::parse("health <").is_err());
+        assert!(PropFilter::parse("health < 30 30").is_err());
+        assert!(PropFilter::default().matches(&|_: &str| None));
+    }
+}
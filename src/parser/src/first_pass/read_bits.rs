@@ -249,6 +249,12 @@ pub enum DemoParserError {
     ImpossibleCmd,
     UnkVoiceFormat,
     MalformedVoicePacket,
+    InvalidOverviewFile(String),
+    InvalidNavFile(String),
+    InvalidGeometryFile(String),
+    ThreadPoolBuildError(String),
+    InvalidPropFilter(String),
+    ChunkPanicked(String),
 }
 
 impl std::error::Error for DemoParserError {}
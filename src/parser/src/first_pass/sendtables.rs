@@ -4,6 +4,10 @@ use crate::first_pass::parser_settings::needs_velocity;
 use crate::first_pass::parser_settings::FirstPassParser;
 use crate::first_pass::prop_controller::PropController;
 use crate::first_pass::prop_controller::FLATTENED_VEC_MAX_LEN;
+use crate::first_pass::prop_controller::INFERNO_FIRE_IS_BURNING;
+use crate::first_pass::prop_controller::INFERNO_FIRE_X_DELTA;
+use crate::first_pass::prop_controller::INFERNO_FIRE_Y_DELTA;
+use crate::first_pass::prop_controller::INFERNO_FIRE_Z_DELTA;
 use crate::first_pass::prop_controller::ITEM_PURCHASE_COST;
 use crate::first_pass::prop_controller::ITEM_PURCHASE_COUNT;
 use crate::first_pass::prop_controller::ITEM_PURCHASE_DEF_IDX;
@@ -486,6 +490,9 @@ pub fn get_propinfo(field: &Field, path: &FieldPath) -> Option<FieldInfo> {
             fi.prop_id = MY_WEAPONS_OFFSET + path.path[2] as u32 + 1;
         }
     }
+    if fi.prop_id == INFERNO_FIRE_X_DELTA || fi.prop_id == INFERNO_FIRE_Y_DELTA || fi.prop_id == INFERNO_FIRE_Z_DELTA || fi.prop_id == INFERNO_FIRE_IS_BURNING {
+        fi.prop_id += path.path[path.last] as u32;
+    }
     if fi.prop_id == WEAPON_SKIN_ID {
         fi.prop_id = WEAPON_SKIN_ID + path.path[1] as u32;
     }
@@ -8,6 +8,10 @@ use phf_macros::phf_map;
 use phf_macros::phf_set;
 use NetmessageType::*;
 
+pub mod geometry;
+pub mod nav;
+pub mod overview;
+
 pub static FACTORIES_MAP: phf::Set<&'static str> = phf_set! {
     "uint64",
     "float32",
@@ -408,6 +412,7 @@ pub fn netmessage_type_from_int(msg_type: i32) -> NetmessageType {
         381 => CS_UM_DeepStats,
         382 => CS_UM_UtilMsg,
         383 => CS_UM_ShootInfo,
+        385 => CS_UM_CounterStrafe,
         _ => Unknown,
     }
 }
@@ -546,6 +551,7 @@ pub enum NetmessageType {
     CS_UM_DeepStats,
     CS_UM_UtilMsg,
     CS_UM_ShootInfo,
+    CS_UM_CounterStrafe,
     UM_AchievementEvent,
     UM_CloseCaption,
     UM_CloseCaptionDirect,
@@ -1948,6 +1954,14 @@ pub static CUSTOM_PLAYER_PROP_IDS: phf::Map<&'static str, u32> = phf_map! {
     "yaw"=> YAW_ID,
     "is_alive" => IS_ALIVE_ID,
     "is_airborne" => IS_AIRBORNE_ID,
+    "in_smoke" => IN_SMOKE_ID,
+    "radar_x" => RADAR_X_ID,
+    "radar_y" => RADAR_Y_ID,
+    "radar_level" => RADAR_LEVEL_ID,
+    "nav_area_id" => NAV_AREA_ID_ID,
+    "nav_place" => NAV_PLACE_ID,
+    "closest_enemy_angle" => CLOSEST_ENEMY_ANGLE_ID,
+    "enemies_in_fov" => ENEMIES_IN_FOV_ID,
     "agent_skin" => AGENT_SKIN_ID,
     "inventory" => INVENTORY_ID,
     "inventory_as_ids" => INVENTORY_AS_IDS_ID,
@@ -1974,6 +1988,16 @@ pub static CUSTOM_PLAYER_PROP_IDS: phf::Map<&'static str, u32> = phf_map! {
     "usercmd_left_hand_desired" => USERCMD_SUBTICK_LEFT_HAND_DESIRED,
     "usercmd_weapon_select" => USERCMD_WEAPON_SELECT,
     "usercmd_input_history" => USERCMD_INPUT_HISTORY_BASEID,
+    "usercmd_up_move" => USERCMD_UPMOVE,
+    "usercmd_legacy_command_number" => USERCMD_LEGACY_COMMAND_NUMBER,
+    "usercmd_client_tick" => USERCMD_CLIENT_TICK,
+    "usercmd_random_seed" => USERCMD_RANDOM_SEED,
+    "usercmd_cmd_flags" => USERCMD_CMD_FLAGS,
+    "usercmd_move_crc" => USERCMD_MOVE_CRC,
+    "usercmd_attack_start_history_index_1" => USERCMD_ATTACK_START_HISTORY_INDEX_1,
+    "usercmd_attack_start_history_index_2" => USERCMD_ATTACK_START_HISTORY_INDEX_2,
+    "usercmd_attack_start_history_index_3" => USERCMD_ATTACK_START_HISTORY_INDEX_3,
+    "usercmd_subtick_moves" => USERCMD_SUBTICK_MOVES,
 };
 
 pub static TYPEHM: phf::Map<&'static str, PropType> = phf_map! {
@@ -2005,6 +2029,16 @@ pub static TYPEHM: phf::Map<&'static str, PropType> = phf_map! {
     "usercmd_left_hand_desired" => PropType::Player,
     "usercmd_consumed_server_angle_changes" => PropType::Player,
     "usercmd_input_history" => PropType::Custom,
+    "usercmd_up_move" => PropType::Player,
+    "usercmd_legacy_command_number" => PropType::Player,
+    "usercmd_client_tick" => PropType::Player,
+    "usercmd_random_seed" => PropType::Player,
+    "usercmd_cmd_flags" => PropType::Player,
+    "usercmd_move_crc" => PropType::Player,
+    "usercmd_attack_start_history_index_1" => PropType::Player,
+    "usercmd_attack_start_history_index_2" => PropType::Player,
+    "usercmd_attack_start_history_index_3" => PropType::Player,
+    "usercmd_subtick_moves" => PropType::Custom,
 
 
     "CCSPlayerPawn.CCSPlayer_MovementServices.m_nButtonDownMaskPrev" => PropType::Player,
@@ -2403,6 +2437,14 @@ pub static TYPEHM: phf::Map<&'static str, PropType> = phf_map! {
     "weapon_float" => PropType::Custom,
     "weapon_stickers" => PropType::Custom,
     "is_airborne" => PropType::Custom,
+    "in_smoke" => PropType::Custom,
+    "radar_x" => PropType::Custom,
+    "radar_y" => PropType::Custom,
+    "radar_level" => PropType::Custom,
+    "nav_area_id" => PropType::Custom,
+    "nav_place" => PropType::Custom,
+    "closest_enemy_angle" => PropType::Custom,
+    "enemies_in_fov" => PropType::Custom,
     // Weapon
     "m_flAnimTime" => PropType::Weapon,
     "m_flSimulationTime"=> PropType::Weapon,
@@ -2604,6 +2646,16 @@ pub static FRIENDLY_NAMES_MAPPING: phf::Map<&'static str, &'static str> = phf_ma
     "usercmd_left_hand_desired" => "usercmd_left_hand_desired",
     "usercmd_consumed_server_angle_changes" => "usercmd_consumed_server_angle_changes",
     "usercmd_input_history" => "usercmd_input_history",
+    "usercmd_up_move" => "usercmd_up_move",
+    "usercmd_legacy_command_number" => "usercmd_legacy_command_number",
+    "usercmd_client_tick" => "usercmd_client_tick",
+    "usercmd_random_seed" => "usercmd_random_seed",
+    "usercmd_cmd_flags" => "usercmd_cmd_flags",
+    "usercmd_move_crc" => "usercmd_move_crc",
+    "usercmd_attack_start_history_index_1" => "usercmd_attack_start_history_index_1",
+    "usercmd_attack_start_history_index_2" => "usercmd_attack_start_history_index_2",
+    "usercmd_attack_start_history_index_3" => "usercmd_attack_start_history_index_3",
+    "usercmd_subtick_moves" => "usercmd_subtick_moves",
 
     "active_weapon_skin" => "weapon_skin",
     "weapon_skin_id" => "weapon_skin_id",
@@ -2885,6 +2937,14 @@ pub static FRIENDLY_NAMES_MAPPING: phf::Map<&'static str, &'static str> = phf_ma
     "next_secondary_attack_tick" => "m_nNextSecondaryAttackTick",
     "next_secondary_attack_tick_ratio"=> "m_flNextSecondaryAttackTickRatio",
     "is_airborne" => "is_airborne",
+    "in_smoke" => "in_smoke",
+    "radar_x" => "radar_x",
+    "radar_y" => "radar_y",
+    "radar_level" => "radar_level",
+    "nav_area_id" => "nav_area_id",
+    "nav_place" => "nav_place",
+    "closest_enemy_angle" => "closest_enemy_angle",
+    "enemies_in_fov" => "enemies_in_fov",
 };
 
 pub static GRENADE_FRIENDLY_NAMES: phf::Map<&'static str, &'static str> = phf_map! {
@@ -2894,6 +2954,25 @@ pub static GRENADE_FRIENDLY_NAMES: phf::Map<&'static str, &'static str> = phf_ma
     "CHEGrenadeProjectile" => "he_grenade",
     "CFlashbangProjectile" => "flashbang",
 };
+// Competitive kill rewards by the "weapon" field of player_death. Anything missing pays the default.
+pub static KILL_REWARDS: phf::Map<&'static str, i32> = phf_map! {
+    "awp" => 100,
+    "cz75a" => 100,
+    "taser" => 0,
+    "p90" => 300,
+    "mac10" => 600,
+    "mp9" => 600,
+    "mp7" => 600,
+    "mp5sd" => 600,
+    "ump45" => 600,
+    "bizon" => 600,
+    "nova" => 900,
+    "xm1014" => 900,
+    "mag7" => 900,
+    "sawedoff" => 900,
+};
+// Props that fullpackets don't have in sync with the deltas before them. Chunks that want these
+// get the entity state the previous chunks ended with, see second_pass/chunk_state.rs
 pub static NON_MULTITHREADABLE_PROPS: phf::Set<&'static str> = phf_set! {
     "CCSPlayerController.CCSPlayerController_ActionTrackingServices.m_iUtilityDamage",
     "CCSPlayerController.CCSPlayerController_ActionTrackingServices.CSPerRoundStats_t.m_iCashEarned",
@@ -0,0 +1,281 @@
+use crate::first_pass::read_bits::DemoParserError;
+use std::fmt::Debug;
+use std::fs;
+
+const MAX_TRIANGLES_PER_LEAF: usize = 4;
+const EPSILON: f32 = 1e-6;
+
+pub type Triangle = [[f32; 3]; 3];
+
+// Anything that can answer line-of-sight queries for a map
+pub trait MapGeometry: Debug + Send + Sync {
+    // True if the segment from -> to hits the geometry
+    fn raycast(&self, from: [f32; 3], to: [f32; 3]) -> bool;
+}
+
+#[derive(Debug, Clone)]
+struct BvhNode {
+    min: [f32; 3],
+    max: [f32; 3],
+    // Leaf: first triangle and count. Inner node: index of the left child (right is left + 1) and count 0
+    first: usize,
+    count: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct TriangleMesh {
+    pub triangles: Vec<Triangle>,
+    nodes: Vec<BvhNode>,
+}
+
+impl TriangleMesh {
+    pub fn new(mut triangles: Vec<Triangle>) -> TriangleMesh {
+        let mut nodes = vec![];
+        if !triangles.is_empty() {
+            let len = triangles.len();
+            nodes.push(BvhNode {
+                min: [0.0; 3],
+                max: [0.0; 3],
+                first: 0,
+                count: len,
+            });
+            build_node(&mut nodes, &mut triangles, 0);
+        }
+        TriangleMesh { triangles, nodes }
+    }
+    // Picks the format from the extension: .obj or a raw soup of little endian f32 triangles (9 per triangle)
+    pub fn from_file(path: &str) -> Result<TriangleMesh, DemoParserError> {
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) => return Err(DemoParserError::FileNotFound(format!("{}: {}", path, e))),
+        };
+        if path.to_lowercase().ends_with(".obj") {
+            match String::from_utf8(bytes) {
+                Ok(text) => TriangleMesh::from_obj(&text),
+                Err(_) => Err(DemoParserError::InvalidGeometryFile("obj file is not valid utf-8".to_string())),
+            }
+        } else {
+            TriangleMesh::from_tri_soup(&bytes)
+        }
+    }
+    pub fn from_obj(text: &str) -> Result<TriangleMesh, DemoParserError> {
+        let mut vertices: Vec<[f32; 3]> = vec![];
+        let mut triangles = vec![];
+        for line in text.lines() {
+            let mut parts = line.split_whitespace();
+            match parts.next() {
+                Some("v") => {
+                    let coords: Vec<f32> = parts.take(3).filter_map(|p| p.parse().ok()).collect();
+                    if coords.len() != 3 {
+                        return Err(DemoParserError::InvalidGeometryFile(format!("bad vertex: {}", line)));
+                    }
+                    vertices.push([coords[0], coords[1], coords[2]]);
+                }
+                Some("f") => {
+                    // Faces look like "f 1 2 3" or "f 1/1/1 2/2/2 3/3/3", indices start at 1 and negative ones are relative
+                    let mut face = vec![];
+                    for part in parts {
+                        let idx = part.split('/').next().and_then(|i| i.parse::<i64>().ok());
+                        let idx = match idx {
+                            Some(i) if i < 0 => vertices.len() as i64 + i,
+                            Some(i) => i - 1,
+                            None => return Err(DemoParserError::InvalidGeometryFile(format!("bad face: {}", line))),
+                        };
+                        match vertices.get(idx as usize) {
+                            Some(v) if idx >= 0 => face.push(*v),
+                            _ => return Err(DemoParserError::InvalidGeometryFile(format!("face index out of bounds: {}", line))),
+                        }
+                    }
+                    // Fan triangulation for polygons
+                    for i in 1..face.len().saturating_sub(1) {
+                        triangles.push([face[0], face[i], face[i + 1]]);
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(TriangleMesh::new(triangles))
+    }
+    pub fn from_tri_soup(bytes: &[u8]) -> Result<TriangleMesh, DemoParserError> {
+        if !bytes.len().is_multiple_of(36) {
+            return Err(DemoParserError::InvalidGeometryFile(format!("length {} is not a multiple of 36", bytes.len())));
+        }
+        let floats: Vec<f32> = bytes.chunks_exact(4).map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect();
+        let triangles = floats
+            .chunks_exact(9)
+            .map(|f| [[f[0], f[1], f[2]], [f[3], f[4], f[5]], [f[6], f[7], f[8]]])
+            .collect();
+        Ok(TriangleMesh::new(triangles))
+    }
+}
+
+impl MapGeometry for TriangleMesh {
+    fn raycast(&self, from: [f32; 3], to: [f32; 3]) -> bool {
+        if self.nodes.is_empty() {
+            return false;
+        }
+        let dir = sub(to, from);
+        let inv_dir = [1.0 / dir[0], 1.0 / dir[1], 1.0 / dir[2]];
+        let mut stack = vec![0];
+        while let Some(idx) = stack.pop() {
+            let node = &self.nodes[idx];
+            if !segment_hits_aabb(from, inv_dir, node.min, node.max) {
+                continue;
+            }
+            if node.count > 0 {
+                if self.triangles[node.first..node.first + node.count]
+                    .iter()
+                    .any(|tri| segment_hits_triangle(from, dir, tri))
+                {
+                    return true;
+                }
+            } else {
+                stack.push(node.first);
+                stack.push(node.first + 1);
+            }
+        }
+        false
+    }
+}
+
+fn build_node(nodes: &mut Vec<BvhNode>, triangles: &mut [Triangle], node_idx: usize) {
+    let (first, count) = (nodes[node_idx].first, nodes[node_idx].count);
+    let tris = &mut triangles[first..first + count];
+    let (min, max) = bounds(tris.iter().flat_map(|t| t.iter()));
+    nodes[node_idx].min = min;
+    nodes[node_idx].max = max;
+    if count <= MAX_TRIANGLES_PER_LEAF {
+        return;
+    }
+    // Split on the median of the longest axis of the centroids
+    let (cmin, cmax) = bounds(tris.iter().map(centroid).collect::<Vec<_>>().iter());
+    let extent = sub(cmax, cmin);
+    let axis = (0..3).max_by(|a, b| extent[*a].total_cmp(&extent[*b])).unwrap_or(0);
+    tris.sort_by(|a, b| centroid(a)[axis].total_cmp(&centroid(b)[axis]));
+    let half = count / 2;
+    let left = nodes.len();
+    nodes[node_idx].first = left;
+    nodes[node_idx].count = 0;
+    for (first, count) in [(first, half), (first + half, count - half)] {
+        nodes.push(BvhNode {
+            min: [0.0; 3],
+            max: [0.0; 3],
+            first,
+            count,
+        });
+    }
+    build_node(nodes, triangles, left);
+    build_node(nodes, triangles, left + 1);
+}
+
+fn bounds<'a, I: Iterator<Item = &'a [f32; 3]>>(points: I) -> ([f32; 3], [f32; 3]) {
+    let mut min = [f32::MAX; 3];
+    let mut max = [f32::MIN; 3];
+    for p in points {
+        for i in 0..3 {
+            min[i] = min[i].min(p[i]);
+            max[i] = max[i].max(p[i]);
+        }
+    }
+    (min, max)
+}
+
+fn centroid(tri: &Triangle) -> [f32; 3] {
+    let mut c = [0.0; 3];
+    for i in 0..3 {
+        c[i] = (tri[0][i] + tri[1][i] + tri[2][i]) / 3.0;
+    }
+    c
+}
+
+fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]]
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn segment_hits_aabb(origin: [f32; 3], inv_dir: [f32; 3], min: [f32; 3], max: [f32; 3]) -> bool {
+    // Slab test limited to the segment (t in [0, 1])
+    let mut t_min: f32 = 0.0;
+    let mut t_max: f32 = 1.0;
+    for i in 0..3 {
+        let t1 = (min[i] - origin[i]) * inv_dir[i];
+        let t2 = (max[i] - origin[i]) * inv_dir[i];
+        // NaN happens when the segment lies exactly on a slab boundary, treat as inside
+        if t1.is_nan() || t2.is_nan() {
+            continue;
+        }
+        t_min = t_min.max(t1.min(t2));
+        t_max = t_max.min(t1.max(t2));
+    }
+    t_min <= t_max
+}
+
+fn segment_hits_triangle(origin: [f32; 3], dir: [f32; 3], tri: &Triangle) -> bool {
+    // Möller–Trumbore, hits at the very ends of the segment don't count
+    let edge1 = sub(tri[1], tri[0]);
+    let edge2 = sub(tri[2], tri[0]);
+    let p = cross(dir, edge2);
+    let det = dot(edge1, p);
+    if det.abs() < EPSILON {
+        return false;
+    }
+    let inv_det = 1.0 / det;
+    let s = sub(origin, tri[0]);
+    let u = dot(s, p) * inv_det;
+    if !(0.0..=1.0).contains(&u) {
+        return false;
+    }
+    let q = cross(s, edge1);
+    let v = dot(dir, q) * inv_det;
+    if v < 0.0 || u + v > 1.0 {
+        return false;
+    }
+    let t = dot(edge2, q) * inv_det;
+    t > EPSILON && t < 1.0 - EPSILON
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Wall on the plane x = 0 from y -100..100 and z 0..100
+    fn wall(x: f32) -> Vec<Triangle> {
+        vec![
+            [[x, -100.0, 0.0], [x, 100.0, 0.0], [x, 100.0, 100.0]],
+            [[x, -100.0, 0.0], [x, 100.0, 100.0], [x, -100.0, 100.0]],
+        ]
+    }
+
+    #[test]
+    fn test_raycast_blocked_by_wall() {
+        // Enough walls to force a few levels of BVH
+        let mut triangles = vec![];
+        for i in 0..10 {
+            triangles.extend(wall(i as f32 * 1000.0));
+        }
+        let mesh = TriangleMesh::new(triangles);
+        assert!(mesh.raycast([-50.0, 0.0, 50.0], [50.0, 0.0, 50.0]));
+        assert!(mesh.raycast([8950.0, 0.0, 50.0], [9050.0, 10.0, 60.0]));
+        // Over the wall, next to the wall and stopping before the wall
+        assert!(!mesh.raycast([-50.0, 0.0, 150.0], [50.0, 0.0, 150.0]));
+        assert!(!mesh.raycast([-50.0, 150.0, 50.0], [50.0, 150.0, 50.0]));
+        assert!(!mesh.raycast([-50.0, 0.0, 50.0], [-10.0, 0.0, 50.0]));
+        assert!(!mesh.raycast([10.0, 0.0, 50.0], [990.0, 0.0, 50.0]));
+    }
+    #[test]
+    fn test_mesh_from_obj() {
+        let text = "# quad\nv 0 -100 0\nv 0 100 0\nv 0 100 100\nv 0 -100 100\nf 1/1 2/2 3/3 4/4\n";
+        let mesh = TriangleMesh::from_obj(text).unwrap();
+        assert_eq!(mesh.triangles.len(), 2);
+        assert!(mesh.raycast([-50.0, 0.0, 50.0], [50.0, 0.0, 50.0]));
+        assert!(TriangleMesh::from_obj("v 0 0 0\nf 1 2 3\n").is_err());
+        assert!(TriangleMesh::from_tri_soup(&[0; 35]).is_err());
+    }
+}
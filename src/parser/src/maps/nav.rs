@@ -0,0 +1,347 @@
+use crate::first_pass::read_bits::DemoParserError;
+use ahash::AHashMap;
+use std::fs;
+
+const NAV_MAGIC: u32 = 0xFEEDFACE;
+// Oldest and newest CS2 nav versions
+const MIN_NAV_VERSION: u32 = 30;
+const MAX_NAV_VERSION: u32 = 35;
+const GRID_CELL_SIZE: f32 = 256.0;
+// Players standing on stairs/slopes can be slightly below the area
+const AREA_Z_TOLERANCE: f32 = 32.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NavArea {
+    pub area_id: u32,
+    pub hull_index: u8,
+    pub dynamic_attribute_flags: u64,
+    pub corners: Vec<[f32; 3]>,
+    pub connections: Vec<u32>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NavMesh {
+    pub version: u32,
+    pub sub_version: u32,
+    pub areas: Vec<NavArea>,
+    // Uniform grid on the xy-plane, each cell holds the indices of the areas overlapping it
+    grid: AHashMap<(i32, i32), Vec<usize>>,
+}
+
+struct NavReader<'a> {
+    bytes: &'a [u8],
+    ptr: usize,
+}
+
+impl<'a> NavReader<'a> {
+    fn read_n<const N: usize>(&mut self) -> Result<[u8; N], DemoParserError> {
+        match self.bytes.get(self.ptr..self.ptr + N) {
+            Some(b) => {
+                self.ptr += N;
+                let mut arr = [0; N];
+                arr.copy_from_slice(b);
+                Ok(arr)
+            }
+            None => Err(DemoParserError::InvalidNavFile(format!("unexpected end of file at byte {}", self.ptr))),
+        }
+    }
+    fn read_u8(&mut self) -> Result<u8, DemoParserError> {
+        Ok(self.read_n::<1>()?[0])
+    }
+    fn read_u32(&mut self) -> Result<u32, DemoParserError> {
+        Ok(u32::from_le_bytes(self.read_n()?))
+    }
+    fn read_u64(&mut self) -> Result<u64, DemoParserError> {
+        Ok(u64::from_le_bytes(self.read_n()?))
+    }
+    fn read_f32(&mut self) -> Result<f32, DemoParserError> {
+        Ok(f32::from_le_bytes(self.read_n()?))
+    }
+    fn read_vector(&mut self) -> Result<[f32; 3], DemoParserError> {
+        Ok([self.read_f32()?, self.read_f32()?, self.read_f32()?])
+    }
+    fn skip(&mut self, n: usize) -> Result<(), DemoParserError> {
+        if self.ptr + n > self.bytes.len() {
+            return Err(DemoParserError::InvalidNavFile(format!("unexpected end of file at byte {}", self.ptr)));
+        }
+        self.ptr += n;
+        Ok(())
+    }
+}
+
+impl NavMesh {
+    pub fn from_nav_file(path: &str) -> Result<NavMesh, DemoParserError> {
+        match fs::read(path) {
+            Ok(bytes) => NavMesh::from_bytes(&bytes),
+            Err(e) => Err(DemoParserError::FileNotFound(format!("{}: {}", path, e))),
+        }
+    }
+    pub fn from_bytes(bytes: &[u8]) -> Result<NavMesh, DemoParserError> {
+        let mut reader = NavReader { bytes, ptr: 0 };
+        if reader.read_u32()? != NAV_MAGIC {
+            return Err(DemoParserError::InvalidNavFile("wrong magic".to_string()));
+        }
+        let version = reader.read_u32()?;
+        if !(MIN_NAV_VERSION..=MAX_NAV_VERSION).contains(&version) {
+            return Err(DemoParserError::InvalidNavFile(format!("unsupported version {}", version)));
+        }
+        let sub_version = reader.read_u32()?;
+        // Analyzed flag
+        reader.read_u32()?;
+        // Areas refer to shared polygons instead of storing their own corners
+        let polygons = match version >= 31 {
+            true => Some(read_polygons(&mut reader, version)?),
+            false => None,
+        };
+        if version >= 32 {
+            reader.read_u32()?;
+        }
+        if version >= 35 {
+            reader.read_u32()?;
+        }
+        let area_count = reader.read_u32()?;
+        let mut areas = Vec::with_capacity(area_count as usize);
+        for _ in 0..area_count {
+            areas.push(read_area(&mut reader, polygons.as_deref())?);
+        }
+        Ok(NavMesh::new(version, sub_version, areas))
+    }
+    pub fn new(version: u32, sub_version: u32, areas: Vec<NavArea>) -> NavMesh {
+        let mut grid: AHashMap<(i32, i32), Vec<usize>> = AHashMap::default();
+        for (idx, area) in areas.iter().enumerate() {
+            let (min, max) = area_bounds(area);
+            let (min_cell, max_cell) = (grid_cell(min[0], min[1]), grid_cell(max[0], max[1]));
+            for cx in min_cell.0..=max_cell.0 {
+                for cy in min_cell.1..=max_cell.1 {
+                    grid.entry((cx, cy)).or_default().push(idx);
+                }
+            }
+        }
+        NavMesh {
+            version,
+            sub_version,
+            areas,
+            grid,
+        }
+    }
+    pub fn find_area(&self, x: f32, y: f32, z: f32) -> Option<&NavArea> {
+        let (cx, cy) = grid_cell(x, y);
+        let candidates = self.grid.get(&(cx, cy));
+        // Pick the highest area below the position that contains it on the xy-plane
+        let mut best: Option<(&NavArea, f32)> = None;
+        for idx in candidates.into_iter().flatten() {
+            let area = &self.areas[*idx];
+            if !contains_xy(&area.corners, x, y) {
+                continue;
+            }
+            let dz = z - area_z(area);
+            if dz >= -AREA_Z_TOLERANCE && best.is_none_or(|(_, best_dz)| dz.abs() < best_dz.abs()) {
+                best = Some((area, dz));
+            }
+        }
+        if let Some((area, _)) = best {
+            return Some(area);
+        }
+        // Off the mesh (jumping, falling, clipping), use the closest area nearby instead
+        let mut closest: Option<(&NavArea, f32)> = None;
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                for idx in self.grid.get(&(cx + dx, cy + dy)).into_iter().flatten() {
+                    let area = &self.areas[*idx];
+                    let center = area_center(area);
+                    let dist = (center[0] - x).powi(2) + (center[1] - y).powi(2) + (center[2] - z).powi(2);
+                    if closest.is_none_or(|(_, best)| dist < best) {
+                        closest = Some((area, dist));
+                    }
+                }
+            }
+        }
+        closest.map(|(area, _)| area)
+    }
+}
+
+fn read_polygons(reader: &mut NavReader, version: u32) -> Result<Vec<Vec<[f32; 3]>>, DemoParserError> {
+    let corner_count = reader.read_u32()?;
+    let mut corners = Vec::with_capacity(corner_count as usize);
+    for _ in 0..corner_count {
+        corners.push(reader.read_vector()?);
+    }
+    let polygon_count = reader.read_u32()?;
+    let mut polygons = Vec::with_capacity(polygon_count as usize);
+    for _ in 0..polygon_count {
+        let n = reader.read_u8()?;
+        let mut polygon = Vec::with_capacity(n as usize);
+        for _ in 0..n {
+            match corners.get(reader.read_u32()? as usize) {
+                Some(corner) => polygon.push(*corner),
+                None => return Err(DemoParserError::InvalidNavFile("polygon corner out of bounds".to_string())),
+            }
+        }
+        if version >= 35 {
+            reader.read_u32()?;
+        }
+        polygons.push(polygon);
+    }
+    Ok(polygons)
+}
+
+fn read_area(reader: &mut NavReader, polygons: Option<&[Vec<[f32; 3]>]>) -> Result<NavArea, DemoParserError> {
+    let area_id = reader.read_u32()?;
+    let dynamic_attribute_flags = reader.read_u64()?;
+    let hull_index = reader.read_u8()?;
+    let corners = match polygons {
+        Some(polygons) => match polygons.get(reader.read_u32()? as usize) {
+            Some(polygon) => polygon.clone(),
+            None => return Err(DemoParserError::InvalidNavFile(format!("polygon of area {} out of bounds", area_id))),
+        },
+        None => {
+            let n = reader.read_u32()?;
+            let mut corners = Vec::with_capacity(n as usize);
+            for _ in 0..n {
+                corners.push(reader.read_vector()?);
+            }
+            corners
+        }
+    };
+    reader.read_f32()?;
+    // One list of connections per edge
+    let mut connections = vec![];
+    for _ in 0..corners.len() {
+        let n = reader.read_u32()?;
+        for _ in 0..n {
+            connections.push(reader.read_u32()?);
+            // Edge index
+            reader.read_u32()?;
+        }
+    }
+    // Legacy hiding spots: id, position and flags
+    let hiding_spots = reader.read_u8()?;
+    reader.skip(hiding_spots as usize * 17)?;
+    // Ladders above and below
+    for _ in 0..2 {
+        let n = reader.read_u32()?;
+        reader.skip(n as usize * 4)?;
+    }
+    // Earliest occupy time per team
+    reader.read_f32()?;
+    reader.read_f32()?;
+    Ok(NavArea {
+        area_id,
+        hull_index,
+        dynamic_attribute_flags,
+        corners,
+        connections,
+    })
+}
+
+fn grid_cell(x: f32, y: f32) -> (i32, i32) {
+    ((x / GRID_CELL_SIZE).floor() as i32, (y / GRID_CELL_SIZE).floor() as i32)
+}
+
+fn area_bounds(area: &NavArea) -> ([f32; 3], [f32; 3]) {
+    let mut min = [f32::MAX; 3];
+    let mut max = [f32::MIN; 3];
+    for corner in &area.corners {
+        for i in 0..3 {
+            min[i] = min[i].min(corner[i]);
+            max[i] = max[i].max(corner[i]);
+        }
+    }
+    (min, max)
+}
+
+fn area_center(area: &NavArea) -> [f32; 3] {
+    let mut center = [0.0; 3];
+    for corner in &area.corners {
+        for i in 0..3 {
+            center[i] += corner[i] / area.corners.len() as f32;
+        }
+    }
+    center
+}
+
+fn area_z(area: &NavArea) -> f32 {
+    area_center(area)[2]
+}
+
+fn contains_xy(corners: &[[f32; 3]], x: f32, y: f32) -> bool {
+    // Even-odd rule, works for the convex polygons used by nav areas and for anything else too
+    let mut inside = false;
+    let mut j = corners.len().wrapping_sub(1);
+    for i in 0..corners.len() {
+        let (a, b) = (corners[i], corners[j]);
+        if (a[1] > y) != (b[1] > y) && x < (b[0] - a[0]) * (y - a[1]) / (b[1] - a[1]) + a[0] {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(area_id: u32, x: f32, y: f32, z: f32, size: f32) -> NavArea {
+        NavArea {
+            area_id,
+            hull_index: 0,
+            dynamic_attribute_flags: 0,
+            corners: vec![[x, y, z], [x + size, y, z], [x + size, y + size, z], [x, y + size, z]],
+            connections: vec![],
+        }
+    }
+
+    #[test]
+    fn test_find_area_picks_level_below() {
+        // Two floors on top of each other and one area next to them
+        let mesh = NavMesh::new(
+            35,
+            0,
+            vec![
+                square(1, 0.0, 0.0, 0.0, 100.0),
+                square(2, 0.0, 0.0, 200.0, 100.0),
+                square(3, 100.0, 0.0, 0.0, 100.0),
+            ],
+        );
+        assert_eq!(mesh.find_area(50.0, 50.0, 0.0).map(|a| a.area_id), Some(1));
+        assert_eq!(mesh.find_area(50.0, 50.0, 210.0).map(|a| a.area_id), Some(2));
+        assert_eq!(mesh.find_area(150.0, 50.0, 0.0).map(|a| a.area_id), Some(3));
+        // In the air next to the mesh
+        assert_eq!(mesh.find_area(260.0, 50.0, 0.0).map(|a| a.area_id), Some(3));
+        assert!(mesh.find_area(5000.0, 5000.0, 0.0).is_none());
+    }
+    #[test]
+    fn test_parse_nav_bytes() {
+        let mut b = vec![];
+        let u32s = |b: &mut Vec<u8>, v: &[u32]| v.iter().for_each(|x| b.extend(x.to_le_bytes()));
+        u32s(&mut b, &[NAV_MAGIC, 35, 1, 1]);
+        // Shared corners and one polygon
+        u32s(&mut b, &[4]);
+        for corner in [[0.0f32, 0.0, 5.0], [64.0, 0.0, 5.0], [64.0, 64.0, 5.0], [0.0, 64.0, 5.0]] {
+            corner.iter().for_each(|f| b.extend(f.to_le_bytes()));
+        }
+        u32s(&mut b, &[1]);
+        b.push(4);
+        u32s(&mut b, &[0, 1, 2, 3, 0]);
+        u32s(&mut b, &[0, 0]);
+        // One area
+        u32s(&mut b, &[1]);
+        u32s(&mut b, &[77]);
+        b.extend(0u64.to_le_bytes());
+        b.push(0);
+        u32s(&mut b, &[0]);
+        b.extend(0f32.to_le_bytes());
+        u32s(&mut b, &[1, 78, 0, 0, 0, 0]);
+        b.push(0);
+        u32s(&mut b, &[0, 0]);
+        b.extend(0f32.to_le_bytes());
+        b.extend(0f32.to_le_bytes());
+
+        let mesh = NavMesh::from_bytes(&b).unwrap();
+        assert_eq!(mesh.areas.len(), 1);
+        assert_eq!(mesh.areas[0].connections, vec![78]);
+        assert_eq!(mesh.find_area(10.0, 10.0, 5.0).map(|a| a.area_id), Some(77));
+        assert!(NavMesh::from_bytes(&b[..b.len() - 3]).is_err());
+    }
+}
@@ -0,0 +1,260 @@
+use crate::first_pass::read_bits::DemoParserError;
+use std::fs;
+
+// Name of the section used when the map has no vertical sections or the height falls outside of them
+pub const DEFAULT_RADAR_LEVEL: &str = "default";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerticalSection {
+    pub name: String,
+    pub altitude_min: f32,
+    pub altitude_max: f32,
+}
+
+// Values from resource/overviews/<map>.txt
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapOverview {
+    pub pos_x: f32,
+    pub pos_y: f32,
+    pub scale: f32,
+    pub vertical_sections: Vec<VerticalSection>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RadarPosition {
+    pub x: f32,
+    pub y: f32,
+    pub level: String,
+}
+
+impl MapOverview {
+    fn new(pos_x: f32, pos_y: f32, scale: f32) -> Self {
+        MapOverview {
+            pos_x,
+            pos_y,
+            scale,
+            vertical_sections: vec![],
+        }
+    }
+    fn with_lower_level(mut self, split_z: f32) -> Self {
+        self.vertical_sections = vec![
+            VerticalSection {
+                name: DEFAULT_RADAR_LEVEL.to_string(),
+                altitude_min: split_z,
+                altitude_max: f32::MAX,
+            },
+            VerticalSection {
+                name: "lower".to_string(),
+                altitude_min: f32::MIN,
+                altitude_max: split_z,
+            },
+        ];
+        self
+    }
+    pub fn from_overview_file(path: &str) -> Result<MapOverview, DemoParserError> {
+        match fs::read_to_string(path) {
+            Ok(text) => MapOverview::from_overview_text(&text),
+            Err(e) => Err(DemoParserError::FileNotFound(format!("{}: {}", path, e))),
+        }
+    }
+    pub fn from_overview_text(text: &str) -> Result<MapOverview, DemoParserError> {
+        let tokens = tokenize_keyvalues(text);
+        let mut idx = 0;
+        // File is "map_name" { ... }
+        let root = match (tokens.first(), tokens.get(1)) {
+            (Some(KvToken::Str(_)), Some(KvToken::Open)) => {
+                idx += 2;
+                parse_kv_block(&tokens, &mut idx)
+            }
+            _ => return Err(DemoParserError::InvalidOverviewFile("expected map name followed by {".to_string())),
+        };
+        let find_f32 = |key: &str| -> Result<f32, DemoParserError> {
+            match root.find_value(key).map(|v| v.parse::<f32>()) {
+                Some(Ok(f)) => Ok(f),
+                _ => Err(DemoParserError::InvalidOverviewFile(format!("missing or invalid {}", key))),
+            }
+        };
+        let mut overview = MapOverview::new(find_f32("pos_x")?, find_f32("pos_y")?, find_f32("scale")?);
+        if let Some(sections) = root.find_block("verticalsections") {
+            for (name, section) in &sections.blocks {
+                let bound = |key: &str, default: f32| section.find_value(key).and_then(|v| v.parse::<f32>().ok()).unwrap_or(default);
+                overview.vertical_sections.push(VerticalSection {
+                    name: name.clone(),
+                    altitude_min: bound("altitudemin", f32::MIN),
+                    altitude_max: bound("altitudemax", f32::MAX),
+                });
+            }
+        }
+        Ok(overview)
+    }
+    pub fn world_to_radar(&self, x: f32, y: f32, z: f32) -> RadarPosition {
+        RadarPosition {
+            x: (x - self.pos_x) / self.scale,
+            y: (self.pos_y - y) / self.scale,
+            level: self.find_level(z).to_string(),
+        }
+    }
+    pub fn find_level(&self, z: f32) -> &str {
+        self.vertical_sections
+            .iter()
+            .find(|s| z >= s.altitude_min && z < s.altitude_max)
+            .map(|s| s.name.as_str())
+            .unwrap_or(DEFAULT_RADAR_LEVEL)
+    }
+}
+
+pub fn builtin_overview(map_name: &str) -> Option<MapOverview> {
+    // Workshop maps come in as workshop/<id>/<map>
+    let map_name = map_name.rsplit('/').next().unwrap_or(map_name);
+    let overview = match map_name {
+        "de_ancient" => MapOverview::new(-2953.0, 2164.0, 5.0),
+        "de_anubis" => MapOverview::new(-2796.0, 3328.0, 5.22),
+        "de_dust2" => MapOverview::new(-2476.0, 3239.0, 4.4),
+        "de_inferno" => MapOverview::new(-2087.0, 3870.0, 4.9),
+        "de_mirage" => MapOverview::new(-3230.0, 1713.0, 5.0),
+        "de_nuke" => MapOverview::new(-3453.0, 2887.0, 7.0).with_lower_level(-495.0),
+        "de_overpass" => MapOverview::new(-4831.0, 1781.0, 5.2),
+        "de_train" => MapOverview::new(-2308.0, 2078.0, 4.082077),
+        "de_vertigo" => MapOverview::new(-3168.0, 1762.0, 4.0).with_lower_level(11700.0),
+        _ => return None,
+    };
+    Some(overview)
+}
+
+#[derive(Debug, PartialEq)]
+enum KvToken {
+    Str(String),
+    Open,
+    Close,
+}
+
+#[derive(Debug, Default)]
+struct KvBlock {
+    values: Vec<(String, String)>,
+    blocks: Vec<(String, KvBlock)>,
+}
+
+impl KvBlock {
+    // Keys in KeyValues are case insensitive
+    fn find_value(&self, key: &str) -> Option<&str> {
+        self.values.iter().find(|(k, _)| k.eq_ignore_ascii_case(key)).map(|(_, v)| v.as_str())
+    }
+    fn find_block(&self, key: &str) -> Option<&KvBlock> {
+        self.blocks.iter().find(|(k, _)| k.eq_ignore_ascii_case(key)).map(|(_, b)| b)
+    }
+}
+
+fn tokenize_keyvalues(text: &str) -> Vec<KvToken> {
+    let mut tokens = vec![];
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => tokens.push(KvToken::Open),
+            '}' => tokens.push(KvToken::Close),
+            '"' => {
+                let mut s = String::new();
+                for c in chars.by_ref() {
+                    if c == '"' {
+                        break;
+                    }
+                    s.push(c);
+                }
+                tokens.push(KvToken::Str(s));
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            c if c.is_whitespace() => {}
+            c => {
+                let mut s = c.to_string();
+                while let Some(&next) = chars.peek() {
+                    if next.is_whitespace() || next == '{' || next == '}' || next == '"' {
+                        break;
+                    }
+                    s.push(next);
+                    chars.next();
+                }
+                tokens.push(KvToken::Str(s));
+            }
+        }
+    }
+    tokens
+}
+
+fn parse_kv_block(tokens: &[KvToken], idx: &mut usize) -> KvBlock {
+    let mut block = KvBlock::default();
+    while let Some(token) = tokens.get(*idx) {
+        *idx += 1;
+        let key = match token {
+            KvToken::Str(key) => key.clone(),
+            KvToken::Close => break,
+            KvToken::Open => continue,
+        };
+        match tokens.get(*idx) {
+            Some(KvToken::Str(value)) => {
+                block.values.push((key, value.clone()));
+                *idx += 1;
+            }
+            Some(KvToken::Open) => {
+                *idx += 1;
+                block.blocks.push((key, parse_kv_block(tokens, idx)));
+            }
+            _ => {}
+        }
+    }
+    block
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overview_from_text() {
+        let text = r#"
+        "de_nuke"
+        {
+            "material"  "overviews/de_nuke" // comment
+            "pos_x"     "-3453"
+            "pos_y"     "2887"
+            "scale"     "7.00"
+            "verticalsections"
+            {
+                "default"
+                {
+                    "AltitudeMax" "10000"
+                    "AltitudeMin" "-495"
+                }
+                "lower"
+                {
+                    "AltitudeMax" "-495"
+                    "AltitudeMin" "-10000"
+                }
+            }
+        }
+        "#;
+        let overview = MapOverview::from_overview_text(text).unwrap();
+        assert_eq!(overview.pos_x, -3453.0);
+        assert_eq!(overview.pos_y, 2887.0);
+        assert_eq!(overview.scale, 7.0);
+        assert_eq!(overview.vertical_sections.len(), 2);
+        assert_eq!(overview.find_level(-600.0), "lower");
+        assert_eq!(overview.find_level(0.0), "default");
+        assert!(MapOverview::from_overview_text("\"de_x\" { \"pos_x\" \"1\" }").is_err());
+    }
+    #[test]
+    fn test_world_to_radar() {
+        let overview = builtin_overview("de_mirage").unwrap();
+        let pos = overview.world_to_radar(-3230.0 + 500.0, 1713.0 - 250.0, 0.0);
+        assert_eq!(pos.x, 100.0);
+        assert_eq!(pos.y, 50.0);
+        assert_eq!(pos.level, "default");
+        let vertigo = builtin_overview("workshop/123/de_vertigo").unwrap();
+        assert_eq!(vertigo.world_to_radar(0.0, 0.0, 11000.0).level, "lower");
+        assert!(builtin_overview("de_unknown").is_none());
+    }
+}
@@ -2,13 +2,29 @@ use crate::first_pass::frameparser::{FrameParser, StartEndOffset, StartEndType};
 use crate::first_pass::parser::FirstPassOutput;
 use crate::first_pass::parser_settings::check_multithreadability;
 use crate::first_pass::parser_settings::{FirstPassParser, ParserInputs};
-use crate::first_pass::prop_controller::{PropController, NAME_ID, STEAMID_ID, TICK_ID};
+use crate::first_pass::prop_controller::{PropController, NAME_ID, STEAMID_ID, TICKRATE, TICK_ID};
 use crate::first_pass::read_bits::DemoParserError;
+use crate::second_pass::chunk_state::{fold_chunk_states, recompute_velocity, ChunkState, WrittenProps};
+use crate::second_pass::change_log::ChangeLog;
+use crate::second_pass::bomb::{create_bomb_timelines, BombTimeline};
 use crate::second_pass::collect_data::ProjectileRecord;
+use crate::second_pass::entity_rows::EntityRecord;
+use crate::second_pass::fov::{merge_crosshair_placement, CrosshairPlacementRecord, FovRecord};
 use crate::second_pass::game_events::{EventField, GameEvent};
+use crate::second_pass::grenade_lifecycle::{merge_grenade_lifecycles, GrenadeLifecycle};
+use crate::second_pass::inferno::InfernoFireRecord;
+use crate::second_pass::money_ledger::{create_money_ledger, MoneyLedgerRecord};
+use crate::second_pass::movement::{create_movement_events, MovementEvent};
 use crate::second_pass::parser::SecondPassOutput;
 use crate::second_pass::parser_settings::*;
+use crate::second_pass::shots::{create_accuracy, create_shots, AccuracyRecord, ShotRecord};
+use crate::second_pass::spray::{create_sprays, SprayBulletRecord, SprayRecord};
+use crate::second_pass::usercmd::UserCmdRecord;
+use crate::second_pass::spotting::{create_spotting_rounds, merge_spotting_edges, SpottingEdge, SpottingRoundRecord};
+use crate::second_pass::utility_effects::{create_utility_effects, UtilityEffectRecord};
 use crate::second_pass::variants::VarVec;
+use crate::second_pass::visibility::VisibilityRecord;
+use crate::second_pass::weapons::{create_weapon_provenance, GroundWeaponRecord, WeaponProvenanceRecord};
 use crate::second_pass::variants::{PropColumn, Variant};
 use ahash::AHashMap;
 use ahash::AHashSet;
@@ -16,9 +32,11 @@ use csgoproto::netmessages::CSVCMsg_VoiceData;
 use itertools::Itertools;
 use rayon::iter::IntoParallelRefIterator;
 use rayon::prelude::ParallelIterator;
+use rayon::{Scope, ThreadPool};
+use std::panic::{self, AssertUnwindSafe};
 use std::sync::mpsc::{channel, Receiver};
+use std::sync::Arc;
 use std::thread;
-use std::time::Duration;
 
 pub const HEADER_ENDS_AT_BYTE: usize = 16;
 
@@ -37,11 +55,33 @@ pub struct DemoOutput {
     pub voice_data: Vec<CSVCMsg_VoiceData>,
     pub prop_controller: PropController,
     pub df_per_player: AHashMap<u64, AHashMap<u32, PropColumn>>,
+    pub change_log: Option<ChangeLog>,
+    pub money_ledger: Vec<MoneyLedgerRecord>,
+    pub grenade_lifecycles: Vec<GrenadeLifecycle>,
+    pub utility_effects: Vec<UtilityEffectRecord>,
+    pub inferno_fires: Vec<InfernoFireRecord>,
+    pub bomb_timelines: Vec<BombTimeline>,
+    pub visibility: Vec<VisibilityRecord>,
+    pub spotting_edges: Vec<SpottingEdge>,
+    pub spotting_rounds: Vec<SpottingRoundRecord>,
+    pub fov: Vec<FovRecord>,
+    pub crosshair_placement: Vec<CrosshairPlacementRecord>,
+    pub shots: Vec<ShotRecord>,
+    pub accuracy: Vec<AccuracyRecord>,
+    pub sprays: Vec<SprayRecord>,
+    pub spray_bullets: Vec<SprayBulletRecord>,
+    pub usercmds: Vec<UserCmdRecord>,
+    pub movement: Vec<MovementEvent>,
+    pub entities: Vec<EntityRecord>,
+    pub weapons: Vec<WeaponProvenanceRecord>,
+    pub ground_weapons: Vec<GroundWeaponRecord>,
+    pub parallelism: ParallelismReport,
 }
 
 pub struct Parser<'a> {
     input: ParserInputs<'a>,
     pub parsing_mode: ParsingMode,
+    pool: Option<Arc<ThreadPool>>,
 }
 #[derive(PartialEq)]
 pub enum ParsingMode {
@@ -50,24 +90,45 @@ pub enum ParsingMode {
     Normal,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParallelStrategy {
+    SingleThreaded,
+    // Chunks are parsed as the frame scanner finds them, while the first pass is still running
+    Channels,
+    // Frame scanner failed or timed out, chunks come from the fullpacket offsets of a full first pass
+    FullpacketOffsets,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParallelismReport {
+    pub strategy: ParallelStrategy,
+    pub threads: usize,
+    pub chunks: usize,
+    // Chunks started from the state the chunks before them left behind, see second_pass/chunk_state.rs
+    pub carried_state: bool,
+}
+
+type Chunk = (usize, Option<StartEndOffset>, Option<ChunkState>);
+
 impl<'a> Parser<'a> {
     pub fn new(input: ParserInputs<'a>, parsing_mode: ParsingMode) -> Self {
         Parser {
             input: input,
             parsing_mode: parsing_mode,
+            pool: None,
         }
     }
 
     pub fn parse_demo(&mut self, demo_bytes: &[u8]) -> Result<DemoOutput, DemoParserError> {
         // Multi threaded second pass
-        if self.parsing_mode == ParsingMode::ForceMultiThreaded
-            || check_multithreadability(&self.input.wanted_player_props)
-                && !(self.parsing_mode == ParsingMode::ForceSingleThreaded)
-        {
+        if self.parsing_mode != ParsingMode::ForceSingleThreaded {
+            self.pool = self.input.parallelism.build_pool()?;
+            let num_chunks = self.input.parallelism.num_chunks(demo_bytes.len());
+            let pool = self.pool.clone();
             let (sender, receiver) = channel();
             let mut fp = FrameParser::new();
             return thread::scope(|s| {
-                let _handle = s.spawn(|| fp.par_start(demo_bytes, sender));
+                let _handle = s.spawn(|| install(&pool, || fp.par_start(demo_bytes, num_chunks, sender)));
                 let mut first_pass_parser = FirstPassParser::new(&self.input);
                 match first_pass_parser.parse_demo(&demo_bytes, true) {
                     Ok(first_pass_output) => self.second_pass_threaded_with_channels(demo_bytes, first_pass_output, receiver),
@@ -89,7 +150,7 @@ impl<'a> Parser<'a> {
         let mut parser = SecondPassParser::new(first_pass_output.clone(), 16, true, None)?;
         parser.start(outer_bytes)?;
         let second_pass_output = parser.create_output();
-        let mut outputs = self.combine_outputs(&mut vec![second_pass_output], first_pass_output);
+        let mut outputs = self.combine_outputs(&mut vec![second_pass_output], first_pass_output, ParallelStrategy::SingleThreaded);
         if let Some(new_df) = self.rm_unwanted_ticks(&mut outputs.df) {
             outputs.df = new_df;
         }
@@ -103,79 +164,114 @@ impl<'a> Parser<'a> {
         first_pass_output: FirstPassOutput,
         reciever: Receiver<StartEndOffset>,
     ) -> Result<DemoOutput, DemoParserError> {
-        thread::scope(|s| {
-            let mut handles = vec![];
-            let mut channel_threading_was_ok = true;
-            loop {
-                if let Ok(start_end_offset) = reciever.recv_timeout(Duration::from_secs(3)) {
-                    match start_end_offset.msg_type {
-                        StartEndType::EndOfMessages => break,
-                        StartEndType::OK => {}
-                        StartEndType::MultithreadingWasNotOk => {
-                            channel_threading_was_ok = false;
-                            break;
-                        }
+        let carry_state = self.needs_chunk_state();
+        let timeout = self.input.parallelism.fallback_timeout;
+        let (output_sender, output_receiver) = channel();
+        let (state_sender, state_receiver) = channel();
+        let mut channel_threading_was_ok = true;
+        self.in_place_scope(|s| loop {
+            if let Ok(start_end_offset) = reciever.recv_timeout(timeout) {
+                match start_end_offset.msg_type {
+                    StartEndType::EndOfMessages => break,
+                    StartEndType::OK => {}
+                    StartEndType::MultithreadingWasNotOk => {
+                        channel_threading_was_ok = false;
+                        break;
                     }
-                    let my_first_out = first_pass_output.clone();
-                    handles.push(s.spawn(move || {
-                        let mut parser =
-                            SecondPassParser::new(my_first_out, start_end_offset.start, false, Some(start_end_offset))?;
+                }
+                let my_first_out = first_pass_output.clone();
+                if carry_state {
+                    let state_sender = state_sender.clone();
+                    s.spawn(move |_| {
+                        let _ = state_sender.send(catch_chunk_panic(|| {
+                            let mut parser = SecondPassParser::new(my_first_out, start_end_offset.start, false, Some(start_end_offset))?;
+                            parser.written_props = Some(WrittenProps::default());
+                            parser.start(outer_bytes)?;
+                            Ok((start_end_offset, parser.create_chunk_state()))
+                        }));
+                    });
+                    continue;
+                }
+                let output_sender = output_sender.clone();
+                s.spawn(move |_| {
+                    let _ = output_sender.send(catch_chunk_panic(|| {
+                        let mut parser = SecondPassParser::new(my_first_out, start_end_offset.start, false, Some(start_end_offset))?;
                         parser.start(outer_bytes)?;
                         Ok(parser.create_output())
                     }));
-                } else {
-                    channel_threading_was_ok = false;
-                    break;
-                }
-            }
-            // Fallback if channels failed to find all fullpackets. Should be rare.
-            if !channel_threading_was_ok {
-                let mut first_pass_parser = FirstPassParser::new(&self.input);
-                let first_pass_output = first_pass_parser.parse_demo(outer_bytes, false)?;
-                return self.second_pass_multi_threaded_no_channels(outer_bytes, first_pass_output);
+                });
+            } else {
+                channel_threading_was_ok = false;
+                break;
             }
-            // check for errors
-            let mut ok = vec![];
-            for result in handles {
-                match result.join() {
-                    Err(_e) => return Err(DemoParserError::MalformedMessage),
-                    Ok(r) => {
-                        ok.push(r?);
-                    }
-                };
+        });
+        // Fallback if channels failed to find all fullpackets. Should be rare.
+        if !channel_threading_was_ok {
+            let mut first_pass_parser = FirstPassParser::new(&self.input);
+            let first_pass_output = first_pass_parser.parse_demo(outer_bytes, false)?;
+            return self.second_pass_multi_threaded_no_channels(outer_bytes, first_pass_output);
+        }
+        // check for errors
+        let mut ok = vec![];
+        if carry_state {
+            let mut chunk_states = vec![];
+            for result in state_receiver.try_iter() {
+                chunk_states.push(result?);
             }
-            let mut outputs = self.combine_outputs(&mut ok, first_pass_output);
-            if let Some(new_df) = self.rm_unwanted_ticks(&mut outputs.df) {
-                outputs.df = new_df;
+            chunk_states.sort_by_key(|(offset, _)| offset.start);
+            let (offsets, chunk_states): (Vec<StartEndOffset>, Vec<ChunkState>) = chunk_states.into_iter().unzip();
+            let chunks = offsets
+                .into_iter()
+                .zip(fold_chunk_states(&chunk_states))
+                .map(|(offset, carried_state)| (offset.start, Some(offset), Some(carried_state)))
+                .collect();
+            ok = self.parse_chunks(outer_bytes, &first_pass_output, chunks)?;
+        } else {
+            for result in output_receiver.try_iter() {
+                ok.push(result?);
             }
-            Parser::add_item_purchase_sell_column(&mut outputs.game_events);
-            Parser::remove_item_sold_events(&mut outputs.game_events);
-            return Ok(outputs);
-        })
+        }
+        let mut outputs = self.combine_outputs(&mut ok, first_pass_output, ParallelStrategy::Channels);
+        if let Some(new_df) = self.rm_unwanted_ticks(&mut outputs.df) {
+            outputs.df = new_df;
+        }
+        Parser::add_item_purchase_sell_column(&mut outputs.game_events);
+        Parser::remove_item_sold_events(&mut outputs.game_events);
+        Ok(outputs)
     }
     fn second_pass_multi_threaded_no_channels(
         &self,
         outer_bytes: &[u8],
         first_pass_output: FirstPassOutput,
     ) -> Result<DemoOutput, DemoParserError> {
-        let second_pass_outputs: Vec<Result<SecondPassOutput, DemoParserError>> = first_pass_output
-            .fullpacket_offsets
-            .par_iter()
-            .map(|offset| {
-                let mut parser = SecondPassParser::new(first_pass_output.clone(), *offset, false, None)?;
-                parser.start(outer_bytes)?;
-                Ok(parser.create_output())
-            })
+        let fullpacket_offsets = &first_pass_output.fullpacket_offsets;
+        let carried_states = match self.needs_chunk_state() {
+            false => vec![None; fullpacket_offsets.len()],
+            true => {
+                let chunk_states: Vec<Result<ChunkState, DemoParserError>> = install(&self.pool, || {
+                    fullpacket_offsets
+                        .par_iter()
+                        .map(|offset| {
+                            catch_chunk_panic(|| {
+                                let mut parser = SecondPassParser::new(first_pass_output.clone(), *offset, false, None)?;
+                                parser.written_props = Some(WrittenProps::default());
+                                parser.start(outer_bytes)?;
+                                Ok(parser.create_chunk_state())
+                            })
+                        })
+                        .collect()
+                });
+                let chunk_states = chunk_states.into_iter().collect::<Result<Vec<ChunkState>, DemoParserError>>()?;
+                fold_chunk_states(&chunk_states).into_iter().map(Some).collect()
+            }
+        };
+        let chunks = fullpacket_offsets
+            .iter()
+            .zip(carried_states)
+            .map(|(offset, carried_state)| (*offset, None, carried_state))
             .collect();
-        // check for errors
-        let mut ok = vec![];
-        for result in second_pass_outputs {
-            match result {
-                Err(e) => return Err(e),
-                Ok(r) => ok.push(r),
-            };
-        }
-        let mut outputs = self.combine_outputs(&mut ok, first_pass_output);
+        let mut ok = self.parse_chunks(outer_bytes, &first_pass_output, chunks)?;
+        let mut outputs = self.combine_outputs(&mut ok, first_pass_output, ParallelStrategy::FullpacketOffsets);
         if let Some(new_df) = self.rm_unwanted_ticks(&mut outputs.df) {
             outputs.df = new_df;
         }
@@ -183,6 +279,41 @@ impl<'a> Parser<'a> {
         Parser::remove_item_sold_events(&mut outputs.game_events);
         Ok(outputs)
     }
+    // Props that fullpackets don't have right and the kill context need the state the previous chunks left behind
+    fn needs_chunk_state(&self) -> bool {
+        !check_multithreadability(&self.input.wanted_player_props) || self.input.parse_kill_context
+    }
+    fn parse_chunks(&self, outer_bytes: &[u8], first_pass_output: &FirstPassOutput, chunks: Vec<Chunk>) -> Result<Vec<SecondPassOutput>, DemoParserError> {
+        let second_pass_outputs: Vec<Result<SecondPassOutput, DemoParserError>> = install(&self.pool, || {
+            chunks
+                .par_iter()
+                .map(|(offset, start_end_offset, carried_state)| {
+                    catch_chunk_panic(|| {
+                        let mut parser = SecondPassParser::new(first_pass_output.clone(), *offset, false, *start_end_offset)?;
+                        parser.carried_state = carried_state.clone();
+                        parser.start(outer_bytes)?;
+                        Ok(parser.create_output())
+                    })
+                })
+                .collect()
+        });
+        second_pass_outputs.into_iter().collect()
+    }
+    fn in_place_scope<'scope, OP, R>(&self, op: OP) -> R
+    where
+        OP: FnOnce(&Scope<'scope>) -> R,
+    {
+        match &self.pool {
+            Some(pool) => pool.in_place_scope(op),
+            None => rayon::in_place_scope(op),
+        }
+    }
+    fn num_threads(&self) -> usize {
+        match &self.pool {
+            Some(pool) => pool.current_num_threads(),
+            None => rayon::current_num_threads(),
+        }
+    }
     fn remove_item_sold_events(events: &mut Vec<GameEvent>) {
         events.retain(|x| x.name != "item_sold")
     }
@@ -258,17 +389,29 @@ impl<'a> Parser<'a> {
         Some(new_df)
     }
 
-    fn combine_outputs(&self, second_pass_outputs: &mut Vec<SecondPassOutput>, first_pass_output: FirstPassOutput) -> DemoOutput {
+    fn combine_outputs(&self, second_pass_outputs: &mut Vec<SecondPassOutput>, first_pass_output: FirstPassOutput, strategy: ParallelStrategy) -> DemoOutput {
         // Combines all inner DemoOutputs into one big output
         second_pass_outputs.sort_by_key(|x| x.ptr);
 
         let mut dfs = second_pass_outputs.iter().map(|x| x.df.clone()).collect();
-        let all_dfs_combined = self.combine_dfs(&mut dfs, false);
+        let mut all_dfs_combined = self.combine_dfs(&mut dfs, false);
+        if second_pass_outputs.len() > 1 {
+            recompute_velocity(&mut all_dfs_combined);
+        }
         let all_game_events: AHashSet<String> =
             AHashSet::from_iter(second_pass_outputs.iter().flat_map(|x| x.game_events_counter.iter().cloned()));
+        let mut change_log = second_pass_outputs.iter_mut().filter_map(|x| x.change_log.take()).reduce(|mut log, chunk| {
+            log.append(chunk);
+            log
+        });
         // Remove temp props
         let mut prop_controller = first_pass_output.prop_controller.clone();
         for prop in first_pass_output.added_temp_props {
+            if let (Some(change_log), Some(prop_info)) =
+                (change_log.as_mut(), prop_controller.prop_infos.iter().find(|x| x.prop_name == prop))
+            {
+                change_log.remove_prop(prop_info.id);
+            }
             prop_controller.wanted_player_props.retain(|x| x != &prop);
             prop_controller.prop_infos.retain(|x| &x.prop_name != &prop);
         }
@@ -280,6 +423,21 @@ impl<'a> Parser<'a> {
                 all_steamids.insert(k);
             }
         }
+        let money_changes = second_pass_outputs.iter().flat_map(|x| x.money_changes.clone()).collect_vec();
+        let money_causes = second_pass_outputs.iter().flat_map(|x| x.money_causes.clone()).collect_vec();
+        let grenade_lifecycles = merge_grenade_lifecycles(
+            second_pass_outputs.iter().flat_map(|x| x.grenade_lifecycles.clone()).collect(),
+            second_pass_outputs.iter().flat_map(|x| x.inferno_expires.clone()).collect(),
+        );
+        let utility_events = second_pass_outputs.iter().flat_map(|x| x.utility_events.clone()).collect_vec();
+        let spotting_edges = merge_spotting_edges(second_pass_outputs.iter().map(|x| (x.spotting_start_tick, x.spotting_edges.clone())).collect());
+        let shots = create_shots(second_pass_outputs.iter().flat_map(|x| x.shot_events.clone()).collect());
+        let (sprays, spray_bullets) = match self.input.parse_sprays {
+            true => create_sprays(&shots),
+            false => (vec![], vec![]),
+        };
+        let movement = create_movement_events(second_pass_outputs.iter().flat_map(|x| x.movement_inputs.clone()).collect());
+        let last_tick = second_pass_outputs.iter().map(|x| x.last_tick).max().unwrap_or(0);
         let mut pp = AHashMap::default();
         for steamid in all_steamids {
             let mut v = vec![];
@@ -288,7 +446,10 @@ impl<'a> Parser<'a> {
                     v.push(df.clone());
                 }
             }
-            let combined = self.combine_dfs(&mut v, true);
+            let mut combined = self.combine_dfs(&mut v, true);
+            if second_pass_outputs.len() > 1 {
+                recompute_velocity(&mut combined);
+            }
             pp.insert(*steamid, combined);
         }
 
@@ -306,6 +467,35 @@ impl<'a> Parser<'a> {
             projectiles: second_pass_outputs.iter().flat_map(|x| x.projectiles.clone()).collect(),
             voice_data: second_pass_outputs.iter().flat_map(|x| x.voice_data.clone()).collect_vec(),
             df_per_player: pp,
+            change_log: change_log,
+            money_ledger: create_money_ledger(&money_changes, &money_causes),
+            utility_effects: create_utility_effects(&grenade_lifecycles, &utility_events),
+            grenade_lifecycles,
+            inferno_fires: second_pass_outputs.iter().flat_map(|x| x.inferno_fires.clone()).collect(),
+            bomb_timelines: create_bomb_timelines(second_pass_outputs.iter().flat_map(|x| x.bomb_events.clone()).collect()),
+            visibility: second_pass_outputs.iter().flat_map(|x| x.visibility.clone()).collect(),
+            spotting_rounds: create_spotting_rounds(&spotting_edges, last_tick, TICKRATE),
+            spotting_edges,
+            fov: second_pass_outputs.iter().flat_map(|x| x.fov_records.clone()).collect(),
+            accuracy: create_accuracy(&shots),
+            shots,
+            sprays,
+            spray_bullets,
+            usercmds: second_pass_outputs.iter().flat_map(|x| x.usercmds.clone()).collect(),
+            movement,
+            entities: second_pass_outputs.iter().flat_map(|x| x.entity_records.clone()).collect(),
+            weapons: create_weapon_provenance(second_pass_outputs.iter().flat_map(|x| x.weapon_inputs.clone()).collect()),
+            ground_weapons: second_pass_outputs.iter().flat_map(|x| x.ground_weapons.clone()).collect(),
+            parallelism: ParallelismReport {
+                strategy,
+                threads: match strategy {
+                    ParallelStrategy::SingleThreaded => 1,
+                    _ => self.num_threads(),
+                },
+                chunks: second_pass_outputs.len(),
+                carried_state: strategy != ParallelStrategy::SingleThreaded && self.needs_chunk_state(),
+            },
+            crosshair_placement: merge_crosshair_placement(second_pass_outputs.iter().flat_map(|x| x.crosshair_placement.clone()).collect()),
         }
     }
 
@@ -341,6 +531,29 @@ impl<'a> Parser<'a> {
     }
 }
 
+// Runs parallel work on the configured pool, None is the global pool
+fn install<R: Send>(pool: &Option<Arc<ThreadPool>>, op: impl FnOnce() -> R + Send) -> R {
+    match pool {
+        Some(pool) => pool.install(op),
+        None => op(),
+    }
+}
+
+// A panic in one chunk is an error for the whole parse instead of bringing the caller down
+fn catch_chunk_panic<T>(op: impl FnOnce() -> Result<T, DemoParserError>) -> Result<T, DemoParserError> {
+    match panic::catch_unwind(AssertUnwindSafe(op)) {
+        Ok(result) => result,
+        Err(payload) => {
+            let message = match (payload.downcast_ref::<&str>(), payload.downcast_ref::<String>()) {
+                (Some(message), _) => message.to_string(),
+                (_, Some(message)) => message.clone(),
+                _ => "unknown panic".to_string(),
+            };
+            Err(DemoParserError::ChunkPanicked(message))
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct SellBackHelper {
     pub tick: i32,
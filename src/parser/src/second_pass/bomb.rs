@@ -0,0 +1,333 @@
+use crate::first_pass::read_bits::DemoParserError;
+use crate::second_pass::collect_data::CoordinateAxis;
+use crate::second_pass::game_events::DecodedGameEvent;
+use crate::second_pass::parser_settings::SecondPassParser;
+use crate::second_pass::variants::Variant;
+use ahash::AHashMap;
+
+// CPlantedC4 is sometimes created a few ticks after bomb_planted
+const PENDING_BOMB_EVENT_TICKS: i32 = 8;
+const ROUND_END_REASON_TIME_RAN_OUT: i32 = 12;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BombEventKind {
+    Pickup,
+    Dropped,
+    PlantBegin,
+    PlantAbort,
+    Planted,
+    DefuseBegin,
+    DefuseAbort,
+    Defused,
+    Exploded,
+    // Only used to split the timeline into rounds
+    RoundStart,
+    RoundEnd,
+}
+
+impl BombEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BombEventKind::Pickup => "pickup",
+            BombEventKind::Dropped => "dropped",
+            BombEventKind::PlantBegin => "plant_begin",
+            BombEventKind::PlantAbort => "plant_abort",
+            BombEventKind::Planted => "planted",
+            BombEventKind::DefuseBegin => "defuse_begin",
+            BombEventKind::DefuseAbort => "defuse_abort",
+            BombEventKind::Defused => "defused",
+            BombEventKind::Exploded => "exploded",
+            BombEventKind::RoundStart => "round_start",
+            BombEventKind::RoundEnd => "round_end",
+        }
+    }
+    fn uses_planted_c4(&self) -> bool {
+        matches!(
+            self,
+            BombEventKind::Planted | BombEventKind::DefuseBegin | BombEventKind::DefuseAbort | BombEventKind::Defused | BombEventKind::Exploded
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BombEvent {
+    pub tick: i32,
+    pub round: Option<i32>,
+    pub kind: BombEventKind,
+    pub steamid: Option<u64>,
+    pub name: Option<String>,
+    pub x: Option<f32>,
+    pub y: Option<f32>,
+    pub z: Option<f32>,
+    pub site: Option<String>,
+    pub has_kit: Option<bool>,
+    pub bomb_time_remaining: Option<f32>,
+    pub defuse_time_remaining: Option<f32>,
+    // Entity index of the bombsite from the event, mapped to a site name once a plant reveals it
+    pub site_index: Option<i32>,
+    pub round_end_reason: Option<i32>,
+    pub game_time: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct BombTimeline {
+    pub round: Option<i32>,
+    pub outcome: Option<String>,
+    pub events: Vec<BombEvent>,
+}
+
+impl<'a> SecondPassParser<'a> {
+    pub fn collect_bomb_event(&mut self, event: &DecodedGameEvent) -> Result<(), DemoParserError> {
+        let kind = match event.name() {
+            "bomb_pickup" => BombEventKind::Pickup,
+            "bomb_dropped" => BombEventKind::Dropped,
+            "bomb_beginplant" => BombEventKind::PlantBegin,
+            "bomb_abortplant" => BombEventKind::PlantAbort,
+            "bomb_planted" => BombEventKind::Planted,
+            "bomb_begindefuse" => BombEventKind::DefuseBegin,
+            "bomb_abortdefuse" => BombEventKind::DefuseAbort,
+            "bomb_defused" => BombEventKind::Defused,
+            "bomb_exploded" => BombEventKind::Exploded,
+            "round_start" => BombEventKind::RoundStart,
+            "round_end" => BombEventKind::RoundEnd,
+            _ => return Ok(()),
+        };
+        let player_entity_id = match event.key("userid") {
+            Some(Variant::I32(userid)) => self.entity_id_from_userid(userid),
+            _ => None,
+        };
+        let player = player_entity_id.and_then(|entity_id| self.find_player_metadata(entity_id).ok());
+        let coordinate = |axis: CoordinateAxis| match player_entity_id.map(|entity_id| self.collect_cell_coordinate_player(axis, &entity_id)) {
+            Some(Ok(Variant::F32(f))) => Some(f),
+            _ => None,
+        };
+        let mut bomb_event = BombEvent {
+            tick: self.tick,
+            round: match self.find_current_round() {
+                Some(Variant::I32(round)) => Some(round),
+                _ => None,
+            },
+            kind,
+            steamid: player.and_then(|p| p.steamid),
+            name: player.and_then(|p| p.name.clone()),
+            x: coordinate(CoordinateAxis::X),
+            y: coordinate(CoordinateAxis::Y),
+            z: coordinate(CoordinateAxis::Z),
+            site: None,
+            has_kit: match event.key("haskit") {
+                Some(Variant::Bool(has_kit)) => Some(has_kit),
+                _ => None,
+            },
+            bomb_time_remaining: None,
+            defuse_time_remaining: None,
+            site_index: match event.key("site") {
+                Some(Variant::I32(site)) => Some(site),
+                _ => None,
+            },
+            round_end_reason: match (kind, event.key("reason")) {
+                (BombEventKind::RoundEnd, Some(Variant::I32(reason))) => Some(reason),
+                _ => None,
+            },
+            game_time: self.net_tick as f32 / 64.0,
+        };
+        if kind.uses_planted_c4() && !self.fill_from_planted_c4(&mut bomb_event) {
+            self.pending_bomb_events.push(self.bomb_events.len());
+        }
+        self.bomb_events.push(bomb_event);
+        Ok(())
+    }
+    pub fn fill_pending_bomb_events(&mut self) {
+        let pending = std::mem::take(&mut self.pending_bomb_events);
+        for idx in pending {
+            let mut bomb_event = self.bomb_events[idx].clone();
+            if self.fill_from_planted_c4(&mut bomb_event) {
+                self.bomb_events[idx] = bomb_event;
+            } else if self.tick - bomb_event.tick <= PENDING_BOMB_EVENT_TICKS {
+                self.pending_bomb_events.push(idx);
+            }
+        }
+    }
+    fn fill_from_planted_c4(&self, bomb_event: &mut BombEvent) -> bool {
+        let entity_id = match self.planted_c4_entity_id {
+            Some(entity_id) => entity_id,
+            None => return false,
+        };
+        let ids = &self.prop_controller.special_ids;
+        let find_f32 = |prop_id: Option<u32>| match prop_id.map(|id| self.get_prop_from_ent(&id, &entity_id)) {
+            Some(Ok(Variant::F32(f))) => Some(f),
+            _ => None,
+        };
+        let c4_blow = find_f32(ids.c4_blow);
+        let defuse_countdown = find_f32(ids.c4_defuse_countdown);
+        // Countdown is left over from earlier attempts until the new defuse is networked
+        if bomb_event.kind == BombEventKind::DefuseBegin && defuse_countdown.is_none_or(|t| t <= bomb_event.game_time) {
+            return false;
+        }
+        if bomb_event.kind == BombEventKind::Planted && c4_blow.is_none_or(|t| t <= bomb_event.game_time) {
+            return false;
+        }
+        bomb_event.bomb_time_remaining = c4_blow.map(|t| (t - bomb_event.game_time).max(0.0));
+        if bomb_event.kind == BombEventKind::DefuseBegin {
+            bomb_event.defuse_time_remaining = defuse_countdown.map(|t| (t - bomb_event.game_time).max(0.0));
+        }
+        if let Some(prop_id) = ids.c4_bomb_site {
+            bomb_event.site = match self.get_prop_from_ent(&prop_id, &entity_id) {
+                Ok(Variant::I32(0)) | Ok(Variant::U32(0)) => Some("BombsiteA".to_string()),
+                Ok(Variant::I32(1)) | Ok(Variant::U32(1)) => Some("BombsiteB".to_string()),
+                _ => None,
+            };
+        }
+        true
+    }
+}
+
+fn round_outcome(events: &[BombEvent], round_end_reason: Option<i32>) -> String {
+    let happened = |kind: BombEventKind| events.iter().any(|e| e.kind == kind);
+    if happened(BombEventKind::Exploded) {
+        return "exploded".to_string();
+    }
+    if happened(BombEventKind::Defused) {
+        return "defused".to_string();
+    }
+    if happened(BombEventKind::Planted) {
+        return "planted".to_string();
+    }
+    match round_end_reason {
+        Some(ROUND_END_REASON_TIME_RAN_OUT) => "time_ran_out".to_string(),
+        _ => "no_plant".to_string(),
+    }
+}
+
+pub fn create_bomb_timelines(mut events: Vec<BombEvent>) -> Vec<BombTimeline> {
+    events.sort_by_key(|e| e.tick);
+    // Site index from the events is an entity index, learn which site it is from the plants
+    let mut sites: AHashMap<i32, String> = AHashMap::default();
+    for event in &events {
+        if let (Some(index), Some(site)) = (event.site_index, &event.site) {
+            sites.insert(index, site.clone());
+        }
+    }
+    let mut timelines = vec![];
+    let mut current: Vec<BombEvent> = vec![];
+    let mut round = None;
+    let mut round_end_reason = None;
+    let mut round_ended = false;
+    for mut event in events {
+        match event.kind {
+            BombEventKind::RoundStart => {
+                if !current.is_empty() || round_ended {
+                    timelines.push(finish_timeline(round, std::mem::take(&mut current), round_end_reason));
+                }
+                round = event.round;
+                round_end_reason = None;
+                round_ended = false;
+            }
+            BombEventKind::RoundEnd => {
+                // Bomb can still explode or be defused after the round was decided
+                if !round_ended {
+                    round_end_reason = event.round_end_reason;
+                    round_ended = true;
+                }
+            }
+            _ => {
+                if round.is_none() {
+                    round = event.round;
+                }
+                if event.site.is_none() {
+                    event.site = event.site_index.and_then(|idx| sites.get(&idx).cloned());
+                }
+                current.push(event);
+            }
+        }
+    }
+    if !current.is_empty() || round_ended {
+        timelines.push(finish_timeline(round, current, round_end_reason));
+    }
+    timelines
+}
+
+fn finish_timeline(round: Option<i32>, mut events: Vec<BombEvent>, round_end_reason: Option<i32>) -> BombTimeline {
+    // Explosion has no player so use where the bomb was planted
+    let planted = events.iter().find(|e| e.kind == BombEventKind::Planted).cloned();
+    if let Some(planted) = planted {
+        for event in events.iter_mut().filter(|e| e.kind == BombEventKind::Exploded) {
+            event.x = planted.x;
+            event.y = planted.y;
+            event.z = planted.z;
+            if event.site.is_none() {
+                event.site = planted.site.clone();
+            }
+        }
+    }
+    BombTimeline {
+        round,
+        outcome: Some(round_outcome(&events, round_end_reason)),
+        events,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(tick: i32, kind: BombEventKind) -> BombEvent {
+        BombEvent {
+            tick,
+            round: Some(1),
+            kind,
+            steamid: None,
+            name: None,
+            x: Some(tick as f32),
+            y: None,
+            z: None,
+            site: None,
+            has_kit: None,
+            bomb_time_remaining: None,
+            defuse_time_remaining: None,
+            site_index: None,
+            round_end_reason: None,
+            game_time: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_bomb_timelines_split_by_round() {
+        let mut plant_begin = event(20, BombEventKind::PlantBegin);
+        plant_begin.site_index = Some(204);
+        let mut planted = event(30, BombEventKind::Planted);
+        planted.site_index = Some(204);
+        planted.site = Some("BombsiteB".to_string());
+        let mut time_ran_out = event(200, BombEventKind::RoundEnd);
+        time_ran_out.round_end_reason = Some(ROUND_END_REASON_TIME_RAN_OUT);
+        let events = vec![
+            event(1, BombEventKind::RoundStart),
+            event(10, BombEventKind::Pickup),
+            plant_begin,
+            planted,
+            event(40, BombEventKind::RoundEnd),
+            event(50, BombEventKind::Exploded),
+            event(100, BombEventKind::RoundStart),
+            time_ran_out,
+        ];
+        let timelines = create_bomb_timelines(events);
+        assert_eq!(timelines.len(), 2);
+        assert_eq!(timelines[0].outcome.as_deref(), Some("exploded"));
+        assert_eq!(timelines[0].events.len(), 4);
+        assert_eq!(timelines[0].events[1].site.as_deref(), Some("BombsiteB"));
+        assert_eq!(timelines[0].events[3].x, Some(30.0));
+        assert_eq!(timelines[1].outcome.as_deref(), Some("time_ran_out"));
+        assert!(timelines[1].events.is_empty());
+    }
+    #[test]
+    fn test_bomb_outcome() {
+        let defused = vec![
+            event(10, BombEventKind::Planted),
+            event(20, BombEventKind::DefuseBegin),
+            event(30, BombEventKind::Defused),
+        ];
+        assert_eq!(round_outcome(&defused, None), "defused");
+        let dropped = vec![event(10, BombEventKind::Pickup), event(20, BombEventKind::Dropped)];
+        assert_eq!(round_outcome(&dropped, Some(9)), "no_plant");
+        assert_eq!(round_outcome(&dropped, Some(ROUND_END_REASON_TIME_RAN_OUT)), "time_ran_out");
+    }
+}
@@ -0,0 +1,298 @@
+use crate::first_pass::prop_controller::{
+    PLAYER_X_ID, PLAYER_Y_ID, PLAYER_Z_ID, STEAMID_ID, TICK_ID, VELOCITY_ID, VELOCITY_X_ID, VELOCITY_Y_ID, VELOCITY_Z_ID,
+};
+use crate::second_pass::variants::{PropColumn, RlePropColumn, Variant};
+use ahash::{AHashMap, AHashSet};
+
+/*
+Sparse form of the parse_ticks output. The dense frame has one row per player per tick and
+repeats every value on every row, which is what makes a full demo take gigabytes. Here the
+rows of every player are kept apart and every prop is run-length encoded over them as the
+ticks are collected, so a value is only stored on the tick it changes and the dense frame is
+never built.
+
+Players are told apart by steamid. Bots all have steamid 0 so they are told apart by the entity
+id of their controller instead, which stays the same for as long as the bot is in the game.
+*/
+
+const VELOCITY_IDS: [u32; 4] = [VELOCITY_ID, VELOCITY_X_ID, VELOCITY_Y_ID, VELOCITY_Z_ID];
+
+#[derive(Debug, Clone)]
+pub struct PlayerChangeLog {
+    pub steamid: u64,
+    // Only set for bots
+    pub entity_id: Option<i32>,
+    // Inclusive tick ranges the player has rows in, rows are numbered through them in order
+    pub tick_spans: Vec<[i32; 2]>,
+    pub columns: AHashMap<u32, RlePropColumn>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ChangeLog {
+    pub players: Vec<PlayerChangeLog>,
+    player_idx: AHashMap<(u64, Option<i32>), usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropChange {
+    pub tick: i32,
+    pub steamid: u64,
+    pub prop_id: u32,
+    pub value: Option<Variant>,
+}
+
+fn none_column(rows: usize) -> RlePropColumn {
+    let mut column = RlePropColumn::new();
+    column.push_n(None, rows);
+    column
+}
+
+impl PlayerChangeLog {
+    fn new(steamid: u64, entity_id: Option<i32>) -> Self {
+        PlayerChangeLog {
+            steamid,
+            entity_id,
+            tick_spans: vec![],
+            columns: AHashMap::default(),
+        }
+    }
+    fn push_tick(&mut self, tick: i32) {
+        match self.tick_spans.last_mut() {
+            Some(span) if span[1] + 1 == tick => span[1] = tick,
+            _ => self.tick_spans.push([tick, tick]),
+        }
+    }
+    fn push_row(&mut self, tick: i32, values: Vec<(u32, Option<Variant>)>) {
+        let row = self.num_rows();
+        self.push_tick(tick);
+        for (prop_id, value) in values {
+            if prop_id == TICK_ID || prop_id == STEAMID_ID {
+                continue;
+            }
+            // The velocity props are normally computed from the dense output
+            let value = match VELOCITY_IDS.contains(&prop_id) {
+                true => self.velocity(prop_id, row),
+                false => value,
+            };
+            self.columns.entry(prop_id).or_insert_with(|| none_column(row)).push(value);
+        }
+    }
+    // Adds the rows the same player has in the chunk after this one
+    fn append(&mut self, mut other: PlayerChangeLog) {
+        let offset = self.num_rows();
+        let other_rows = other.num_rows();
+        for span in other.tick_spans {
+            self.push_tick(span[0]);
+            if let Some(last) = self.tick_spans.last_mut() {
+                last[1] = span[1];
+            }
+        }
+        let velocity_columns: Vec<(u32, RlePropColumn)> = VELOCITY_IDS.iter().filter_map(|id| other.columns.remove_entry(id)).collect();
+        for (prop_id, column) in other.columns {
+            self.columns.entry(prop_id).or_insert_with(|| none_column(offset)).append_from(&column, 0);
+        }
+        // First rows of a chunk had no earlier rows to compute the velocity from
+        for (prop_id, column) in velocity_columns {
+            let fixed: Vec<Option<Variant>> = (offset..offset + other_rows.min(2)).map(|row| self.velocity(prop_id, row)).collect();
+            let own = self.columns.entry(prop_id).or_insert_with(|| none_column(offset));
+            for value in fixed {
+                own.push(value);
+            }
+            own.append_from(&column, 2);
+        }
+        for column in self.columns.values_mut() {
+            if column.len < offset + other_rows {
+                column.push_n(None, offset + other_rows - column.len);
+            }
+        }
+    }
+    // Same as recompute_velocity: difference between the two rows before this one
+    fn velocity(&self, prop_id: u32, row: usize) -> Option<Variant> {
+        if row < 2 {
+            return None;
+        }
+        let axis = |axis_id: u32| match self.columns.get(&axis_id).map(|column| (column.get(row - 1), column.get(row - 2))) {
+            Some((Some(Variant::F32(previous)), Some(Variant::F32(before_previous)))) => Some(previous * 64.0 - before_previous * 64.0),
+            _ => None,
+        };
+        let velocity = match prop_id {
+            VELOCITY_ID => match (axis(PLAYER_X_ID), axis(PLAYER_Y_ID)) {
+                (Some(x), Some(y)) => Some((x.powi(2) + y.powi(2)).sqrt()),
+                _ => None,
+            },
+            VELOCITY_X_ID => axis(PLAYER_X_ID),
+            VELOCITY_Y_ID => axis(PLAYER_Y_ID),
+            VELOCITY_Z_ID => axis(PLAYER_Z_ID),
+            _ => None,
+        };
+        velocity.map(Variant::F32)
+    }
+    pub fn num_rows(&self) -> usize {
+        self.tick_spans.iter().map(|span| (span[1] - span[0] + 1) as usize).sum()
+    }
+    pub fn row_at(&self, tick: i32) -> Option<usize> {
+        let mut rows_before = 0;
+        for span in &self.tick_spans {
+            if tick < span[0] {
+                return None;
+            }
+            if tick <= span[1] {
+                return Some(rows_before + (tick - span[0]) as usize);
+            }
+            rows_before += (span[1] - span[0] + 1) as usize;
+        }
+        None
+    }
+    fn ticks_with_rows(&self) -> impl Iterator<Item = i32> + '_ {
+        self.tick_spans.iter().flat_map(|span| span[0]..=span[1])
+    }
+}
+
+impl ChangeLog {
+    // values holds every collected prop of the player on this tick
+    pub fn push_row(&mut self, tick: i32, steamid: u64, entity_id: i32, values: Vec<(u32, Option<Variant>)>) {
+        let key = (steamid, if steamid == 0 { Some(entity_id) } else { None });
+        let idx = *self.player_idx.entry(key).or_insert_with(|| {
+            self.players.push(PlayerChangeLog::new(key.0, key.1));
+            self.players.len() - 1
+        });
+        let player = &mut self.players[idx];
+        // Two controllers with the same steamid on one tick, keep the first one
+        if player.tick_spans.last().map(|span| span[1]) == Some(tick) {
+            return;
+        }
+        player.push_row(tick, values);
+    }
+    // Adds the log of the chunk that comes right after this one
+    pub fn append(&mut self, other: ChangeLog) {
+        for player in other.players {
+            let key = (player.steamid, player.entity_id);
+            match self.player_idx.get(&key) {
+                Some(idx) => self.players[*idx].append(player),
+                None => {
+                    self.player_idx.insert(key, self.players.len());
+                    self.players.push(player);
+                }
+            }
+        }
+    }
+    pub fn remove_prop(&mut self, prop_id: u32) {
+        for player in &mut self.players {
+            player.columns.remove(&prop_id);
+        }
+    }
+    // Dense frame in the same layout as the parse_ticks output. Empty ticks means every tick
+    pub fn to_df(&self, ticks: &[i32]) -> AHashMap<u32, PropColumn> {
+        let mut rows = vec![];
+        if ticks.is_empty() {
+            for (player_idx, player) in self.players.iter().enumerate() {
+                for (row, tick) in player.ticks_with_rows().enumerate() {
+                    rows.push((tick, player_idx, row));
+                }
+            }
+        } else {
+            let wanted: AHashSet<i32> = ticks.iter().copied().collect();
+            for (player_idx, player) in self.players.iter().enumerate() {
+                for tick in &wanted {
+                    if let Some(row) = player.row_at(*tick) {
+                        rows.push((*tick, player_idx, row));
+                    }
+                }
+            }
+        }
+        rows.sort_unstable();
+
+        let mut df: AHashMap<u32, PropColumn> = AHashMap::default();
+        let mut prop_ids: AHashSet<u32> = AHashSet::default();
+        for player in &self.players {
+            prop_ids.extend(player.columns.keys());
+        }
+        for (tick, player_idx, row) in &rows {
+            df.entry(TICK_ID).or_insert_with(PropColumn::new).push(Some(Variant::I32(*tick)));
+            df.entry(STEAMID_ID)
+                .or_insert_with(PropColumn::new)
+                .push(Some(Variant::U64(self.players[*player_idx].steamid)));
+            for prop_id in &prop_ids {
+                let value = self.players[*player_idx].columns.get(prop_id).and_then(|column| column.get(*row));
+                df.entry(*prop_id).or_insert_with(PropColumn::new).push(value);
+            }
+        }
+        df
+    }
+    // Every value change as a (tick, steamid, prop, value) record, ordered by tick
+    pub fn changes(&self) -> Vec<PropChange> {
+        let mut changes = vec![];
+        for player in &self.players {
+            let row_ticks: Vec<i32> = player.ticks_with_rows().collect();
+            for (prop_id, column) in &player.columns {
+                for (run, start) in column.run_starts.iter().enumerate() {
+                    changes.push(PropChange {
+                        tick: row_ticks[*start],
+                        steamid: player.steamid,
+                        prop_id: *prop_id,
+                        value: column.values.get(run),
+                    });
+                }
+            }
+        }
+        changes.sort_by_key(|change| (change.tick, change.steamid, change.prop_id));
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEALTH_ID: u32 = 1;
+
+    fn row(health: Option<i32>, x: f32) -> Vec<(u32, Option<Variant>)> {
+        vec![
+            (HEALTH_ID, health.map(Variant::I32)),
+            (PLAYER_X_ID, Some(Variant::F32(x))),
+            (VELOCITY_X_ID, None),
+        ]
+    }
+    fn column(log: &ChangeLog, player: usize, prop_id: u32) -> Vec<Option<Variant>> {
+        let column = &log.players[player].columns[&prop_id];
+        (0..column.len).map(|row| column.get(row)).collect()
+    }
+
+    #[test]
+    fn test_change_log_across_chunks() {
+        // Steamid 1 and two bots on entities 5 and 6, the bots show up in a different order in the second chunk
+        let mut first = ChangeLog::default();
+        for (tick, x) in [(1, 0.0), (2, 1.0)] {
+            first.push_row(tick, 1, 1, row(Some(100), x));
+            first.push_row(tick, 0, 5, row(Some(50), 0.0));
+            first.push_row(tick, 0, 6, row(None, 0.0));
+        }
+        let mut second = ChangeLog::default();
+        for (tick, x) in [(3, 3.0), (4, 6.0)] {
+            second.push_row(tick, 0, 6, row(Some(20), 0.0));
+            second.push_row(tick, 1, 1, row(Some(90), x));
+            second.push_row(tick, 0, 5, row(Some(50), 0.0));
+        }
+        first.append(second);
+        let log = first;
+
+        assert_eq!(log.players.len(), 3);
+        assert_eq!(log.players[0].tick_spans, vec![[1, 4]]);
+        assert_eq!(column(&log, 1, HEALTH_ID), vec![Some(Variant::I32(50)); 4]);
+        assert_eq!(log.players[1].columns[&HEALTH_ID].num_runs(), 1);
+        assert_eq!(column(&log, 2, HEALTH_ID), [None, None, Some(20), Some(20)].map(|h| h.map(Variant::I32)).to_vec());
+        // Velocity of the first rows of the second chunk uses the rows of the first chunk
+        assert_eq!(
+            column(&log, 0, VELOCITY_X_ID),
+            [None, None, Some(64.0), Some(128.0)].map(|v| v.map(Variant::F32)).to_vec()
+        );
+        assert_eq!(log.changes().iter().filter(|change| change.prop_id == HEALTH_ID).count(), 5);
+
+        let subset = log.to_df(&[2, 4]);
+        let health: Vec<Option<Variant>> = (0..subset[&HEALTH_ID].len()).map(|row| subset[&HEALTH_ID].get(row)).collect();
+        assert_eq!(
+            health,
+            [Some(100), Some(50), None, Some(90), Some(50), Some(20)].map(|h| h.map(Variant::I32)).to_vec()
+        );
+    }
+}
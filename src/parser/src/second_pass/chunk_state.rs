@@ -0,0 +1,306 @@
+use crate::first_pass::prop_controller::{PLAYER_X_ID, PLAYER_Y_ID, PLAYER_Z_ID, STEAMID_ID, TICK_ID, VELOCITY_ID, VELOCITY_X_ID, VELOCITY_Y_ID, VELOCITY_Z_ID};
+use crate::first_pass::read_bits::DemoParserError;
+use crate::second_pass::parser_settings::SecondPassParser;
+use crate::second_pass::variants::{PropColumn, VarVec, Variant};
+use ahash::{AHashMap, AHashSet};
+
+/*
+Chunks start from the entity snapshot in a fullpacket, but some props are not in sync with
+what the delta packets before it said (duck state, per round stats, reserve ammo...). The
+single threaded parser never reads entities from fullpackets so it does not have this problem.
+
+To get identical results every chunk is first parsed once without collecting anything,
+remembering the last value every prop was given by a delta packet. Folding these in order
+gives each chunk the state the single threaded parser would have had at the same offset.
+The spotted pairs and recent positions the kill context reads are carried the same way.
+*/
+
+#[derive(Debug, Clone, Default)]
+pub struct CarriedEntity {
+    // None if the entity was deleted
+    pub cls_id: Option<u32>,
+    // Created or deleted during the chunk, earlier values no longer apply
+    pub reset: bool,
+    pub props: AHashMap<u32, Variant>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ChunkState {
+    pub entities: AHashMap<i32, CarriedEntity>,
+    // Kill context state, only filled when parse_kill_context is set. spotted_since_from is the
+    // first tick the pairs were collected on
+    pub spotted_since: AHashMap<(u64, u64), Option<i32>>,
+    pub spotted_since_from: Option<i32>,
+    pub recent_positions: AHashMap<u64, Vec<(i32, [f32; 3])>>,
+}
+
+// Delta written props of one chunk, filled while parsing it
+#[derive(Debug, Clone, Default)]
+pub struct WrittenProps {
+    pub props: AHashMap<i32, AHashSet<u32>>,
+    pub reset: AHashSet<i32>,
+}
+
+impl WrittenProps {
+    pub fn reset_entity(&mut self, entity_id: i32) {
+        self.props.remove(&entity_id);
+        self.reset.insert(entity_id);
+    }
+}
+
+impl ChunkState {
+    // Applies the state at the end of the next chunk on top of this one
+    pub fn extend(&mut self, next: &ChunkState) {
+        for (entity_id, carried) in &next.entities {
+            match self.entities.get_mut(entity_id) {
+                Some(existing) if !carried.reset && carried.cls_id.is_some() => {
+                    existing.props.extend(carried.props.iter().map(|(k, v)| (*k, v.clone())));
+                }
+                _ => {
+                    self.entities.insert(*entity_id, carried.clone());
+                }
+            }
+        }
+        if let Some(next_from) = next.spotted_since_from {
+            // The first chunk starts where the single threaded parser does and keeps its unknown ticks.
+            // Pairs spotted when a later chunk started were either spotted here already or on its first tick.
+            let resolve = self.spotted_since_from.is_some();
+            self.spotted_since = next
+                .spotted_since
+                .iter()
+                .map(|(pair, since)| match (since, resolve) {
+                    (None, true) => (*pair, self.spotted_since.get(pair).copied().unwrap_or(Some(next_from))),
+                    _ => (*pair, *since),
+                })
+                .collect();
+            self.spotted_since_from.get_or_insert(next_from);
+        }
+        for (steamid, samples) in &next.recent_positions {
+            let carried = self.recent_positions.entry(*steamid).or_default();
+            carried.extend(samples);
+            carried.dedup_by_key(|(tick, _)| *tick);
+            carried.drain(..carried.len().saturating_sub(2));
+        }
+    }
+}
+
+// The state each chunk starts from, given the states the chunks before it ended with
+pub fn fold_chunk_states(chunk_states: &[ChunkState]) -> Vec<ChunkState> {
+    let mut carried_state = ChunkState::default();
+    let mut carried_states = Vec::with_capacity(chunk_states.len());
+    for chunk_state in chunk_states {
+        carried_states.push(carried_state.clone());
+        carried_state.extend(chunk_state);
+    }
+    carried_states
+}
+
+impl<'a> SecondPassParser<'a> {
+    pub fn create_chunk_state(&self) -> ChunkState {
+        let written = match &self.written_props {
+            Some(written) => written,
+            None => return ChunkState::default(),
+        };
+        let mut state = ChunkState::default();
+        let entity_ids: AHashSet<&i32> = written.props.keys().chain(written.reset.iter()).collect();
+        for entity_id in entity_ids {
+            let entity = match self.entities.get(*entity_id as usize) {
+                Some(Some(entity)) => Some(entity),
+                _ => None,
+            };
+            let props = match (entity, written.props.get(entity_id)) {
+                (Some(entity), Some(prop_ids)) => prop_ids.iter().filter_map(|id| entity.props.get(id).map(|v| (*id, v.clone()))).collect(),
+                _ => AHashMap::default(),
+            };
+            state.entities.insert(
+                *entity_id,
+                CarriedEntity {
+                    cls_id: entity.map(|e| e.cls_id),
+                    reset: written.reset.contains(entity_id),
+                    props,
+                },
+            );
+        }
+        state.spotted_since = self.spotted_since.clone();
+        state.spotted_since_from = self.spotted_since_from;
+        state.recent_positions = self.recent_positions.clone();
+        state
+    }
+    // Called instead of collect_entities while the chunk state is being found
+    pub fn collect_chunk_state(&mut self) {
+        if self.parse_kill_context {
+            self.collect_recent_positions();
+            self.collect_spotted_since();
+        }
+    }
+    // Called right after the first fullpacket of the chunk has been parsed
+    pub fn apply_chunk_state(&mut self) -> Result<(), DemoParserError> {
+        let state = match self.carried_state.take() {
+            Some(state) => state,
+            None => return Ok(()),
+        };
+        if state.spotted_since_from.is_some() {
+            self.spotted_since = state.spotted_since;
+            self.spotted_since_from = state.spotted_since_from;
+        }
+        self.recent_positions = state.recent_positions;
+        for (entity_id, carried) in state.entities {
+            if let Some(Some(entity)) = self.entities.get_mut(entity_id as usize) {
+                if Some(entity.cls_id) == carried.cls_id {
+                    entity.props.extend(carried.props);
+                    self.gather_extra_info(&entity_id, false)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn velocity_axis(column: Option<&PropColumn>, previous: usize, before_previous: usize) -> Option<f32> {
+    match column.and_then(|c| c.data.as_ref()) {
+        Some(VarVec::F32(v)) => match (v.get(previous).copied().flatten(), v.get(before_previous).copied().flatten()) {
+            (Some(a), Some(b)) => Some(a * 64.0 - b * 64.0),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// Velocity is computed from the two previous rows of the same player, which the first rows of
+// a chunk do not have. Recomputing it over the combined output fills them in.
+pub fn recompute_velocity(df: &mut AHashMap<u32, PropColumn>) {
+    let velocity_ids = [VELOCITY_ID, VELOCITY_X_ID, VELOCITY_Y_ID, VELOCITY_Z_ID];
+    if !velocity_ids.iter().any(|id| df.contains_key(id)) {
+        return;
+    }
+    let steamids = match (df.get(&STEAMID_ID).and_then(|c| c.data.as_ref()), df.get(&TICK_ID)) {
+        (Some(VarVec::U64(steamids)), _) => steamids.clone(),
+        // Per player frames have no steamid column, every row is the same player
+        (None, Some(ticks)) => vec![Some(0); ticks.len()],
+        _ => return,
+    };
+    let mut recent_rows: AHashMap<u64, (Option<usize>, Option<usize>)> = AHashMap::default();
+    let mut velocities: AHashMap<u32, Vec<Option<f32>>> = velocity_ids.iter().map(|id| (*id, Vec::with_capacity(steamids.len()))).collect();
+    for (row, steamid) in steamids.iter().enumerate() {
+        let rows = steamid.and_then(|s| recent_rows.get(&s)).copied();
+        let (x, y, z) = match rows {
+            Some((Some(previous), Some(before_previous))) => (
+                velocity_axis(df.get(&PLAYER_X_ID), previous, before_previous),
+                velocity_axis(df.get(&PLAYER_Y_ID), previous, before_previous),
+                velocity_axis(df.get(&PLAYER_Z_ID), previous, before_previous),
+            ),
+            _ => (None, None, None),
+        };
+        let speed = match (x, y) {
+            (Some(x), Some(y)) => Some((x.powi(2) + y.powi(2)).sqrt()),
+            _ => None,
+        };
+        for (id, value) in [(VELOCITY_ID, speed), (VELOCITY_X_ID, x), (VELOCITY_Y_ID, y), (VELOCITY_Z_ID, z)] {
+            if let Some(v) = velocities.get_mut(&id) {
+                v.push(value);
+            }
+        }
+        if let Some(steamid) = steamid {
+            let entry = recent_rows.entry(*steamid).or_insert((None, None));
+            *entry = (Some(row), entry.0);
+        }
+    }
+    for (id, values) in velocities {
+        if let Some(column) = df.get_mut(&id) {
+            column.num_nones = 0;
+            column.data = Some(VarVec::F32(values));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn carried(cls_id: Option<u32>, reset: bool, props: &[(u32, i32)]) -> CarriedEntity {
+        CarriedEntity {
+            cls_id,
+            reset,
+            props: props.iter().map(|(k, v)| (*k, Variant::I32(*v))).collect(),
+        }
+    }
+
+    #[test]
+    fn test_extend_chunk_states() {
+        let first = ChunkState {
+            entities: AHashMap::from_iter([(1, carried(Some(10), true, &[(1, 1), (2, 2)])), (2, carried(Some(11), false, &[(1, 5)]))]),
+            ..Default::default()
+        };
+        let second = ChunkState {
+            entities: AHashMap::from_iter([(1, carried(Some(10), false, &[(2, 3)])), (2, carried(None, true, &[]))]),
+            ..Default::default()
+        };
+        let third = ChunkState {
+            entities: AHashMap::from_iter([(2, carried(Some(12), true, &[(3, 7)]))]),
+            ..Default::default()
+        };
+        let carried = fold_chunk_states(&[first, second, third.clone()]);
+        assert!(carried[0].entities.is_empty());
+        let mut folded = carried[2].clone();
+        folded.extend(&third);
+        assert_eq!(folded.entities[&1].props[&1], Variant::I32(1));
+        assert_eq!(folded.entities[&1].props[&2], Variant::I32(3));
+        // Deleted and recreated as something else, nothing from before survives
+        assert_eq!(folded.entities[&2].cls_id, Some(12));
+        assert_eq!(folded.entities[&2].props.len(), 1);
+    }
+
+    #[test]
+    fn test_extend_kill_context_state() {
+        let chunk = |from: i32, spotted: &[((u64, u64), Option<i32>)], positions: &[(i32, f32)]| ChunkState {
+            spotted_since: spotted.iter().copied().collect(),
+            spotted_since_from: Some(from),
+            recent_positions: AHashMap::from_iter([(1, positions.iter().map(|(tick, x)| (*tick, [*x, 0.0, 0.0])).collect())]),
+            ..Default::default()
+        };
+        let first = chunk(1, &[((1, 2), None), ((1, 3), Some(5))], &[(9, 1.0), (10, 2.0)]);
+        // (1, 2) and (1, 3) stayed spotted into the second chunk, (2, 1) got spotted on its first tick
+        let second = chunk(11, &[((1, 2), None), ((1, 3), None), ((2, 1), None), ((3, 1), Some(15))], &[(11, 3.0)]);
+        let carried = fold_chunk_states(&[first, second.clone()]);
+        let mut folded = carried[1].clone();
+        folded.extend(&second);
+        // Unknown in the first chunk stays unknown like in the single threaded parser
+        assert_eq!(folded.spotted_since[&(1, 2)], None);
+        assert_eq!(folded.spotted_since[&(1, 3)], Some(5));
+        assert_eq!(folded.spotted_since[&(2, 1)], Some(11));
+        assert_eq!(folded.spotted_since[&(3, 1)], Some(15));
+        // Pairs no longer spotted are dropped, positions keep the last two ticks
+        folded.extend(&chunk(21, &[((1, 3), None)], &[(21, 4.0)]));
+        assert_eq!(folded.spotted_since.len(), 1);
+        assert_eq!(folded.spotted_since[&(1, 3)], Some(5));
+        assert_eq!(folded.recent_positions[&1], vec![(11, [3.0, 0.0, 0.0]), (21, [4.0, 0.0, 0.0])]);
+    }
+
+    #[test]
+    fn test_recompute_velocity() {
+        let mut df = AHashMap::default();
+        let column = |data: VarVec| PropColumn {
+            data: Some(data),
+            num_nones: 0,
+        };
+        df.insert(STEAMID_ID, column(VarVec::U64(vec![Some(1), Some(2), Some(1), Some(2), Some(1)])));
+        df.insert(PLAYER_X_ID, column(VarVec::F32(vec![Some(0.0), Some(0.0), Some(1.0), Some(0.0), Some(3.0)])));
+        df.insert(PLAYER_Y_ID, column(VarVec::F32(vec![Some(0.0); 5])));
+        df.insert(VELOCITY_X_ID, column(VarVec::F32(vec![None; 5])));
+        recompute_velocity(&mut df);
+        match &df[&VELOCITY_X_ID].data {
+            Some(VarVec::F32(v)) => assert_eq!(v, &vec![None, None, None, None, Some(64.0)]),
+            _ => panic!("velocity_X is not f32"),
+        }
+
+        df.remove(&STEAMID_ID);
+        // Per player frame of steamid 1, one row per tick
+        df.insert(TICK_ID, column(VarVec::I32(vec![Some(1), Some(2), Some(3), Some(4), Some(5)])));
+        df.insert(PLAYER_X_ID, column(VarVec::F32(vec![Some(0.0), Some(1.0), Some(3.0), Some(6.0), Some(6.0)])));
+        recompute_velocity(&mut df);
+        match &df[&VELOCITY_X_ID].data {
+            Some(VarVec::F32(v)) => assert_eq!(v, &vec![None, None, Some(64.0), Some(128.0), Some(192.0)]),
+            _ => panic!("velocity_X is not f32"),
+        }
+    }
+}
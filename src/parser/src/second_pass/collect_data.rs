@@ -59,6 +59,29 @@ pub enum CoordinateAxis {
 
 impl<'a> SecondPassParser<'a> {
     pub fn collect_entities(&mut self) {
+        if self.parse_spotting {
+            self.collect_spotting();
+        }
+        if self.fov.is_some() {
+            self.collect_crosshair_samples();
+        }
+        if self.parse_kill_context || self.parse_shots {
+            self.collect_recent_positions();
+        }
+        if self.parse_kill_context {
+            self.collect_spotted_since();
+        }
+        if self.parse_movement {
+            self.collect_movement_samples();
+        }
+        // Lifecycles and fires follow every tick of the grenade, not just the wanted ones
+        if self.parse_projectiles {
+            self.collect_grenade_trajectories();
+            self.collect_inferno_fires();
+        }
+        if self.parse_weapons {
+            self.collect_weapon_owners();
+        }
         if !self.prop_controller.event_with_velocity {
             if !self.wanted_ticks.contains(&self.tick) && self.wanted_ticks.len() != 0 || self.wanted_events.len() != 0 {
                 return;
@@ -67,20 +90,38 @@ impl<'a> SecondPassParser<'a> {
         if self.parse_projectiles {
             self.collect_projectiles();
         }
+        if !self.wanted_entity_classes.is_empty() {
+            self.collect_entity_rows();
+        }
+        if self.parse_weapons {
+            self.collect_ground_weapons();
+        }
+        self.collect_visibility();
+        self.collect_fov();
         // iterate every player and every wanted prop name
         // if either one is missing then push None to output
         for (entity_id, player) in &self.players {
-            // iterate every wanted prop state
-            // if any prop's state for this tick is not the wanted state, dont extract info from tick
-            for wanted_prop_state_info in &self.prop_controller.wanted_prop_state_infos {
-                match self.find_prop(&wanted_prop_state_info.base, entity_id, player) {
-                    Ok(prop) => {
-                        if prop != wanted_prop_state_info.wanted_prop_state {
-                            return;
-                        }
-                    }
-                    Err(_e) => return,
+            // rows that don't pass the prop_states filter are never collected
+            let filter = &self.prop_controller.wanted_prop_states;
+            if !filter.is_empty() && !filter.matches(&|prop_name| self.find_prop_state(prop_name, entity_id, player)) {
+                continue;
+            }
+            // change_log=True keeps the rows run-length encoded instead of filling the dense output
+            if self.change_log.is_some() {
+                let player_steamid = player.steamid.unwrap_or(0);
+                if !self.wanted_players.is_empty() && !self.wanted_players.contains(&player_steamid) {
+                    continue;
+                }
+                let values = self
+                    .prop_controller
+                    .prop_infos
+                    .iter()
+                    .map(|prop_info| (prop_info.id, self.find_prop(prop_info, entity_id, player).ok()))
+                    .collect();
+                if let Some(change_log) = self.change_log.as_mut() {
+                    change_log.push_row(self.tick, player_steamid, *entity_id, values);
                 }
+                continue;
             }
 
             for prop_info in &self.prop_controller.prop_infos {
@@ -137,6 +178,11 @@ impl<'a> SecondPassParser<'a> {
             PropType::GameTime => return Ok(Variant::F32(self.net_tick as f32 / 64.0)),
         }
     }
+    // Value of a prop the prop_states filter refers to
+    fn find_prop_state(&self, prop_name: &str, entity_id: &i32, player: &PlayerMetaData) -> Option<Variant> {
+        let info = self.prop_controller.wanted_prop_state_infos.get(prop_name)?;
+        self.find_prop(&info.base, entity_id, player).ok()
+    }
     pub fn get_prop_from_ent(&self, prop_id: &u32, entity_id: &i32) -> Result<Variant, PropCollectionError> {
         match self.entities.get(*entity_id as usize) {
             Some(Some(e)) => match e.props.get(&prop_id) {
@@ -188,7 +234,7 @@ impl<'a> SecondPassParser<'a> {
             None => return Err(PropCollectionError::ControllerEntityIdNotSet),
         }
     }
-    fn find_owner_entid(&self, entity_id: &i32) -> Result<u32, PropCollectionError> {
+    pub fn find_owner_entid(&self, entity_id: &i32) -> Result<u32, PropCollectionError> {
         let owner_id = match self.prop_controller.special_ids.grenade_owner_id {
             Some(owner_id) => owner_id,
             None => return Err(PropCollectionError::GrenadeOwnerIdNotSet),
@@ -223,7 +269,7 @@ impl<'a> SecondPassParser<'a> {
         }
     }
 
-    fn find_grenade_type(&self, entity_id: &i32) -> Option<String> {
+    pub fn find_grenade_type(&self, entity_id: &i32) -> Option<String> {
         if let Some(Some(ent)) = self.entities.get(*entity_id as usize) {
             if let Some(cls) = self.cls_by_id.get(ent.cls_id as usize) {
                 match GRENADE_FRIENDLY_NAMES.get(&cls.name) {
@@ -403,7 +449,7 @@ impl<'a> SecondPassParser<'a> {
         };
         Ok(Variant::F32(coordinate?))
     }
-    fn find_pitch_or_yaw(&self, entity_id: &i32, idx: usize) -> Result<Variant, PropCollectionError> {
+    pub fn find_pitch_or_yaw(&self, entity_id: &i32, idx: usize) -> Result<Variant, PropCollectionError> {
         match self.prop_controller.special_ids.eye_angles {
             Some(prop_id) => match self.get_prop_from_ent(&prop_id, entity_id) {
                 Ok(Variant::VecXYZ(v)) => return Ok(Variant::F32(v[idx])),
@@ -438,9 +484,16 @@ impl<'a> SecondPassParser<'a> {
             "is_alive" => return self.find_is_alive(entity_id),
             "user_id" => return self.get_userid(player),
             "is_airborne" => self.find_is_airborne(player),
+            "in_smoke" => self.find_in_smoke(player),
+            "radar_x" | "radar_y" | "radar_level" => self.find_radar_position(prop_name, entity_id),
+            "nav_area_id" => self.find_nav_area_id(entity_id),
+            "nav_place" => self.find_nav_place(),
+            "closest_enemy_angle" => self.find_closest_enemy_angle(entity_id),
+            "enemies_in_fov" => self.find_enemies_in_fov(entity_id),
             "agent_skin" => return self.find_agent_skin(player),
             "CCSPlayerController.m_iCompTeammateColor" => return self.find_player_color(player, prop_info),
             "usercmd_input_history" => self.get_prop_from_ent(&USERCMD_INPUT_HISTORY_BASEID, entity_id),
+            "usercmd_subtick_moves" => self.get_prop_from_ent(&USERCMD_SUBTICK_MOVES, entity_id),
             _ => Err(PropCollectionError::UnknownCustomPropName),
         }
     }
@@ -473,6 +526,48 @@ impl<'a> SecondPassParser<'a> {
         }
         Ok(Variant::Bool(false))
     }
+    pub fn find_radar_position(&self, prop_name: &str, entity_id: &i32) -> Result<Variant, PropCollectionError> {
+        let overview = match &self.map_overview {
+            Some(overview) => overview,
+            None => return Err(PropCollectionError::MapOverviewNotFound),
+        };
+        let x = self.collect_cell_coordinate_player(CoordinateAxis::X, entity_id);
+        let y = self.collect_cell_coordinate_player(CoordinateAxis::Y, entity_id);
+        let z = self.collect_cell_coordinate_player(CoordinateAxis::Z, entity_id);
+        let position = match (x?, y?, z?) {
+            (Variant::F32(x), Variant::F32(y), Variant::F32(z)) => overview.world_to_radar(x, y, z),
+            _ => return Err(PropCollectionError::CoordinateIncorrectTypes),
+        };
+        match prop_name {
+            "radar_x" => Ok(Variant::F32(position.x)),
+            "radar_y" => Ok(Variant::F32(position.y)),
+            _ => Ok(Variant::String(position.level)),
+        }
+    }
+    pub fn find_nav_area_id(&self, entity_id: &i32) -> Result<Variant, PropCollectionError> {
+        let nav_mesh = match &self.nav_mesh {
+            Some(nav_mesh) => nav_mesh,
+            None => return Err(PropCollectionError::NavMeshNotFound),
+        };
+        let x = self.collect_cell_coordinate_player(CoordinateAxis::X, entity_id);
+        let y = self.collect_cell_coordinate_player(CoordinateAxis::Y, entity_id);
+        let z = self.collect_cell_coordinate_player(CoordinateAxis::Z, entity_id);
+        match (x?, y?, z?) {
+            (Variant::F32(x), Variant::F32(y), Variant::F32(z)) => match nav_mesh.find_area(x, y, z) {
+                Some(area) => Ok(Variant::U32(area.area_id)),
+                None => Err(PropCollectionError::NavAreaNotFound),
+            },
+            _ => Err(PropCollectionError::CoordinateIncorrectTypes),
+        }
+    }
+    pub fn find_nav_place(&self) -> Result<Variant, PropCollectionError> {
+        // CS2 nav meshes don't store place names (the place directory of older formats is gone),
+        // so there is nothing to name the area after. m_szLastPlaceName is the game's own value.
+        match &self.nav_mesh {
+            Some(_) => Err(PropCollectionError::NavPlaceNotInMesh),
+            None => Err(PropCollectionError::NavMeshNotFound),
+        }
+    }
     pub fn find_skin_float(&self, player: &PlayerMetaData) -> Result<Variant, PropCollectionError> {
         if let Some(player_entity_id) = &player.player_entity_id {
             return self.find_weapon_prop(&WEAPON_FLOAT, &player_entity_id);
@@ -550,13 +645,50 @@ impl<'a> SecondPassParser<'a> {
             Err(_) => return Err(PropCollectionError::AgentPropNotFound),
         }
     }
+    // Last two positions of every player, for speeds at events. The velocity props need the collected tick output instead
+    pub fn collect_recent_positions(&mut self) {
+        let mut positions = vec![];
+        for (entity_id, player) in &self.players {
+            if let (Some(steamid), Some(position)) = (player.steamid, self.find_position(entity_id)) {
+                positions.push((steamid, position));
+            }
+        }
+        for (steamid, position) in positions {
+            let samples = self.recent_positions.entry(steamid).or_default();
+            match samples.last() {
+                Some((tick, _)) if *tick == self.tick => {}
+                _ => samples.push((self.tick, position)),
+            }
+            if samples.len() > 2 {
+                samples.remove(0);
+            }
+        }
+    }
+    pub fn find_position(&self, entity_id: &i32) -> Option<[f32; 3]> {
+        match (
+            self.collect_cell_coordinate_player(CoordinateAxis::X, entity_id),
+            self.collect_cell_coordinate_player(CoordinateAxis::Y, entity_id),
+            self.collect_cell_coordinate_player(CoordinateAxis::Z, entity_id),
+        ) {
+            (Ok(Variant::F32(x)), Ok(Variant::F32(y)), Ok(Variant::F32(z))) => Some([x, y, z]),
+            _ => None,
+        }
+    }
+    pub fn find_horizontal_speed(&self, steamid: u64) -> Option<f32> {
+        match self.recent_positions.get(&steamid).map(|v| v.as_slice()) {
+            Some([(start_tick, start), (end_tick, end)]) if end_tick > start_tick => {
+                let (dx, dy) = (end[0] - start[0], end[1] - start[1]);
+                Some((dx * dx + dy * dy).sqrt() / ((end_tick - start_tick) as f32 / 64.0))
+            }
+            _ => None,
+        }
+    }
     pub fn collect_velocity(&self, player: &PlayerMetaData) -> Result<Variant, PropCollectionError> {
         if let Some(s) = player.steamid {
-            let steamids = self.output.get(&STEAMID_ID);
-            let indicies = self.find_wanted_indicies(steamids, s);
+            let (df, indicies) = self.velocity_rows(s);
 
-            let x = self.velocity_from_indicies(&indicies, CoordinateAxis::X)?;
-            let y = self.velocity_from_indicies(&indicies, CoordinateAxis::Y)?;
+            let x = self.velocity_from_indicies(df, &indicies, CoordinateAxis::X)?;
+            let y = self.velocity_from_indicies(df, &indicies, CoordinateAxis::Y)?;
 
             if let (Variant::F32(x), Variant::F32(y)) = (x, y) {
                 return Ok(Variant::F32((f32::powi(x, 2) + f32::powi(y, 2)).sqrt()));
@@ -566,12 +698,20 @@ impl<'a> SecondPassParser<'a> {
     }
     pub fn collect_velocity_axis(&self, player: &PlayerMetaData, axis: CoordinateAxis) -> Result<Variant, PropCollectionError> {
         if let Some(s) = player.steamid {
-            let steamids = self.output.get(&STEAMID_ID);
-            let indicies = self.find_wanted_indicies(steamids, s);
-            return Ok(self.velocity_from_indicies(&indicies, axis)?);
+            let (df, indicies) = self.velocity_rows(s);
+            return Ok(self.velocity_from_indicies(df, &indicies, axis)?);
         }
         return Err(PropCollectionError::PlayerNotFound);
     }
+    // With order_by_steamid the earlier rows of the player are in their own frame, not in output
+    fn velocity_rows(&self, steamid: u64) -> (Option<&AHashMap<u32, PropColumn>>, Vec<usize>) {
+        let df = match self.order_by_steamid {
+            true => self.df_per_player.get(&steamid),
+            false => Some(&self.output),
+        };
+        let indicies = self.find_wanted_indicies(df.and_then(|df| df.get(&STEAMID_ID)), steamid);
+        (df, indicies)
+    }
     fn find_most_recent_coordinate_idx(&self, optv: Option<&PropColumn>, wanted_steamid: u64) -> Option<usize> {
         if let Some(v) = optv {
             if let Some(VarVec::U64(steamid_vec)) = &v.data {
@@ -606,11 +746,11 @@ impl<'a> SecondPassParser<'a> {
         vec![]
     }
 
-    fn velocity_from_indicies(&self, indicies: &[usize], axis: CoordinateAxis) -> Result<Variant, PropCollectionError> {
+    fn velocity_from_indicies(&self, df: Option<&AHashMap<u32, PropColumn>>, indicies: &[usize], axis: CoordinateAxis) -> Result<Variant, PropCollectionError> {
         let col = match axis {
-            CoordinateAxis::X => self.output.get(&PLAYER_X_ID),
-            CoordinateAxis::Y => self.output.get(&PLAYER_Y_ID),
-            CoordinateAxis::Z => self.output.get(&PLAYER_Z_ID),
+            CoordinateAxis::X => df.and_then(|df| df.get(&PLAYER_X_ID)),
+            CoordinateAxis::Y => df.and_then(|df| df.get(&PLAYER_Y_ID)),
+            CoordinateAxis::Z => df.and_then(|df| df.get(&PLAYER_Z_ID)),
         };
         if let Some(c) = col {
             if let Some((Some(v1), Some(v2))) = self.index_coordinates_from_propcol(c, indicies) {
@@ -651,7 +791,7 @@ impl<'a> SecondPassParser<'a> {
             Err(e) => return Err(e),
         }
     }
-    fn steamids_from_mask(&self, uid: u32) -> Vec<u64> {
+    pub fn steamids_from_mask(&self, uid: u32) -> Vec<u64> {
         let mut steamids = vec![];
         for i in 0..16 {
             if (uid & (1 << i)) != 0 {
@@ -1031,7 +1171,7 @@ impl<'a> SecondPassParser<'a> {
     }
 }
 
-fn coord_from_cell(cell: Result<Variant, PropCollectionError>, offset: Result<Variant, PropCollectionError>) -> Result<f32, PropCollectionError> {
+pub fn coord_from_cell(cell: Result<Variant, PropCollectionError>, offset: Result<Variant, PropCollectionError>) -> Result<f32, PropCollectionError> {
     // Both cell and offset are needed for calculation
     match (offset, cell) {
         (Ok(Variant::F32(offset)), Ok(Variant::U32(cell))) => {
@@ -1106,6 +1246,11 @@ pub enum PropCollectionError {
     AgentSpecialIdNotSet,
     UseridNotFound,
     InventoryMaxNotFound,
+    MapOverviewNotFound,
+    NavMeshNotFound,
+    NavAreaNotFound,
+    NavPlaceNotInMesh,
+    NoEnemiesAlive,
 }
 impl std::error::Error for PropCollectionError {}
 impl fmt::Display for PropCollectionError {
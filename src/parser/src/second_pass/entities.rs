@@ -10,6 +10,7 @@ use crate::second_pass::other_netmessages::Class;
 use crate::second_pass::parser_settings::SecondPassParser;
 use crate::second_pass::path_ops::*;
 use crate::second_pass::variants::Variant;
+use crate::second_pass::weapons::{is_weapon_class, WeaponInput};
 use ahash::AHashMap;
 use csgoproto::netmessages::CSVCMsg_PacketEntities;
 use protobuf::Message;
@@ -42,6 +43,9 @@ pub enum EntityType {
     Team,
     Normal,
     C4,
+    Inferno,
+    PlantedC4,
+    Weapon,
 }
 enum EntityCmd {
     Delete,
@@ -75,14 +79,43 @@ impl<'a> SecondPassParser<'a> {
 
             match cmd {
                 EntityCmd::Delete => {
+                    if self.parse_projectiles {
+                        self.end_grenade_lifecycle(entity_id);
+                    }
                     self.projectiles.remove(&entity_id);
+                    self.infernos.remove(&entity_id);
+                    if self.weapons.remove(&entity_id) && self.parse_weapons {
+                        self.weapon_owners.remove(&entity_id);
+                        self.weapon_inputs.push(WeaponInput::Deleted { tick: self.tick, entity_id });
+                    }
+                    if self.planted_c4_entity_id == Some(entity_id) {
+                        self.planted_c4_entity_id = None;
+                    }
+                    self.create_custom_event_entity_lifecycle("entity_deleted", entity_id);
                     if let Some(entry) = self.entities.get_mut(entity_id as usize) {
                         *entry = None;
                     }
+                    if let Some(written) = &mut self.written_props {
+                        written.reset_entity(entity_id);
+                    }
                 }
                 EntityCmd::CreateAndUpdate => {
+                    if let (Some(written), false) = (&mut self.written_props, is_fullpacket) {
+                        written.reset_entity(entity_id);
+                    }
                     self.create_new_entity(&mut bitreader, &entity_id, &mut events_to_emit)?;
                     self.update_entity(&mut bitreader, entity_id, false, &mut events_to_emit, is_fullpacket)?;
+                    if self.parse_projectiles {
+                        self.start_grenade_lifecycle(entity_id, is_fullpacket);
+                    }
+                    // Entities in fullpackets already existed, they are only sent again
+                    if !is_fullpacket {
+                        self.create_custom_event_entity_lifecycle("entity_created", entity_id);
+                    }
+                    if self.parse_weapons && !is_fullpacket && self.weapons.contains(&entity_id) {
+                        self.weapon_owners.remove(&entity_id);
+                        self.weapon_inputs.push(WeaponInput::Created { tick: self.tick, entity_id });
+                    }
                 }
                 EntityCmd::Update => {
                     if msg.has_pvs_vis_bits() > 0 {
@@ -98,6 +131,9 @@ impl<'a> SecondPassParser<'a> {
         if !events_to_emit.is_empty() {
             self.emit_events(events_to_emit)?;
         }
+        if !self.pending_bomb_events.is_empty() {
+            self.fill_pending_bomb_events();
+        }
         Ok(())
     }
 
@@ -236,7 +272,7 @@ impl<'a> SecondPassParser<'a> {
             let result = bitreader.decode(&decoder, self.qf_mapper)?;
 
             if !is_fullpacket && !is_baseline {
-                events_to_emit.extend(SecondPassParser::listen_for_events(entity, &result, field, field_info, &self.prop_controller));
+                events_to_emit.extend(SecondPassParser::listen_for_events(entity, &result, field, field_info, self.prop_controller, &self.watched_props));
             }
             if self.is_debug_mode {
                 SecondPassParser::debug_inspect(
@@ -253,6 +289,11 @@ impl<'a> SecondPassParser<'a> {
                 );
             }
 
+            if let (Some(written), Some(fi)) = (&mut self.written_props, field_info) {
+                if !is_fullpacket && !is_baseline && fi.should_parse {
+                    written.props.entry(entity_id).or_default().insert(fi.prop_id);
+                }
+            }
             SecondPassParser::insert_field(entity, result, field_info);
         }
         Ok(n_updates)
@@ -309,8 +350,18 @@ impl<'a> SecondPassParser<'a> {
             EntityType::Projectile => {
                 self.projectiles.insert(*entity_id);
             }
+            EntityType::Inferno => {
+                self.infernos.insert(*entity_id);
+            }
             EntityType::Rules => self.rules_entity_id = Some(*entity_id),
-            EntityType::C4 => self.c4_entity_id = Some(*entity_id),
+            EntityType::C4 => {
+                self.c4_entity_id = Some(*entity_id);
+                self.weapons.insert(*entity_id);
+            }
+            EntityType::Weapon => {
+                self.weapons.insert(*entity_id);
+            }
+            EntityType::PlantedC4 => self.planted_c4_entity_id = Some(*entity_id),
             _ => {}
         };
         let entity = Entity {
@@ -351,11 +402,16 @@ impl<'a> SecondPassParser<'a> {
             "CCSGameRulesProxy" => return Ok(EntityType::Rules),
             "CCSTeam" => return Ok(EntityType::Team),
             "CC4" => return Ok(EntityType::C4),
+            "CInferno" => return Ok(EntityType::Inferno),
+            "CPlantedC4" => return Ok(EntityType::PlantedC4),
             _ => {}
         }
         if class.name.contains("Projectile") || class.name == "CIncendiaryGrenade" {
             return Ok(EntityType::Projectile);
         }
+        if is_weapon_class(&class.name) {
+            return Ok(EntityType::Weapon);
+        }
         return Ok(EntityType::Normal);
     }
 }
@@ -0,0 +1,195 @@
+use crate::first_pass::prop_controller::PropController;
+use crate::first_pass::read_bits::DemoParserError;
+use crate::second_pass::collect_data::coord_from_cell;
+use crate::second_pass::collect_data::PropCollectionError;
+use crate::second_pass::entities::Entity;
+use crate::second_pass::game_events::EventField;
+use crate::second_pass::parser_settings::SecondPassParser;
+use crate::second_pass::variants::Variant;
+
+#[derive(Debug, Clone)]
+pub struct EntityRecord {
+    pub tick: i32,
+    pub entity_id: i32,
+    pub class_name: String,
+    // One field per wanted prop, in the order they were asked for
+    pub props: Vec<EventField>,
+}
+
+// Where a wanted prop is found on entities of one class
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EntityPropSource {
+    Prop(u32),
+    // "X", "Y" and "Z" are computed from the cell and the offset inside it like for players
+    Coordinate { cell: u32, offset: u32 },
+    // The prop exists in the demo but not on this class
+    Missing,
+}
+
+impl<'a> SecondPassParser<'a> {
+    pub fn collect_entity_rows(&mut self) {
+        for entity in self.entities.iter().flatten() {
+            if self.entity_prop_sources.contains_key(&entity.cls_id) {
+                continue;
+            }
+            if let Some(class) = self.cls_by_id.get(entity.cls_id as usize) {
+                if self.wanted_entity_classes.contains(&class.name) {
+                    let sources = resolve_entity_props(self.prop_controller, &class.name, &self.wanted_entity_props);
+                    self.entity_prop_sources.insert(entity.cls_id, sources);
+                }
+            }
+        }
+        let mut records = vec![];
+        for entity in self.entities.iter().flatten() {
+            let sources = match self.entity_prop_sources.get(&entity.cls_id) {
+                Some(sources) => sources,
+                None => continue,
+            };
+            let class_name = match self.cls_by_id.get(entity.cls_id as usize) {
+                Some(class) => class.name.clone(),
+                None => continue,
+            };
+            records.push(EntityRecord {
+                tick: self.tick,
+                entity_id: entity.entity_id,
+                class_name,
+                props: self
+                    .wanted_entity_props
+                    .iter()
+                    .zip(sources)
+                    .map(|(name, source)| EventField {
+                        name: name.clone(),
+                        data: entity_prop_value(entity, source),
+                    })
+                    .collect(),
+            });
+        }
+        self.entity_records.extend(records);
+    }
+    pub fn find_entity_position(&mut self, entity_id: i32) -> Option<[f32; 3]> {
+        let entity = self.entities.get(entity_id as usize)?.as_ref()?;
+        let class = self.cls_by_id.get(entity.cls_id as usize)?;
+        let prop_controller = self.prop_controller;
+        let sources = self
+            .entity_position_sources
+            .entry(entity.cls_id)
+            .or_insert_with(|| resolve_entity_props(prop_controller, &class.name, &["X", "Y", "Z"].map(|axis| axis.to_string())));
+        match sources.iter().map(|source| entity_prop_value(entity, source)).collect::<Vec<_>>()[..] {
+            [Some(Variant::F32(x)), Some(Variant::F32(y)), Some(Variant::F32(z))] => Some([x, y, z]),
+            _ => None,
+        }
+    }
+}
+
+fn entity_prop_value(entity: &Entity, source: &EntityPropSource) -> Option<Variant> {
+    match source {
+        EntityPropSource::Prop(id) => entity.props.get(id).cloned(),
+        EntityPropSource::Coordinate { cell, offset } => {
+            let cell = entity.props.get(cell).cloned().ok_or(PropCollectionError::CoordinateCellNone);
+            let offset = entity.props.get(offset).cloned().ok_or(PropCollectionError::CoordinateOffsetNone);
+            coord_from_cell(cell, offset).ok().map(Variant::F32)
+        }
+        EntityPropSource::Missing => None,
+    }
+}
+
+// Props are resolved per class once an entity of the class shows up, so names that match no
+// class at all are rejected up front instead of ending up as an all None column.
+pub fn check_entity_props(prop_controller: &PropController, wanted_props: &[String]) -> Result<(), DemoParserError> {
+    for prop in wanted_props {
+        if matches!(prop.as_str(), "X" | "Y" | "Z") {
+            continue;
+        }
+        let suffix = format!(".{prop}");
+        let found = prop_controller
+            .name_to_id
+            .keys()
+            .any(|name| name == prop || name.ends_with(&suffix) || prop.ends_with(&format!(".{name}")));
+        if !found {
+            return Err(DemoParserError::UnknownPropName(prop.clone()));
+        }
+    }
+    Ok(())
+}
+
+pub fn resolve_entity_props(prop_controller: &PropController, class_name: &str, wanted_props: &[String]) -> Vec<EntityPropSource> {
+    wanted_props
+        .iter()
+        .map(|prop| match prop.as_str() {
+            "X" | "Y" | "Z" => {
+                let cell = find_body_component_prop(prop_controller, class_name, &format!("m_cell{prop}"));
+                let offset = find_body_component_prop(prop_controller, class_name, &format!("m_vec{prop}"));
+                match (cell, offset) {
+                    (Some(cell), Some(offset)) => EntityPropSource::Coordinate { cell, offset },
+                    _ => EntityPropSource::Missing,
+                }
+            }
+            _ => match find_entity_prop(prop_controller, class_name, prop) {
+                Some(id) => EntityPropSource::Prop(id),
+                None => EntityPropSource::Missing,
+            },
+        })
+        .collect()
+}
+
+fn find_entity_prop(prop_controller: &PropController, class_name: &str, prop: &str) -> Option<u32> {
+    // Accepts "CChicken.m_iHealth" and "m_iHealth". Weapon and grenade props are stored
+    // without the class name (CAK47.m_iClip1 => m_iClip1) so try that too.
+    let relative = prop.strip_prefix(&format!("{class_name}.")).unwrap_or(prop);
+    prop_controller
+        .name_to_id
+        .get(&format!("{class_name}.{relative}"))
+        .or_else(|| prop_controller.name_to_id.get(relative))
+        .copied()
+}
+
+fn find_body_component_prop(prop_controller: &PropController, class_name: &str, field: &str) -> Option<u32> {
+    // Body component differs between classes (CBodyComponentBaseAnimGraph, CBodyComponentPoint...)
+    let prefix = format!("{class_name}.CBodyComponent");
+    let suffix = format!(".{field}");
+    let mut candidates: Vec<(&String, &u32)> = prop_controller
+        .name_to_id
+        .iter()
+        .filter(|(name, _)| name.ends_with(&suffix) && (name.starts_with(&prefix) || name.starts_with("CBodyComponent")))
+        .collect();
+    // Names with the class first, then the shortest so the lookup does not depend on hashmap order
+    candidates.sort_by_key(|(name, _)| (!name.starts_with(&prefix), name.len(), name.to_string()));
+    candidates.first().map(|(_, id)| **id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::first_pass::prop_filter::PropFilter;
+    use ahash::AHashMap;
+
+    #[test]
+    fn test_resolve_entity_props() {
+        let mut prop_controller = PropController::new(vec![], vec![], PropFilter::default(), AHashMap::default(), false, &["none".to_string()]);
+        for (name, id) in [
+            ("CChicken.m_iHealth", 1),
+            ("CChicken.CBodyComponentBaseAnimGraph.m_cellX", 2),
+            ("CChicken.CBodyComponentBaseAnimGraph.m_vecX", 3),
+            ("m_iClip1", 4),
+            ("CBodyComponentBaseAnimGraph.m_cellY", 5),
+            ("CBodyComponentBaseAnimGraph.m_vecY", 6),
+        ] {
+            prop_controller.name_to_id.insert(name.to_string(), id);
+        }
+        let wanted = ["m_iHealth", "CChicken.m_iHealth", "X", "Y", "m_iClip1", "CChicken.m_iClip1"].map(|s| s.to_string());
+        assert!(check_entity_props(&prop_controller, &wanted).is_ok());
+        assert!(check_entity_props(&prop_controller, &["m_nothing".to_string()]).is_err());
+        let sources = resolve_entity_props(&prop_controller, "CChicken", &wanted);
+        assert_eq!(
+            sources,
+            vec![
+                EntityPropSource::Prop(1),
+                EntityPropSource::Prop(1),
+                EntityPropSource::Coordinate { cell: 2, offset: 3 },
+                EntityPropSource::Coordinate { cell: 5, offset: 6 },
+                EntityPropSource::Prop(4),
+                EntityPropSource::Prop(4),
+            ]
+        );
+    }
+}
@@ -0,0 +1,313 @@
+use crate::first_pass::read_bits::DemoParserError;
+use crate::second_pass::collect_data::CoordinateAxis;
+use crate::second_pass::collect_data::PropCollectionError;
+use crate::second_pass::game_events::DecodedGameEvent;
+use crate::second_pass::parser_settings::SecondPassParser;
+use crate::second_pass::variants::Variant;
+use crate::second_pass::visibility::angle_between;
+use crate::second_pass::visibility::view_direction;
+use ahash::AHashSet;
+
+// Horizontal fov of CS2 on a 16:9 screen
+pub const DEFAULT_FOV: f32 = 106.26;
+const ASPECT_RATIO: f32 = 16.0 / 9.0;
+
+#[derive(Debug, Clone)]
+pub struct FovRecord {
+    pub tick: i32,
+    pub observer_steamid: Option<u64>,
+    pub observer_name: Option<String>,
+    pub target_steamid: Option<u64>,
+    pub target_name: Option<String>,
+    // Degrees between the crosshair and the target
+    pub head_angle: f32,
+    pub body_angle: f32,
+    // How far the crosshair has to move to reach the head, positive yaw is to the left and positive pitch is down
+    pub yaw_error: f32,
+    pub pitch_error: f32,
+    pub distance: f32,
+    pub in_fov: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct CrosshairSample {
+    pub tick: i32,
+    pub angle: f32,
+    pub yaw_error: f32,
+    pub pitch_error: f32,
+    pub distance: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct CrosshairPlacementRecord {
+    pub round: Option<i32>,
+    // Tick of the first damage in the duel
+    pub tick: i32,
+    pub attacker_steamid: u64,
+    pub attacker_name: Option<String>,
+    pub victim_steamid: u64,
+    pub victim_name: Option<String>,
+    // Tick the attacker spotted the victim, errors are measured here. None if they are measured at the damage tick
+    pub spotted_tick: Option<i32>,
+    pub angle_error: f32,
+    pub yaw_error: f32,
+    pub pitch_error: f32,
+    pub distance: f32,
+}
+
+// Eye position and view angles of a player
+#[derive(Debug, Clone, Copy)]
+pub struct AimView {
+    pub eyes: [f32; 3],
+    pub pitch: f32,
+    pub yaw: f32,
+}
+
+pub fn yaw_pitch_error(view: &AimView, target: [f32; 3]) -> (f32, f32) {
+    let d = [target[0] - view.eyes[0], target[1] - view.eyes[1], target[2] - view.eyes[2]];
+    let wanted_yaw = d[1].atan2(d[0]).to_degrees();
+    let wanted_pitch = -d[2].atan2((d[0] * d[0] + d[1] * d[1]).sqrt()).to_degrees();
+    // Yaw wraps around at +-180
+    let yaw_error = (wanted_yaw - view.yaw + 540.0).rem_euclid(360.0) - 180.0;
+    (yaw_error, wanted_pitch - view.pitch)
+}
+
+pub fn is_in_fov(view: &AimView, target: [f32; 3], fov: f32) -> bool {
+    // Project the target into view space and compare against the screen edges
+    let d = [target[0] - view.eyes[0], target[1] - view.eyes[1], target[2] - view.eyes[2]];
+    let forward = view_direction(view.pitch, view.yaw);
+    let right = view_direction(0.0, view.yaw - 90.0);
+    let up = view_direction(view.pitch - 90.0, view.yaw);
+    let depth = dot(d, forward);
+    if depth <= 0.0 {
+        return false;
+    }
+    let half_width = (fov.to_radians() / 2.0).tan();
+    (dot(d, right) / depth).abs() <= half_width && (dot(d, up) / depth).abs() <= half_width / ASPECT_RATIO
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn sample(view: &AimView, head: [f32; 3], tick: i32) -> CrosshairSample {
+    let to_head = [head[0] - view.eyes[0], head[1] - view.eyes[1], head[2] - view.eyes[2]];
+    let (yaw_error, pitch_error) = yaw_pitch_error(view, head);
+    CrosshairSample {
+        tick,
+        angle: angle_between(view_direction(view.pitch, view.yaw), to_head).unwrap_or(0.0),
+        yaw_error,
+        pitch_error,
+        distance: dot(to_head, to_head).sqrt(),
+    }
+}
+
+impl<'a> SecondPassParser<'a> {
+    pub fn find_aim_view(&self, entity_id: &i32) -> Option<AimView> {
+        let eyes = self.find_eye_position(entity_id)?;
+        match (self.find_pitch_or_yaw(entity_id, 0), self.find_pitch_or_yaw(entity_id, 1)) {
+            (Ok(Variant::F32(pitch)), Ok(Variant::F32(yaw))) => Some(AimView { eyes, pitch, yaw }),
+            _ => None,
+        }
+    }
+    // Head is at eye level, body is halfway between the feet and the eyes so crouching moves it too
+    pub fn find_head_and_body(&self, entity_id: &i32) -> Option<([f32; 3], [f32; 3])> {
+        let eyes = self.find_eye_position(entity_id)?;
+        match self.collect_cell_coordinate_player(CoordinateAxis::Z, entity_id) {
+            Ok(Variant::F32(z)) => Some((eyes, [eyes[0], eyes[1], (eyes[2] + z) / 2.0])),
+            _ => None,
+        }
+    }
+    fn find_alive_enemies(&self, entity_id: &i32) -> Vec<i32> {
+        let team = self.players.get(entity_id).and_then(|p| p.team_num);
+        self.players
+            .iter()
+            .filter(|(id, p)| *id != entity_id && p.team_num.is_some() && p.team_num != team)
+            .filter(|(id, _)| matches!(self.find_is_alive(id), Ok(Variant::Bool(true))))
+            .map(|(id, _)| *id)
+            .collect()
+    }
+    pub fn find_closest_enemy_angle(&self, entity_id: &i32) -> Result<Variant, PropCollectionError> {
+        let view = match self.find_aim_view(entity_id) {
+            Some(view) => view,
+            None => return Err(PropCollectionError::PlayerNotFound),
+        };
+        let forward = view_direction(view.pitch, view.yaw);
+        self.find_alive_enemies(entity_id)
+            .iter()
+            .filter_map(|id| self.find_head_and_body(id))
+            .filter_map(|(head, _)| angle_between(forward, [head[0] - view.eyes[0], head[1] - view.eyes[1], head[2] - view.eyes[2]]))
+            .min_by(|a, b| a.total_cmp(b))
+            .map(Variant::F32)
+            .ok_or(PropCollectionError::NoEnemiesAlive)
+    }
+    pub fn find_enemies_in_fov(&self, entity_id: &i32) -> Result<Variant, PropCollectionError> {
+        let view = match self.find_aim_view(entity_id) {
+            Some(view) => view,
+            None => return Err(PropCollectionError::PlayerNotFound),
+        };
+        let fov = self.fov.unwrap_or(DEFAULT_FOV);
+        let count = self
+            .find_alive_enemies(entity_id)
+            .iter()
+            .filter_map(|id| self.find_head_and_body(id))
+            .filter(|(head, body)| is_in_fov(&view, *head, fov) || is_in_fov(&view, *body, fov))
+            .count();
+        Ok(Variant::I32(count as i32))
+    }
+    pub fn collect_fov(&mut self) {
+        let fov = match self.fov {
+            Some(fov) => fov,
+            None => return,
+        };
+        let mut records = vec![];
+        for (observer_id, observer) in &self.players {
+            let view = match self.find_aim_view(observer_id) {
+                Some(view) if matches!(self.find_is_alive(observer_id), Ok(Variant::Bool(true))) => view,
+                _ => continue,
+            };
+            for (target_id, target) in &self.players {
+                if target_id == observer_id || !matches!(self.find_is_alive(target_id), Ok(Variant::Bool(true))) {
+                    continue;
+                }
+                let (head, body) = match self.find_head_and_body(target_id) {
+                    Some(points) => points,
+                    None => continue,
+                };
+                let forward = view_direction(view.pitch, view.yaw);
+                let to_body = [body[0] - view.eyes[0], body[1] - view.eyes[1], body[2] - view.eyes[2]];
+                let head_sample = sample(&view, head, self.tick);
+                records.push(FovRecord {
+                    tick: self.tick,
+                    observer_steamid: observer.steamid,
+                    observer_name: observer.name.clone(),
+                    target_steamid: target.steamid,
+                    target_name: target.name.clone(),
+                    head_angle: head_sample.angle,
+                    body_angle: angle_between(forward, to_body).unwrap_or(0.0),
+                    yaw_error: head_sample.yaw_error,
+                    pitch_error: head_sample.pitch_error,
+                    distance: head_sample.distance,
+                    in_fov: is_in_fov(&view, head, fov) || is_in_fov(&view, body, fov),
+                });
+            }
+        }
+        self.fov_records.extend(records);
+    }
+    // Remembers where every player's crosshair was when they spotted each enemy
+    pub fn collect_crosshair_samples(&mut self) {
+        let spotted = self.find_spotted_pairs();
+        self.crosshair_samples.retain(|k, _| spotted.iter().any(|(a, b, _)| (*a, *b) == *k));
+        // Pairs already spotted when the parser starts (a new chunk) were spotted at an unknown tick
+        let first_collection = !self.crosshair_samples_started;
+        self.crosshair_samples_started = true;
+        for (spotter_steamid, spotted_steamid, spotted_id) in spotted {
+            if self.crosshair_samples.contains_key(&(spotter_steamid, spotted_steamid)) {
+                continue;
+            }
+            let spotter_id = self.players.iter().find(|(_, p)| p.steamid == Some(spotter_steamid)).map(|(id, _)| *id);
+            let new_sample = match (
+                first_collection,
+                spotter_id.and_then(|id| self.find_aim_view(&id)),
+                self.find_head_and_body(&spotted_id),
+            ) {
+                (false, Some(view), Some((head, _))) => Some(sample(&view, head, self.tick)),
+                _ => None,
+            };
+            self.crosshair_samples.insert((spotter_steamid, spotted_steamid), new_sample);
+        }
+    }
+    pub fn collect_crosshair_placement(&mut self, event: &DecodedGameEvent) -> Result<(), DemoParserError> {
+        if event.name() != "player_hurt" {
+            return Ok(());
+        }
+        let find_entity_id = |name: &str| match event.key(name) {
+            Some(Variant::I32(userid)) => self.entity_id_from_userid(userid),
+            _ => None,
+        };
+        let (attacker_id, victim_id) = match (find_entity_id("attacker"), find_entity_id("userid")) {
+            (Some(attacker_id), Some(victim_id)) => (attacker_id, victim_id),
+            _ => return Ok(()),
+        };
+        let (attacker, victim) = match (self.players.get(&attacker_id), self.players.get(&victim_id)) {
+            (Some(attacker), Some(victim)) if attacker.team_num != victim.team_num => (attacker, victim),
+            _ => return Ok(()),
+        };
+        let (attacker_steamid, victim_steamid) = match (attacker.steamid, victim.steamid) {
+            (Some(attacker_steamid), Some(victim_steamid)) => (attacker_steamid, victim_steamid),
+            _ => return Ok(()),
+        };
+        let round = match self.find_current_round() {
+            Some(Variant::I32(round)) => Some(round),
+            _ => None,
+        };
+        if self.crosshair_duels.contains(&(round, attacker_steamid, victim_steamid)) {
+            return Ok(());
+        }
+        let spotted_sample = self.crosshair_samples.get(&(attacker_steamid, victim_steamid)).cloned().flatten();
+        let (spotted_tick, sample) = match spotted_sample {
+            Some(sample) => (Some(sample.tick), sample),
+            None => match (self.find_aim_view(&attacker_id), self.find_head_and_body(&victim_id)) {
+                (Some(view), Some((head, _))) => (None, sample(&view, head, self.tick)),
+                _ => return Ok(()),
+            },
+        };
+        let record = CrosshairPlacementRecord {
+            round,
+            tick: self.tick,
+            attacker_steamid,
+            attacker_name: attacker.name.clone(),
+            victim_steamid,
+            victim_name: victim.name.clone(),
+            spotted_tick,
+            angle_error: sample.angle,
+            yaw_error: sample.yaw_error,
+            pitch_error: sample.pitch_error,
+            distance: sample.distance,
+        };
+        self.crosshair_duels.insert((round, attacker_steamid, victim_steamid));
+        self.crosshair_placement.push(record);
+        Ok(())
+    }
+}
+
+// A duel can only have one first damage, chunks don't know about damage done in earlier chunks
+pub fn merge_crosshair_placement(mut records: Vec<CrosshairPlacementRecord>) -> Vec<CrosshairPlacementRecord> {
+    records.sort_by_key(|r| r.tick);
+    let mut seen = AHashSet::default();
+    records.retain(|r| seen.insert((r.round, r.attacker_steamid, r.victim_steamid)));
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_yaw_pitch_error() {
+        let view = AimView {
+            eyes: [0.0, 0.0, 64.0],
+            pitch: 0.0,
+            yaw: 170.0,
+        };
+        // Target at yaw -170, across the -180/180 wrap, and below eye level
+        let target = [(-170.0_f32).to_radians().cos() * 100.0, (-170.0_f32).to_radians().sin() * 100.0, 54.0];
+        let (yaw_error, pitch_error) = yaw_pitch_error(&view, target);
+        assert!((yaw_error - 20.0).abs() < 0.1);
+        assert!(pitch_error > 0.0);
+    }
+    #[test]
+    fn test_is_in_fov() {
+        let view = AimView {
+            eyes: [0.0, 0.0, 0.0],
+            pitch: 0.0,
+            yaw: 0.0,
+        };
+        assert!(is_in_fov(&view, [100.0, 0.0, 0.0], DEFAULT_FOV));
+        // 50 degrees to the side fits the horizontal fov but the same angle up doesn't fit the vertical one
+        assert!(is_in_fov(&view, [100.0, 119.0, 0.0], DEFAULT_FOV));
+        assert!(!is_in_fov(&view, [100.0, 0.0, 119.0], DEFAULT_FOV));
+        assert!(!is_in_fov(&view, [-100.0, 0.0, 0.0], DEFAULT_FOV));
+        assert!(!is_in_fov(&view, [100.0, 119.0, 0.0], 90.0));
+    }
+}
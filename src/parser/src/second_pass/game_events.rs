@@ -19,7 +19,9 @@ use crate::second_pass::entities::Entity;
 use crate::second_pass::entities::PlayerMetaData;
 use crate::second_pass::parser_settings::SecondPassParser;
 use crate::second_pass::variants::*;
+use ahash::AHashMap;
 use csgoproto::cstrike15_usermessages::CCSUsrMsg_ServerRankUpdate;
+use csgoproto::netmessages::csvcmsg_game_event_list::Descriptor_t;
 use csgoproto::networkbasetypes::csvcmsg_game_event::Key_t;
 use csgoproto::networkbasetypes::CNETMsg_SetConVar;
 use csgoproto::networkbasetypes::CSVCMsg_GameEvent;
@@ -59,32 +61,57 @@ pub enum GameEventInfo {
     WeaponCreateNCost((Variant, i32)),
     WeaponCreateDefIdx((Variant, i32, u32)),
     WeaponPurchaseCount((Variant, i32, u32)),
+    AccountChange((Option<Variant>, Variant, i32)),
+    // prop id, old value, new value, entity id of a prop registered with wanted_prop_changes
+    PropChanged((u32, Option<Variant>, Variant, i32)),
+}
+
+// Game event with its keys parsed. Decoded once per message and handed to every collector.
+#[derive(Debug, Clone)]
+pub struct DecodedGameEvent<'a> {
+    pub desc: &'a Descriptor_t,
+    // Same order as the keys in the descriptor
+    pub keys: Vec<Option<Variant>>,
+}
+
+impl<'a> DecodedGameEvent<'a> {
+    pub fn name(&self) -> &'a str {
+        self.desc.name()
+    }
+    pub fn key(&self, name: &str) -> Option<Variant> {
+        self.desc.keys.iter().position(|k| k.name() == name).and_then(|idx| self.keys.get(idx)).cloned().flatten()
+    }
 }
 
 static ENTITIES_FIRST_EVENTS: &'static [&str] = &["inferno_startburn", "decoy_started", "inferno_expire"];
 static REMOVEDEVENTS: &'static [&str] = &["server_cvar"];
 
-const ENTITYIDNONE: i32 = 2047;
+pub const ENTITYIDNONE: i32 = 2047;
 // https://developer.valvesoftware.com/wiki/SteamID
 const STEAMID64INDIVIDUALIDENTIFIER: u64 = 0x0110000100000000;
 
 impl<'a> SecondPassParser<'a> {
-    pub fn parse_event(&mut self, bytes: &[u8]) -> Result<Option<GameEvent>, DemoParserError> {
-        if self.wanted_events.len() == 0 && self.wanted_events.first() != Some(&"all".to_string()) {
-            return Ok(None);
-        }
-
-        let event: CSVCMsg_GameEvent = match Message::parse_from_bytes(&bytes) {
+    pub fn decode_game_event(&self, bytes: &[u8]) -> Result<Option<DecodedGameEvent<'a>>, DemoParserError> {
+        let event: CSVCMsg_GameEvent = match Message::parse_from_bytes(bytes) {
             Ok(event) => event,
             Err(_) => return Err(DemoParserError::MalformedMessage),
         };
         // Check if this events id is found in our game event list
-        let event_desc = match self.ge_list.get(&event.eventid()) {
+        let ge_list = self.ge_list;
+        let desc = match ge_list.get(&event.eventid()) {
             Some(desc) => desc,
-            None => {
-                return Ok(None);
-            }
+            None => return Ok(None),
         };
+        Ok(Some(DecodedGameEvent {
+            desc,
+            keys: event.keys.iter().map(parse_key).collect(),
+        }))
+    }
+    pub fn parse_event(&mut self, event: &DecodedGameEvent) -> Result<Option<GameEvent>, DemoParserError> {
+        if self.wanted_events.len() == 0 && self.wanted_events.first() != Some(&"all".to_string()) {
+            return Ok(None);
+        }
+        let event_desc = event.desc;
         if let Some(event_name) = &event_desc.name {
             self.game_events_counter.insert(event_name.to_owned());
         }
@@ -100,12 +127,10 @@ impl<'a> SecondPassParser<'a> {
 
         // Parsing game events is this easy, the complexity comes from adding "extra" fields into events.
         for i in 0..event.keys.len() {
-            let ge = &event.keys[i];
             let desc = &event_desc.keys[i];
-            let val = parse_key(ge);
             event_fields.push(EventField {
                 name: desc.name().to_owned(),
-                data: val,
+                data: event.keys[i].clone(),
             });
         }
         if ENTITIES_FIRST_EVENTS.contains(&event_desc.name()) {
@@ -117,7 +142,10 @@ impl<'a> SecondPassParser<'a> {
             return Ok(Some(event));
         } else {
             // Add extra fields
-            event_fields.extend(self.find_extra(&event_fields)?);
+            event_fields.extend(self.find_extra(&event_fields, event_desc.name())?);
+            if event_desc.name() == "player_death" || event_desc.name() == "player_hurt" {
+                event_fields.push(self.find_through_smoke_computed(&event_fields));
+            }
             // Remove fields that user does nothing with like userid and user_pawn
             event_fields.retain(|ref x| !INTERNALEVENTFIELDS.contains(&x.name.as_str()));
             let mut event = GameEvent {
@@ -146,7 +174,7 @@ impl<'a> SecondPassParser<'a> {
     }
     pub fn resolve_wrong_order_event(&mut self, events: &mut Vec<GameEvent>) -> Result<(), DemoParserError> {
         for event in events {
-            event.fields.extend(self.find_extra(&event.fields)?);
+            event.fields.extend(self.find_extra(&event.fields, &event.name)?);
             // Remove fields that user does nothing with like userid and user_pawn
             event.fields.retain(|ref x| !INTERNALEVENTFIELDS.contains(&x.name.as_str()));
             let event = GameEvent {
@@ -193,7 +221,7 @@ impl<'a> SecondPassParser<'a> {
         }
         return None;
     }
-    pub fn find_extra(&self, fields: &Vec<EventField>) -> Result<Vec<EventField>, DemoParserError> {
+    pub fn find_extra(&self, fields: &Vec<EventField>, event_name: &str) -> Result<Vec<EventField>, DemoParserError> {
         let mut extra_fields = vec![];
         // Always add tick to event
         extra_fields.push(EventField {
@@ -250,6 +278,9 @@ impl<'a> SecondPassParser<'a> {
         }
         // Values from Teams and Rules entity. Not bound to any player so can be added to any event.
         extra_fields.extend(self.find_non_player_props());
+        if self.parse_kill_context && event_name == "player_death" {
+            extra_fields.extend(self.find_kill_context(fields));
+        }
         Ok(extra_fields)
     }
     pub fn entity_id_from_user_pawn(&self, pawn_handle: i32) -> Option<i32> {
@@ -509,6 +540,9 @@ impl<'a> SecondPassParser<'a> {
         })
     }
     pub fn emit_events(&mut self, events: Vec<GameEventInfo>) -> Result<(), DemoParserError> {
+        if self.parse_money_ledger {
+            self.collect_money_changes(&events);
+        }
         if SecondPassParser::contains_round_end_event(&events) {
             self.create_custom_event_round_end(&events)?;
         }
@@ -523,6 +557,11 @@ impl<'a> SecondPassParser<'a> {
             self.create_custom_event_weapon_purchase(&events);
         }
         self.create_custom_event_weapon_sold(&events);
+        if !self.watched_props.is_empty()
+            && (self.wanted_events.contains(&"prop_changed".to_string()) || self.wanted_events.first() == Some(&"all".to_string()))
+        {
+            self.create_custom_event_prop_changed(&events);
+        }
         Ok(())
     }
     fn create_custom_event_weapon_sold(&mut self, events: &[GameEventInfo]) {
@@ -585,7 +624,7 @@ impl<'a> SecondPassParser<'a> {
             _ => {}
         });
     }
-    fn combine_purchase_events(events: &[GameEventInfo]) -> Vec<PurchaseEvent> {
+    pub fn combine_purchase_events(events: &[GameEventInfo]) -> Vec<PurchaseEvent> {
         // Vec<Gameventinfo> --> Vec<(def_idx, weapon_cost)>
         // Filter purchase events
         let filtered_events = events
@@ -988,6 +1027,58 @@ impl<'a> SecondPassParser<'a> {
         Ok(())
     }
 
+    // Opt-in only, "all" does not include these as they would add a row for every entity ever created
+    pub fn create_custom_event_entity_lifecycle(&mut self, event_name: &str, entity_id: i32) {
+        if !self.game_events_counter.contains(event_name) {
+            self.game_events_counter.insert(event_name.to_string());
+        }
+        if !self.wanted_events.iter().any(|name| name == event_name) {
+            return;
+        }
+        let class_name = match self.entities.get(entity_id as usize) {
+            Some(Some(entity)) => match self.cls_by_id.get(entity.cls_id as usize) {
+                Some(cls) => cls.name.clone(),
+                None => return,
+            },
+            _ => return,
+        };
+        if !self.entity_event_classes.is_empty() && !self.entity_event_classes.contains(&class_name) {
+            return;
+        }
+        let position = self.find_entity_position(entity_id);
+        let owner_id = match self.prop_controller.special_ids.h_owner_entity.map(|id| self.get_prop_from_ent(&id, &entity_id)) {
+            Some(Ok(Variant::U32(handle))) => (handle & 0x7FF) as i32,
+            _ => ENTITYIDNONE,
+        };
+        let mut fields = vec![
+            EventField {
+                data: Some(Variant::I32(entity_id)),
+                name: "entity_id".to_string(),
+            },
+            EventField {
+                data: Some(Variant::String(class_name)),
+                name: "class_name".to_string(),
+            },
+        ];
+        for (idx, axis) in ["X", "Y", "Z"].iter().enumerate() {
+            fields.push(EventField {
+                data: position.map(|p| Variant::F32(p[idx])),
+                name: axis.to_string(),
+            });
+        }
+        fields.push(self.create_player_name_field(owner_id, "owner"));
+        fields.push(self.create_player_steamid_field(owner_id, "owner"));
+        fields.push(EventField {
+            data: Some(Variant::I32(self.tick)),
+            name: "tick".to_string(),
+        });
+        fields.extend(self.find_non_player_props());
+        self.game_events.push(GameEvent {
+            name: event_name.to_string(),
+            fields,
+            tick: self.tick,
+        });
+    }
     pub fn create_custom_event_rank_update(&mut self, msg_bytes: &[u8]) -> Result<(), DemoParserError> {
         self.game_events_counter.insert("rank_update".to_string());
         if !self.wanted_events.contains(&"rank_update".to_string()) && self.wanted_events.first() != Some(&"all".to_string()) {
@@ -1050,10 +1141,17 @@ impl<'a> SecondPassParser<'a> {
         _field: &Field,
         field_info: Option<FieldInfo>,
         prop_controller: &PropController,
+        watched_props: &AHashMap<u32, String>,
     ) -> Vec<GameEventInfo> {
         // Might want to start splitting this function
         let mut events = vec![];
         if let Some(fi) = field_info {
+            if watched_props.contains_key(&fi.prop_id) {
+                let old_value = entity.props.get(&fi.prop_id).cloned();
+                if old_value.as_ref() != Some(result) {
+                    events.push(GameEventInfo::PropChanged((fi.prop_id, old_value, result.clone(), entity.entity_id)));
+                }
+            }
             // round end
             if let Some(id) = prop_controller.special_ids.round_end_count {
                 if fi.prop_id == id {
@@ -1082,6 +1180,15 @@ impl<'a> SecondPassParser<'a> {
                     events.push(GameEventInfo::MatchEnd());
                 }
             }
+            if let Some(id) = prop_controller.special_ids.account {
+                if fi.prop_id == id {
+                    events.push(GameEventInfo::AccountChange((
+                        entity.props.get(&id).cloned(),
+                        result.clone(),
+                        entity.entity_id,
+                    )));
+                }
+            }
             use crate::first_pass::prop_controller::FLATTENED_VEC_MAX_LEN;
             use crate::first_pass::prop_controller::ITEM_PURCHASE_HANDLE;
             if fi.prop_id >= ITEM_PURCHASE_COST && fi.prop_id < ITEM_PURCHASE_COST + FLATTENED_VEC_MAX_LEN {
@@ -1109,7 +1216,7 @@ impl<'a> SecondPassParser<'a> {
     }
 }
 // what is this shit
-fn parse_key(key: &Key_t) -> Option<Variant> {
+pub fn parse_key(key: &Key_t) -> Option<Variant> {
     match key.type_() {
         1 => Some(Variant::String(key.val_string().to_owned())),
         2 => Some(Variant::F32(key.val_float())),
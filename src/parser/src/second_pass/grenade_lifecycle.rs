@@ -0,0 +1,355 @@
+use crate::first_pass::read_bits::DemoParserError;
+use crate::second_pass::collect_data::CoordinateAxis;
+use crate::second_pass::entities::EntityType;
+use crate::second_pass::game_events::DecodedGameEvent;
+use crate::second_pass::parser_settings::SecondPassParser;
+use crate::second_pass::variants::Variant;
+
+// Detonation events can arrive a few ticks after the projectile was deleted.
+const DETONATION_TICK_WINDOW: i32 = 8;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrajectoryPoint {
+    pub tick: i32,
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct GrenadeLifecycle {
+    pub entity_id: Option<i32>,
+    pub grenade_type: Option<String>,
+    pub thrower_steamid: Option<u64>,
+    pub thrower_name: Option<String>,
+    pub throw_tick: Option<i32>,
+    pub throw_x: Option<f32>,
+    pub throw_y: Option<f32>,
+    pub throw_z: Option<f32>,
+    pub throw_pitch: Option<f32>,
+    pub throw_yaw: Option<f32>,
+    pub trajectory: Vec<TrajectoryPoint>,
+    pub bounces: Option<i32>,
+    pub detonate_tick: Option<i32>,
+    pub detonate_x: Option<f32>,
+    pub detonate_y: Option<f32>,
+    pub detonate_z: Option<f32>,
+    pub expire_tick: Option<i32>,
+    // Molotovs detonate into a separate inferno entity that later expires
+    pub inferno_entity_id: Option<i32>,
+    // Grenade was already in the air when this chunk of the demo started
+    pub continues_previous: bool,
+}
+
+impl GrenadeLifecycle {
+    pub fn downsampled_trajectory(&self, step: usize) -> Vec<TrajectoryPoint> {
+        // Keeps every nth point and always the last one
+        if step <= 1 || self.trajectory.len() <= 2 {
+            return self.trajectory.clone();
+        }
+        let mut points: Vec<TrajectoryPoint> = self.trajectory.iter().step_by(step).cloned().collect();
+        if let Some(last) = self.trajectory.last() {
+            if points.last() != Some(last) {
+                points.push(last.clone());
+            }
+        }
+        points
+    }
+}
+
+impl<'a> SecondPassParser<'a> {
+    pub fn start_grenade_lifecycle(&mut self, entity_id: i32, is_fullpacket: bool) {
+        match self.entities.get(entity_id as usize) {
+            Some(Some(entity)) if entity.entity_type == EntityType::Projectile => {}
+            _ => return,
+        }
+        // Fullpackets re-create every entity, don't start a new grenade for one we are already following
+        if self.open_grenades.contains_key(&entity_id) {
+            if !is_fullpacket {
+                self.end_grenade_lifecycle(entity_id);
+            } else {
+                return;
+            }
+        }
+        let grenade_type = match self.find_grenade_type(&entity_id) {
+            Some(t) => t,
+            None => return,
+        };
+        let position = self.find_grenade_position(&entity_id);
+        let (throw_pitch, throw_yaw) = match self.find_owner_entid(&entity_id) {
+            Ok(owner) => (self.find_pitch_or_yaw(&(owner as i32), 0).ok(), self.find_pitch_or_yaw(&(owner as i32), 1).ok()),
+            Err(_) => (None, None),
+        };
+        let (throw_pitch, throw_yaw) = match (throw_pitch, throw_yaw) {
+            (Some(Variant::F32(pitch)), Some(Variant::F32(yaw))) => (Some(pitch), Some(yaw)),
+            _ => (None, None),
+        };
+        // Throw info only makes sense if we saw the grenade being created
+        let (throw_tick, throw_pitch, throw_yaw) = match is_fullpacket {
+            true => (None, None, None),
+            false => (Some(self.tick), throw_pitch, throw_yaw),
+        };
+        self.open_grenades.insert(entity_id, self.grenade_lifecycles.len());
+        self.grenade_lifecycles.push(GrenadeLifecycle {
+            entity_id: Some(entity_id),
+            grenade_type: Some(grenade_type),
+            thrower_steamid: self.find_thrower_steamid(&entity_id).ok(),
+            thrower_name: self.find_thrower_name(&entity_id).ok(),
+            throw_tick,
+            throw_x: position.as_ref().filter(|_| !is_fullpacket).map(|p| p.x),
+            throw_y: position.as_ref().filter(|_| !is_fullpacket).map(|p| p.y),
+            throw_z: position.as_ref().filter(|_| !is_fullpacket).map(|p| p.z),
+            throw_pitch,
+            throw_yaw,
+            trajectory: vec![],
+            bounces: None,
+            detonate_tick: None,
+            detonate_x: None,
+            detonate_y: None,
+            detonate_z: None,
+            expire_tick: None,
+            inferno_entity_id: None,
+            continues_previous: is_fullpacket,
+        });
+    }
+    pub fn end_grenade_lifecycle(&mut self, entity_id: i32) {
+        if let Some(idx) = self.open_grenades.remove(&entity_id) {
+            let bounces = self.find_grenade_bounces(&entity_id);
+            if let Some(lifecycle) = self.grenade_lifecycles.get_mut(idx) {
+                if bounces.is_some() {
+                    lifecycle.bounces = bounces;
+                }
+                if lifecycle.expire_tick.is_none() {
+                    lifecycle.expire_tick = Some(self.tick);
+                }
+            }
+        }
+    }
+    pub fn collect_grenade_trajectories(&mut self) {
+        let open: Vec<(i32, usize)> = self.open_grenades.iter().map(|(k, v)| (*k, *v)).collect();
+        for (entity_id, idx) in open {
+            let position = self.find_grenade_position(&entity_id);
+            let bounces = self.find_grenade_bounces(&entity_id);
+            let steamid = self.find_thrower_steamid(&entity_id).ok();
+            let name = self.find_thrower_name(&entity_id).ok();
+            let tick = self.tick;
+            if let Some(lifecycle) = self.grenade_lifecycles.get_mut(idx) {
+                // Owner is not always known on the tick the grenade is created
+                if lifecycle.thrower_steamid.is_none() {
+                    lifecycle.thrower_steamid = steamid;
+                    lifecycle.thrower_name = name;
+                }
+                if bounces.is_some() {
+                    lifecycle.bounces = bounces;
+                }
+                if let Some(p) = position {
+                    if lifecycle.throw_tick.is_some() && lifecycle.throw_x.is_none() {
+                        lifecycle.throw_x = Some(p.x);
+                        lifecycle.throw_y = Some(p.y);
+                        lifecycle.throw_z = Some(p.z);
+                    }
+                    if lifecycle.trajectory.last().map(|last| last.tick) != Some(tick) {
+                        lifecycle.trajectory.push(TrajectoryPoint { tick, ..p });
+                    }
+                }
+            }
+        }
+    }
+    pub fn collect_grenade_event(&mut self, event: &DecodedGameEvent) -> Result<(), DemoParserError> {
+        let entity_id = match event.key("entityid") {
+            Some(Variant::I32(id)) => id,
+            _ => return Ok(()),
+        };
+        let coordinate = |name: &str| match event.key(name) {
+            Some(Variant::F32(f)) => Some(f),
+            _ => None,
+        };
+        let (x, y, z) = (coordinate("x"), coordinate("y"), coordinate("z"));
+        match event.name() {
+            "smokegrenade_detonate" | "hegrenade_detonate" | "flashbang_detonate" | "decoy_started" => {
+                if let Some(idx) = self.find_recent_grenade(|g| g.entity_id == Some(entity_id)) {
+                    self.set_detonation(idx, x, y, z);
+                }
+            }
+            "inferno_startburn" => {
+                // The event refers to the inferno entity and not the projectile so match on position instead
+                let is_molotov =
+                    |g: &GrenadeLifecycle| g.detonate_tick.is_none() && matches!(g.grenade_type.as_deref(), Some("molotov") | Some("incendiary_grenade"));
+                let mut best: Option<(usize, f32)> = None;
+                let mut idx = self.grenade_lifecycles.len();
+                while let Some(candidate) = self.find_recent_grenade_before(idx, is_molotov) {
+                    let distance = match (self.grenade_lifecycles[candidate].trajectory.last(), x, y, z) {
+                        (Some(p), Some(x), Some(y), Some(z)) => ((p.x - x).powi(2) + (p.y - y).powi(2) + (p.z - z).powi(2)).sqrt(),
+                        _ => f32::MAX,
+                    };
+                    if best.is_none_or(|(_, d)| distance < d) {
+                        best = Some((candidate, distance));
+                    }
+                    idx = candidate;
+                }
+                if let Some((idx, _)) = best {
+                    self.set_detonation(idx, x, y, z);
+                    self.grenade_lifecycles[idx].inferno_entity_id = Some(entity_id);
+                }
+            }
+            "inferno_expire" => {
+                match self.grenade_lifecycles.iter_mut().rev().find(|g| g.inferno_entity_id == Some(entity_id)) {
+                    Some(lifecycle) => lifecycle.expire_tick = Some(self.tick),
+                    // Fire started in an earlier chunk, applied when merging the chunks
+                    None => self.inferno_expires.push((entity_id, self.tick)),
+                }
+            }
+            "smokegrenade_expired" | "decoy_detonate" => {
+                if let Some(idx) = self.find_recent_grenade(|g| g.entity_id == Some(entity_id)) {
+                    self.grenade_lifecycles[idx].expire_tick = Some(self.tick);
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+    fn set_detonation(&mut self, idx: usize, x: Option<f32>, y: Option<f32>, z: Option<f32>) {
+        let tick = self.tick;
+        if let Some(lifecycle) = self.grenade_lifecycles.get_mut(idx) {
+            lifecycle.detonate_tick = Some(tick);
+            lifecycle.detonate_x = x;
+            lifecycle.detonate_y = y;
+            lifecycle.detonate_z = z;
+        }
+    }
+    fn find_recent_grenade<F: Fn(&GrenadeLifecycle) -> bool>(&self, f: F) -> Option<usize> {
+        self.find_recent_grenade_before(self.grenade_lifecycles.len(), f)
+    }
+    fn find_recent_grenade_before<F: Fn(&GrenadeLifecycle) -> bool>(&self, before: usize, f: F) -> Option<usize> {
+        // Grenade must still exist or have been removed very recently
+        self.grenade_lifecycles[..before].iter().rposition(|g| {
+            let is_recent = match g.expire_tick {
+                None => g.entity_id.is_some_and(|id| self.open_grenades.contains_key(&id)),
+                Some(expired) => self.tick - expired <= DETONATION_TICK_WINDOW,
+            };
+            is_recent && f(g)
+        })
+    }
+    fn find_grenade_position(&self, entity_id: &i32) -> Option<TrajectoryPoint> {
+        let x = self.collect_cell_coordinate_grenade(CoordinateAxis::X, entity_id);
+        let y = self.collect_cell_coordinate_grenade(CoordinateAxis::Y, entity_id);
+        let z = self.collect_cell_coordinate_grenade(CoordinateAxis::Z, entity_id);
+        match (x, y, z) {
+            (Ok(Variant::F32(x)), Ok(Variant::F32(y)), Ok(Variant::F32(z))) => Some(TrajectoryPoint { tick: self.tick, x, y, z }),
+            _ => None,
+        }
+    }
+    fn find_grenade_bounces(&self, entity_id: &i32) -> Option<i32> {
+        let prop_id = self.prop_controller.special_ids.grenade_bounces?;
+        match self.get_prop_from_ent(&prop_id, entity_id) {
+            Ok(Variant::I32(bounces)) => Some(bounces),
+            Ok(Variant::U32(bounces)) => Some(bounces as i32),
+            _ => None,
+        }
+    }
+}
+
+pub fn merge_grenade_lifecycles(lifecycles: Vec<GrenadeLifecycle>, inferno_expires: Vec<(i32, i32)>) -> Vec<GrenadeLifecycle> {
+    // When parsing in chunks a grenade in the air at a chunk boundary shows up in both chunks.
+    let mut merged: Vec<GrenadeLifecycle> = vec![];
+    for lifecycle in lifecycles {
+        if lifecycle.continues_previous {
+            let previous = merged
+                .iter_mut()
+                .rev()
+                .find(|g| g.entity_id == lifecycle.entity_id && g.grenade_type == lifecycle.grenade_type && g.expire_tick.is_none());
+            if let Some(previous) = previous {
+                let last_tick = previous.trajectory.last().map_or(i32::MIN, |p| p.tick);
+                previous.trajectory.extend(lifecycle.trajectory.into_iter().filter(|p| p.tick > last_tick));
+                if lifecycle.bounces.is_some() {
+                    previous.bounces = lifecycle.bounces;
+                }
+                if previous.thrower_steamid.is_none() {
+                    previous.thrower_steamid = lifecycle.thrower_steamid;
+                    previous.thrower_name = lifecycle.thrower_name;
+                }
+                if previous.detonate_tick.is_none() {
+                    previous.detonate_tick = lifecycle.detonate_tick;
+                    previous.detonate_x = lifecycle.detonate_x;
+                    previous.detonate_y = lifecycle.detonate_y;
+                    previous.detonate_z = lifecycle.detonate_z;
+                }
+                previous.inferno_entity_id = previous.inferno_entity_id.or(lifecycle.inferno_entity_id);
+                previous.expire_tick = lifecycle.expire_tick;
+                continue;
+            }
+        }
+        merged.push(lifecycle);
+    }
+    for (inferno_entity_id, tick) in inferno_expires {
+        let lifecycle = merged
+            .iter_mut()
+            .rev()
+            .find(|g| g.inferno_entity_id == Some(inferno_entity_id) && g.detonate_tick.is_some_and(|detonated| detonated <= tick));
+        if let Some(lifecycle) = lifecycle {
+            lifecycle.expire_tick = Some(tick);
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lifecycle(entity_id: i32, ticks: &[i32], continues_previous: bool, expire_tick: Option<i32>) -> GrenadeLifecycle {
+        GrenadeLifecycle {
+            entity_id: Some(entity_id),
+            grenade_type: Some("smoke".to_string()),
+            thrower_steamid: None,
+            thrower_name: None,
+            throw_tick: if continues_previous { None } else { ticks.first().copied() },
+            throw_x: None,
+            throw_y: None,
+            throw_z: None,
+            throw_pitch: None,
+            throw_yaw: None,
+            trajectory: ticks
+                .iter()
+                .map(|t| TrajectoryPoint {
+                    tick: *t,
+                    x: 0.0,
+                    y: 0.0,
+                    z: 0.0,
+                })
+                .collect(),
+            bounces: None,
+            detonate_tick: None,
+            detonate_x: None,
+            detonate_y: None,
+            detonate_z: None,
+            expire_tick,
+            inferno_entity_id: None,
+            continues_previous,
+        }
+    }
+
+    #[test]
+    fn test_merge_grenade_lifecycles_across_chunks() {
+        let merged = merge_grenade_lifecycles(
+            vec![
+                lifecycle(100, &[1, 2, 3], false, None),
+                lifecycle(100, &[3, 4, 5], true, Some(5)),
+                lifecycle(101, &[7, 8], true, Some(8)),
+            ],
+            vec![],
+        );
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].throw_tick, Some(1));
+        assert_eq!(merged[0].expire_tick, Some(5));
+        assert_eq!(merged[0].trajectory.iter().map(|p| p.tick).collect::<Vec<i32>>(), vec![1, 2, 3, 4, 5]);
+        assert_eq!(merged[1].throw_tick, None);
+    }
+    #[test]
+    fn test_downsampled_trajectory_keeps_last_point() {
+        let g = lifecycle(100, &[1, 2, 3, 4, 5, 6], false, None);
+        let ticks: Vec<i32> = g.downsampled_trajectory(4).iter().map(|p| p.tick).collect();
+        assert_eq!(ticks, vec![1, 5, 6]);
+        assert_eq!(g.downsampled_trajectory(1).len(), 6);
+    }
+}
@@ -0,0 +1,166 @@
+use crate::first_pass::prop_controller::INFERNO_FIRE_IS_BURNING;
+use crate::first_pass::prop_controller::INFERNO_FIRE_X_DELTA;
+use crate::first_pass::prop_controller::INFERNO_FIRE_Y_DELTA;
+use crate::first_pass::prop_controller::INFERNO_FIRE_Z_DELTA;
+use crate::second_pass::collect_data::coord_from_cell;
+use crate::second_pass::parser_settings::SecondPassParser;
+use crate::second_pass::variants::Variant;
+
+// Infernos can hold at most this many fires
+const MAX_INFERNO_FIRES: i32 = 64;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FireCell {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct InfernoFireRecord {
+    pub tick: Option<i32>,
+    pub entity_id: Option<i32>,
+    pub thrower_steamid: Option<u64>,
+    pub thrower_name: Option<String>,
+    pub fire_count: Option<i32>,
+    pub area: Option<f32>,
+    pub fires: Vec<FireCell>,
+}
+
+impl<'a> SecondPassParser<'a> {
+    pub fn collect_inferno_fires(&mut self) {
+        let mut records = vec![];
+        for entity_id in &self.infernos {
+            let fires = self.find_burning_fires(entity_id);
+            if fires.is_empty() {
+                continue;
+            }
+            let (thrower_steamid, thrower_name) = self.find_inferno_thrower(entity_id);
+            records.push(InfernoFireRecord {
+                tick: Some(self.tick),
+                entity_id: Some(*entity_id),
+                thrower_steamid,
+                thrower_name,
+                fire_count: Some(fires.len() as i32),
+                area: Some(convex_hull_area(&fires)),
+                fires,
+            });
+        }
+        self.inferno_fires.extend(records);
+    }
+    fn find_burning_fires(&self, entity_id: &i32) -> Vec<FireCell> {
+        let origin = match self.find_inferno_origin(entity_id) {
+            Some(origin) => origin,
+            None => return vec![],
+        };
+        let fire_count = match self.prop_controller.special_ids.inferno_fire_count {
+            Some(id) => match self.get_prop_from_ent(&id, entity_id) {
+                Ok(Variant::I32(count)) => count,
+                Ok(Variant::U32(count)) => count as i32,
+                _ => 0,
+            },
+            None => 0,
+        };
+        let mut fires = vec![];
+        for idx in 0..fire_count.min(MAX_INFERNO_FIRES) as u32 {
+            if let Ok(Variant::Bool(false)) | Err(_) = self.get_prop_from_ent(&(INFERNO_FIRE_IS_BURNING + idx), entity_id) {
+                continue;
+            }
+            let dx = self.find_fire_delta(INFERNO_FIRE_X_DELTA + idx, entity_id);
+            let dy = self.find_fire_delta(INFERNO_FIRE_Y_DELTA + idx, entity_id);
+            let dz = self.find_fire_delta(INFERNO_FIRE_Z_DELTA + idx, entity_id);
+            // Deltas are relative to the origin of the inferno
+            fires.push(FireCell {
+                x: origin.x + dx,
+                y: origin.y + dy,
+                z: origin.z + dz,
+            });
+        }
+        fires
+    }
+    fn find_fire_delta(&self, prop_id: u32, entity_id: &i32) -> f32 {
+        match self.get_prop_from_ent(&prop_id, entity_id) {
+            Ok(Variant::I32(delta)) => delta as f32,
+            Ok(Variant::U32(delta)) => delta as f32,
+            Ok(Variant::F32(delta)) => delta,
+            _ => 0.0,
+        }
+    }
+    fn find_inferno_origin(&self, entity_id: &i32) -> Option<FireCell> {
+        let ids = &self.prop_controller.special_ids;
+        let axis = |cell: Option<u32>, offset: Option<u32>| -> Option<f32> {
+            let cell = self.get_prop_from_ent(&cell?, entity_id);
+            let offset = self.get_prop_from_ent(&offset?, entity_id);
+            coord_from_cell(cell, offset).ok()
+        };
+        Some(FireCell {
+            x: axis(ids.inferno_cell_x, ids.inferno_vec_x)?,
+            y: axis(ids.inferno_cell_y, ids.inferno_vec_y)?,
+            z: axis(ids.inferno_cell_z, ids.inferno_vec_z)?,
+        })
+    }
+    fn find_inferno_thrower(&self, entity_id: &i32) -> (Option<u64>, Option<String>) {
+        let owner_handle = match self.prop_controller.special_ids.h_owner_entity {
+            Some(id) => self.get_prop_from_ent(&id, entity_id),
+            None => return (None, None),
+        };
+        match owner_handle {
+            Ok(Variant::U32(handle)) => match self.find_player_metadata((handle & 0x7FF) as i32) {
+                Ok(player) => (player.steamid, player.name.clone()),
+                Err(_) => (None, None),
+            },
+            _ => (None, None),
+        }
+    }
+}
+
+pub fn convex_hull_area(fires: &[FireCell]) -> f32 {
+    // Area on the xy-plane of the convex hull around the fires (monotone chain + shoelace)
+    let mut points: Vec<(f32, f32)> = fires.iter().map(|f| (f.x, f.y)).collect();
+    if points.len() < 3 {
+        return 0.0;
+    }
+    points.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let mut hull = half_hull(points.iter());
+    hull.extend(half_hull(points.iter().rev()));
+    let mut area = 0.0;
+    for i in 0..hull.len() {
+        let (a, b) = (hull[i], hull[(i + 1) % hull.len()]);
+        area += a.0 * b.1 - b.0 * a.1;
+    }
+    (area / 2.0).abs()
+}
+
+fn half_hull<'a, I: Iterator<Item = &'a (f32, f32)>>(points: I) -> Vec<(f32, f32)> {
+    let cross = |o: (f32, f32), a: (f32, f32), b: (f32, f32)| (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0);
+    let mut hull: Vec<(f32, f32)> = vec![];
+    for p in points {
+        while hull.len() >= 2 && cross(hull[hull.len() - 2], hull[hull.len() - 1], *p) <= 0.0 {
+            hull.pop();
+        }
+        hull.push(*p);
+    }
+    // Last point is the first point of the other half
+    hull.pop();
+    hull
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cells(points: &[(f32, f32)]) -> Vec<FireCell> {
+        points.iter().map(|(x, y)| FireCell { x: *x, y: *y, z: 0.0 }).collect()
+    }
+
+    #[test]
+    fn test_convex_hull_area_ignores_inner_points() {
+        let fires = cells(&[(0.0, 0.0), (100.0, 0.0), (100.0, 100.0), (0.0, 100.0), (50.0, 50.0), (20.0, 70.0)]);
+        assert_eq!(convex_hull_area(&fires), 10000.0);
+    }
+    #[test]
+    fn test_convex_hull_area_degenerate() {
+        assert_eq!(convex_hull_area(&cells(&[(0.0, 0.0), (10.0, 10.0)])), 0.0);
+        assert_eq!(convex_hull_area(&cells(&[(0.0, 0.0), (10.0, 10.0), (20.0, 20.0)])), 0.0);
+    }
+}
@@ -0,0 +1,100 @@
+use crate::second_pass::game_events::EventField;
+use crate::second_pass::parser_settings::SecondPassParser;
+use crate::second_pass::variants::Variant;
+use crate::second_pass::visibility::angle_between;
+use crate::second_pass::visibility::view_direction;
+
+// Victim is facing away if the attacker is further than this from their crosshair
+const FACING_AWAY_ANGLE: f32 = 90.0;
+
+impl<'a> SecondPassParser<'a> {
+    // Since when every player has been spotted by each enemy
+    pub fn collect_spotted_since(&mut self) {
+        let spotted = self.find_spotted_pairs();
+        self.spotted_since.retain(|k, _| spotted.iter().any(|(a, b, _)| (*a, *b) == *k));
+        // Pairs already spotted when the parser starts were spotted at an unknown tick. Chunks after
+        // the first start from the pairs the chunks before them left, see chunk_state.rs
+        let first_collection = self.spotted_since_from.is_none();
+        self.spotted_since_from.get_or_insert(self.tick);
+        for (spotter_steamid, spotted_steamid, _) in spotted {
+            let since = if first_collection { None } else { Some(self.tick) };
+            self.spotted_since.entry((spotter_steamid, spotted_steamid)).or_insert(since);
+        }
+    }
+    pub fn find_kill_context(&self, fields: &[EventField]) -> Vec<EventField> {
+        let find_entity_id = |name: &str| match fields.iter().find(|f| f.name == name).and_then(|f| f.data.as_ref()) {
+            Some(Variant::I32(userid)) => self.entity_id_from_userid(*userid),
+            _ => None,
+        };
+        let attacker_id = find_entity_id("attacker");
+        let victim_id = find_entity_id("userid");
+        let attacker_steamid = attacker_id.and_then(|id| self.players.get(&id)).and_then(|p| p.steamid);
+        let victim_steamid = victim_id.and_then(|id| self.players.get(&id)).and_then(|p| p.steamid);
+        let attacker_position = attacker_id.and_then(|id| self.find_position(&id));
+        let victim_position = victim_id.and_then(|id| self.find_position(&id));
+
+        let mut distance = None;
+        let mut height_advantage = None;
+        if let (Some(a), Some(v)) = (attacker_position, victim_position) {
+            let d = [a[0] - v[0], a[1] - v[1], a[2] - v[2]];
+            distance = Some(Variant::F32((d[0] * d[0] + d[1] * d[1] + d[2] * d[2]).sqrt()));
+            height_advantage = Some(Variant::F32(d[2]));
+        }
+        // Suicides and world damage have no attacker to face
+        let victim_view_angle = match (
+            attacker_id.and_then(|id| self.find_eye_position(&id)),
+            victim_id.and_then(|id| self.find_aim_view(&id)),
+        ) {
+            (Some(attacker_eyes), Some(view)) if attacker_id != victim_id => angle_between(
+                view_direction(view.pitch, view.yaw),
+                [
+                    attacker_eyes[0] - view.eyes[0],
+                    attacker_eyes[1] - view.eyes[1],
+                    attacker_eyes[2] - view.eyes[2],
+                ],
+            ),
+            _ => None,
+        };
+        let time_since_spotted = match (attacker_steamid, victim_steamid) {
+            (Some(attacker), Some(victim)) => match self.spotted_since.get(&(attacker, victim)) {
+                Some(Some(since)) => Some(Variant::F32((self.tick - since) as f32 / 64.0)),
+                _ => None,
+            },
+            _ => None,
+        };
+        let weapon_ammo = match (attacker_id, self.prop_controller.special_ids.weapon_clip1) {
+            (Some(attacker_id), Some(clip_id)) => self.find_weapon_prop(&clip_id, &attacker_id).ok(),
+            _ => None,
+        };
+        vec![
+            EventField {
+                name: "kill_distance".to_string(),
+                data: distance,
+            },
+            EventField {
+                name: "attacker_horizontal_speed".to_string(),
+                data: attacker_steamid.and_then(|s| self.find_horizontal_speed(s)).map(Variant::F32),
+            },
+            EventField {
+                name: "victim_view_angle".to_string(),
+                data: victim_view_angle.map(Variant::F32),
+            },
+            EventField {
+                name: "victim_facing_away".to_string(),
+                data: victim_view_angle.map(|angle| Variant::Bool(angle > FACING_AWAY_ANGLE)),
+            },
+            EventField {
+                name: "attacker_height_advantage".to_string(),
+                data: height_advantage,
+            },
+            EventField {
+                name: "attacker_time_since_spotted".to_string(),
+                data: time_since_spotted,
+            },
+            EventField {
+                name: "attacker_weapon_ammo".to_string(),
+                data: weapon_ammo,
+            },
+        ]
+    }
+}
@@ -1,10 +1,29 @@
+pub mod bomb;
+pub mod change_log;
+pub mod chunk_state;
 pub mod collect_data;
 pub mod decoder;
 pub mod entities;
+pub mod entity_rows;
+pub mod fov;
 pub mod game_events;
+pub mod grenade_lifecycle;
+pub mod inferno;
+pub mod kill_context;
+pub mod money_ledger;
+pub mod movement;
 pub mod other_netmessages;
 pub mod parser;
 pub mod parser_settings;
 pub mod path_ops;
+pub mod prop_changes;
+pub mod shots;
+pub mod smoke;
+pub mod spray;
+pub mod usercmd;
+pub mod spotting;
+pub mod utility_effects;
 pub mod variants;
+pub mod visibility;
 pub mod voice_data;
+pub mod weapons;
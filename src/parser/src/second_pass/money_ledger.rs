@@ -0,0 +1,325 @@
+use crate::first_pass::prop_controller::ITEM_PURCHASE_COST;
+use crate::first_pass::prop_controller::ITEM_PURCHASE_COUNT;
+use crate::first_pass::read_bits::DemoParserError;
+use crate::maps::KILL_REWARDS;
+use crate::second_pass::game_events::DecodedGameEvent;
+use crate::second_pass::game_events::GameEventInfo;
+use crate::second_pass::parser_settings::SecondPassParser;
+use crate::second_pass::variants::Variant;
+use csgoproto::cstrike15_usermessages::CCSUsrMsg_AdjustMoney;
+use protobuf::Message;
+
+// Account updates and the events causing them don't always land on the same tick.
+const MONEY_CAUSE_TICK_WINDOW: i32 = 8;
+const DEFAULT_KILL_REWARD: i32 = 300;
+const KNIFE_KILL_REWARD: i32 = 1500;
+const TEAM_KILL_PENALTY: i32 = -300;
+const BOMB_PLANT_BONUS: i32 = 300;
+const BOMB_DEFUSE_BONUS: i32 = 300;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MoneyReason {
+    Purchase,
+    Sale,
+    KillReward,
+    TeamKillPenalty,
+    BombPlanted,
+    BombDefused,
+    AdjustMoney,
+    RoundEnd,
+    RoundStart,
+}
+impl MoneyReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MoneyReason::Purchase => "purchase",
+            MoneyReason::Sale => "sale",
+            MoneyReason::KillReward => "kill_reward",
+            MoneyReason::TeamKillPenalty => "team_kill_penalty",
+            MoneyReason::BombPlanted => "plant_bonus",
+            MoneyReason::BombDefused => "defuse_bonus",
+            MoneyReason::AdjustMoney => "adjust_money",
+            MoneyReason::RoundEnd => "round_end_reward",
+            MoneyReason::RoundStart => "round_start",
+        }
+    }
+}
+
+// Raw m_iAccount change on a controller
+#[derive(Debug, Clone)]
+pub struct MoneyChange {
+    pub tick: i32,
+    pub steamid: Option<u64>,
+    pub name: Option<String>,
+    pub old_balance: i32,
+    pub new_balance: i32,
+}
+// Something that can explain a MoneyChange. steamid None means it applies to anyone.
+// amount None means the amount is not known (round end rewards depend on loss streaks etc.)
+#[derive(Debug, Clone)]
+pub struct MoneyCause {
+    pub tick: i32,
+    pub steamid: Option<u64>,
+    pub amount: Option<i32>,
+    pub reason: MoneyReason,
+}
+#[derive(Debug, Clone)]
+pub struct MoneyLedgerRecord {
+    pub tick: Option<i32>,
+    pub steamid: Option<u64>,
+    pub name: Option<String>,
+    pub delta: Option<i32>,
+    pub balance: Option<i32>,
+    pub reason: Option<String>,
+    pub explained: Option<bool>,
+}
+
+impl<'a> SecondPassParser<'a> {
+    pub fn parse_adjust_money(&mut self, bytes: &[u8]) -> Result<(), DemoParserError> {
+        if !self.parse_money_ledger {
+            return Ok(());
+        }
+        let msg: CCSUsrMsg_AdjustMoney = match Message::parse_from_bytes(bytes) {
+            Ok(msg) => msg,
+            Err(_) => return Err(DemoParserError::MalformedMessage),
+        };
+        // The message has no recipient so it can explain a change of any player
+        self.money_causes.push(MoneyCause {
+            tick: self.tick,
+            steamid: None,
+            amount: Some(msg.amount()),
+            reason: MoneyReason::AdjustMoney,
+        });
+        Ok(())
+    }
+    pub fn collect_money_changes(&mut self, events: &[GameEventInfo]) {
+        for event in events {
+            match event {
+                GameEventInfo::AccountChange((Some(Variant::I32(old)), Variant::I32(new), controller_entid)) => {
+                    let (steamid, name) = match self.find_user_by_controller_id(*controller_entid) {
+                        Some(player) => (player.steamid, player.name.clone()),
+                        None => (None, None),
+                    };
+                    self.money_changes.push(MoneyChange {
+                        tick: self.tick,
+                        steamid,
+                        name,
+                        old_balance: *old,
+                        new_balance: *new,
+                    });
+                }
+                GameEventInfo::RoundEnd(_) => self.push_money_cause(None, None, MoneyReason::RoundEnd),
+                GameEventInfo::FreezePeriodStart(_) => self.push_money_cause(None, None, MoneyReason::RoundStart),
+                GameEventInfo::WeaponPurchaseCount((Variant::U32(0), entid, prop_id)) => {
+                    let inventory_slot = prop_id - ITEM_PURCHASE_COUNT;
+                    let steamid = self.find_player_metadata(*entid).ok().and_then(|p| p.steamid);
+                    if let Ok(Variant::I32(cost)) = self.get_prop_from_ent(&(ITEM_PURCHASE_COST + inventory_slot), entid) {
+                        self.push_money_cause(steamid, Some(cost), MoneyReason::Sale);
+                    }
+                }
+                _ => {}
+            }
+        }
+        for purchase in SecondPassParser::combine_purchase_events(events) {
+            let steamid = self.find_player_metadata(purchase.entid).ok().and_then(|p| p.steamid);
+            self.push_money_cause(steamid, Some(-purchase.cost), MoneyReason::Purchase);
+        }
+    }
+    pub fn collect_money_cause_from_event(&mut self, event: &DecodedGameEvent) -> Result<(), DemoParserError> {
+        match event.name() {
+            "player_death" => {
+                let attacker = match event.key("attacker") {
+                    Some(Variant::I32(userid)) => self.player_steamid_and_team_from_userid(userid),
+                    _ => None,
+                };
+                let victim = match event.key("userid") {
+                    Some(Variant::I32(userid)) => self.player_steamid_and_team_from_userid(userid),
+                    _ => None,
+                };
+                let weapon = match event.key("weapon") {
+                    Some(Variant::String(weapon)) => weapon,
+                    _ => String::new(),
+                };
+                if let (Some((attacker_steamid, attacker_team)), Some((victim_steamid, victim_team))) = (attacker, victim) {
+                    if attacker_steamid == victim_steamid {
+                        return Ok(());
+                    }
+                    if attacker_team == victim_team {
+                        self.push_money_cause(Some(attacker_steamid), Some(TEAM_KILL_PENALTY), MoneyReason::TeamKillPenalty);
+                    } else {
+                        self.push_money_cause(Some(attacker_steamid), Some(kill_reward(&weapon)), MoneyReason::KillReward);
+                    }
+                }
+            }
+            "bomb_planted" => {
+                if let Some(Variant::I32(userid)) = event.key("userid") {
+                    if let Some((steamid, _)) = self.player_steamid_and_team_from_userid(userid) {
+                        self.push_money_cause(Some(steamid), Some(BOMB_PLANT_BONUS), MoneyReason::BombPlanted);
+                    }
+                }
+            }
+            "bomb_defused" => {
+                if let Some(Variant::I32(userid)) = event.key("userid") {
+                    if let Some((steamid, _)) = self.player_steamid_and_team_from_userid(userid) {
+                        self.push_money_cause(Some(steamid), Some(BOMB_DEFUSE_BONUS), MoneyReason::BombDefused);
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+    fn player_steamid_and_team_from_userid(&self, userid: i32) -> Option<(u64, Option<u32>)> {
+        let entity_id = self.entity_id_from_userid(userid)?;
+        let player = self.find_player_metadata(entity_id).ok()?;
+        Some((player.steamid?, player.team_num))
+    }
+    fn push_money_cause(&mut self, steamid: Option<u64>, amount: Option<i32>, reason: MoneyReason) {
+        self.money_causes.push(MoneyCause {
+            tick: self.tick,
+            steamid,
+            amount,
+            reason,
+        });
+    }
+}
+
+pub fn kill_reward(weapon: &str) -> i32 {
+    if weapon.contains("knife") || weapon.contains("bayonet") {
+        return KNIFE_KILL_REWARD;
+    }
+    match KILL_REWARDS.get(weapon) {
+        Some(reward) => *reward,
+        None => DEFAULT_KILL_REWARD,
+    }
+}
+
+pub fn create_money_ledger(changes: &[MoneyChange], causes: &[MoneyCause]) -> Vec<MoneyLedgerRecord> {
+    // Pairs every balance change with the causes around the same tick.
+    // Causes with a known amount are consumed when used, round end/start apply to every player.
+    let mut used = vec![false; causes.len()];
+    let mut ledger = vec![];
+    for change in changes {
+        let delta = change.new_balance - change.old_balance;
+        if delta == 0 {
+            continue;
+        }
+        let candidates: Vec<usize> = causes
+            .iter()
+            .enumerate()
+            .filter(|(idx, cause)| {
+                !used[*idx] && (cause.tick - change.tick).abs() <= MONEY_CAUSE_TICK_WINDOW && (cause.steamid.is_none() || cause.steamid == change.steamid)
+            })
+            .map(|(idx, _)| idx)
+            .collect();
+
+        let reason = match find_exact_cause(&candidates, causes, delta) {
+            Some(idx) => {
+                used[idx] = true;
+                Some(causes[idx].reason.as_str().to_string())
+            }
+            None => match find_combined_causes(&candidates, causes, delta) {
+                Some(indicies) => {
+                    for idx in &indicies {
+                        used[*idx] = true;
+                    }
+                    Some(indicies.iter().map(|idx| causes[*idx].reason.as_str()).collect::<Vec<&str>>().join("+"))
+                }
+                None => candidates
+                    .iter()
+                    .find(|idx| causes[**idx].amount.is_none())
+                    .map(|idx| causes[*idx].reason.as_str().to_string()),
+            },
+        };
+        ledger.push(MoneyLedgerRecord {
+            tick: Some(change.tick),
+            steamid: change.steamid,
+            name: change.name.clone(),
+            delta: Some(delta),
+            balance: Some(change.new_balance),
+            explained: Some(reason.is_some()),
+            reason: reason.or(Some("unexplained".to_string())),
+        });
+    }
+    ledger
+}
+fn find_exact_cause(candidates: &[usize], causes: &[MoneyCause], delta: i32) -> Option<usize> {
+    // Prefer causes that name the player over ones that could be anyone's
+    let exact = candidates.iter().filter(|idx| causes[**idx].amount == Some(delta));
+    exact.clone().find(|idx| causes[**idx].steamid.is_some()).or(exact.clone().next()).copied()
+}
+fn find_combined_causes(candidates: &[usize], causes: &[MoneyCause], delta: i32) -> Option<Vec<usize>> {
+    // Multiple causes on the same tick end up as one account update, for example two kills with one shot.
+    let player_causes: Vec<usize> = candidates
+        .iter()
+        .filter(|idx| causes[**idx].steamid.is_some() && causes[**idx].amount.is_some())
+        .copied()
+        .collect();
+    if player_causes.len() < 2 {
+        return None;
+    }
+    let total: i32 = player_causes.iter().filter_map(|idx| causes[*idx].amount).sum();
+    if total == delta {
+        return Some(player_causes);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(tick: i32, old_balance: i32, new_balance: i32) -> MoneyChange {
+        MoneyChange {
+            tick,
+            steamid: Some(1),
+            name: None,
+            old_balance,
+            new_balance,
+        }
+    }
+    fn cause(tick: i32, steamid: Option<u64>, amount: Option<i32>, reason: MoneyReason) -> MoneyCause {
+        MoneyCause { tick, steamid, amount, reason }
+    }
+
+    #[test]
+    fn test_money_ledger_attribution() {
+        let changes = vec![change(100, 800, 600), change(200, 600, 900), change(300, 900, 4150), change(400, 4150, 4000)];
+        let causes = vec![
+            cause(101, Some(1), Some(-200), MoneyReason::Purchase),
+            cause(199, Some(1), Some(300), MoneyReason::KillReward),
+            cause(300, None, None, MoneyReason::RoundEnd),
+        ];
+        let ledger = create_money_ledger(&changes, &causes);
+        let reasons: Vec<Option<String>> = ledger.iter().map(|x| x.reason.clone()).collect();
+        assert_eq!(
+            reasons,
+            vec![
+                Some("purchase".to_string()),
+                Some("kill_reward".to_string()),
+                Some("round_end_reward".to_string()),
+                Some("unexplained".to_string()),
+            ]
+        );
+        assert_eq!(ledger[3].explained, Some(false));
+        assert_eq!(ledger[3].delta, Some(-150));
+    }
+    #[test]
+    fn test_money_ledger_combined_causes() {
+        let changes = vec![change(100, 1000, 1600)];
+        let causes = vec![
+            cause(100, Some(1), Some(300), MoneyReason::KillReward),
+            cause(100, Some(1), Some(300), MoneyReason::BombPlanted),
+            cause(100, Some(2), Some(300), MoneyReason::KillReward),
+        ];
+        let ledger = create_money_ledger(&changes, &causes);
+        assert_eq!(ledger[0].reason, Some("kill_reward+plant_bonus".to_string()));
+        assert_eq!(ledger[0].explained, Some(true));
+    }
+    #[test]
+    fn test_kill_reward() {
+        assert_eq!(kill_reward("awp"), 100);
+        assert_eq!(kill_reward("knife_t"), 1500);
+        assert_eq!(kill_reward("ak47"), 300);
+    }
+}
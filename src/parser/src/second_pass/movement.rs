@@ -0,0 +1,488 @@
+use crate::first_pass::prop_controller::TICKRATE;
+use crate::first_pass::read_bits::DemoParserError;
+use crate::maps::BUTTONMAP;
+use crate::second_pass::game_events::DecodedGameEvent;
+use crate::second_pass::parser_settings::SecondPassParser;
+use crate::second_pass::shots::MOVING_SPEED;
+use crate::second_pass::variants::Variant;
+use ahash::AHashMap;
+use csgoproto::cstrike15_usermessages::CCSUsrMsg_CounterStrafe;
+use protobuf::Message;
+
+// MoveType_t::MOVETYPE_LADDER
+const MOVETYPE_LADDER: u64 = 9;
+// Standing eyes are at 64 units, fully ducked at 46
+const DUCKED_VIEW_OFFSET: f32 = 55.0;
+// Speed the player needs to have been moving at for a stop to count as a counter-strafe
+const RUNNING_SPEED: f32 = 150.0;
+const COUNTER_STRAFE_WINDOW_TICKS: i32 = 32;
+// A shot at most this long after ducking is a crouch-peek
+const CROUCH_PEEK_WINDOW_TICKS: i32 = 32;
+// Jumping again within this many ticks of landing avoids ground friction
+const BHOP_MAX_GROUND_TICKS: i32 = 2;
+// Larger gaps between samples (death, disconnect, chunk without the player) are not moves
+const MAX_SAMPLE_GAP_TICKS: i32 = 8;
+// Faster than anything reachable by moving, spawns and other teleports
+const MAX_SPEED: f32 = 1500.0;
+
+#[derive(Debug, Clone)]
+pub struct MovementSample {
+    pub tick: i32,
+    pub steamid: u64,
+    pub name: Option<String>,
+    pub round: Option<i32>,
+    pub position: [f32; 3],
+    pub yaw: Option<f32>,
+    pub airborne: bool,
+    pub ducking: bool,
+    pub on_ladder: bool,
+    pub buttons: Option<u64>,
+}
+
+// Collected per chunk and turned into events once every chunk is done, so states
+// that span chunk boundaries (a jump, a long duck) survive multithreaded parsing
+#[derive(Debug, Clone)]
+pub enum MovementInput {
+    Sample(MovementSample),
+    Fire {
+        tick: i32,
+        steamid: u64,
+        weapon: String,
+    },
+    CounterStrafe {
+        tick: i32,
+        steamid: u64,
+        name: Option<String>,
+        round: Option<i32>,
+        press_to_release_ns: i32,
+        total_keys_down: i32,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct MovementEvent {
+    pub tick: i32,
+    pub round: Option<i32>,
+    pub steamid: u64,
+    pub name: Option<String>,
+    // jump, land, bhop, counter_strafe, counter_strafe_feedback, crouch_peek, duck or ladder
+    pub event_type: String,
+    // Where the state started: takeoff for land, previous landing for bhop, deceleration start for counter_strafe
+    pub start_tick: i32,
+    // Seconds between start_tick and tick, press to release time for counter_strafe_feedback
+    pub duration: Option<f32>,
+    // Horizontal speed at tick, takeoff speed for jumps and speed at fire for shots
+    pub speed: Option<f32>,
+    pub weapon: Option<String>,
+    // Whether a movement key against the direction of travel was held while stopping
+    pub opposing_key: Option<bool>,
+    pub keys_down: Option<i32>,
+}
+
+impl<'a> SecondPassParser<'a> {
+    pub fn collect_movement_samples(&mut self) {
+        let round = match self.find_current_round() {
+            Some(Variant::I32(round)) => Some(round),
+            _ => None,
+        };
+        let special_ids = &self.prop_controller.special_ids;
+        let mut samples = vec![];
+        for (entity_id, player) in &self.players {
+            let steamid = match player.steamid {
+                Some(steamid) => steamid,
+                None => continue,
+            };
+            if !matches!(self.find_is_alive(entity_id), Ok(Variant::Bool(true))) {
+                continue;
+            }
+            let position = match self.find_position(entity_id) {
+                Some(position) => position,
+                None => continue,
+            };
+            let view_offset = match special_ids.view_offset_z.map(|id| self.get_prop_from_ent(&id, entity_id)) {
+                Some(Ok(Variant::F32(offset))) => Some(offset),
+                _ => None,
+            };
+            let move_type = match special_ids.move_type.map(|id| self.get_prop_from_ent(&id, entity_id)) {
+                Some(Ok(Variant::U64(move_type))) => Some(move_type),
+                Some(Ok(Variant::U32(move_type))) => Some(move_type as u64),
+                _ => None,
+            };
+            let buttons = match special_ids.buttons.map(|id| self.get_prop_from_ent(&id, entity_id)) {
+                Some(Ok(Variant::U64(buttons))) => Some(buttons),
+                _ => None,
+            };
+            let sample = MovementSample {
+                tick: self.tick,
+                steamid,
+                name: player.name.clone(),
+                round,
+                position,
+                yaw: match self.find_pitch_or_yaw(entity_id, 1) {
+                    Ok(Variant::F32(yaw)) => Some(yaw),
+                    _ => None,
+                },
+                airborne: matches!(self.find_is_airborne(player), Ok(Variant::Bool(true))),
+                ducking: view_offset.is_some_and(|offset| offset < DUCKED_VIEW_OFFSET),
+                on_ladder: move_type == Some(MOVETYPE_LADDER),
+                buttons,
+            };
+            samples.push(MovementInput::Sample(sample));
+        }
+        self.movement_inputs.extend(samples);
+    }
+    pub fn collect_movement_event(&mut self, event: &DecodedGameEvent) -> Result<(), DemoParserError> {
+        if event.name() != "weapon_fire" {
+            return Ok(());
+        }
+        let steamid = match event.key("userid") {
+            Some(Variant::I32(userid)) => self.entity_id_from_userid(userid).and_then(|id| self.players.get(&id)).and_then(|p| p.steamid),
+            _ => None,
+        };
+        if let (Some(steamid), Some(Variant::String(weapon))) = (steamid, event.key("weapon")) {
+            self.movement_inputs.push(MovementInput::Fire {
+                tick: self.tick,
+                steamid,
+                weapon: weapon.strip_prefix("weapon_").unwrap_or(&weapon).to_string(),
+            });
+        }
+        Ok(())
+    }
+    // The message has no player in it, it is only sent to the player it is about so
+    // it can only be attributed in POV demos
+    pub fn parse_counter_strafe(&mut self, bytes: &[u8]) -> Result<(), DemoParserError> {
+        if !self.parse_movement {
+            return Ok(());
+        }
+        let msg: CCSUsrMsg_CounterStrafe = match Message::parse_from_bytes(bytes) {
+            Ok(msg) => msg,
+            Err(_) => return Err(DemoParserError::MalformedMessage),
+        };
+        let player = match self.find_pov_player_entity_id().and_then(|id| self.players.get(&id)) {
+            Some(player) => player,
+            None => return Ok(()),
+        };
+        if let Some(steamid) = player.steamid {
+            self.movement_inputs.push(MovementInput::CounterStrafe {
+                tick: self.tick,
+                steamid,
+                name: player.name.clone(),
+                round: match self.find_current_round() {
+                    Some(Variant::I32(round)) => Some(round),
+                    _ => None,
+                },
+                press_to_release_ns: msg.press_to_release_ns(),
+                total_keys_down: msg.total_keys_down(),
+            });
+        }
+        Ok(())
+    }
+}
+
+fn seconds(start_tick: i32, end_tick: i32) -> f32 {
+    (end_tick - start_tick) as f32 / TICKRATE
+}
+
+// Horizontal speed going into every sample, None after gaps and teleports
+fn sample_speeds(samples: &[&MovementSample]) -> Vec<Option<f32>> {
+    let mut speeds = vec![None];
+    for pair in samples.windows(2) {
+        let (prev, cur) = (pair[0], pair[1]);
+        let ticks = cur.tick - prev.tick;
+        if ticks <= 0 || ticks > MAX_SAMPLE_GAP_TICKS {
+            speeds.push(None);
+            continue;
+        }
+        let (dx, dy) = (cur.position[0] - prev.position[0], cur.position[1] - prev.position[1]);
+        let speed = (dx * dx + dy * dy).sqrt() / seconds(prev.tick, cur.tick);
+        speeds.push(if speed > MAX_SPEED { None } else { Some(speed) });
+    }
+    speeds
+}
+
+// Movement keys held against the direction the player was travelling in
+fn has_opposing_key(buttons: u64, yaw: f32, velocity: [f32; 2]) -> bool {
+    let (sin, cos) = yaw.to_radians().sin_cos();
+    let keys = [("FORWARD", [cos, sin]), ("BACK", [-cos, -sin]), ("LEFT", [-sin, cos]), ("RIGHT", [sin, -cos])];
+    keys.iter().any(|(key, direction)| match BUTTONMAP.get(key) {
+        Some(flag) => buttons & flag != 0 && direction[0] * velocity[0] + direction[1] * velocity[1] < 0.0,
+        None => false,
+    })
+}
+
+struct PlayerMovement<'a> {
+    samples: Vec<&'a MovementSample>,
+    speeds: Vec<Option<f32>>,
+}
+
+impl<'a> PlayerMovement<'a> {
+    fn event(&self, idx: usize, event_type: &str, start_tick: i32) -> MovementEvent {
+        let sample = self.samples[idx];
+        MovementEvent {
+            tick: sample.tick,
+            round: sample.round,
+            steamid: sample.steamid,
+            name: sample.name.clone(),
+            event_type: event_type.to_string(),
+            start_tick,
+            duration: Some(seconds(start_tick, sample.tick)),
+            speed: self.speeds[idx],
+            weapon: None,
+            opposing_key: None,
+            keys_down: None,
+        }
+    }
+    fn state_events(&self, events: &mut Vec<MovementEvent>) {
+        let mut takeoff: Option<(usize, bool)> = None;
+        let mut last_landing: Option<(i32, bool)> = None;
+        let mut duck_start = None;
+        let mut ladder_start = None;
+        for idx in 1..self.samples.len() {
+            let (prev, cur) = (self.samples[idx - 1], self.samples[idx]);
+            if self.speeds[idx].is_none() && cur.tick - prev.tick > MAX_SAMPLE_GAP_TICKS {
+                // Player was gone for a while, whatever they were doing did not finish normally
+                takeoff = None;
+                last_landing = None;
+                duck_start = None;
+                ladder_start = None;
+                continue;
+            }
+            if !prev.airborne && cur.airborne {
+                // Walking off a ledge does not go up
+                let rises = self
+                    .samples
+                    .get(idx + 1)
+                    .is_some_and(|next| next.airborne && next.position[2] > cur.position[2]);
+                let jumped = rises || cur.position[2] > prev.position[2];
+                if jumped {
+                    events.push(MovementEvent {
+                        speed: self.speeds[idx - 1].or(self.speeds[idx]),
+                        duration: None,
+                        ..self.event(idx, "jump", cur.tick)
+                    });
+                    if let Some((landing_tick, true)) = last_landing {
+                        if cur.tick - landing_tick <= BHOP_MAX_GROUND_TICKS {
+                            events.push(MovementEvent {
+                                speed: self.speeds[idx - 1].or(self.speeds[idx]),
+                                ..self.event(idx, "bhop", landing_tick)
+                            });
+                        }
+                    }
+                }
+                takeoff = Some((idx, jumped));
+            }
+            if prev.airborne && !cur.airborne {
+                if let Some((takeoff_idx, jumped)) = takeoff {
+                    events.push(self.event(idx, "land", self.samples[takeoff_idx].tick));
+                    last_landing = Some((cur.tick, jumped));
+                }
+                takeoff = None;
+            }
+            match (prev.ducking, cur.ducking, duck_start) {
+                (false, true, _) => duck_start = Some(cur.tick),
+                (true, false, Some(start)) => {
+                    events.push(self.event(idx, "duck", start));
+                    duck_start = None;
+                }
+                _ => {}
+            }
+            match (prev.on_ladder, cur.on_ladder, ladder_start) {
+                (false, true, _) => ladder_start = Some(cur.tick),
+                (true, false, Some(start)) => {
+                    events.push(self.event(idx, "ladder", start));
+                    ladder_start = None;
+                }
+                _ => {}
+            }
+        }
+    }
+    fn shot_events(&self, tick: i32, weapon: &str, events: &mut Vec<MovementEvent>) {
+        // Last sample at or before the shot
+        let idx = match self.samples.partition_point(|s| s.tick <= tick) {
+            0 => return,
+            n => n - 1,
+        };
+        let sample = self.samples[idx];
+        if tick - sample.tick > MAX_SAMPLE_GAP_TICKS || sample.airborne {
+            return;
+        }
+        let speed_at_fire = match self.speeds[idx] {
+            Some(speed) => speed,
+            None => return,
+        };
+        let shot_event = |event_type: &str, start_tick: i32| MovementEvent {
+            tick,
+            speed: Some(speed_at_fire),
+            weapon: Some(weapon.to_string()),
+            duration: Some(seconds(start_tick, tick)),
+            ..self.event(idx, event_type, start_tick)
+        };
+        if speed_at_fire <= MOVING_SPEED {
+            // Walk back to the last tick the player was still running
+            let mut running_idx = None;
+            for i in (0..=idx).rev() {
+                if tick - self.samples[i].tick > COUNTER_STRAFE_WINDOW_TICKS || self.speeds[i].is_none() {
+                    break;
+                }
+                if self.speeds[i].unwrap_or(0.0) >= RUNNING_SPEED {
+                    running_idx = Some(i);
+                    break;
+                }
+            }
+            if let Some(running_idx) = running_idx {
+                let stopped_idx = (running_idx..=idx)
+                    .find(|i| self.speeds[*i].is_some_and(|s| s <= MOVING_SPEED))
+                    .unwrap_or(idx);
+                let start = self.samples[running_idx];
+                let before = self.samples[running_idx - 1];
+                let velocity = [start.position[0] - before.position[0], start.position[1] - before.position[1]];
+                let mut opposing_key = None;
+                for s in &self.samples[running_idx..=stopped_idx] {
+                    if let (Some(buttons), Some(yaw)) = (s.buttons, s.yaw) {
+                        let opposing = has_opposing_key(buttons, yaw, velocity);
+                        opposing_key = Some(opposing_key.unwrap_or(false) || opposing);
+                    }
+                }
+                events.push(MovementEvent {
+                    duration: Some(seconds(start.tick, self.samples[stopped_idx].tick)),
+                    opposing_key,
+                    ..shot_event("counter_strafe", start.tick)
+                });
+            }
+        }
+        if sample.ducking {
+            let mut duck_idx = idx;
+            while duck_idx > 0 && self.samples[duck_idx - 1].ducking && self.speeds[duck_idx].is_some() {
+                duck_idx -= 1;
+            }
+            let duck_tick = self.samples[duck_idx].tick;
+            // Already ducking when the player appeared is not a peek
+            if duck_idx > 0 && tick - duck_tick <= CROUCH_PEEK_WINDOW_TICKS {
+                events.push(shot_event("crouch_peek", duck_tick));
+            }
+        }
+    }
+}
+
+pub fn create_movement_events(inputs: Vec<MovementInput>) -> Vec<MovementEvent> {
+    let mut samples_per_player: AHashMap<u64, Vec<MovementSample>> = AHashMap::default();
+    let mut fires = vec![];
+    let mut events = vec![];
+    for input in inputs {
+        match input {
+            MovementInput::Sample(sample) => samples_per_player.entry(sample.steamid).or_default().push(sample),
+            MovementInput::Fire { tick, steamid, weapon } => fires.push((tick, steamid, weapon)),
+            MovementInput::CounterStrafe {
+                tick,
+                steamid,
+                name,
+                round,
+                press_to_release_ns,
+                total_keys_down,
+            } => events.push(MovementEvent {
+                tick,
+                round,
+                steamid,
+                name,
+                event_type: "counter_strafe_feedback".to_string(),
+                start_tick: tick,
+                duration: Some(press_to_release_ns as f32 / 1_000_000_000.0),
+                speed: None,
+                weapon: None,
+                opposing_key: None,
+                keys_down: Some(total_keys_down),
+            }),
+        }
+    }
+    let mut players: AHashMap<u64, PlayerMovement> = AHashMap::default();
+    for (steamid, samples) in samples_per_player.iter_mut() {
+        // Entities can be collected more than once per tick, keep the last state
+        samples.sort_by_key(|s| s.tick);
+        let mut deduped: Vec<&MovementSample> = vec![];
+        for sample in samples.iter() {
+            match deduped.last_mut() {
+                Some(last) if last.tick == sample.tick => *last = sample,
+                _ => deduped.push(sample),
+            }
+        }
+        let speeds = sample_speeds(&deduped);
+        players.insert(*steamid, PlayerMovement { samples: deduped, speeds });
+    }
+    for player in players.values() {
+        player.state_events(&mut events);
+    }
+    for (tick, steamid, weapon) in &fires {
+        if let Some(player) = players.get(steamid) {
+            player.shot_events(*tick, weapon, &mut events);
+        }
+    }
+    events.sort_by_key(|e| (e.tick, e.steamid));
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(tick: i32, x: f32, z: f32, airborne: bool) -> MovementInput {
+        MovementInput::Sample(MovementSample {
+            tick,
+            steamid: 1,
+            name: None,
+            round: Some(1),
+            position: [x, 0.0, z],
+            yaw: Some(0.0),
+            airborne,
+            ducking: false,
+            on_ladder: false,
+            buttons: None,
+        })
+    }
+
+    #[test]
+    fn test_movement_events() {
+        let mut inputs = vec![];
+        // Running along x at 250 u/s
+        let mut x = 0.0;
+        for tick in 0..10 {
+            inputs.push(sample(tick, x, 0.0, false));
+            x += 250.0 / 64.0;
+        }
+        // Jump, land and jump again the next tick
+        for (tick, z, airborne) in [
+            (10, 5.0, true),
+            (11, 9.0, true),
+            (12, 5.0, true),
+            (13, 0.0, false),
+            (14, 4.0, true),
+            (15, 0.0, false),
+        ] {
+            inputs.push(sample(tick, x, z, airborne));
+            x += 250.0 / 64.0;
+        }
+        // Run, then hold the back key (opposing x) to stop and fire
+        for tick in 16..20 {
+            let MovementInput::Sample(mut s) = sample(tick, x, 0.0, false) else {
+                unreachable!()
+            };
+            s.buttons = Some(BUTTONMAP["BACK"]);
+            inputs.push(MovementInput::Sample(s));
+            x += if tick < 17 { 250.0 / 64.0 } else { 1.0 / 64.0 };
+        }
+        inputs.push(MovementInput::Fire {
+            tick: 19,
+            steamid: 1,
+            weapon: "ak47".to_string(),
+        });
+        let events = create_movement_events(inputs);
+        let types: Vec<&str> = events.iter().map(|e| e.event_type.as_str()).collect();
+        assert_eq!(types, vec!["jump", "land", "jump", "bhop", "land", "counter_strafe"]);
+        assert_eq!(events[1].duration, Some(3.0 / 64.0));
+        assert_eq!(events[3].start_tick, 13);
+        let counter_strafe = &events[5];
+        assert_eq!((counter_strafe.start_tick, counter_strafe.tick), (17, 19));
+        assert_eq!(counter_strafe.duration, Some(1.0 / 64.0));
+        assert_eq!(counter_strafe.opposing_key, Some(true));
+        assert!(counter_strafe.speed.unwrap() < MOVING_SPEED);
+    }
+}
@@ -2,24 +2,38 @@ use crate::first_pass::parser::Frame;
 use crate::first_pass::parser::HEADER_ENDS_AT_BYTE;
 use crate::first_pass::parser_settings::FirstPassParser;
 use crate::first_pass::prop_controller::PropController;
-use crate::first_pass::prop_controller::*;
 use crate::first_pass::read_bits::read_varint;
 use crate::first_pass::read_bits::Bitreader;
 use crate::first_pass::read_bits::DemoParserError;
 use crate::first_pass::stringtables::parse_userinfo;
 use crate::maps::demo_cmd_type_from_int;
 use crate::maps::netmessage_type_from_int;
+use crate::maps::NetmessageType;
 use crate::maps::NetmessageType::*;
+use crate::second_pass::bomb::BombEvent;
+use crate::second_pass::change_log::ChangeLog;
 use crate::second_pass::collect_data::ProjectileRecord;
 use crate::second_pass::entities::Entity;
+use crate::second_pass::entity_rows::EntityRecord;
+use crate::second_pass::fov::CrosshairPlacementRecord;
+use crate::second_pass::fov::FovRecord;
 use crate::second_pass::game_events::GameEvent;
+use crate::second_pass::grenade_lifecycle::GrenadeLifecycle;
+use crate::second_pass::inferno::InfernoFireRecord;
+use crate::second_pass::money_ledger::MoneyCause;
+use crate::second_pass::money_ledger::MoneyChange;
 use crate::second_pass::parser_settings::SecondPassParser;
 use crate::second_pass::parser_settings::*;
+use crate::second_pass::movement::MovementInput;
+use crate::second_pass::shots::ShotEvent;
+use crate::second_pass::weapons::{GroundWeaponRecord, WeaponInput};
+use crate::second_pass::usercmd::UserCmdRecord;
+use crate::second_pass::spotting::SpottingEdge;
+use crate::second_pass::utility_effects::UtilityEvent;
+use crate::second_pass::visibility::VisibilityRecord;
 use crate::second_pass::variants::PropColumn;
-use crate::second_pass::variants::Variant;
 use ahash::AHashMap;
 use ahash::AHashSet;
-use csgoproto::cs_usercmd::CSGOUserCmdPB;
 use csgoproto::demo::*;
 use csgoproto::netmessages::*;
 use csgoproto::networkbasetypes::CNETMsg_Tick;
@@ -28,8 +42,6 @@ use snap::raw::decompress_len;
 use snap::raw::Decoder as SnapDecoder;
 use EDemoCommands::*;
 
-use super::variants::InputHistory;
-
 const OUTER_BUF_DEFAULT_LEN: usize = 400_000;
 const INNER_BUF_DEFAULT_LEN: usize = 8192 * 15;
 
@@ -49,8 +61,27 @@ pub struct SecondPassOutput {
     pub ptr: usize,
     pub voice_data: Vec<CSVCMsg_VoiceData>,
     pub df_per_player: AHashMap<u64, AHashMap<u32, PropColumn>>,
+    pub change_log: Option<ChangeLog>,
     pub entities: Vec<Option<Entity>>,
     pub last_tick: i32,
+    pub money_changes: Vec<MoneyChange>,
+    pub money_causes: Vec<MoneyCause>,
+    pub grenade_lifecycles: Vec<GrenadeLifecycle>,
+    pub inferno_expires: Vec<(i32, i32)>,
+    pub utility_events: Vec<UtilityEvent>,
+    pub inferno_fires: Vec<InfernoFireRecord>,
+    pub bomb_events: Vec<BombEvent>,
+    pub visibility: Vec<VisibilityRecord>,
+    pub spotting_edges: Vec<SpottingEdge>,
+    pub spotting_start_tick: Option<i32>,
+    pub fov_records: Vec<FovRecord>,
+    pub crosshair_placement: Vec<CrosshairPlacementRecord>,
+    pub shot_events: Vec<ShotEvent>,
+    pub usercmds: Vec<UserCmdRecord>,
+    pub movement_inputs: Vec<MovementInput>,
+    pub entity_records: Vec<EntityRecord>,
+    pub weapon_inputs: Vec<WeaponInput>,
+    pub ground_weapons: Vec<GroundWeaponRecord>,
 }
 impl<'a> SecondPassParser<'a> {
     pub fn start(&mut self, demo_bytes: &'a [u8]) -> Result<(), DemoParserError> {
@@ -73,7 +104,7 @@ impl<'a> SecondPassParser<'a> {
                 DEM_SignonPacket => self.parse_packet(&bytes, &mut buf2),
                 DEM_Packet => self.parse_packet(&bytes, &mut buf2),
                 DEM_Stop => break,
-                DEM_UserCmd => Ok(()),
+                DEM_UserCmd => self.parse_user_command_cmd(bytes),
                 DEM_FullPacket => {
                     if self.parse_full_packet_and_break_if_needed(&bytes, &mut buf2, started_at)? {
                         break;
@@ -92,6 +123,7 @@ impl<'a> SecondPassParser<'a> {
                 return Ok(true);
             } else {
                 self.parse_full_packet(&bytes, true, buf)?;
+                self.apply_chunk_state()?;
                 return Ok(false);
             }
         }
@@ -102,6 +134,7 @@ impl<'a> SecondPassParser<'a> {
             false => {
                 if self.fullpackets_parsed == 0 && started_at != HEADER_ENDS_AT_BYTE {
                     self.parse_full_packet(&bytes, true, buf)?;
+                    self.apply_chunk_state()?;
                     self.fullpackets_parsed += 1;
                 } else {
                     return Ok(true);
@@ -186,12 +219,18 @@ impl<'a> SecondPassParser<'a> {
             }
             bitreader.read_n_bytes_mut(size as usize, buf)?;
             let msg_bytes = &buf[..size as usize];
-            let ok = match netmessage_type_from_int(msg_type as i32) {
+            let msg_type = netmessage_type_from_int(msg_type as i32);
+            if self.written_props.is_some() && !is_state_message(&msg_type) {
+                continue;
+            }
+            let ok = match msg_type {
                 svc_PacketEntities => {
                     if should_parse_entities {
                         self.parse_packet_ents(&msg_bytes, is_fullpacket)?;
-                        if !is_fullpacket {
+                        if !is_fullpacket && self.written_props.is_none() {
                             self.collect_entities();
+                        } else if !is_fullpacket {
+                            self.collect_chunk_state();
                         }
                     }
                     Ok(())
@@ -206,6 +245,9 @@ impl<'a> SecondPassParser<'a> {
                 net_SetConVar => self.create_custom_event_parse_convars(msg_bytes),
                 CS_UM_PlayerStatsUpdate => self.parse_player_stats_update(msg_bytes),
                 CS_UM_ServerRankUpdate => self.create_custom_event_rank_update(msg_bytes),
+                CS_UM_AdjustMoney => self.parse_adjust_money(msg_bytes),
+                CS_UM_ShootInfo => self.parse_shoot_info(msg_bytes),
+                CS_UM_CounterStrafe => self.parse_counter_strafe(msg_bytes),
                 net_Tick => self.parse_net_tick(msg_bytes),
                 svc_ClearAllStringTables => self.clear_stringtables(),
                 svc_VoiceData => self.parse_voice_data(msg_bytes),
@@ -220,60 +262,6 @@ impl<'a> SecondPassParser<'a> {
         }
         Ok(())
     }
-    pub fn parse_user_cmd(&mut self, bytes: &[u8]) -> Result<(), DemoParserError> {
-        // We simply inject the values into the entities as if they came from packet_ents like any other val.
-
-        // This method is quite expensive so early exit it if not needed.
-        if !self.parse_usercmd {
-            return Ok(());
-        }
-
-        let msg: CSVCMsg_UserCommands = match CSVCMsg_UserCommands::parse_from_bytes(bytes) {
-            Ok(m) => m,
-            _ => return Ok(()),
-        };
-        for cmd in msg.commands {
-            let user_cmd: CSGOUserCmdPB = match CSGOUserCmdPB::parse_from_bytes(cmd.data()) {
-                Ok(m) => m,
-                _ => return Ok(()),
-            };
-
-            let entity_id = user_cmd.base.pawn_entity_handle() & 0x7FF;
-            if let Some(Some(ent)) = self.entities.get_mut(entity_id as usize) {
-                let mut history = vec![];
-                for input in user_cmd.input_history {
-                    let ih = InputHistory {
-                        player_tick_count: input.player_tick_count(),
-                        player_tick_fraction: input.player_tick_fraction(),
-                        render_tick_count: input.render_tick_count(),
-                        render_tick_fraction: input.render_tick_fraction(),
-                        x: input.view_angles.x(),
-                        y: input.view_angles.y(),
-                        z: input.view_angles.z(),
-                    };
-                    history.push(ih);
-                }
-                ent.props.insert(USERCMD_INPUT_HISTORY_BASEID, Variant::InputHistory(history));
-                ent.props.insert(USERCMD_LEFTMOVE, Variant::F32(user_cmd.base.leftmove()));
-                ent.props.insert(USERCMD_IMPULSE, Variant::I32(user_cmd.base.impulse()));
-                ent.props.insert(USERCMD_MOUSE_DX, Variant::I32(user_cmd.base.mousedx()));
-                ent.props.insert(USERCMD_MOUSE_DY, Variant::I32(user_cmd.base.mousedy()));
-                ent.props.insert(USERCMD_VIEWANGLE_X, Variant::F32(user_cmd.base.viewangles.x()));
-                ent.props.insert(USERCMD_VIEWANGLE_Y, Variant::F32(user_cmd.base.viewangles.y()));
-                ent.props.insert(USERCMD_VIEWANGLE_Z, Variant::F32(user_cmd.base.viewangles.z()));
-                ent.props.insert(USERCMD_FORWARDMOVE, Variant::F32(user_cmd.base.forwardmove()));
-                ent.props.insert(USERCMD_BUTTONSTATE_1, Variant::U64(user_cmd.base.buttons_pb.buttonstate1()));
-                ent.props.insert(USERCMD_BUTTONSTATE_2, Variant::U64(user_cmd.base.buttons_pb.buttonstate2()));
-                ent.props.insert(USERCMD_BUTTONSTATE_3, Variant::U64(user_cmd.base.buttons_pb.buttonstate3()));
-                ent.props.insert(
-                    USERCMD_CONSUMED_SERVER_ANGLE_CHANGES,
-                    Variant::U32(user_cmd.base.consumed_server_angle_changes()),
-                );
-            }
-        }
-        Ok(())
-    }
-
     pub fn parse_voice_data(&mut self, bytes: &[u8]) -> Result<(), DemoParserError> {
         if let Ok(m) = Message::parse_from_bytes(bytes) {
             self.voice_data.push(m);
@@ -281,7 +269,43 @@ impl<'a> SecondPassParser<'a> {
         Ok(())
     }
     pub fn parse_game_event(&mut self, bytes: &[u8], wrong_order_events: &mut Vec<GameEvent>) -> Result<(), DemoParserError> {
-        match self.parse_event(bytes) {
+        let has_collectors = self.parse_money_ledger
+            || self.parse_projectiles
+            || self.parse_bomb
+            || self.fov.is_some()
+            || self.parse_shots
+            || self.parse_movement
+            || self.parse_weapons;
+        if !has_collectors && self.wanted_events.is_empty() {
+            return Ok(());
+        }
+        let event = match self.decode_game_event(bytes)? {
+            Some(event) => event,
+            None => return Ok(()),
+        };
+        if self.parse_money_ledger {
+            self.collect_money_cause_from_event(&event)?;
+        }
+        if self.parse_projectiles {
+            self.collect_grenade_event(&event)?;
+            self.collect_utility_event(&event)?;
+        }
+        if self.parse_bomb {
+            self.collect_bomb_event(&event)?;
+        }
+        if self.fov.is_some() {
+            self.collect_crosshair_placement(&event)?;
+        }
+        if self.parse_shots {
+            self.collect_shot_event(&event)?;
+        }
+        if self.parse_movement {
+            self.collect_movement_event(&event)?;
+        }
+        if self.parse_weapons {
+            self.collect_weapon_event(&event)?;
+        }
+        match self.parse_event(&event) {
             Ok(Some(event)) => {
                 wrong_order_events.push(event);
                 Ok(())
@@ -343,8 +367,11 @@ impl<'a> SecondPassParser<'a> {
         self.cls_bits = Some((class_count as f32 + 1.).log2().ceil() as u32);
         Ok(())
     }
-    pub fn parse_user_command_cmd(&mut self, _data: &[u8]) -> Result<(), DemoParserError> {
-        // Only in pov demos. Maybe implement sometime. Includes buttons etc.
-        Ok(())
-    }
+}
+// Messages the entity state depends on, the only ones read when tracking state for other chunks
+fn is_state_message(msg_type: &NetmessageType) -> bool {
+    matches!(
+        msg_type,
+        svc_PacketEntities | svc_CreateStringTable | svc_UpdateStringTable | svc_ClearAllStringTables | svc_ServerInfo
+    )
 }
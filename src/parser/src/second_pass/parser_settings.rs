@@ -1,19 +1,43 @@
 use crate::first_pass::frameparser::StartEndOffset;
 use crate::first_pass::parser::FirstPassOutput;
 use crate::first_pass::prop_controller::PropController;
+use crate::first_pass::prop_filter::PropFilter;
 use crate::first_pass::read_bits::DemoParserError;
 use crate::first_pass::sendtables::Serializer;
 use crate::first_pass::stringtables::StringTable;
 use crate::first_pass::stringtables::UserInfo;
+use crate::second_pass::change_log::ChangeLog;
 use crate::second_pass::collect_data::ProjectileRecord;
 use crate::second_pass::decoder::QfMapper;
+use crate::maps::geometry::MapGeometry;
+use crate::maps::nav::NavMesh;
+use crate::maps::overview::builtin_overview;
+use crate::maps::overview::MapOverview;
+use crate::second_pass::bomb::BombEvent;
 use crate::second_pass::entities::Entity;
 use crate::second_pass::entities::PlayerMetaData;
+use crate::second_pass::entity_rows::{check_entity_props, EntityPropSource, EntityRecord};
+use crate::second_pass::fov::CrosshairPlacementRecord;
+use crate::second_pass::fov::CrosshairSample;
+use crate::second_pass::fov::FovRecord;
 use crate::second_pass::game_events::GameEvent;
+use crate::second_pass::grenade_lifecycle::GrenadeLifecycle;
+use crate::second_pass::inferno::InfernoFireRecord;
+use crate::second_pass::money_ledger::MoneyCause;
+use crate::second_pass::money_ledger::MoneyChange;
 use crate::second_pass::other_netmessages::Class;
 use crate::second_pass::parser::SecondPassOutput;
 use crate::second_pass::path_ops::FieldPath;
+use crate::second_pass::prop_changes::resolve_watched_props;
+use crate::second_pass::chunk_state::{ChunkState, WrittenProps};
+use crate::second_pass::movement::MovementInput;
+use crate::second_pass::shots::ShotEvent;
+use crate::second_pass::weapons::{GroundWeaponRecord, WeaponInput};
+use crate::second_pass::usercmd::UserCmdRecord;
+use crate::second_pass::spotting::SpottingEdge;
+use crate::second_pass::utility_effects::UtilityEvent;
 use crate::second_pass::variants::PropColumn;
+use crate::second_pass::visibility::VisibilityRecord;
 use ahash::AHashMap;
 use ahash::AHashSet;
 use ahash::HashMap;
@@ -23,6 +47,7 @@ use csgoproto::netmessages::CSVCMsg_VoiceData;
 use std::collections::BTreeMap;
 use std::collections::BTreeSet;
 use std::env;
+use std::sync::Arc;
 const HUF_LOOKUPTABLE_MAXVALUE: u32 = (1 << 17) - 1;
 const DEFAULT_MAX_ENTITY_ID: usize = 1024;
 
@@ -49,9 +74,12 @@ pub struct SecondPassParser<'a> {
     pub string_tables: Vec<StringTable>,
     pub rules_entity_id: Option<i32>,
     pub c4_entity_id: Option<i32>,
+    pub planted_c4_entity_id: Option<i32>,
     pub game_events_counter: AHashSet<String>,
     pub baselines: AHashMap<u32, Vec<u8>, RandomState>,
     pub projectiles: BTreeSet<i32>,
+    pub infernos: BTreeSet<i32>,
+    pub weapons: BTreeSet<i32>,
     pub fullpackets_parsed: u32,
     pub wanted_players: AHashSet<u64>,
     pub wanted_ticks: AHashSet<i32>,
@@ -65,15 +93,76 @@ pub struct SecondPassParser<'a> {
     pub convars: AHashMap<String, String>,
     pub chat_messages: Vec<ChatMessageRecord>,
     pub player_end_data: Vec<PlayerEndMetaData>,
+    pub money_changes: Vec<MoneyChange>,
+    pub money_causes: Vec<MoneyCause>,
+    pub grenade_lifecycles: Vec<GrenadeLifecycle>,
+    // (inferno entity id, tick) of inferno_expire events whose molotov detonated in an earlier chunk
+    pub inferno_expires: Vec<(i32, i32)>,
+    pub open_grenades: AHashMap<i32, usize>,
+    pub utility_events: Vec<UtilityEvent>,
+    pub inferno_fires: Vec<InfernoFireRecord>,
+    pub bomb_events: Vec<BombEvent>,
+    pub visibility: Vec<VisibilityRecord>,
+    pub spotting_edges: Vec<SpottingEdge>,
+    pub spotted_pairs: AHashMap<(u64, u64), (Option<String>, Option<String>)>,
+    pub spotting_start_tick: Option<i32>,
+    pub fov_records: Vec<FovRecord>,
+    // (spotter, spotted) -> crosshair when spotted, None if already spotted when the parser started
+    pub crosshair_samples: AHashMap<(u64, u64), Option<CrosshairSample>>,
+    pub crosshair_samples_started: bool,
+    pub crosshair_duels: AHashSet<(Option<i32>, u64, u64)>,
+    pub crosshair_placement: Vec<CrosshairPlacementRecord>,
+    // Last two (tick, position) of every player, for speeds at kill ticks
+    pub recent_positions: AHashMap<u64, Vec<(i32, [f32; 3])>>,
+    // (spotter, spotted) -> tick spotted, None if already spotted when the parser started
+    pub spotted_since: AHashMap<(u64, u64), Option<i32>>,
+    // First tick the pairs were collected on
+    pub spotted_since_from: Option<i32>,
+    pub shot_events: Vec<ShotEvent>,
+    pub usercmds: Vec<UserCmdRecord>,
+    pub movement_inputs: Vec<MovementInput>,
+    pub entity_records: Vec<EntityRecord>,
+    // Resolved once per class
+    pub entity_prop_sources: AHashMap<u32, Vec<EntityPropSource>>,
+    pub entity_position_sources: AHashMap<u32, Vec<EntityPropSource>>,
+    pub weapon_inputs: Vec<WeaponInput>,
+    // Last owner pushed for every weapon in this chunk
+    pub weapon_owners: AHashMap<i32, Option<u64>>,
+    pub ground_weapons: Vec<GroundWeaponRecord>,
+    // Set for the pass that only tracks entity state for the next chunks, nothing is collected
+    pub written_props: Option<WrittenProps>,
+    // State left by the previous chunks, applied after the first fullpacket
+    pub carried_state: Option<ChunkState>,
+    pub pending_bomb_events: Vec<usize>,
     // Settings
     pub wanted_events: Vec<String>,
     pub parse_entities: bool,
     pub parse_projectiles: bool,
     pub is_debug_mode: bool,
     pub df_per_player: AHashMap<u64, AHashMap<u32, PropColumn>>,
+    pub change_log: Option<ChangeLog>,
     pub order_by_steamid: bool,
     pub last_tick: i32,
     pub parse_usercmd: bool,
+    pub parse_usercmds: bool,
+    pub pov_player_slot: Option<i32>,
+    pub parse_money_ledger: bool,
+    pub parse_bomb: bool,
+    pub parse_spotting: bool,
+    pub parse_kill_context: bool,
+    pub parse_shots: bool,
+    pub parse_movement: bool,
+    pub parse_weapons: bool,
+    pub wanted_entity_classes: AHashSet<String>,
+    pub wanted_entity_props: Vec<String>,
+    pub entity_event_classes: AHashSet<String>,
+    // prop id -> name asked for in wanted_prop_changes
+    pub watched_props: AHashMap<u32, String>,
+    pub map_overview: Option<MapOverview>,
+    pub nav_mesh: Option<Arc<NavMesh>>,
+    pub map_geometry: Option<Arc<dyn MapGeometry>>,
+    pub visibility_pairs: Option<Vec<(u64, u64)>>,
+    pub fov: Option<f32>,
 }
 #[derive(Debug, Clone)]
 pub struct Teams {
@@ -141,7 +230,7 @@ impl<'a> SecondPassParser<'a> {
             prop_info: PropController::new(
                 vec![],
                 vec![],
-                AHashMap::default(),
+                PropFilter::default(),
                 AHashMap::default(),
                 false,
                 &["none".to_string()],
@@ -149,8 +238,27 @@ impl<'a> SecondPassParser<'a> {
             projectiles: self.projectile_records,
             ptr: self.ptr,
             df_per_player: self.df_per_player,
+            change_log: self.change_log,
             entities: self.entities,
             last_tick: self.tick,
+            money_changes: self.money_changes,
+            money_causes: self.money_causes,
+            grenade_lifecycles: self.grenade_lifecycles,
+            inferno_expires: self.inferno_expires,
+            utility_events: self.utility_events,
+            inferno_fires: self.inferno_fires,
+            bomb_events: self.bomb_events,
+            visibility: self.visibility,
+            spotting_edges: self.spotting_edges,
+            spotting_start_tick: self.spotting_start_tick,
+            fov_records: self.fov_records,
+            crosshair_placement: self.crosshair_placement,
+            shot_events: self.shot_events,
+            usercmds: self.usercmds,
+            movement_inputs: self.movement_inputs,
+            entity_records: self.entity_records,
+            weapon_inputs: self.weapon_inputs,
+            ground_weapons: self.ground_weapons,
         }
     }
     pub fn new(
@@ -166,13 +274,19 @@ impl<'a> SecondPassParser<'a> {
             .extend(vec!["tick".to_owned(), "steamid".to_owned(), "name".to_owned()]);
         let args: Vec<String> = env::args().collect();
         let debug = if args.len() > 2 { args[2] == "true" } else { false };
+        check_entity_props(first_pass_output.prop_controller, &first_pass_output.settings.wanted_entity_props)?;
 
         Ok(SecondPassParser {
-            parse_usercmd: contains_usercmd_prop(&first_pass_output.settings.wanted_player_props),
+            parse_usercmd: contains_usercmd_prop(&first_pass_output.settings.wanted_player_props)
+                || first_pass_output.settings.parse_sprays
+                || first_pass_output.settings.parse_usercmds,
+            parse_usercmds: first_pass_output.settings.parse_usercmds,
+            pov_player_slot: first_pass_output.pov_player_slot,
             last_tick: 0,
             start_end_offset: start_end_offset,
             order_by_steamid: first_pass_output.order_by_steamid,
             df_per_player: AHashMap::default(),
+            change_log: first_pass_output.settings.change_log.then(ChangeLog::default),
             voice_data: vec![],
             paths: vec![
                 FieldPath {
@@ -184,6 +298,7 @@ impl<'a> SecondPassParser<'a> {
             parse_inventory: first_pass_output.prop_controller.wanted_player_props.contains(&"inventory".to_string()),
             net_tick: 0,
             c4_entity_id: None,
+            planted_c4_entity_id: None,
             stringtable_players: first_pass_output.stringtable_players,
             is_debug_mode: debug,
             projectile_records: vec![],
@@ -206,11 +321,64 @@ impl<'a> SecondPassParser<'a> {
             wanted_events: first_pass_output.settings.wanted_events.clone(),
             parse_entities: first_pass_output.settings.parse_ents,
             projectiles: BTreeSet::default(),
+            infernos: BTreeSet::default(),
+            weapons: BTreeSet::default(),
             baselines: first_pass_output.baselines.clone(),
             string_tables: first_pass_output.string_tables.clone(),
             teams: Teams::new(),
             game_events_counter: AHashSet::default(),
             parse_projectiles: first_pass_output.settings.parse_projectiles,
+            parse_money_ledger: first_pass_output.settings.parse_money_ledger,
+            parse_bomb: first_pass_output.settings.parse_bomb,
+            parse_spotting: first_pass_output.settings.parse_spotting,
+            parse_kill_context: first_pass_output.settings.parse_kill_context,
+            parse_shots: first_pass_output.settings.parse_shots || first_pass_output.settings.parse_sprays,
+            parse_movement: first_pass_output.settings.parse_movement,
+            parse_weapons: first_pass_output.settings.parse_weapons,
+            wanted_entity_classes: first_pass_output.settings.wanted_entity_classes.iter().cloned().collect(),
+            wanted_entity_props: first_pass_output.settings.wanted_entity_props.clone(),
+            entity_event_classes: first_pass_output.settings.entity_event_classes.iter().cloned().collect(),
+            watched_props: resolve_watched_props(first_pass_output.prop_controller, &first_pass_output.settings.wanted_prop_changes)?,
+            map_overview: match &first_pass_output.settings.map_overview {
+                Some(overview) => Some(overview.clone()),
+                None => first_pass_output.header.get("map_name").and_then(|name| builtin_overview(name)),
+            },
+            nav_mesh: first_pass_output.settings.nav_mesh.clone(),
+            map_geometry: first_pass_output.settings.map_geometry.clone(),
+            visibility_pairs: first_pass_output.settings.visibility_pairs.clone(),
+            fov: first_pass_output.settings.fov,
+            money_changes: vec![],
+            money_causes: vec![],
+            grenade_lifecycles: vec![],
+            inferno_expires: vec![],
+            open_grenades: AHashMap::default(),
+            utility_events: vec![],
+            inferno_fires: vec![],
+            bomb_events: vec![],
+            visibility: vec![],
+            spotting_edges: vec![],
+            spotted_pairs: AHashMap::default(),
+            spotting_start_tick: None,
+            fov_records: vec![],
+            crosshair_samples: AHashMap::default(),
+            crosshair_samples_started: false,
+            crosshair_duels: AHashSet::default(),
+            crosshair_placement: vec![],
+            recent_positions: AHashMap::default(),
+            spotted_since: AHashMap::default(),
+            spotted_since_from: None,
+            shot_events: vec![],
+            usercmds: vec![],
+            movement_inputs: vec![],
+            entity_records: vec![],
+            entity_prop_sources: AHashMap::default(),
+            entity_position_sources: AHashMap::default(),
+            weapon_inputs: vec![],
+            weapon_owners: AHashMap::default(),
+            ground_weapons: vec![],
+            written_props: None,
+            carried_state: None,
+            pending_bomb_events: vec![],
             rules_entity_id: None,
             convars: AHashMap::default(),
             chat_messages: vec![],
@@ -270,6 +438,21 @@ pub struct SpecialIDs {
     pub match_end_count: Option<u32>,
 
     pub is_incendiary_grenade: Option<u32>,
+    pub grenade_bounces: Option<u32>,
+    pub c4_blow: Option<u32>,
+    pub c4_defuse_countdown: Option<u32>,
+    pub c4_bomb_site: Option<u32>,
+    pub view_offset_z: Option<u32>,
+    pub spotted_by_mask: Option<u32>,
+    pub ducked: Option<u32>,
+    pub did_smoke_effect: Option<u32>,
+    pub inferno_fire_count: Option<u32>,
+    pub inferno_cell_x: Option<u32>,
+    pub inferno_cell_y: Option<u32>,
+    pub inferno_cell_z: Option<u32>,
+    pub inferno_vec_x: Option<u32>,
+    pub inferno_vec_y: Option<u32>,
+    pub inferno_vec_z: Option<u32>,
     pub sellback_entry_def_idx: Option<u32>,
     pub sellback_entry_n_cost: Option<u32>,
     pub sellback_entry_prev_armor: Option<u32>,
@@ -279,8 +462,14 @@ pub struct SpecialIDs {
     pub weapon_purchase_count: Option<u32>,
     pub in_buy_zone: Option<u32>,
     pub custom_name: Option<u32>,
+    pub weapon_clip1: Option<u32>,
+    pub recoil_index: Option<u32>,
+    pub accuracy_penalty: Option<u32>,
+    pub aim_punch_angle: Option<u32>,
+    pub move_type: Option<u32>,
 
     pub is_airborn: Option<u32>,
+    pub account: Option<u32>,
 }
 impl SpecialIDs {
     pub fn new() -> Self {
@@ -320,6 +509,21 @@ impl SpecialIDs {
             life_state: None,
             agent_skin_idx: None,
             is_incendiary_grenade: None,
+            grenade_bounces: None,
+            c4_blow: None,
+            c4_defuse_countdown: None,
+            c4_bomb_site: None,
+            view_offset_z: None,
+            spotted_by_mask: None,
+            ducked: None,
+            did_smoke_effect: None,
+            inferno_fire_count: None,
+            inferno_cell_x: None,
+            inferno_cell_y: None,
+            inferno_cell_z: None,
+            inferno_vec_x: None,
+            inferno_vec_y: None,
+            inferno_vec_z: None,
             sellback_entry_def_idx: None,
             sellback_entry_h_item: None,
             sellback_entry_n_cost: None,
@@ -328,7 +532,13 @@ impl SpecialIDs {
             weapon_purchase_count: None,
             in_buy_zone: None,
             custom_name: None,
+            weapon_clip1: None,
+            recoil_index: None,
+            accuracy_penalty: None,
+            aim_punch_angle: None,
+            move_type: None,
             is_airborn: None,
+            account: None,
         }
     }
 }
@@ -0,0 +1,140 @@
+use crate::first_pass::prop_controller::PropController;
+use crate::first_pass::read_bits::DemoParserError;
+use crate::maps::FRIENDLY_NAMES_MAPPING;
+use crate::second_pass::game_events::EventField;
+use crate::second_pass::game_events::GameEvent;
+use crate::second_pass::game_events::GameEventInfo;
+use crate::second_pass::game_events::ENTITYIDNONE;
+use crate::second_pass::parser_settings::SecondPassParser;
+use crate::second_pass::variants::Variant;
+use ahash::AHashMap;
+
+// prop id -> name the user asked for. Accepts friendly names ("health"), full names
+// ("CCSPlayerPawn.m_iHealth") and bare names ("m_iHealth") that match every class having the prop.
+pub fn resolve_watched_props(prop_controller: &PropController, names: &[String]) -> Result<AHashMap<u32, String>, DemoParserError> {
+    let mut watched = AHashMap::default();
+    for name in names {
+        let real_name = FRIENDLY_NAMES_MAPPING.get(name).copied().unwrap_or(name.as_str());
+        let suffix = format!(".{real_name}");
+        let mut found = false;
+        for (prop_name, id) in &prop_controller.name_to_id {
+            if prop_name == real_name || prop_name.ends_with(&suffix) {
+                watched.insert(*id, name.clone());
+                found = true;
+            }
+        }
+        if !found {
+            return Err(DemoParserError::UnknownPropName(name.clone()));
+        }
+    }
+    Ok(watched)
+}
+
+impl<'a> SecondPassParser<'a> {
+    pub fn create_custom_event_prop_changed(&mut self, events: &[GameEventInfo]) {
+        if !self.game_events_counter.contains("prop_changed") {
+            self.game_events_counter.insert("prop_changed".to_string());
+        }
+        for event in events {
+            let (prop_id, old_value, new_value, entity_id) = match event {
+                GameEventInfo::PropChanged((prop_id, old_value, new_value, entity_id)) => (prop_id, old_value, new_value, *entity_id),
+                _ => continue,
+            };
+            let prop_name = match self.watched_props.get(prop_id) {
+                Some(name) => name.clone(),
+                None => continue,
+            };
+            let class_name = match self.entities.get(entity_id as usize) {
+                Some(Some(entity)) => self.cls_by_id.get(entity.cls_id as usize).map(|cls| cls.name.clone()),
+                _ => None,
+            };
+            let player_entity_id = self.find_prop_change_player(entity_id);
+            let mut fields = vec![
+                EventField {
+                    data: Some(Variant::I32(entity_id)),
+                    name: "entity_id".to_string(),
+                },
+                EventField {
+                    data: class_name.map(Variant::String),
+                    name: "class_name".to_string(),
+                },
+                EventField {
+                    data: Some(Variant::String(prop_name)),
+                    name: "prop_name".to_string(),
+                },
+                EventField {
+                    data: old_value.clone(),
+                    name: "old_value".to_string(),
+                },
+                EventField {
+                    data: Some(new_value.clone()),
+                    name: "new_value".to_string(),
+                },
+            ];
+            fields.push(self.create_player_name_field(player_entity_id, "user"));
+            fields.push(self.create_player_steamid_field(player_entity_id, "user"));
+            fields.extend(self.find_extra_props_events(player_entity_id, "user"));
+            fields.push(EventField {
+                data: Some(Variant::I32(self.tick)),
+                name: "tick".to_string(),
+            });
+            fields.extend(self.find_non_player_props());
+            self.game_events.push(GameEvent {
+                name: "prop_changed".to_string(),
+                fields,
+                tick: self.tick,
+            });
+        }
+    }
+    // Player pawn the changed entity belongs to: the pawn itself, its controller or the owner of a weapon
+    fn find_prop_change_player(&self, entity_id: i32) -> i32 {
+        if self.players.contains_key(&entity_id) {
+            return entity_id;
+        }
+        if let Some(player_entity_id) = self.find_user_by_controller_id(entity_id).and_then(|player| player.player_entity_id) {
+            return player_entity_id;
+        }
+        if let Some(Ok(Variant::U32(handle))) = self
+            .prop_controller
+            .special_ids
+            .h_owner_entity
+            .map(|id| self.get_prop_from_ent(&id, &entity_id))
+        {
+            if self.players.contains_key(&((handle & 0x7FF) as i32)) {
+                return (handle & 0x7FF) as i32;
+            }
+        }
+        ENTITYIDNONE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::first_pass::prop_filter::PropFilter;
+
+    #[test]
+    fn test_resolve_watched_props() {
+        let mut prop_controller = PropController::new(vec![], vec![], PropFilter::default(), AHashMap::default(), false, &["none".to_string()]);
+        for (name, id) in [
+            ("CCSPlayerPawn.m_iHealth", 1),
+            ("CChicken.m_iHealth", 2),
+            ("CCSPlayerPawn.m_bIsScoped", 3),
+            ("CCSPlayerController.CCSPlayerController_InGameMoneyServices.m_iAccount", 4),
+        ] {
+            prop_controller.name_to_id.insert(name.to_string(), id);
+        }
+        let names = [
+            "m_iHealth",
+            "is_scoped",
+            "CCSPlayerController.CCSPlayerController_InGameMoneyServices.m_iAccount",
+        ]
+        .map(|s| s.to_string());
+        let watched = resolve_watched_props(&prop_controller, &names).unwrap();
+        assert_eq!(watched.len(), 4);
+        assert_eq!(watched.get(&1), Some(&"m_iHealth".to_string()));
+        assert_eq!(watched.get(&2), Some(&"m_iHealth".to_string()));
+        assert_eq!(watched.get(&3), Some(&"is_scoped".to_string()));
+        assert!(resolve_watched_props(&prop_controller, &["m_nothing".to_string()]).is_err());
+    }
+}
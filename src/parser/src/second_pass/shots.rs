@@ -0,0 +1,457 @@
+use crate::first_pass::prop_controller::USERCMD_VIEWANGLE_X;
+use crate::first_pass::prop_controller::USERCMD_VIEWANGLE_Y;
+use crate::first_pass::read_bits::DemoParserError;
+use crate::maps::HIT_GROUP;
+use crate::second_pass::game_events::DecodedGameEvent;
+use crate::second_pass::parser_settings::SecondPassParser;
+use crate::second_pass::variants::Variant;
+use ahash::AHashMap;
+use csgoproto::cstrike15_usermessages::CCSUsrMsg_ShootInfo;
+use protobuf::Message;
+
+// Hurts and impacts can arrive a few ticks after the weapon_fire that caused them
+const SHOT_HIT_WINDOW_TICKS: i32 = 4;
+// A shot continues a spray if the previous shot with the same weapon was at most this long ago (~0.375s)
+const SPRAY_RESET_TICKS: i32 = 24;
+// Roughly where rifles start losing accuracy (34% of their max speed)
+pub const MOVING_SPEED: f32 = 80.0;
+// ShootInfo has no shooter, it is matched to the shot fired from the closest eyes
+const MAX_SHOOT_POS_DISTANCE: f32 = 32.0;
+
+#[derive(Debug, Clone)]
+pub struct ShotRecord {
+    pub tick: i32,
+    pub round: Option<i32>,
+    pub steamid: u64,
+    pub name: Option<String>,
+    pub weapon: String,
+    pub recoil_index: Option<f32>,
+    pub inaccuracy: Option<f32>,
+    pub speed: Option<f32>,
+    // standing, moving, ducking or airborne
+    pub movement: String,
+    // 0 for the first bullet, counts up during a spray
+    pub burst_index: i32,
+    pub hit: bool,
+    // First player hit by the shot, shotguns and wallbangs can hit more
+    pub victim_steamid: Option<u64>,
+    pub victim_name: Option<String>,
+    pub hitgroup: Option<String>,
+    pub damage: i32,
+    pub players_hit: i32,
+    pub impact: Option<[f32; 3]>,
+    pub shoot_pitch: Option<f32>,
+    pub shoot_yaw: Option<f32>,
+    pub eyes: Option<[f32; 3]>,
+    // Pitch and yaw the player was looking at, from the usercmd when the demo has them
+    pub view: Option<[f32; 2]>,
+    pub view_from_usercmd: bool,
+    // Pitch and yaw of m_aimPunchAngle
+    pub aim_punch: Option<[f32; 2]>,
+}
+
+#[derive(Debug, Clone)]
+pub enum ShotEvent {
+    Fire(ShotRecord),
+    ShootInfo {
+        tick: i32,
+        position: [f32; 3],
+        pitch: f32,
+        yaw: f32,
+    },
+    Impact {
+        tick: i32,
+        steamid: u64,
+        position: [f32; 3],
+    },
+    Hurt {
+        tick: i32,
+        attacker_steamid: u64,
+        victim_steamid: Option<u64>,
+        victim_name: Option<String>,
+        weapon: String,
+        hitgroup: Option<String>,
+        damage: i32,
+    },
+}
+
+impl ShotEvent {
+    fn tick(&self) -> i32 {
+        match self {
+            ShotEvent::Fire(shot) => shot.tick,
+            ShotEvent::ShootInfo { tick, .. } => *tick,
+            ShotEvent::Impact { tick, .. } => *tick,
+            ShotEvent::Hurt { tick, .. } => *tick,
+        }
+    }
+    // Shots need to exist before the messages that refer to them
+    fn order(&self) -> i32 {
+        match self {
+            ShotEvent::Fire(_) => 0,
+            ShotEvent::ShootInfo { .. } => 1,
+            ShotEvent::Impact { .. } => 2,
+            ShotEvent::Hurt { .. } => 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AccuracyRecord {
+    pub steamid: u64,
+    pub name: Option<String>,
+    pub weapon: String,
+    pub shots: i32,
+    pub hits: i32,
+    pub accuracy: Option<f32>,
+    pub first_bullet_shots: i32,
+    pub first_bullet_hits: i32,
+    pub first_bullet_accuracy: Option<f32>,
+    pub spray_shots: i32,
+    pub spray_hits: i32,
+    pub spray_accuracy: Option<f32>,
+}
+
+// weapon_fire says "weapon_ak47" while player_hurt says "ak47"
+fn strip_weapon_prefix(weapon: &str) -> String {
+    weapon.strip_prefix("weapon_").unwrap_or(weapon).to_string()
+}
+
+impl<'a> SecondPassParser<'a> {
+    pub fn collect_shot_event(&mut self, event: &DecodedGameEvent) -> Result<(), DemoParserError> {
+        let find_entity_id = |name: &str| match event.key(name) {
+            Some(Variant::I32(userid)) => self.entity_id_from_userid(userid),
+            _ => None,
+        };
+        let find_f32 = |name: &str| match event.key(name) {
+            Some(Variant::F32(f)) => Some(f),
+            _ => None,
+        };
+        let shot_event = match event.name() {
+            "weapon_fire" => {
+                let weapon = match event.key("weapon") {
+                    Some(Variant::String(weapon)) => strip_weapon_prefix(&weapon),
+                    _ => return Ok(()),
+                };
+                match find_entity_id("userid") {
+                    Some(entity_id) => self.create_shot(entity_id, weapon),
+                    None => None,
+                }
+            }
+            "player_hurt" => {
+                let attacker_steamid = find_entity_id("attacker").and_then(|id| self.players.get(&id)).and_then(|p| p.steamid);
+                let victim = find_entity_id("userid").and_then(|id| self.players.get(&id));
+                let hitgroup = match event.key("hitgroup") {
+                    Some(Variant::I32(i)) => Some(HIT_GROUP.get(&i).map(|s| s.to_string()).unwrap_or(i.to_string())),
+                    _ => None,
+                };
+                let weapon = match event.key("weapon") {
+                    Some(Variant::String(weapon)) => strip_weapon_prefix(&weapon),
+                    _ => String::new(),
+                };
+                let damage = match event.key("dmg_health") {
+                    Some(Variant::I32(damage)) => damage,
+                    _ => 0,
+                };
+                attacker_steamid.map(|attacker_steamid| ShotEvent::Hurt {
+                    tick: self.tick,
+                    attacker_steamid,
+                    victim_steamid: victim.and_then(|p| p.steamid),
+                    victim_name: victim.and_then(|p| p.name.clone()),
+                    weapon,
+                    hitgroup,
+                    damage,
+                })
+            }
+            "bullet_impact" => {
+                let steamid = find_entity_id("userid").and_then(|id| self.players.get(&id)).and_then(|p| p.steamid);
+                match (steamid, find_f32("x"), find_f32("y"), find_f32("z")) {
+                    (Some(steamid), Some(x), Some(y), Some(z)) => Some(ShotEvent::Impact {
+                        tick: self.tick,
+                        steamid,
+                        position: [x, y, z],
+                    }),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        if let Some(shot_event) = shot_event {
+            self.shot_events.push(shot_event);
+        }
+        Ok(())
+    }
+    fn create_shot(&self, entity_id: i32, weapon: String) -> Option<ShotEvent> {
+        let player = self.players.get(&entity_id)?;
+        let steamid = player.steamid?;
+        let find_weapon_f32 = |prop_id: Option<u32>| match prop_id.map(|id| self.find_weapon_prop(&id, &entity_id)) {
+            Some(Ok(Variant::F32(f))) => Some(f),
+            _ => None,
+        };
+        let speed = self.find_horizontal_speed(steamid);
+        let ducked = self.prop_controller.special_ids.ducked.map(|id| self.get_prop_from_ent(&id, &entity_id));
+        let movement = if matches!(self.find_is_airborne(player), Ok(Variant::Bool(true))) {
+            "airborne"
+        } else if matches!(ducked, Some(Ok(Variant::Bool(true)))) {
+            "ducking"
+        } else if speed.unwrap_or(0.0) > MOVING_SPEED {
+            "moving"
+        } else {
+            "standing"
+        };
+        let usercmd_view = match (
+            self.get_prop_from_ent(&USERCMD_VIEWANGLE_X, &entity_id),
+            self.get_prop_from_ent(&USERCMD_VIEWANGLE_Y, &entity_id),
+        ) {
+            (Ok(Variant::F32(pitch)), Ok(Variant::F32(yaw))) => Some([pitch, yaw]),
+            _ => None,
+        };
+        let eye_view = match (self.find_pitch_or_yaw(&entity_id, 0), self.find_pitch_or_yaw(&entity_id, 1)) {
+            (Ok(Variant::F32(pitch)), Ok(Variant::F32(yaw))) => Some([pitch, yaw]),
+            _ => None,
+        };
+        Some(ShotEvent::Fire(ShotRecord {
+            tick: self.tick,
+            round: match self.find_current_round() {
+                Some(Variant::I32(round)) => Some(round),
+                _ => None,
+            },
+            steamid,
+            name: player.name.clone(),
+            weapon,
+            recoil_index: find_weapon_f32(self.prop_controller.special_ids.recoil_index),
+            inaccuracy: find_weapon_f32(self.prop_controller.special_ids.accuracy_penalty),
+            speed,
+            movement: movement.to_string(),
+            burst_index: 0,
+            hit: false,
+            victim_steamid: None,
+            victim_name: None,
+            hitgroup: None,
+            damage: 0,
+            players_hit: 0,
+            impact: None,
+            shoot_pitch: None,
+            shoot_yaw: None,
+            eyes: self.find_eye_position(&entity_id),
+            view: usercmd_view.or(eye_view),
+            view_from_usercmd: usercmd_view.is_some(),
+            aim_punch: match self.prop_controller.special_ids.aim_punch_angle.map(|id| self.get_prop_from_ent(&id, &entity_id)) {
+                Some(Ok(Variant::VecXYZ(v))) => Some([v[0], v[1]]),
+                _ => None,
+            },
+        }))
+    }
+    pub fn parse_shoot_info(&mut self, bytes: &[u8]) -> Result<(), DemoParserError> {
+        if !self.parse_shots {
+            return Ok(());
+        }
+        let msg: CCSUsrMsg_ShootInfo = match Message::parse_from_bytes(bytes) {
+            Ok(msg) => msg,
+            Err(_) => return Err(DemoParserError::MalformedMessage),
+        };
+        if let (Some(pos), Some(dir)) = (msg.shoot_pos.as_ref(), msg.shoot_dir.as_ref()) {
+            self.shot_events.push(ShotEvent::ShootInfo {
+                tick: self.tick,
+                position: [pos.x(), pos.y(), pos.z()],
+                pitch: dir.x(),
+                yaw: dir.y(),
+            });
+        }
+        Ok(())
+    }
+}
+
+pub fn create_shots(mut events: Vec<ShotEvent>) -> Vec<ShotRecord> {
+    events.sort_by_key(|e| (e.tick(), e.order()));
+    let mut shots: Vec<ShotRecord> = vec![];
+    // Index of the latest shot of every player
+    let mut last_shot: AHashMap<u64, usize> = AHashMap::default();
+    for event in events {
+        match event {
+            ShotEvent::Fire(mut shot) => {
+                if let Some(prev) = last_shot.get(&shot.steamid).map(|idx| &shots[*idx]) {
+                    if prev.weapon == shot.weapon && shot.tick - prev.tick <= SPRAY_RESET_TICKS {
+                        shot.burst_index = prev.burst_index + 1;
+                    }
+                }
+                last_shot.insert(shot.steamid, shots.len());
+                shots.push(shot);
+            }
+            ShotEvent::ShootInfo { tick, position, pitch, yaw } => {
+                let closest = shots
+                    .iter_mut()
+                    .rev()
+                    .take_while(|s| s.tick == tick)
+                    .filter(|s| s.shoot_pitch.is_none())
+                    .filter_map(|s| s.eyes.map(|eyes| (distance(eyes, position), s)))
+                    .filter(|(d, _)| *d <= MAX_SHOOT_POS_DISTANCE)
+                    .min_by(|a, b| a.0.total_cmp(&b.0));
+                if let Some((_, shot)) = closest {
+                    shot.shoot_pitch = Some(pitch);
+                    shot.shoot_yaw = Some(yaw);
+                }
+            }
+            ShotEvent::Impact { tick, steamid, position } => {
+                if let Some(shot) = last_shot.get(&steamid).map(|idx| &mut shots[*idx]) {
+                    if tick - shot.tick <= SHOT_HIT_WINDOW_TICKS && shot.impact.is_none() {
+                        shot.impact = Some(position);
+                    }
+                }
+            }
+            ShotEvent::Hurt {
+                tick,
+                attacker_steamid,
+                victim_steamid,
+                victim_name,
+                weapon,
+                hitgroup,
+                damage,
+            } => {
+                // Grenades, fire and the bomb also cause player_hurt
+                if let Some(shot) = last_shot.get(&attacker_steamid).map(|idx| &mut shots[*idx]) {
+                    if tick - shot.tick <= SHOT_HIT_WINDOW_TICKS && shot.weapon == weapon {
+                        if !shot.hit {
+                            shot.hit = true;
+                            shot.victim_steamid = victim_steamid;
+                            shot.victim_name = victim_name;
+                            shot.hitgroup = hitgroup;
+                        }
+                        shot.damage += damage;
+                        shot.players_hit += 1;
+                    }
+                }
+            }
+        }
+    }
+    shots
+}
+
+fn distance(a: [f32; 3], b: [f32; 3]) -> f32 {
+    ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)).sqrt()
+}
+
+pub fn create_accuracy(shots: &[ShotRecord]) -> Vec<AccuracyRecord> {
+    let mut records: Vec<AccuracyRecord> = vec![];
+    let mut record_idx: AHashMap<(u64, &str), usize> = AHashMap::default();
+    for shot in shots {
+        let idx = *record_idx.entry((shot.steamid, &shot.weapon)).or_insert_with(|| {
+            records.push(AccuracyRecord {
+                steamid: shot.steamid,
+                name: shot.name.clone(),
+                weapon: shot.weapon.clone(),
+                shots: 0,
+                hits: 0,
+                accuracy: None,
+                first_bullet_shots: 0,
+                first_bullet_hits: 0,
+                first_bullet_accuracy: None,
+                spray_shots: 0,
+                spray_hits: 0,
+                spray_accuracy: None,
+            });
+            records.len() - 1
+        });
+        let record = &mut records[idx];
+        let hit = shot.hit as i32;
+        record.shots += 1;
+        record.hits += hit;
+        if shot.burst_index == 0 {
+            record.first_bullet_shots += 1;
+            record.first_bullet_hits += hit;
+        } else {
+            record.spray_shots += 1;
+            record.spray_hits += hit;
+        }
+    }
+    let ratio = |hits: i32, shots: i32| if shots > 0 { Some(hits as f32 / shots as f32) } else { None };
+    for record in records.iter_mut() {
+        record.accuracy = ratio(record.hits, record.shots);
+        record.first_bullet_accuracy = ratio(record.first_bullet_hits, record.first_bullet_shots);
+        record.spray_accuracy = ratio(record.spray_hits, record.spray_shots);
+    }
+    records.sort_by(|a, b| (a.steamid, &a.weapon).cmp(&(b.steamid, &b.weapon)));
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fire(tick: i32, steamid: u64, weapon: &str) -> ShotEvent {
+        ShotEvent::Fire(ShotRecord {
+            tick,
+            round: Some(1),
+            steamid,
+            name: None,
+            weapon: weapon.to_string(),
+            recoil_index: None,
+            inaccuracy: None,
+            speed: None,
+            movement: "standing".to_string(),
+            burst_index: 0,
+            hit: false,
+            victim_steamid: None,
+            victim_name: None,
+            hitgroup: None,
+            damage: 0,
+            players_hit: 0,
+            impact: None,
+            shoot_pitch: None,
+            shoot_yaw: None,
+            eyes: Some([0.0, 0.0, 64.0]),
+            view: None,
+            view_from_usercmd: false,
+            aim_punch: None,
+        })
+    }
+    fn hurt(tick: i32, attacker: u64, victim: u64, weapon: &str, damage: i32) -> ShotEvent {
+        ShotEvent::Hurt {
+            tick,
+            attacker_steamid: attacker,
+            victim_steamid: Some(victim),
+            victim_name: None,
+            weapon: weapon.to_string(),
+            hitgroup: Some("head".to_string()),
+            damage,
+        }
+    }
+
+    #[test]
+    fn test_shots_and_accuracy() {
+        let events = vec![
+            // Hurt parsed before the weapon_fire of the same tick
+            hurt(100, 1, 2, "ak47", 100),
+            fire(100, 1, "ak47"),
+            fire(107, 1, "ak47"),
+            fire(114, 1, "ak47"),
+            hurt(115, 1, 3, "ak47", 27),
+            // Grenade damage right after a shot is not a hit
+            hurt(115, 1, 3, "hegrenade", 40),
+            fire(200, 1, "ak47"),
+            // Shotgun pellets hitting two players
+            fire(300, 4, "nova"),
+            hurt(300, 4, 5, "nova", 20),
+            hurt(300, 4, 6, "nova", 15),
+        ];
+        let shots = create_shots(events);
+        let summary: Vec<(i32, i32, bool, i32)> = shots.iter().map(|s| (s.tick, s.burst_index, s.hit, s.damage)).collect();
+        assert_eq!(
+            summary,
+            vec![
+                (100, 0, true, 100),
+                (107, 1, false, 0),
+                (114, 2, true, 27),
+                (200, 0, false, 0),
+                (300, 0, true, 35)
+            ]
+        );
+        assert_eq!(shots[4].victim_steamid, Some(5));
+        assert_eq!(shots[4].players_hit, 2);
+
+        let accuracy = create_accuracy(&shots);
+        let ak = accuracy.iter().find(|a| a.weapon == "ak47").unwrap();
+        assert_eq!((ak.shots, ak.hits, ak.first_bullet_shots, ak.first_bullet_hits), (4, 2, 2, 1));
+        assert_eq!(ak.first_bullet_accuracy, Some(0.5));
+        assert_eq!(ak.spray_accuracy, Some(0.5));
+    }
+}
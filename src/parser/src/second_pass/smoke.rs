@@ -0,0 +1,152 @@
+use crate::second_pass::collect_data::CoordinateAxis;
+use crate::second_pass::collect_data::PropCollectionError;
+use crate::second_pass::entities::PlayerMetaData;
+use crate::second_pass::game_events::EventField;
+use crate::second_pass::parser_settings::SecondPassParser;
+use crate::second_pass::variants::Variant;
+
+// Smokes are approximated with an ellipsoid sitting on top of where the grenade came to rest.
+pub const SMOKE_RADIUS_XY: f32 = 144.0;
+pub const SMOKE_RADIUS_Z: f32 = 110.0;
+pub const SMOKE_CENTER_Z_OFFSET: f32 = 60.0;
+// Standing eye height, used when the view offset is not networked
+pub const EYE_HEIGHT: f32 = 64.0;
+
+pub fn is_inside_ellipsoid(point: [f32; 3], center: [f32; 3], radii: [f32; 3]) -> bool {
+    let mut sum = 0.0;
+    for i in 0..3 {
+        sum += ((point[i] - center[i]) / radii[i]).powi(2);
+    }
+    sum <= 1.0
+}
+
+pub fn segment_intersects_ellipsoid(start: [f32; 3], end: [f32; 3], center: [f32; 3], radii: [f32; 3]) -> bool {
+    // Scale space so that the ellipsoid becomes a unit sphere and solve |p + t*d|^2 = 1 for t in [0, 1]
+    let mut p = [0.0; 3];
+    let mut d = [0.0; 3];
+    for i in 0..3 {
+        p[i] = (start[i] - center[i]) / radii[i];
+        d[i] = (end[i] - start[i]) / radii[i];
+    }
+    let a = d[0] * d[0] + d[1] * d[1] + d[2] * d[2];
+    let b = 2.0 * (p[0] * d[0] + p[1] * d[1] + p[2] * d[2]);
+    let c = p[0] * p[0] + p[1] * p[1] + p[2] * p[2] - 1.0;
+    if c <= 0.0 {
+        return true;
+    }
+    if a == 0.0 {
+        return false;
+    }
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return false;
+    }
+    let sqrt_disc = discriminant.sqrt();
+    let t1 = (-b - sqrt_disc) / (2.0 * a);
+    let t2 = (-b + sqrt_disc) / (2.0 * a);
+    (0.0..=1.0).contains(&t1) || (0.0..=1.0).contains(&t2)
+}
+
+pub fn smoke_ellipsoid_center(rest_position: [f32; 3]) -> [f32; 3] {
+    [rest_position[0], rest_position[1], rest_position[2] + SMOKE_CENTER_Z_OFFSET]
+}
+
+pub fn is_line_through_smoke(start: [f32; 3], end: [f32; 3], smokes: &[[f32; 3]]) -> bool {
+    let radii = [SMOKE_RADIUS_XY, SMOKE_RADIUS_XY, SMOKE_RADIUS_Z];
+    smokes.iter().any(|center| segment_intersects_ellipsoid(start, end, *center, radii))
+}
+
+impl<'a> SecondPassParser<'a> {
+    pub fn find_active_smokes(&self) -> Vec<[f32; 3]> {
+        // Smoke projectiles stay around until the smoke fades so no need to track the events.
+        let did_smoke_id = match self.prop_controller.special_ids.did_smoke_effect {
+            Some(id) => id,
+            None => return vec![],
+        };
+        let mut smokes = vec![];
+        for entity_id in &self.projectiles {
+            if let Ok(Variant::Bool(true)) = self.get_prop_from_ent(&did_smoke_id, entity_id) {
+                let x = self.collect_cell_coordinate_grenade(CoordinateAxis::X, entity_id);
+                let y = self.collect_cell_coordinate_grenade(CoordinateAxis::Y, entity_id);
+                let z = self.collect_cell_coordinate_grenade(CoordinateAxis::Z, entity_id);
+                if let (Ok(Variant::F32(x)), Ok(Variant::F32(y)), Ok(Variant::F32(z))) = (x, y, z) {
+                    smokes.push(smoke_ellipsoid_center([x, y, z]));
+                }
+            }
+        }
+        smokes
+    }
+    pub fn find_eye_position(&self, entity_id: &i32) -> Option<[f32; 3]> {
+        let x = self.collect_cell_coordinate_player(CoordinateAxis::X, entity_id);
+        let y = self.collect_cell_coordinate_player(CoordinateAxis::Y, entity_id);
+        let z = self.collect_cell_coordinate_player(CoordinateAxis::Z, entity_id);
+        let view_offset = match self.prop_controller.special_ids.view_offset_z.map(|id| self.get_prop_from_ent(&id, entity_id)) {
+            Some(Ok(Variant::F32(offset))) => offset,
+            _ => EYE_HEIGHT,
+        };
+        match (x, y, z) {
+            (Ok(Variant::F32(x)), Ok(Variant::F32(y)), Ok(Variant::F32(z))) => Some([x, y, z + view_offset]),
+            _ => None,
+        }
+    }
+    pub fn is_line_of_sight_smoked(&self, from_entity_id: &i32, to_entity_id: &i32) -> Option<bool> {
+        let start = self.find_eye_position(from_entity_id)?;
+        let end = self.find_eye_position(to_entity_id)?;
+        Some(is_line_through_smoke(start, end, &self.find_active_smokes()))
+    }
+    pub fn find_in_smoke(&self, player: &PlayerMetaData) -> Result<Variant, PropCollectionError> {
+        let entity_id = match player.player_entity_id {
+            Some(entity_id) => entity_id,
+            None => return Err(PropCollectionError::PlayerNotFound),
+        };
+        let eyes = match self.find_eye_position(&entity_id) {
+            Some(eyes) => eyes,
+            None => return Err(PropCollectionError::PlayerNotFound),
+        };
+        let radii = [SMOKE_RADIUS_XY, SMOKE_RADIUS_XY, SMOKE_RADIUS_Z];
+        let in_smoke = self.find_active_smokes().iter().any(|center| is_inside_ellipsoid(eyes, *center, radii));
+        Ok(Variant::Bool(in_smoke))
+    }
+    pub fn find_through_smoke_computed(&self, fields: &[EventField]) -> EventField {
+        let find_entity_id = |name: &str| match fields.iter().find(|f| f.name == name) {
+            Some(EventField {
+                data: Some(Variant::I32(userid)),
+                ..
+            }) => self.entity_id_from_userid(*userid),
+            _ => None,
+        };
+        let data = match (find_entity_id("attacker"), find_entity_id("userid")) {
+            (Some(attacker), Some(victim)) => self.is_line_of_sight_smoked(&attacker, &victim).map(Variant::Bool),
+            _ => None,
+        };
+        EventField {
+            name: "through_smoke_computed".to_string(),
+            data,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segment_through_smoke() {
+        let smokes = vec![smoke_ellipsoid_center([0.0, 0.0, 0.0])];
+        // Straight through the middle
+        assert!(is_line_through_smoke([-500.0, 0.0, 64.0], [500.0, 0.0, 64.0], &smokes));
+        // Passes next to the smoke
+        assert!(!is_line_through_smoke([-500.0, 200.0, 64.0], [500.0, 200.0, 64.0], &smokes));
+        // Stops before reaching the smoke
+        assert!(!is_line_through_smoke([-500.0, 0.0, 64.0], [-300.0, 0.0, 64.0], &smokes));
+        // Goes over the top
+        assert!(!is_line_through_smoke([-500.0, 0.0, 300.0], [500.0, 0.0, 300.0], &smokes));
+    }
+    #[test]
+    fn test_segment_starting_inside_smoke() {
+        let center = smoke_ellipsoid_center([0.0, 0.0, 0.0]);
+        let radii = [SMOKE_RADIUS_XY, SMOKE_RADIUS_XY, SMOKE_RADIUS_Z];
+        assert!(is_inside_ellipsoid([10.0, 10.0, 64.0], center, radii));
+        assert!(segment_intersects_ellipsoid([10.0, 10.0, 64.0], [1000.0, 1000.0, 64.0], center, radii));
+    }
+}
@@ -0,0 +1,257 @@
+use crate::first_pass::prop_controller::TICKRATE;
+use crate::second_pass::parser_settings::SecondPassParser;
+use crate::second_pass::variants::Variant;
+use ahash::AHashMap;
+use ahash::AHashSet;
+
+#[derive(Debug, Clone)]
+pub struct SpottingEdge {
+    pub tick: i32,
+    pub round: Option<i32>,
+    pub spotter_steamid: u64,
+    pub spotter_name: Option<String>,
+    pub spotted_steamid: u64,
+    pub spotted_name: Option<String>,
+    // false means the spotter lost sight of the spotted player
+    pub started: bool,
+    // Line between the eyes crossed a smoke when the spot started, None for ended edges
+    pub through_smoke: Option<bool>,
+    // Edge only exists because a new chunk starts here, removed when combining chunks
+    pub is_snapshot: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct SpottingRoundRecord {
+    pub round: Option<i32>,
+    pub spotter_steamid: u64,
+    pub spotter_name: Option<String>,
+    pub spotted_steamid: u64,
+    pub spotted_name: Option<String>,
+    pub ticks_spotted: i32,
+    pub time_spotted: f32,
+    pub first_spot_tick: i32,
+    // Spotter saw the spotted player before being seen by them (or was never seen by them)
+    pub spotted_first: bool,
+}
+
+impl<'a> SecondPassParser<'a> {
+    // (spotter steamid, spotted steamid, spotted entity id) for every player currently spotted by someone
+    pub fn find_spotted_pairs(&self) -> Vec<(u64, u64, i32)> {
+        let prop_id = match self.prop_controller.special_ids.spotted_by_mask {
+            Some(prop_id) => prop_id,
+            None => return vec![],
+        };
+        let mut pairs = vec![];
+        for (entity_id, player) in &self.players {
+            if let (Some(spotted_steamid), Ok(Variant::U32(mask))) = (player.steamid, self.get_prop_from_ent(&prop_id, entity_id)) {
+                for spotter_steamid in self.steamids_from_mask(mask) {
+                    pairs.push((spotter_steamid, spotted_steamid, *entity_id));
+                }
+            }
+        }
+        pairs
+    }
+    pub fn collect_spotting(&mut self) {
+        if self.prop_controller.special_ids.spotted_by_mask.is_none() {
+            return;
+        }
+        let mut current: AHashMap<(u64, u64), (Option<String>, Option<String>)> = AHashMap::default();
+        let mut entity_ids: AHashMap<(u64, u64), (Option<i32>, i32)> = AHashMap::default();
+        for (spotter_steamid, spotted_steamid, spotted_id) in self.find_spotted_pairs() {
+            let spotter = self.players.iter().find(|(_, p)| p.steamid == Some(spotter_steamid));
+            let spotter_name = spotter.and_then(|(_, p)| p.name.clone());
+            let spotted_name = self.players.get(&spotted_id).and_then(|p| p.name.clone());
+            current.insert((spotter_steamid, spotted_steamid), (spotter_name, spotted_name));
+            entity_ids.insert((spotter_steamid, spotted_steamid), (spotter.map(|(id, _)| *id), spotted_id));
+        }
+        let round = match self.find_current_round() {
+            Some(Variant::I32(round)) => Some(round),
+            _ => None,
+        };
+        let is_snapshot = self.spotting_start_tick.is_none();
+        if is_snapshot {
+            self.spotting_start_tick = Some(self.tick);
+        }
+        let mut edges = vec![];
+        for ((spotter, spotted), (spotter_name, spotted_name)) in &current {
+            if !self.spotted_pairs.contains_key(&(*spotter, *spotted)) {
+                let through_smoke = match entity_ids.get(&(*spotter, *spotted)) {
+                    Some((Some(spotter_id), spotted_id)) => self.is_line_of_sight_smoked(spotter_id, spotted_id),
+                    _ => None,
+                };
+                edges.push(SpottingEdge {
+                    tick: self.tick,
+                    round,
+                    spotter_steamid: *spotter,
+                    spotter_name: spotter_name.clone(),
+                    spotted_steamid: *spotted,
+                    spotted_name: spotted_name.clone(),
+                    started: true,
+                    through_smoke,
+                    is_snapshot,
+                });
+            }
+        }
+        for ((spotter, spotted), (spotter_name, spotted_name)) in &self.spotted_pairs {
+            if !current.contains_key(&(*spotter, *spotted)) {
+                edges.push(SpottingEdge {
+                    tick: self.tick,
+                    round,
+                    spotter_steamid: *spotter,
+                    spotter_name: spotter_name.clone(),
+                    spotted_steamid: *spotted,
+                    spotted_name: spotted_name.clone(),
+                    started: false,
+                    through_smoke: None,
+                    is_snapshot: false,
+                });
+            }
+        }
+        edges.sort_by_key(|e| (!e.started, e.spotter_steamid, e.spotted_steamid));
+        self.spotting_edges.extend(edges);
+        self.spotted_pairs = current;
+    }
+}
+
+// Chunks each start with a snapshot of who was spotted at their first tick, turn those into real transitions
+pub fn merge_spotting_edges(chunks: Vec<(Option<i32>, Vec<SpottingEdge>)>) -> Vec<SpottingEdge> {
+    let mut active: AHashMap<(u64, u64), SpottingEdge> = AHashMap::default();
+    let mut merged = vec![];
+    for (start_tick, edges) in chunks {
+        if let Some(start_tick) = start_tick {
+            let snapshot: AHashSet<(u64, u64)> = edges.iter().filter(|e| e.is_snapshot).map(|e| (e.spotter_steamid, e.spotted_steamid)).collect();
+            let mut ended: Vec<SpottingEdge> = active.iter().filter(|(k, _)| !snapshot.contains(k)).map(|(_, e)| e.clone()).collect();
+            ended.sort_by_key(|e| (e.spotter_steamid, e.spotted_steamid));
+            for mut edge in ended {
+                active.remove(&(edge.spotter_steamid, edge.spotted_steamid));
+                edge.tick = start_tick;
+                edge.started = false;
+                edge.through_smoke = None;
+                merged.push(edge);
+            }
+        }
+        for mut edge in edges {
+            let key = (edge.spotter_steamid, edge.spotted_steamid);
+            if edge.is_snapshot && active.contains_key(&key) {
+                continue;
+            }
+            edge.is_snapshot = false;
+            match edge.started {
+                true => {
+                    active.insert(key, edge.clone());
+                }
+                false => {
+                    active.remove(&key);
+                }
+            }
+            merged.push(edge);
+        }
+    }
+    merged
+}
+
+pub fn create_spotting_rounds(edges: &[SpottingEdge], last_tick: i32, tickrate: f32) -> Vec<SpottingRoundRecord> {
+    // Round of an interval is the round it started in
+    let mut open: AHashMap<(u64, u64), &SpottingEdge> = AHashMap::default();
+    let mut records: Vec<SpottingRoundRecord> = vec![];
+    let mut record_idx: AHashMap<(Option<i32>, u64, u64), usize> = AHashMap::default();
+    let mut add_interval = |start: &SpottingEdge, end_tick: i32, records: &mut Vec<SpottingRoundRecord>| {
+        let key = (start.round, start.spotter_steamid, start.spotted_steamid);
+        let idx = *record_idx.entry(key).or_insert_with(|| {
+            records.push(SpottingRoundRecord {
+                round: start.round,
+                spotter_steamid: start.spotter_steamid,
+                spotter_name: start.spotter_name.clone(),
+                spotted_steamid: start.spotted_steamid,
+                spotted_name: start.spotted_name.clone(),
+                ticks_spotted: 0,
+                time_spotted: 0.0,
+                first_spot_tick: start.tick,
+                spotted_first: false,
+            });
+            records.len() - 1
+        });
+        records[idx].ticks_spotted += end_tick - start.tick;
+        records[idx].first_spot_tick = records[idx].first_spot_tick.min(start.tick);
+    };
+    for edge in edges {
+        let key = (edge.spotter_steamid, edge.spotted_steamid);
+        match edge.started {
+            true => {
+                open.insert(key, edge);
+            }
+            false => {
+                if let Some(start) = open.remove(&key) {
+                    add_interval(start, edge.tick, &mut records);
+                }
+            }
+        }
+    }
+    let mut still_open: Vec<&SpottingEdge> = open.into_values().collect();
+    still_open.sort_by_key(|e| (e.tick, e.spotter_steamid, e.spotted_steamid));
+    for start in still_open {
+        add_interval(start, last_tick.max(start.tick), &mut records);
+    }
+    // Who saw who first in every duel
+    let first_spots: AHashMap<(Option<i32>, u64, u64), i32> = records
+        .iter()
+        .map(|r| ((r.round, r.spotter_steamid, r.spotted_steamid), r.first_spot_tick))
+        .collect();
+    for record in records.iter_mut() {
+        record.time_spotted = record.ticks_spotted as f32 / tickrate;
+        record.spotted_first = match first_spots.get(&(record.round, record.spotted_steamid, record.spotter_steamid)) {
+            Some(other) => record.first_spot_tick < *other,
+            None => true,
+        };
+    }
+    records.sort_by_key(|r| (r.round, r.first_spot_tick, r.spotter_steamid, r.spotted_steamid));
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edge(tick: i32, spotter: u64, spotted: u64, started: bool, is_snapshot: bool) -> SpottingEdge {
+        SpottingEdge {
+            tick,
+            round: Some(1),
+            spotter_steamid: spotter,
+            spotter_name: None,
+            spotted_steamid: spotted,
+            spotted_name: None,
+            started,
+            through_smoke: None,
+            is_snapshot,
+        }
+    }
+
+    #[test]
+    fn test_merge_spotting_chunks() {
+        // 1 sees 2 across the chunk boundary, 3 stops seeing 4 right at the boundary
+        let chunk1 = vec![edge(10, 1, 2, true, true), edge(20, 3, 4, true, false)];
+        let chunk2 = vec![edge(100, 1, 2, true, true), edge(150, 1, 2, false, false)];
+        let merged = merge_spotting_edges(vec![(Some(10), chunk1), (Some(100), chunk2)]);
+        let summary: Vec<(i32, u64, u64, bool)> = merged.iter().map(|e| (e.tick, e.spotter_steamid, e.spotted_steamid, e.started)).collect();
+        assert_eq!(summary, vec![(10, 1, 2, true), (20, 3, 4, true), (100, 3, 4, false), (150, 1, 2, false)]);
+    }
+    #[test]
+    fn test_spotting_rounds() {
+        let edges = vec![
+            edge(10, 1, 2, true, false),
+            edge(30, 2, 1, true, false),
+            edge(74, 1, 2, false, false),
+            edge(100, 1, 2, true, false),
+        ];
+        let rounds = create_spotting_rounds(&edges, 132, TICKRATE);
+        assert_eq!(rounds.len(), 2);
+        let one_on_two = rounds.iter().find(|r| r.spotter_steamid == 1).unwrap();
+        assert_eq!(one_on_two.ticks_spotted, 64 + 32);
+        assert_eq!(one_on_two.time_spotted, 1.5);
+        assert_eq!(one_on_two.first_spot_tick, 10);
+        assert!(one_on_two.spotted_first);
+        let two_on_one = rounds.iter().find(|r| r.spotter_steamid == 2).unwrap();
+        assert_eq!(two_on_one.ticks_spotted, 102);
+        assert!(!two_on_one.spotted_first);
+    }
+}
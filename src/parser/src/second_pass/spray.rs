@@ -0,0 +1,206 @@
+use crate::second_pass::shots::ShotRecord;
+use ahash::AHashMap;
+
+// weapon_recoil_scale, the bullet goes where view angles + aim punch * scale point
+const RECOIL_SCALE: f32 = 2.0;
+// Shorter bursts say little about recoil control
+const MIN_SPRAY_BULLETS: usize = 3;
+const RIFLES: [&str; 7] = ["ak47", "m4a1", "m4a1_silencer", "galilar", "famas", "aug", "sg556"];
+
+#[derive(Debug, Clone)]
+pub struct SprayRecord {
+    pub spray_id: i32,
+    pub steamid: u64,
+    pub name: Option<String>,
+    pub weapon: String,
+    pub round: Option<i32>,
+    pub start_tick: i32,
+    pub end_tick: i32,
+    pub bullets: i32,
+    pub hits: i32,
+    // usercmd or eye_angles
+    pub angle_source: String,
+    // Degrees between where the bullets went and where the first bullet went
+    pub mean_error: Option<f32>,
+    pub max_error: Option<f32>,
+}
+
+// Angles are relative to the first bullet of the spray so sprays can be compared with each other
+#[derive(Debug, Clone)]
+pub struct SprayBulletRecord {
+    pub spray_id: i32,
+    pub steamid: u64,
+    pub tick: i32,
+    pub bullet: i32,
+    pub recoil_index: Option<f32>,
+    pub view_pitch: Option<f32>,
+    pub view_yaw: Option<f32>,
+    pub aim_punch_pitch: Option<f32>,
+    pub aim_punch_yaw: Option<f32>,
+    // View angle change that would have cancelled the recoil exactly
+    pub ideal_pitch: Option<f32>,
+    pub ideal_yaw: Option<f32>,
+    pub error: Option<f32>,
+    pub hit: bool,
+}
+
+fn normalize_yaw(yaw: f32) -> f32 {
+    (yaw + 180.0).rem_euclid(360.0) - 180.0
+}
+
+fn relative(angles: Option<[f32; 2]>, first: Option<[f32; 2]>) -> Option<[f32; 2]> {
+    match (angles, first) {
+        (Some(a), Some(f)) => Some([a[0] - f[0], normalize_yaw(a[1] - f[1])]),
+        _ => None,
+    }
+}
+
+fn continues_spray(prev: &ShotRecord, shot: &ShotRecord) -> bool {
+    let recoil_increasing = match (prev.recoil_index, shot.recoil_index) {
+        (Some(prev), Some(current)) => current > prev,
+        _ => true,
+    };
+    shot.burst_index > 0 && shot.weapon == prev.weapon && recoil_increasing
+}
+
+fn create_spray(spray_id: i32, spray: &[&ShotRecord]) -> (SprayRecord, Vec<SprayBulletRecord>) {
+    let first = spray[0];
+    let bullets: Vec<SprayBulletRecord> = spray
+        .iter()
+        .enumerate()
+        .map(|(idx, shot)| {
+            let view = relative(shot.view, first.view);
+            let punch = relative(shot.aim_punch, first.aim_punch);
+            let ideal = punch.map(|p| [-p[0] * RECOIL_SCALE, -p[1] * RECOIL_SCALE]);
+            let error = match (view, ideal) {
+                (Some(v), Some(i)) => Some(((v[0] - i[0]).powi(2) + (v[1] - i[1]).powi(2)).sqrt()),
+                _ => None,
+            };
+            SprayBulletRecord {
+                spray_id,
+                steamid: shot.steamid,
+                tick: shot.tick,
+                bullet: idx as i32,
+                recoil_index: shot.recoil_index,
+                view_pitch: view.map(|v| v[0]),
+                view_yaw: view.map(|v| v[1]),
+                aim_punch_pitch: punch.map(|p| p[0]),
+                aim_punch_yaw: punch.map(|p| p[1]),
+                ideal_pitch: ideal.map(|i| i[0]),
+                ideal_yaw: ideal.map(|i| i[1]),
+                error,
+                hit: shot.hit,
+            }
+        })
+        .collect();
+    // The first bullet is the reference so its error is always 0
+    let errors: Vec<f32> = bullets.iter().skip(1).filter_map(|b| b.error).collect();
+    let record = SprayRecord {
+        spray_id,
+        steamid: first.steamid,
+        name: first.name.clone(),
+        weapon: first.weapon.clone(),
+        round: first.round,
+        start_tick: first.tick,
+        end_tick: spray[spray.len() - 1].tick,
+        bullets: spray.len() as i32,
+        hits: spray.iter().filter(|s| s.hit).count() as i32,
+        angle_source: if first.view_from_usercmd { "usercmd" } else { "eye_angles" }.to_string(),
+        mean_error: if errors.is_empty() {
+            None
+        } else {
+            Some(errors.iter().sum::<f32>() / errors.len() as f32)
+        },
+        max_error: errors.iter().copied().reduce(f32::max),
+    };
+    (record, bullets)
+}
+
+// Expects the shots in tick order like create_shots returns them
+pub fn create_sprays(shots: &[ShotRecord]) -> (Vec<SprayRecord>, Vec<SprayBulletRecord>) {
+    let mut sprays_per_player: AHashMap<u64, Vec<Vec<&ShotRecord>>> = AHashMap::default();
+    for shot in shots.iter().filter(|s| RIFLES.contains(&s.weapon.as_str())) {
+        let sprays = sprays_per_player.entry(shot.steamid).or_default();
+        match sprays.last_mut() {
+            Some(spray) if continues_spray(spray[spray.len() - 1], shot) => spray.push(shot),
+            _ => sprays.push(vec![shot]),
+        }
+    }
+    let mut sprays: Vec<Vec<&ShotRecord>> = sprays_per_player
+        .into_values()
+        .flatten()
+        .filter(|spray| spray.len() >= MIN_SPRAY_BULLETS)
+        .collect();
+    sprays.sort_by_key(|spray| (spray[0].tick, spray[0].steamid));
+
+    let mut spray_records = vec![];
+    let mut bullet_records = vec![];
+    for (spray_id, spray) in sprays.iter().enumerate() {
+        let (record, bullets) = create_spray(spray_id as i32, spray);
+        spray_records.push(record);
+        bullet_records.extend(bullets);
+    }
+    (spray_records, bullet_records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shot(tick: i32, weapon: &str, burst_index: i32, view: [f32; 2], aim_punch: [f32; 2]) -> ShotRecord {
+        ShotRecord {
+            tick,
+            round: Some(1),
+            steamid: 1,
+            name: None,
+            weapon: weapon.to_string(),
+            recoil_index: Some(burst_index as f32),
+            inaccuracy: None,
+            speed: None,
+            movement: "standing".to_string(),
+            burst_index,
+            hit: false,
+            victim_steamid: None,
+            victim_name: None,
+            hitgroup: None,
+            damage: 0,
+            players_hit: 0,
+            impact: None,
+            shoot_pitch: None,
+            shoot_yaw: None,
+            eyes: None,
+            view: Some(view),
+            view_from_usercmd: false,
+            aim_punch: Some(aim_punch),
+        }
+    }
+
+    #[test]
+    fn test_sprays() {
+        let shots = vec![
+            // Perfectly pulled down, yaw wraps around 180
+            shot(100, "ak47", 0, [0.0, 179.0], [0.0, 0.0]),
+            shot(107, "ak47", 1, [1.0, -179.5], [-0.5, -0.75]),
+            shot(114, "ak47", 2, [2.0, -179.0], [-1.0, -1.0]),
+            // Not compensated at all
+            shot(200, "ak47", 0, [0.0, 0.0], [0.0, 0.0]),
+            shot(207, "ak47", 1, [0.0, 0.0], [-1.5, 0.0]),
+            shot(214, "ak47", 2, [0.0, 0.0], [-2.0, 0.0]),
+            // Too short and not a rifle
+            shot(300, "ak47", 0, [0.0, 0.0], [0.0, 0.0]),
+            shot(307, "ak47", 1, [0.0, 0.0], [0.0, 0.0]),
+            shot(400, "mac10", 0, [0.0, 0.0], [0.0, 0.0]),
+            shot(407, "mac10", 1, [0.0, 0.0], [0.0, 0.0]),
+            shot(414, "mac10", 2, [0.0, 0.0], [0.0, 0.0]),
+        ];
+        let (sprays, bullets) = create_sprays(&shots);
+        assert_eq!(sprays.len(), 2);
+        assert_eq!(bullets.len(), 6);
+        assert_eq!((sprays[0].start_tick, sprays[0].end_tick, sprays[0].bullets), (100, 114, 3));
+        assert_eq!(sprays[0].mean_error, Some(0.0));
+        assert_eq!(bullets[2].view_yaw, Some(2.0));
+        assert_eq!(bullets[2].ideal_yaw, Some(2.0));
+        assert_eq!(sprays[1].max_error, Some(4.0));
+        assert_eq!(sprays[1].mean_error, Some(3.5));
+    }
+}
@@ -0,0 +1,282 @@
+use crate::first_pass::prop_controller::*;
+use crate::first_pass::read_bits::DemoParserError;
+use crate::second_pass::parser_settings::SecondPassParser;
+use crate::second_pass::variants::InputHistory;
+use crate::second_pass::variants::InterpolationInfo;
+use crate::second_pass::variants::SubtickMove;
+use crate::second_pass::variants::Variant;
+use csgoproto::cs_usercmd::CSGOInterpolationInfoPB;
+use csgoproto::cs_usercmd::CSGOUserCmdPB;
+use csgoproto::demo::CDemoUserCmd;
+use csgoproto::netmessages::CSVCMsg_UserCommands;
+use csgoproto::networkbasetypes::{CMsgQAngle, CMsgVector};
+use protobuf::{Message, MessageField};
+
+// Handle index of an unset entity handle (0xFFFFFFFF)
+const INVALID_ENTITY_ID: i32 = 0x7FF;
+
+// Every command sent by a player, ticks can contain many or none of these
+#[derive(Debug, Clone)]
+pub struct UserCmdRecord {
+    pub tick: i32,
+    pub steamid: Option<u64>,
+    pub name: Option<String>,
+    pub entity_id: i32,
+    pub legacy_command_number: i32,
+    pub client_tick: i32,
+    pub buttonstate_1: u64,
+    pub buttonstate_2: u64,
+    pub buttonstate_3: u64,
+    pub viewangle_x: f32,
+    pub viewangle_y: f32,
+    pub viewangle_z: f32,
+    pub forward_move: f32,
+    pub left_move: f32,
+    pub up_move: f32,
+    pub impulse: i32,
+    pub weapon_select: i32,
+    pub random_seed: i32,
+    pub mouse_dx: i32,
+    pub mouse_dy: i32,
+    pub consumed_server_angle_changes: u32,
+    pub cmd_flags: i32,
+    // Hex encoded
+    pub move_crc: String,
+    pub subtick_moves: Vec<SubtickMove>,
+    pub input_history: Vec<InputHistory>,
+    pub attack_start_history_index_1: i32,
+    pub attack_start_history_index_2: i32,
+    pub attack_start_history_index_3: i32,
+    pub left_hand_desired: bool,
+}
+
+fn vector(v: &MessageField<CMsgVector>) -> Option<[f32; 3]> {
+    v.as_ref().map(|v| [v.x(), v.y(), v.z()])
+}
+fn qangle(v: &MessageField<CMsgQAngle>) -> Option<[f32; 3]> {
+    v.as_ref().map(|v| [v.x(), v.y(), v.z()])
+}
+fn interpolation(v: &MessageField<CSGOInterpolationInfoPB>) -> Option<InterpolationInfo> {
+    v.as_ref().map(|v| InterpolationInfo {
+        src_tick: v.src_tick(),
+        dst_tick: v.dst_tick(),
+        frac: v.frac(),
+    })
+}
+
+fn create_usercmd_record(user_cmd: &CSGOUserCmdPB, tick: i32, entity_id: i32) -> UserCmdRecord {
+    let base = &user_cmd.base;
+    UserCmdRecord {
+        tick,
+        steamid: None,
+        name: None,
+        entity_id,
+        legacy_command_number: base.legacy_command_number(),
+        client_tick: base.client_tick(),
+        buttonstate_1: base.buttons_pb.buttonstate1(),
+        buttonstate_2: base.buttons_pb.buttonstate2(),
+        buttonstate_3: base.buttons_pb.buttonstate3(),
+        viewangle_x: base.viewangles.x(),
+        viewangle_y: base.viewangles.y(),
+        viewangle_z: base.viewangles.z(),
+        forward_move: base.forwardmove(),
+        left_move: base.leftmove(),
+        up_move: base.upmove(),
+        impulse: base.impulse(),
+        weapon_select: base.weaponselect(),
+        random_seed: base.random_seed(),
+        mouse_dx: base.mousedx(),
+        mouse_dy: base.mousedy(),
+        consumed_server_angle_changes: base.consumed_server_angle_changes(),
+        cmd_flags: base.cmd_flags(),
+        move_crc: base.move_crc().iter().map(|b| format!("{:02x}", b)).collect(),
+        subtick_moves: base
+            .subtick_moves
+            .iter()
+            .map(|step| SubtickMove {
+                button: step.button(),
+                pressed: step.pressed(),
+                when: step.when(),
+                analog_forward_delta: step.analog_forward_delta(),
+                analog_left_delta: step.analog_left_delta(),
+            })
+            .collect(),
+        input_history: user_cmd
+            .input_history
+            .iter()
+            .map(|input| InputHistory {
+                player_tick_count: input.player_tick_count(),
+                player_tick_fraction: input.player_tick_fraction(),
+                render_tick_count: input.render_tick_count(),
+                render_tick_fraction: input.render_tick_fraction(),
+                x: input.view_angles.x(),
+                y: input.view_angles.y(),
+                z: input.view_angles.z(),
+                cl_interp: interpolation(&input.cl_interp),
+                sv_interp0: interpolation(&input.sv_interp0),
+                sv_interp1: interpolation(&input.sv_interp1),
+                player_interp: interpolation(&input.player_interp),
+                frame_number: input.frame_number(),
+                target_ent_index: input.target_ent_index(),
+                shoot_position: vector(&input.shoot_position),
+                target_head_pos_check: vector(&input.target_head_pos_check),
+                target_abs_pos_check: vector(&input.target_abs_pos_check),
+                target_abs_ang_check: qangle(&input.target_abs_ang_check),
+            })
+            .collect(),
+        attack_start_history_index_1: user_cmd.attack1_start_history_index(),
+        attack_start_history_index_2: user_cmd.attack2_start_history_index(),
+        attack_start_history_index_3: user_cmd.attack3_start_history_index(),
+        left_hand_desired: user_cmd.left_hand_desired(),
+    }
+}
+
+impl<'a> SecondPassParser<'a> {
+    pub fn parse_user_cmd(&mut self, bytes: &[u8]) -> Result<(), DemoParserError> {
+        // We simply inject the values into the entities as if they came from packet_ents like any other val.
+
+        // This method is quite expensive so early exit it if not needed.
+        if !self.parse_usercmd {
+            return Ok(());
+        }
+
+        let msg: CSVCMsg_UserCommands = match CSVCMsg_UserCommands::parse_from_bytes(bytes) {
+            Ok(m) => m,
+            _ => return Ok(()),
+        };
+        for cmd in msg.commands {
+            let user_cmd: CSGOUserCmdPB = match CSGOUserCmdPB::parse_from_bytes(cmd.data()) {
+                Ok(m) => m,
+                _ => return Ok(()),
+            };
+            let entity_id = (user_cmd.base.pawn_entity_handle() & 0x7FF) as i32;
+            self.insert_user_cmd(&user_cmd, entity_id);
+        }
+        Ok(())
+    }
+    // POV demos store the commands of the recording player in their own frames instead of svc_UserCmds
+    pub fn parse_user_command_cmd(&mut self, bytes: &[u8]) -> Result<(), DemoParserError> {
+        if !self.parse_usercmd {
+            return Ok(());
+        }
+        let cmd: CDemoUserCmd = match CDemoUserCmd::parse_from_bytes(bytes) {
+            Ok(m) => m,
+            _ => return Ok(()),
+        };
+        let mut user_cmd: CSGOUserCmdPB = match CSGOUserCmdPB::parse_from_bytes(cmd.data()) {
+            Ok(m) => m,
+            _ => return Ok(()),
+        };
+        let base = user_cmd.base.mut_or_insert_default();
+        if !base.has_legacy_command_number() {
+            base.set_legacy_command_number(cmd.cmd_number());
+        }
+        // The client doesn't always fill in the pawn, it can only be the recording player
+        let entity_id = match base.pawn_entity_handle.map(|handle| (handle & 0x7FF) as i32) {
+            Some(entity_id) if entity_id != INVALID_ENTITY_ID => Some(entity_id),
+            _ => self.find_pov_player_entity_id(),
+        };
+        if let Some(entity_id) = entity_id {
+            self.insert_user_cmd(&user_cmd, entity_id);
+        }
+        Ok(())
+    }
+    pub fn find_pov_player_entity_id(&self) -> Option<i32> {
+        // Controllers live at entity index slot + 1
+        let controller_id = self.pov_player_slot? + 1;
+        self.players
+            .values()
+            .find(|p| p.controller_entid == Some(controller_id))
+            .and_then(|p| p.player_entity_id)
+    }
+    fn insert_user_cmd(&mut self, user_cmd: &CSGOUserCmdPB, entity_id: i32) {
+        let mut record = create_usercmd_record(user_cmd, self.tick, entity_id);
+        if let Some(Some(ent)) = self.entities.get_mut(entity_id as usize) {
+            ent.props
+                .insert(USERCMD_INPUT_HISTORY_BASEID, Variant::InputHistory(record.input_history.clone()));
+            ent.props.insert(USERCMD_SUBTICK_MOVES, Variant::SubtickMoves(record.subtick_moves.clone()));
+            ent.props.insert(USERCMD_LEFTMOVE, Variant::F32(record.left_move));
+            ent.props.insert(USERCMD_UPMOVE, Variant::F32(record.up_move));
+            ent.props.insert(USERCMD_IMPULSE, Variant::I32(record.impulse));
+            ent.props.insert(USERCMD_MOUSE_DX, Variant::I32(record.mouse_dx));
+            ent.props.insert(USERCMD_MOUSE_DY, Variant::I32(record.mouse_dy));
+            ent.props.insert(USERCMD_VIEWANGLE_X, Variant::F32(record.viewangle_x));
+            ent.props.insert(USERCMD_VIEWANGLE_Y, Variant::F32(record.viewangle_y));
+            ent.props.insert(USERCMD_VIEWANGLE_Z, Variant::F32(record.viewangle_z));
+            ent.props.insert(USERCMD_FORWARDMOVE, Variant::F32(record.forward_move));
+            ent.props.insert(USERCMD_BUTTONSTATE_1, Variant::U64(record.buttonstate_1));
+            ent.props.insert(USERCMD_BUTTONSTATE_2, Variant::U64(record.buttonstate_2));
+            ent.props.insert(USERCMD_BUTTONSTATE_3, Variant::U64(record.buttonstate_3));
+            ent.props
+                .insert(USERCMD_CONSUMED_SERVER_ANGLE_CHANGES, Variant::U32(record.consumed_server_angle_changes));
+            ent.props.insert(USERCMD_WEAPON_SELECT, Variant::I32(record.weapon_select));
+            ent.props.insert(USERCMD_LEGACY_COMMAND_NUMBER, Variant::I32(record.legacy_command_number));
+            ent.props.insert(USERCMD_CLIENT_TICK, Variant::I32(record.client_tick));
+            ent.props.insert(USERCMD_RANDOM_SEED, Variant::I32(record.random_seed));
+            ent.props.insert(USERCMD_CMD_FLAGS, Variant::I32(record.cmd_flags));
+            ent.props.insert(USERCMD_MOVE_CRC, Variant::String(record.move_crc.clone()));
+            ent.props
+                .insert(USERCMD_ATTACK_START_HISTORY_INDEX_1, Variant::I32(record.attack_start_history_index_1));
+            ent.props
+                .insert(USERCMD_ATTACK_START_HISTORY_INDEX_2, Variant::I32(record.attack_start_history_index_2));
+            ent.props
+                .insert(USERCMD_ATTACK_START_HISTORY_INDEX_3, Variant::I32(record.attack_start_history_index_3));
+            ent.props.insert(USERCMD_SUBTICK_LEFT_HAND_DESIRED, Variant::Bool(record.left_hand_desired));
+        }
+        if self.parse_usercmds {
+            if let Some(player) = self.players.get(&entity_id) {
+                record.steamid = player.steamid;
+                record.name = player.name.clone();
+            }
+            self.usercmds.push(record);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use csgoproto::usercmd::CSubtickMoveStep;
+
+    #[test]
+    fn test_usercmd_record() {
+        let mut user_cmd = CSGOUserCmdPB::new();
+        let base = user_cmd.base.mut_or_insert_default();
+        base.set_client_tick(1234);
+        base.set_weaponselect(7);
+        base.set_move_crc(vec![0x0a, 0xff].into());
+        base.viewangles.mut_or_insert_default().set_y(90.0);
+        let mut step = CSubtickMoveStep::new();
+        step.set_button(1);
+        step.set_pressed(true);
+        step.set_when(0.25);
+        base.subtick_moves.push(step);
+        user_cmd.set_attack1_start_history_index(0);
+        user_cmd.set_left_hand_desired(true);
+        let mut input = csgoproto::cs_usercmd::CSGOInputHistoryEntryPB::new();
+        input.set_frame_number(5);
+        input.shoot_position.mut_or_insert_default().set_z(64.0);
+        user_cmd.input_history.push(input);
+
+        let record = create_usercmd_record(&user_cmd, 100, 3);
+        assert_eq!((record.tick, record.entity_id, record.client_tick, record.weapon_select), (100, 3, 1234, 7));
+        assert_eq!(record.viewangle_y, 90.0);
+        assert_eq!(record.move_crc, "0aff");
+        assert_eq!(
+            record.subtick_moves,
+            vec![SubtickMove {
+                button: 1,
+                pressed: true,
+                when: 0.25,
+                analog_forward_delta: 0.0,
+                analog_left_delta: 0.0,
+            }]
+        );
+        assert_eq!(record.attack_start_history_index_1, 0);
+        assert_eq!(record.attack_start_history_index_2, -1);
+        assert!(record.left_hand_desired);
+        assert_eq!(record.input_history[0].frame_number, 5);
+        assert_eq!(record.input_history[0].shoot_position, Some([0.0, 0.0, 64.0]));
+        assert_eq!(record.input_history[0].cl_interp, None);
+    }
+}
@@ -0,0 +1,348 @@
+use crate::first_pass::prop_controller::TICKRATE;
+use crate::first_pass::read_bits::DemoParserError;
+use crate::second_pass::game_events::DecodedGameEvent;
+use crate::second_pass::grenade_lifecycle::GrenadeLifecycle;
+use crate::second_pass::parser_settings::SecondPassParser;
+use crate::second_pass::variants::Variant;
+
+// player_hurt and player_blind can arrive a few ticks after the detonate event
+const EFFECT_TICK_WINDOW: i32 = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UtilityEventKind {
+    Damage,
+    Blind,
+    Death,
+}
+
+#[derive(Debug, Clone)]
+pub struct UtilityEvent {
+    pub tick: i32,
+    pub kind: UtilityEventKind,
+    pub attacker_steamid: Option<u64>,
+    pub attacker_team: Option<u32>,
+    pub victim_steamid: Option<u64>,
+    pub victim_name: Option<String>,
+    pub victim_team: Option<u32>,
+    pub weapon: Option<String>,
+    pub damage: Option<i32>,
+    pub blind_duration: Option<f32>,
+    pub entity_id: Option<i32>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FlashVictim {
+    pub steamid: Option<u64>,
+    pub name: Option<String>,
+    pub blind_duration: Option<f32>,
+    pub is_teammate: Option<bool>,
+    pub tick: i32,
+}
+
+#[derive(Debug, Clone)]
+pub struct UtilityEffectRecord {
+    pub entity_id: Option<i32>,
+    pub grenade_type: Option<String>,
+    pub thrower_steamid: Option<u64>,
+    pub thrower_name: Option<String>,
+    pub throw_tick: Option<i32>,
+    pub detonate_tick: Option<i32>,
+    pub enemy_damage: Option<i32>,
+    pub team_damage: Option<i32>,
+    pub enemies_hurt: Option<i32>,
+    pub teammates_hurt: Option<i32>,
+    pub enemies_flashed: Option<i32>,
+    pub teammates_flashed: Option<i32>,
+    pub enemy_blind_duration: Option<f32>,
+    pub team_blind_duration: Option<f32>,
+    pub kills_on_flashed: Option<i32>,
+    pub flash_victims: Vec<FlashVictim>,
+}
+
+impl<'a> SecondPassParser<'a> {
+    pub fn collect_utility_event(&mut self, event: &DecodedGameEvent) -> Result<(), DemoParserError> {
+        let kind = match event.name() {
+            "player_hurt" => UtilityEventKind::Damage,
+            "player_blind" => UtilityEventKind::Blind,
+            "player_death" => UtilityEventKind::Death,
+            _ => return Ok(()),
+        };
+        let weapon = match event.key("weapon") {
+            Some(Variant::String(weapon)) => Some(weapon),
+            _ => None,
+        };
+        if kind == UtilityEventKind::Damage && !weapon.as_deref().is_some_and(is_grenade_damage) {
+            return Ok(());
+        }
+        let (attacker_steamid, _, attacker_team) = match event.key("attacker") {
+            Some(Variant::I32(userid)) => self.player_info_from_userid(userid),
+            _ => (None, None, None),
+        };
+        let (victim_steamid, victim_name, victim_team) = match event.key("userid") {
+            Some(Variant::I32(userid)) => self.player_info_from_userid(userid),
+            _ => (None, None, None),
+        };
+        let damage = match event.key("dmg_health") {
+            Some(Variant::I32(damage)) => Some(damage),
+            _ => None,
+        };
+        let blind_duration = match event.key("blind_duration") {
+            Some(Variant::F32(duration)) => Some(duration),
+            _ => None,
+        };
+        let entity_id = match event.key("entityid") {
+            Some(Variant::I32(id)) => Some(id),
+            _ => None,
+        };
+        self.utility_events.push(UtilityEvent {
+            tick: self.tick,
+            kind,
+            attacker_steamid,
+            attacker_team,
+            victim_steamid,
+            victim_name,
+            victim_team,
+            weapon,
+            damage,
+            blind_duration,
+            entity_id,
+        });
+        Ok(())
+    }
+    fn player_info_from_userid(&self, userid: i32) -> (Option<u64>, Option<String>, Option<u32>) {
+        let player = match self.entity_id_from_userid(userid) {
+            Some(entity_id) => self.find_player_metadata(entity_id).ok(),
+            None => None,
+        };
+        match player {
+            Some(player) => (player.steamid, player.name.clone(), player.team_num),
+            None => (None, None, None),
+        }
+    }
+}
+
+fn is_grenade_damage(weapon: &str) -> bool {
+    matches!(weapon, "hegrenade" | "inferno" | "molotov" | "incgrenade")
+}
+
+fn is_fire(grenade_type: Option<&str>) -> bool {
+    matches!(grenade_type, Some("molotov") | Some("incendiary_grenade"))
+}
+
+fn find_grenade_for_effect(grenades: &[GrenadeLifecycle], event: &UtilityEvent) -> Option<usize> {
+    // Picks the latest grenade of the right type thrown by the attacker that was active at the time of the event
+    grenades.iter().rposition(|g| {
+        let detonate_tick = match g.detonate_tick {
+            Some(tick) if tick <= event.tick => tick,
+            _ => return false,
+        };
+        if g.thrower_steamid.is_none() || g.thrower_steamid != event.attacker_steamid {
+            return false;
+        }
+        match (event.kind, event.weapon.as_deref()) {
+            (UtilityEventKind::Damage, Some("hegrenade")) => {
+                g.grenade_type.as_deref() == Some("he_grenade") && event.tick - detonate_tick <= EFFECT_TICK_WINDOW
+            }
+            (UtilityEventKind::Damage, _) => {
+                is_fire(g.grenade_type.as_deref()) && g.expire_tick.is_none_or(|expired| event.tick <= expired + EFFECT_TICK_WINDOW)
+            }
+            (UtilityEventKind::Blind, _) => {
+                let same_entity = event.entity_id.is_none() || event.entity_id == g.entity_id;
+                g.grenade_type.as_deref() == Some("flashbang") && same_entity && event.tick - detonate_tick <= EFFECT_TICK_WINDOW
+            }
+            (UtilityEventKind::Death, _) => false,
+        }
+    })
+}
+
+pub fn create_utility_effects(grenades: &[GrenadeLifecycle], events: &[UtilityEvent]) -> Vec<UtilityEffectRecord> {
+    let mut records: Vec<UtilityEffectRecord> = grenades
+        .iter()
+        .map(|g| UtilityEffectRecord {
+            entity_id: g.entity_id,
+            grenade_type: g.grenade_type.clone(),
+            thrower_steamid: g.thrower_steamid,
+            thrower_name: g.thrower_name.clone(),
+            throw_tick: g.throw_tick,
+            detonate_tick: g.detonate_tick,
+            enemy_damage: Some(0),
+            team_damage: Some(0),
+            enemies_hurt: Some(0),
+            teammates_hurt: Some(0),
+            enemies_flashed: Some(0),
+            teammates_flashed: Some(0),
+            enemy_blind_duration: Some(0.0),
+            team_blind_duration: Some(0.0),
+            kills_on_flashed: Some(0),
+            flash_victims: vec![],
+        })
+        .collect();
+    let mut hurt_by_grenade: Vec<Vec<Option<u64>>> = vec![vec![]; grenades.len()];
+    for event in events {
+        if event.kind == UtilityEventKind::Death {
+            // Kill on a player that was still blind from a flash. Team kills don't count.
+            if event.attacker_team.is_some() && event.attacker_team == event.victim_team {
+                continue;
+            }
+            for record in records.iter_mut() {
+                let was_blind = record.flash_victims.iter().any(|v| {
+                    v.is_teammate == Some(false)
+                        && v.steamid == event.victim_steamid
+                        && v.tick <= event.tick
+                        && event.tick as f32 <= v.tick as f32 + v.blind_duration.unwrap_or(0.0) * TICKRATE
+                });
+                if was_blind {
+                    record.kills_on_flashed = record.kills_on_flashed.map(|k| k + 1);
+                }
+            }
+            continue;
+        }
+        let idx = match find_grenade_for_effect(grenades, event) {
+            Some(idx) => idx,
+            None => continue,
+        };
+        let is_teammate = match (event.attacker_team, event.victim_team) {
+            (Some(attacker), Some(victim)) => Some(attacker == victim),
+            _ => None,
+        };
+        let record = &mut records[idx];
+        match event.kind {
+            UtilityEventKind::Damage => {
+                let damage = event.damage.unwrap_or(0);
+                let first_hit = !hurt_by_grenade[idx].contains(&event.victim_steamid);
+                if first_hit {
+                    hurt_by_grenade[idx].push(event.victim_steamid);
+                }
+                match is_teammate {
+                    Some(true) => {
+                        record.team_damage = record.team_damage.map(|d| d + damage);
+                        if first_hit {
+                            record.teammates_hurt = record.teammates_hurt.map(|n| n + 1);
+                        }
+                    }
+                    Some(false) => {
+                        record.enemy_damage = record.enemy_damage.map(|d| d + damage);
+                        if first_hit {
+                            record.enemies_hurt = record.enemies_hurt.map(|n| n + 1);
+                        }
+                    }
+                    None => {}
+                }
+            }
+            UtilityEventKind::Blind => {
+                let duration = event.blind_duration.unwrap_or(0.0);
+                match is_teammate {
+                    Some(true) => {
+                        record.teammates_flashed = record.teammates_flashed.map(|n| n + 1);
+                        record.team_blind_duration = record.team_blind_duration.map(|d| d + duration);
+                    }
+                    Some(false) => {
+                        record.enemies_flashed = record.enemies_flashed.map(|n| n + 1);
+                        record.enemy_blind_duration = record.enemy_blind_duration.map(|d| d + duration);
+                    }
+                    None => {}
+                }
+                record.flash_victims.push(FlashVictim {
+                    steamid: event.victim_steamid,
+                    name: event.victim_name.clone(),
+                    blind_duration: event.blind_duration,
+                    is_teammate,
+                    tick: event.tick,
+                });
+            }
+            UtilityEventKind::Death => {}
+        }
+    }
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::second_pass::grenade_lifecycle::merge_grenade_lifecycles;
+
+    fn grenade(entity_id: i32, grenade_type: &str, detonate_tick: i32, expire_tick: i32) -> GrenadeLifecycle {
+        GrenadeLifecycle {
+            entity_id: Some(entity_id),
+            grenade_type: Some(grenade_type.to_string()),
+            thrower_steamid: Some(1),
+            thrower_name: Some("thrower".to_string()),
+            throw_tick: Some(detonate_tick - 100),
+            throw_x: None,
+            throw_y: None,
+            throw_z: None,
+            throw_pitch: None,
+            throw_yaw: None,
+            trajectory: vec![],
+            bounces: None,
+            detonate_tick: Some(detonate_tick),
+            detonate_x: None,
+            detonate_y: None,
+            detonate_z: None,
+            expire_tick: Some(expire_tick),
+            inferno_entity_id: None,
+            continues_previous: false,
+        }
+    }
+    fn event(tick: i32, kind: UtilityEventKind, attacker: u64, victim: u64, victim_team: u32, weapon: Option<&str>) -> UtilityEvent {
+        UtilityEvent {
+            tick,
+            kind,
+            attacker_steamid: Some(attacker),
+            attacker_team: Some(2),
+            victim_steamid: Some(victim),
+            victim_name: None,
+            victim_team: Some(victim_team),
+            weapon: weapon.map(|w| w.to_string()),
+            damage: Some(40),
+            blind_duration: Some(2.0),
+            entity_id: None,
+        }
+    }
+
+    #[test]
+    fn test_he_and_fire_damage_split_by_team() {
+        let grenades = vec![grenade(100, "he_grenade", 1000, 1000), grenade(101, "molotov", 2000, 2448)];
+        let events = vec![
+            event(1000, UtilityEventKind::Damage, 1, 5, 3, Some("hegrenade")),
+            event(1001, UtilityEventKind::Damage, 1, 6, 2, Some("hegrenade")),
+            event(2100, UtilityEventKind::Damage, 1, 5, 3, Some("inferno")),
+            event(2200, UtilityEventKind::Damage, 1, 5, 3, Some("inferno")),
+            event(3000, UtilityEventKind::Damage, 1, 5, 3, Some("inferno")),
+        ];
+        let records = create_utility_effects(&grenades, &events);
+        assert_eq!(records[0].enemy_damage, Some(40));
+        assert_eq!(records[0].team_damage, Some(40));
+        assert_eq!(records[1].enemy_damage, Some(80));
+        assert_eq!(records[1].enemies_hurt, Some(1));
+    }
+    #[test]
+    fn test_fire_expiring_in_later_chunk() {
+        // Chunk one only saw the projectile get removed right after the fire started
+        let mut molotov = grenade(101, "molotov", 2000, 2001);
+        molotov.inferno_entity_id = Some(300);
+        let grenades = merge_grenade_lifecycles(vec![molotov], vec![(300, 2448)]);
+        assert_eq!(grenades[0].expire_tick, Some(2448));
+        let events = vec![
+            event(2200, UtilityEventKind::Damage, 1, 5, 3, Some("inferno")),
+            event(3000, UtilityEventKind::Damage, 1, 5, 3, Some("inferno")),
+        ];
+        let records = create_utility_effects(&grenades, &events);
+        assert_eq!(records[0].enemy_damage, Some(40));
+    }
+    #[test]
+    fn test_kills_on_flashed_victims() {
+        let grenades = vec![grenade(100, "flashbang", 1000, 1000)];
+        let events = vec![
+            event(1002, UtilityEventKind::Blind, 1, 5, 3, None),
+            event(1003, UtilityEventKind::Blind, 1, 6, 2, None),
+            event(1050, UtilityEventKind::Death, 7, 5, 3, Some("ak47")),
+            event(1500, UtilityEventKind::Death, 7, 8, 3, Some("ak47")),
+        ];
+        let records = create_utility_effects(&grenades, &events);
+        assert_eq!(records[0].enemies_flashed, Some(1));
+        assert_eq!(records[0].teammates_flashed, Some(1));
+        assert_eq!(records[0].flash_victims.len(), 2);
+        assert_eq!(records[0].kills_on_flashed, Some(1));
+    }
+}
@@ -1,6 +1,20 @@
 use crate::first_pass::prop_controller::PropInfo;
 use crate::second_pass::collect_data::ProjectileRecord;
+use crate::second_pass::entity_rows::EntityRecord;
+use crate::second_pass::grenade_lifecycle::{GrenadeLifecycle, TrajectoryPoint};
+use crate::second_pass::bomb::{BombEvent, BombTimeline};
+use crate::second_pass::fov::{CrosshairPlacementRecord, FovRecord};
+use crate::second_pass::inferno::{FireCell, InfernoFireRecord};
+use crate::second_pass::money_ledger::MoneyLedgerRecord;
+use crate::second_pass::movement::MovementEvent;
+use crate::second_pass::spotting::{SpottingEdge, SpottingRoundRecord};
+use crate::second_pass::utility_effects::{FlashVictim, UtilityEffectRecord};
+use crate::second_pass::visibility::VisibilityRecord;
+use crate::second_pass::weapons::{GroundWeaponRecord, WeaponOwnership, WeaponProvenanceRecord};
 use crate::second_pass::parser_settings::{EconItem, PlayerEndMetaData};
+use crate::second_pass::shots::{AccuracyRecord, ShotRecord};
+use crate::second_pass::spray::{SprayBulletRecord, SprayRecord};
+use crate::second_pass::usercmd::UserCmdRecord;
 use ahash::HashMap;
 use itertools::Itertools;
 use memmap2::Mmap;
@@ -25,6 +39,7 @@ pub enum Variant {
     U64Vec(Vec<u64>),
     Stickers(Vec<Sticker>),
     InputHistory(Vec<InputHistory>),
+    SubtickMoves(Vec<SubtickMove>),
 }
 #[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct Sticker {
@@ -43,6 +58,31 @@ pub struct InputHistory {
     pub render_tick_fraction: f32,
     pub player_tick_count: i32,
     pub player_tick_fraction: f32,
+    pub cl_interp: Option<InterpolationInfo>,
+    pub sv_interp0: Option<InterpolationInfo>,
+    pub sv_interp1: Option<InterpolationInfo>,
+    pub player_interp: Option<InterpolationInfo>,
+    pub frame_number: i32,
+    pub target_ent_index: i32,
+    pub shoot_position: Option<[f32; 3]>,
+    pub target_head_pos_check: Option<[f32; 3]>,
+    pub target_abs_pos_check: Option<[f32; 3]>,
+    pub target_abs_ang_check: Option<[f32; 3]>,
+}
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct InterpolationInfo {
+    pub src_tick: i32,
+    pub dst_tick: i32,
+    pub frac: f32,
+}
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SubtickMove {
+    pub button: u64,
+    pub pressed: bool,
+    // Fraction of the tick at which the move happened
+    pub when: f32,
+    pub analog_forward_delta: f32,
+    pub analog_left_delta: f32,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -60,6 +100,7 @@ pub enum VarVec {
     XYZVec(Vec<Option<[f32; 3]>>),
     Stickers(Vec<Vec<Sticker>>),
     InputHistory(Vec<Vec<InputHistory>>),
+    SubtickMoves(Vec<Vec<SubtickMove>>),
 }
 
 impl VarVec {
@@ -80,6 +121,7 @@ impl VarVec {
             Variant::I16(_) => VarVec::I32(vec![]),
             Variant::U8(_) => VarVec::I32(vec![]),
             Variant::InputHistory(_) => VarVec::InputHistory(vec![]),
+            Variant::SubtickMoves(_) => VarVec::SubtickMoves(vec![]),
         }
     }
 }
@@ -112,6 +154,7 @@ impl PropColumn {
             Some(VarVec::XYZVec(b)) => VarVec::XYZVec(indicies.iter().map(|x| b[*x]).collect_vec()),
             Some(VarVec::Stickers(b)) => VarVec::Stickers(indicies.iter().map(|x| b[*x].to_owned()).collect_vec()),
             Some(VarVec::InputHistory(b)) => VarVec::InputHistory(indicies.iter().map(|x| b[*x].to_owned()).collect_vec()),
+            Some(VarVec::SubtickMoves(b)) => VarVec::SubtickMoves(indicies.iter().map(|x| b[*x].to_owned()).collect_vec()),
             None => {
                 return Some(PropColumn {
                     data: None,
@@ -139,6 +182,7 @@ impl PropColumn {
             Some(VarVec::XYZVec(b)) => b.len(),
             Some(VarVec::Stickers(b)) => b.len(),
             Some(VarVec::InputHistory(b)) => b.len(),
+            Some(VarVec::SubtickMoves(b)) => b.len(),
             None => self.num_nones,
         }
     }
@@ -276,6 +320,17 @@ impl PropColumn {
                 }
                 _ => {}
             },
+            Some(VarVec::SubtickMoves(v)) => match &other.data {
+                Some(VarVec::SubtickMoves(v_other)) => {
+                    v.extend_from_slice(&v_other);
+                }
+                None => {
+                    for _ in 0..other.num_nones {
+                        v.push(vec![]);
+                    }
+                }
+                _ => {}
+            },
             Some(VarVec::U32Vec(v)) => match &other.data {
                 Some(VarVec::U32Vec(v_other)) => {
                     v.extend_from_slice(&v_other);
@@ -340,6 +395,10 @@ impl PropColumn {
                     self.resolve_vec_type(PropColumn::get_type(&other.data));
                     self.extend_from(other);
                 }
+                Some(VarVec::SubtickMoves(_inner)) => {
+                    self.resolve_vec_type(PropColumn::get_type(&other.data));
+                    self.extend_from(other);
+                }
                 None => {
                     self.num_nones += other.num_nones;
                 }
@@ -362,6 +421,7 @@ impl PropColumn {
             Some(VarVec::Stickers(_)) => Some(10),
             Some(VarVec::U32Vec(_)) => Some(11),
             Some(VarVec::InputHistory(_)) => Some(12),
+            Some(VarVec::SubtickMoves(_)) => Some(13),
 
             None => None,
         }
@@ -384,6 +444,7 @@ impl PropColumn {
             Some(10) => self.data = Some(VarVec::Stickers(vec![])),
             Some(11) => self.data = Some(VarVec::U32Vec(vec![])),
             Some(12) => self.data = Some(VarVec::InputHistory(vec![])),
+            Some(13) => self.data = Some(VarVec::SubtickMoves(vec![])),
             _ => {}
         }
         for _ in 0..self.num_nones {
@@ -412,6 +473,88 @@ impl PropColumn {
     }
 }
 
+impl PropColumn {
+    pub fn get(&self, idx: usize) -> Option<Variant> {
+        match &self.data {
+            Some(VarVec::Bool(b)) => b.get(idx).copied().flatten().map(Variant::Bool),
+            Some(VarVec::I32(b)) => b.get(idx).copied().flatten().map(Variant::I32),
+            Some(VarVec::F32(b)) => b.get(idx).copied().flatten().map(Variant::F32),
+            Some(VarVec::String(b)) => b.get(idx).cloned().flatten().map(Variant::String),
+            Some(VarVec::U32(b)) => b.get(idx).copied().flatten().map(Variant::U32),
+            Some(VarVec::U64(b)) => b.get(idx).copied().flatten().map(Variant::U64),
+            Some(VarVec::StringVec(b)) => b.get(idx).cloned().map(Variant::StringVec),
+            Some(VarVec::U64Vec(b)) => b.get(idx).cloned().map(Variant::U64Vec),
+            Some(VarVec::U32Vec(b)) => b.get(idx).cloned().map(Variant::U32Vec),
+            Some(VarVec::XYVec(b)) => b.get(idx).copied().flatten().map(Variant::VecXY),
+            Some(VarVec::XYZVec(b)) => b.get(idx).copied().flatten().map(Variant::VecXYZ),
+            Some(VarVec::Stickers(b)) => b.get(idx).cloned().map(Variant::Stickers),
+            Some(VarVec::InputHistory(b)) => b.get(idx).cloned().map(Variant::InputHistory),
+            Some(VarVec::SubtickMoves(b)) => b.get(idx).cloned().map(Variant::SubtickMoves),
+            None => None,
+        }
+    }
+}
+
+// Run-length encoded PropColumn, values[i] holds from row run_starts[i] until the next run starts.
+// Props that rarely change (money, health, names...) shrink to a handful of runs per player.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RlePropColumn {
+    pub run_starts: Vec<usize>,
+    pub values: PropColumn,
+    pub len: usize,
+}
+
+impl Default for RlePropColumn {
+    fn default() -> Self {
+        RlePropColumn::new()
+    }
+}
+
+impl RlePropColumn {
+    pub fn new() -> Self {
+        RlePropColumn {
+            run_starts: vec![],
+            values: PropColumn::new(),
+            len: 0,
+        }
+    }
+    pub fn push(&mut self, item: Option<Variant>) {
+        self.push_n(item, 1);
+    }
+    pub fn push_n(&mut self, item: Option<Variant>, n: usize) {
+        if n == 0 {
+            return;
+        }
+        if self.run_starts.is_empty() || self.values.get(self.run_starts.len() - 1) != item {
+            self.run_starts.push(self.len);
+            self.values.push(item);
+        }
+        self.len += n;
+    }
+    // Appends the rows of other from row start onwards, one push per run
+    pub fn append_from(&mut self, other: &RlePropColumn, start: usize) {
+        if start >= other.len {
+            return;
+        }
+        let first_run = other.run_starts.partition_point(|run_start| *run_start <= start) - 1;
+        for run in first_run..other.run_starts.len() {
+            let run_start = other.run_starts[run].max(start);
+            let run_end = other.run_starts.get(run + 1).copied().unwrap_or(other.len);
+            self.push_n(other.values.get(run), run_end - run_start);
+        }
+    }
+    pub fn get(&self, row: usize) -> Option<Variant> {
+        if row >= self.len {
+            return None;
+        }
+        let run = self.run_starts.partition_point(|start| *start <= row);
+        self.values.get(run - 1)
+    }
+    pub fn num_runs(&self) -> usize {
+        self.run_starts.len()
+    }
+}
+
 impl VarVec {
     #[inline(always)]
     pub fn push_variant(&mut self, item: Option<Variant>) {
@@ -468,6 +611,10 @@ impl VarVec {
                 VarVec::InputHistory(f) => f.push(p),
                 _ => {}
             },
+            Some(Variant::SubtickMoves(p)) => match self {
+                VarVec::SubtickMoves(f) => f.push(p),
+                _ => {}
+            },
             None => self.push_none(),
             _ => {}
         }
@@ -487,6 +634,7 @@ impl VarVec {
             VarVec::U32Vec(f) => f.push(vec![]),
             VarVec::Stickers(f) => f.push(vec![]),
             VarVec::InputHistory(f) => f.push(vec![]),
+            VarVec::SubtickMoves(f) => f.push(vec![]),
         }
     }
 }
@@ -558,6 +706,13 @@ impl Serialize for Variant {
                 }
                 s.end()
             }
+            Variant::SubtickMoves(v) => {
+                let mut s = serializer.serialize_seq(Some(v.len()))?;
+                for item in v {
+                    s.serialize_element(&item)?;
+                }
+                s.end()
+            }
         }
     }
 }
@@ -628,6 +783,516 @@ impl Serialize for ProjectileRecord {
         state.end()
     }
 }
+impl Serialize for MoneyLedgerRecord {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("MoneyLedgerRecord", 7)?;
+        let steamid = match self.steamid {
+            Some(u) => Some(u.to_string()),
+            None => None,
+        };
+        state.serialize_field("tick", &self.tick)?;
+        state.serialize_field("steamid", &steamid)?;
+        state.serialize_field("name", &self.name)?;
+        state.serialize_field("delta", &self.delta)?;
+        state.serialize_field("balance", &self.balance)?;
+        state.serialize_field("reason", &self.reason)?;
+        state.serialize_field("explained", &self.explained)?;
+        state.end()
+    }
+}
+impl Serialize for TrajectoryPoint {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("TrajectoryPoint", 4)?;
+        state.serialize_field("tick", &self.tick)?;
+        state.serialize_field("x", &self.x)?;
+        state.serialize_field("y", &self.y)?;
+        state.serialize_field("z", &self.z)?;
+        state.end()
+    }
+}
+impl Serialize for GrenadeLifecycle {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("GrenadeLifecycle", 18)?;
+        let steamid = match self.thrower_steamid {
+            Some(u) => Some(u.to_string()),
+            None => None,
+        };
+        state.serialize_field("entity_id", &self.entity_id)?;
+        state.serialize_field("grenade_type", &self.grenade_type)?;
+        state.serialize_field("thrower_steamid", &steamid)?;
+        state.serialize_field("thrower_name", &self.thrower_name)?;
+        state.serialize_field("throw_tick", &self.throw_tick)?;
+        state.serialize_field("throw_x", &self.throw_x)?;
+        state.serialize_field("throw_y", &self.throw_y)?;
+        state.serialize_field("throw_z", &self.throw_z)?;
+        state.serialize_field("throw_pitch", &self.throw_pitch)?;
+        state.serialize_field("throw_yaw", &self.throw_yaw)?;
+        state.serialize_field("trajectory", &self.trajectory)?;
+        state.serialize_field("bounces", &self.bounces)?;
+        state.serialize_field("detonate_tick", &self.detonate_tick)?;
+        state.serialize_field("detonate_x", &self.detonate_x)?;
+        state.serialize_field("detonate_y", &self.detonate_y)?;
+        state.serialize_field("detonate_z", &self.detonate_z)?;
+        state.serialize_field("expire_tick", &self.expire_tick)?;
+        state.serialize_field("inferno_entity_id", &self.inferno_entity_id)?;
+        state.end()
+    }
+}
+impl Serialize for FlashVictim {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("FlashVictim", 5)?;
+        let steamid = match self.steamid {
+            Some(u) => Some(u.to_string()),
+            None => None,
+        };
+        state.serialize_field("steamid", &steamid)?;
+        state.serialize_field("name", &self.name)?;
+        state.serialize_field("blind_duration", &self.blind_duration)?;
+        state.serialize_field("is_teammate", &self.is_teammate)?;
+        state.serialize_field("tick", &self.tick)?;
+        state.end()
+    }
+}
+impl Serialize for UtilityEffectRecord {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("UtilityEffectRecord", 16)?;
+        let steamid = match self.thrower_steamid {
+            Some(u) => Some(u.to_string()),
+            None => None,
+        };
+        state.serialize_field("entity_id", &self.entity_id)?;
+        state.serialize_field("grenade_type", &self.grenade_type)?;
+        state.serialize_field("thrower_steamid", &steamid)?;
+        state.serialize_field("thrower_name", &self.thrower_name)?;
+        state.serialize_field("throw_tick", &self.throw_tick)?;
+        state.serialize_field("detonate_tick", &self.detonate_tick)?;
+        state.serialize_field("enemy_damage", &self.enemy_damage)?;
+        state.serialize_field("team_damage", &self.team_damage)?;
+        state.serialize_field("enemies_hurt", &self.enemies_hurt)?;
+        state.serialize_field("teammates_hurt", &self.teammates_hurt)?;
+        state.serialize_field("enemies_flashed", &self.enemies_flashed)?;
+        state.serialize_field("teammates_flashed", &self.teammates_flashed)?;
+        state.serialize_field("enemy_blind_duration", &self.enemy_blind_duration)?;
+        state.serialize_field("team_blind_duration", &self.team_blind_duration)?;
+        state.serialize_field("kills_on_flashed", &self.kills_on_flashed)?;
+        state.serialize_field("flash_victims", &self.flash_victims)?;
+        state.end()
+    }
+}
+impl Serialize for FireCell {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("FireCell", 3)?;
+        state.serialize_field("x", &self.x)?;
+        state.serialize_field("y", &self.y)?;
+        state.serialize_field("z", &self.z)?;
+        state.end()
+    }
+}
+impl Serialize for InfernoFireRecord {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("InfernoFireRecord", 7)?;
+        let steamid = match self.thrower_steamid {
+            Some(u) => Some(u.to_string()),
+            None => None,
+        };
+        state.serialize_field("tick", &self.tick)?;
+        state.serialize_field("entity_id", &self.entity_id)?;
+        state.serialize_field("thrower_steamid", &steamid)?;
+        state.serialize_field("thrower_name", &self.thrower_name)?;
+        state.serialize_field("fire_count", &self.fire_count)?;
+        state.serialize_field("area", &self.area)?;
+        state.serialize_field("fires", &self.fires)?;
+        state.end()
+    }
+}
+impl Serialize for BombEvent {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("BombEvent", 12)?;
+        let steamid = match self.steamid {
+            Some(u) => Some(u.to_string()),
+            None => None,
+        };
+        state.serialize_field("tick", &self.tick)?;
+        state.serialize_field("event", self.kind.as_str())?;
+        state.serialize_field("steamid", &steamid)?;
+        state.serialize_field("name", &self.name)?;
+        state.serialize_field("x", &self.x)?;
+        state.serialize_field("y", &self.y)?;
+        state.serialize_field("z", &self.z)?;
+        state.serialize_field("site", &self.site)?;
+        state.serialize_field("has_kit", &self.has_kit)?;
+        state.serialize_field("bomb_time_remaining", &self.bomb_time_remaining)?;
+        state.serialize_field("defuse_time_remaining", &self.defuse_time_remaining)?;
+        state.serialize_field("game_time", &self.game_time)?;
+        state.end()
+    }
+}
+impl Serialize for VisibilityRecord {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("VisibilityRecord", 8)?;
+        let observer_steamid = match self.observer_steamid {
+            Some(u) => Some(u.to_string()),
+            None => None,
+        };
+        let target_steamid = match self.target_steamid {
+            Some(u) => Some(u.to_string()),
+            None => None,
+        };
+        state.serialize_field("tick", &self.tick)?;
+        state.serialize_field("observer_steamid", &observer_steamid)?;
+        state.serialize_field("observer_name", &self.observer_name)?;
+        state.serialize_field("target_steamid", &target_steamid)?;
+        state.serialize_field("target_name", &self.target_name)?;
+        state.serialize_field("line_of_sight", &self.line_of_sight)?;
+        state.serialize_field("angle", &self.angle)?;
+        state.serialize_field("distance", &self.distance)?;
+        state.end()
+    }
+}
+impl Serialize for SpottingEdge {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("SpottingEdge", 8)?;
+        state.serialize_field("tick", &self.tick)?;
+        state.serialize_field("round", &self.round)?;
+        state.serialize_field("spotter_steamid", &self.spotter_steamid.to_string())?;
+        state.serialize_field("spotter_name", &self.spotter_name)?;
+        state.serialize_field("spotted_steamid", &self.spotted_steamid.to_string())?;
+        state.serialize_field("spotted_name", &self.spotted_name)?;
+        state.serialize_field("event", if self.started { "started" } else { "ended" })?;
+        state.serialize_field("through_smoke", &self.through_smoke)?;
+        state.end()
+    }
+}
+impl Serialize for SpottingRoundRecord {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("SpottingRoundRecord", 9)?;
+        state.serialize_field("round", &self.round)?;
+        state.serialize_field("spotter_steamid", &self.spotter_steamid.to_string())?;
+        state.serialize_field("spotter_name", &self.spotter_name)?;
+        state.serialize_field("spotted_steamid", &self.spotted_steamid.to_string())?;
+        state.serialize_field("spotted_name", &self.spotted_name)?;
+        state.serialize_field("ticks_spotted", &self.ticks_spotted)?;
+        state.serialize_field("time_spotted", &self.time_spotted)?;
+        state.serialize_field("first_spot_tick", &self.first_spot_tick)?;
+        state.serialize_field("spotted_first", &self.spotted_first)?;
+        state.end()
+    }
+}
+impl Serialize for FovRecord {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("FovRecord", 11)?;
+        let observer_steamid = match self.observer_steamid {
+            Some(u) => Some(u.to_string()),
+            None => None,
+        };
+        let target_steamid = match self.target_steamid {
+            Some(u) => Some(u.to_string()),
+            None => None,
+        };
+        state.serialize_field("tick", &self.tick)?;
+        state.serialize_field("observer_steamid", &observer_steamid)?;
+        state.serialize_field("observer_name", &self.observer_name)?;
+        state.serialize_field("target_steamid", &target_steamid)?;
+        state.serialize_field("target_name", &self.target_name)?;
+        state.serialize_field("head_angle", &self.head_angle)?;
+        state.serialize_field("body_angle", &self.body_angle)?;
+        state.serialize_field("yaw_error", &self.yaw_error)?;
+        state.serialize_field("pitch_error", &self.pitch_error)?;
+        state.serialize_field("distance", &self.distance)?;
+        state.serialize_field("in_fov", &self.in_fov)?;
+        state.end()
+    }
+}
+impl Serialize for CrosshairPlacementRecord {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("CrosshairPlacementRecord", 11)?;
+        state.serialize_field("round", &self.round)?;
+        state.serialize_field("tick", &self.tick)?;
+        state.serialize_field("attacker_steamid", &self.attacker_steamid.to_string())?;
+        state.serialize_field("attacker_name", &self.attacker_name)?;
+        state.serialize_field("victim_steamid", &self.victim_steamid.to_string())?;
+        state.serialize_field("victim_name", &self.victim_name)?;
+        state.serialize_field("spotted_tick", &self.spotted_tick)?;
+        state.serialize_field("angle_error", &self.angle_error)?;
+        state.serialize_field("yaw_error", &self.yaw_error)?;
+        state.serialize_field("pitch_error", &self.pitch_error)?;
+        state.serialize_field("distance", &self.distance)?;
+        state.end()
+    }
+}
+impl Serialize for ShotRecord {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("ShotRecord", 21)?;
+        let victim_steamid = self.victim_steamid.map(|u| u.to_string());
+        state.serialize_field("tick", &self.tick)?;
+        state.serialize_field("round", &self.round)?;
+        state.serialize_field("steamid", &self.steamid.to_string())?;
+        state.serialize_field("name", &self.name)?;
+        state.serialize_field("weapon", &self.weapon)?;
+        state.serialize_field("recoil_index", &self.recoil_index)?;
+        state.serialize_field("inaccuracy", &self.inaccuracy)?;
+        state.serialize_field("speed", &self.speed)?;
+        state.serialize_field("movement", &self.movement)?;
+        state.serialize_field("burst_index", &self.burst_index)?;
+        state.serialize_field("hit", &self.hit)?;
+        state.serialize_field("victim_steamid", &victim_steamid)?;
+        state.serialize_field("victim_name", &self.victim_name)?;
+        state.serialize_field("hitgroup", &self.hitgroup)?;
+        state.serialize_field("damage", &self.damage)?;
+        state.serialize_field("players_hit", &self.players_hit)?;
+        state.serialize_field("impact_x", &self.impact.map(|p| p[0]))?;
+        state.serialize_field("impact_y", &self.impact.map(|p| p[1]))?;
+        state.serialize_field("impact_z", &self.impact.map(|p| p[2]))?;
+        state.serialize_field("shoot_pitch", &self.shoot_pitch)?;
+        state.serialize_field("shoot_yaw", &self.shoot_yaw)?;
+        state.end()
+    }
+}
+impl Serialize for AccuracyRecord {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("AccuracyRecord", 12)?;
+        state.serialize_field("steamid", &self.steamid.to_string())?;
+        state.serialize_field("name", &self.name)?;
+        state.serialize_field("weapon", &self.weapon)?;
+        state.serialize_field("shots", &self.shots)?;
+        state.serialize_field("hits", &self.hits)?;
+        state.serialize_field("accuracy", &self.accuracy)?;
+        state.serialize_field("first_bullet_shots", &self.first_bullet_shots)?;
+        state.serialize_field("first_bullet_hits", &self.first_bullet_hits)?;
+        state.serialize_field("first_bullet_accuracy", &self.first_bullet_accuracy)?;
+        state.serialize_field("spray_shots", &self.spray_shots)?;
+        state.serialize_field("spray_hits", &self.spray_hits)?;
+        state.serialize_field("spray_accuracy", &self.spray_accuracy)?;
+        state.end()
+    }
+}
+impl Serialize for SprayRecord {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("SprayRecord", 12)?;
+        state.serialize_field("spray_id", &self.spray_id)?;
+        state.serialize_field("steamid", &self.steamid.to_string())?;
+        state.serialize_field("name", &self.name)?;
+        state.serialize_field("weapon", &self.weapon)?;
+        state.serialize_field("round", &self.round)?;
+        state.serialize_field("start_tick", &self.start_tick)?;
+        state.serialize_field("end_tick", &self.end_tick)?;
+        state.serialize_field("bullets", &self.bullets)?;
+        state.serialize_field("hits", &self.hits)?;
+        state.serialize_field("angle_source", &self.angle_source)?;
+        state.serialize_field("mean_error", &self.mean_error)?;
+        state.serialize_field("max_error", &self.max_error)?;
+        state.end()
+    }
+}
+impl Serialize for SprayBulletRecord {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("SprayBulletRecord", 13)?;
+        state.serialize_field("spray_id", &self.spray_id)?;
+        state.serialize_field("steamid", &self.steamid.to_string())?;
+        state.serialize_field("tick", &self.tick)?;
+        state.serialize_field("bullet", &self.bullet)?;
+        state.serialize_field("recoil_index", &self.recoil_index)?;
+        state.serialize_field("view_pitch", &self.view_pitch)?;
+        state.serialize_field("view_yaw", &self.view_yaw)?;
+        state.serialize_field("aim_punch_pitch", &self.aim_punch_pitch)?;
+        state.serialize_field("aim_punch_yaw", &self.aim_punch_yaw)?;
+        state.serialize_field("ideal_pitch", &self.ideal_pitch)?;
+        state.serialize_field("ideal_yaw", &self.ideal_yaw)?;
+        state.serialize_field("error", &self.error)?;
+        state.serialize_field("hit", &self.hit)?;
+        state.end()
+    }
+}
+impl Serialize for UserCmdRecord {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("UserCmdRecord", 29)?;
+        state.serialize_field("tick", &self.tick)?;
+        state.serialize_field("steamid", &self.steamid.map(|s| s.to_string()))?;
+        state.serialize_field("name", &self.name)?;
+        state.serialize_field("entity_id", &self.entity_id)?;
+        state.serialize_field("legacy_command_number", &self.legacy_command_number)?;
+        state.serialize_field("client_tick", &self.client_tick)?;
+        state.serialize_field("buttonstate_1", &self.buttonstate_1.to_string())?;
+        state.serialize_field("buttonstate_2", &self.buttonstate_2.to_string())?;
+        state.serialize_field("buttonstate_3", &self.buttonstate_3.to_string())?;
+        state.serialize_field("viewangle_x", &self.viewangle_x)?;
+        state.serialize_field("viewangle_y", &self.viewangle_y)?;
+        state.serialize_field("viewangle_z", &self.viewangle_z)?;
+        state.serialize_field("forward_move", &self.forward_move)?;
+        state.serialize_field("left_move", &self.left_move)?;
+        state.serialize_field("up_move", &self.up_move)?;
+        state.serialize_field("impulse", &self.impulse)?;
+        state.serialize_field("weapon_select", &self.weapon_select)?;
+        state.serialize_field("random_seed", &self.random_seed)?;
+        state.serialize_field("mouse_dx", &self.mouse_dx)?;
+        state.serialize_field("mouse_dy", &self.mouse_dy)?;
+        state.serialize_field("consumed_server_angle_changes", &self.consumed_server_angle_changes)?;
+        state.serialize_field("cmd_flags", &self.cmd_flags)?;
+        state.serialize_field("move_crc", &self.move_crc)?;
+        state.serialize_field("subtick_moves", &self.subtick_moves)?;
+        state.serialize_field("input_history", &self.input_history)?;
+        state.serialize_field("attack_start_history_index_1", &self.attack_start_history_index_1)?;
+        state.serialize_field("attack_start_history_index_2", &self.attack_start_history_index_2)?;
+        state.serialize_field("attack_start_history_index_3", &self.attack_start_history_index_3)?;
+        state.serialize_field("left_hand_desired", &self.left_hand_desired)?;
+        state.end()
+    }
+}
+impl Serialize for MovementEvent {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("MovementEvent", 12)?;
+        state.serialize_field("tick", &self.tick)?;
+        state.serialize_field("round", &self.round)?;
+        state.serialize_field("steamid", &self.steamid.to_string())?;
+        state.serialize_field("name", &self.name)?;
+        state.serialize_field("event_type", &self.event_type)?;
+        state.serialize_field("start_tick", &self.start_tick)?;
+        state.serialize_field("duration", &self.duration)?;
+        state.serialize_field("speed", &self.speed)?;
+        state.serialize_field("weapon", &self.weapon)?;
+        state.serialize_field("opposing_key", &self.opposing_key)?;
+        state.serialize_field("keys_down", &self.keys_down)?;
+        state.end()
+    }
+}
+impl Serialize for EntityRecord {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(3 + self.props.len()))?;
+        map.serialize_entry(&"tick", &self.tick)?;
+        map.serialize_entry(&"entity_id", &self.entity_id)?;
+        map.serialize_entry(&"class_name", &self.class_name)?;
+        for field in &self.props {
+            map.serialize_entry(&field.name, &field.data)?;
+        }
+        map.end()
+    }
+}
+impl Serialize for WeaponOwnership {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("WeaponOwnership", 8)?;
+        state.serialize_field("tick", &self.tick)?;
+        state.serialize_field("reason", &self.reason)?;
+        state.serialize_field("steamid", &self.steamid.map(|s| s.to_string()))?;
+        state.serialize_field("name", &self.name)?;
+        state.serialize_field("x", &self.position.map(|p| p[0]))?;
+        state.serialize_field("y", &self.position.map(|p| p[1]))?;
+        state.serialize_field("z", &self.position.map(|p| p[2]))?;
+        state.serialize_field("kills", &self.kills)?;
+        state.end()
+    }
+}
+impl Serialize for WeaponProvenanceRecord {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("WeaponProvenanceRecord", 13)?;
+        state.serialize_field("entity_id", &self.entity_id)?;
+        state.serialize_field("weapon", &self.weapon)?;
+        state.serialize_field("round", &self.round)?;
+        state.serialize_field("created_tick", &self.created_tick)?;
+        state.serialize_field("deleted_tick", &self.deleted_tick)?;
+        state.serialize_field("purchaser_steamid", &self.purchaser_steamid.map(|s| s.to_string()))?;
+        state.serialize_field("purchaser_name", &self.purchaser_name)?;
+        state.serialize_field("purchase_tick", &self.purchase_tick)?;
+        state.serialize_field("original_owner_steamid", &self.original_owner_steamid.map(|s| s.to_string()))?;
+        state.serialize_field("owners", &self.owners)?;
+        state.serialize_field("kills", &self.kills)?;
+        state.serialize_field("survived_round", &self.survived_round)?;
+        state.end()
+    }
+}
+impl Serialize for GroundWeaponRecord {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("GroundWeaponRecord", 7)?;
+        state.serialize_field("tick", &self.tick)?;
+        state.serialize_field("entity_id", &self.entity_id)?;
+        state.serialize_field("weapon", &self.weapon)?;
+        state.serialize_field("x", &self.x)?;
+        state.serialize_field("y", &self.y)?;
+        state.serialize_field("z", &self.z)?;
+        state.serialize_field("original_owner_steamid", &self.original_owner_steamid.map(|s| s.to_string()))?;
+        state.end()
+    }
+}
+impl Serialize for BombTimeline {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("BombTimeline", 3)?;
+        state.serialize_field("round", &self.round)?;
+        state.serialize_field("outcome", &self.outcome)?;
+        state.serialize_field("events", &self.events)?;
+        state.end()
+    }
+}
 #[derive(Debug)]
 pub enum BytesVariant {
     Mmap(Mmap),
@@ -726,6 +1391,10 @@ pub fn soa_to_aos(soa: OutputSerdeHelperStruct) -> Vec<std::collections::HashMap
                         Some(f) => hm.insert(prop_info.prop_friendly_name.clone(), Some(Variant::InputHistory(f.clone()))),
                         _ => hm.insert(prop_info.prop_friendly_name.clone(), None),
                     },
+                    Some(VarVec::SubtickMoves(val)) => match val.get(idx) {
+                        Some(f) => hm.insert(prop_info.prop_friendly_name.clone(), Some(Variant::SubtickMoves(f.clone()))),
+                        _ => hm.insert(prop_info.prop_friendly_name.clone(), None),
+                    },
                 };
             }
         }
@@ -788,6 +1457,9 @@ impl Serialize for OutputSerdeHelperStruct {
                     Some(VarVec::InputHistory(val)) => {
                         map.serialize_entry(&prop_info.prop_friendly_name, val)?;
                     }
+                    Some(VarVec::SubtickMoves(val)) => {
+                        map.serialize_entry(&prop_info.prop_friendly_name, val)?;
+                    }
                     Some(VarVec::U64Vec(val)) => {
                         let string_sid = val
                             .iter()
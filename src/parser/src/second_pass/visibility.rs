@@ -0,0 +1,94 @@
+use crate::second_pass::parser_settings::SecondPassParser;
+use crate::second_pass::variants::Variant;
+
+#[derive(Debug, Clone)]
+pub struct VisibilityRecord {
+    pub tick: i32,
+    pub observer_steamid: Option<u64>,
+    pub observer_name: Option<String>,
+    pub target_steamid: Option<u64>,
+    pub target_name: Option<String>,
+    pub line_of_sight: bool,
+    // Angle in degrees between where the observer is looking and the eyes of the target
+    pub angle: Option<f32>,
+    pub distance: f32,
+}
+
+pub fn view_direction(pitch: f32, yaw: f32) -> [f32; 3] {
+    let (pitch, yaw) = (pitch.to_radians(), yaw.to_radians());
+    // Positive pitch looks down
+    [pitch.cos() * yaw.cos(), pitch.cos() * yaw.sin(), -pitch.sin()]
+}
+
+pub fn angle_between(a: [f32; 3], b: [f32; 3]) -> Option<f32> {
+    let len = (a[0] * a[0] + a[1] * a[1] + a[2] * a[2]).sqrt() * (b[0] * b[0] + b[1] * b[1] + b[2] * b[2]).sqrt();
+    if len == 0.0 {
+        return None;
+    }
+    let cos = ((a[0] * b[0] + a[1] * b[1] + a[2] * b[2]) / len).clamp(-1.0, 1.0);
+    Some(cos.acos().to_degrees())
+}
+
+impl<'a> SecondPassParser<'a> {
+    pub fn collect_visibility(&mut self) {
+        let (geometry, pairs) = match (&self.map_geometry, &self.visibility_pairs) {
+            (Some(geometry), Some(pairs)) => (geometry, pairs),
+            _ => return,
+        };
+        let mut alive = vec![];
+        for (entity_id, player) in &self.players {
+            if let (Ok(Variant::Bool(true)), Some(eyes)) = (self.find_is_alive(entity_id), self.find_eye_position(entity_id)) {
+                alive.push((*entity_id, player, eyes));
+            }
+        }
+        let mut records = vec![];
+        for (observer_id, observer, observer_eyes) in &alive {
+            for (target_id, target, target_eyes) in &alive {
+                if observer_id == target_id {
+                    continue;
+                }
+                // Empty list means every pair
+                if !pairs.is_empty() && !pairs.iter().any(|(o, t)| Some(*o) == observer.steamid && Some(*t) == target.steamid) {
+                    continue;
+                }
+                let to_target = [
+                    target_eyes[0] - observer_eyes[0],
+                    target_eyes[1] - observer_eyes[1],
+                    target_eyes[2] - observer_eyes[2],
+                ];
+                let angle = match (self.find_pitch_or_yaw(observer_id, 0), self.find_pitch_or_yaw(observer_id, 1)) {
+                    (Ok(Variant::F32(pitch)), Ok(Variant::F32(yaw))) => angle_between(view_direction(pitch, yaw), to_target),
+                    _ => None,
+                };
+                records.push(VisibilityRecord {
+                    tick: self.tick,
+                    observer_steamid: observer.steamid,
+                    observer_name: observer.name.clone(),
+                    target_steamid: target.steamid,
+                    target_name: target.name.clone(),
+                    line_of_sight: !geometry.raycast(*observer_eyes, *target_eyes),
+                    angle,
+                    distance: (to_target[0] * to_target[0] + to_target[1] * to_target[1] + to_target[2] * to_target[2]).sqrt(),
+                });
+            }
+        }
+        self.visibility.extend(records);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_angle_to_target() {
+        // Looking straight along +x
+        let forward = view_direction(0.0, 0.0);
+        assert!(angle_between(forward, [100.0, 0.0, 0.0]).unwrap() < 0.01);
+        assert!((angle_between(forward, [0.0, 100.0, 0.0]).unwrap() - 90.0).abs() < 0.01);
+        // Looking 45 degrees down at something below
+        let down = view_direction(45.0, 90.0);
+        assert!(angle_between(down, [0.0, 100.0, -100.0]).unwrap() < 0.01);
+        assert!(angle_between(forward, [0.0, 0.0, 0.0]).is_none());
+    }
+}
@@ -0,0 +1,488 @@
+use crate::first_pass::read_bits::DemoParserError;
+use crate::maps::WEAPINDICIES;
+use crate::second_pass::collect_data::CoordinateAxis;
+use crate::second_pass::game_events::DecodedGameEvent;
+use crate::second_pass::parser_settings::SecondPassParser;
+use crate::second_pass::variants::Variant;
+use ahash::AHashMap;
+
+// A weapon dropped at most this many ticks from its owner's death was dropped by the death
+const DEATH_DROP_WINDOW_TICKS: i32 = 2;
+// item_purchase and the weapon entity do not always arrive on the same tick
+const PURCHASE_WINDOW_TICKS: i32 = 8;
+// Kills with these were not made with the weapon the attacker was holding
+const INDIRECT_KILL_WEAPONS: [&str; 10] = [
+    "hegrenade",
+    "inferno",
+    "molotov",
+    "incgrenade",
+    "flashbang",
+    "smokegrenade",
+    "decoy",
+    "planted_c4",
+    "world",
+    "trigger_hurt",
+];
+
+pub fn is_weapon_class(class_name: &str) -> bool {
+    class_name.starts_with("CWeapon")
+        || matches!(
+            class_name,
+            "CAK47" | "CDEagle" | "CKnife" | "CC4" | "CFlashbang" | "CHEGrenade" | "CSmokeGrenade" | "CMolotovGrenade" | "CDecoyGrenade"
+        )
+}
+
+#[derive(Debug, Clone)]
+pub struct WeaponObservation {
+    pub tick: i32,
+    pub round: Option<i32>,
+    pub entity_id: i32,
+    pub weapon: String,
+    // None while the weapon is lying on the ground
+    pub owner_steamid: Option<u64>,
+    pub owner_name: Option<String>,
+    pub original_owner_steamid: Option<u64>,
+    // Owner's position while held, the weapon's own position on the ground
+    pub position: Option<[f32; 3]>,
+}
+
+// Collected per chunk and merged once every chunk is done, a weapon often lives
+// across chunk boundaries (bought in one, dropped in the next)
+#[derive(Debug, Clone)]
+pub enum WeaponInput {
+    Created {
+        tick: i32,
+        entity_id: i32,
+    },
+    Deleted {
+        tick: i32,
+        entity_id: i32,
+    },
+    // Only pushed when the owner changes (and once per weapon at the start of every chunk)
+    Observation(WeaponObservation),
+    Purchase {
+        tick: i32,
+        steamid: u64,
+        name: Option<String>,
+    },
+    Death {
+        tick: i32,
+        victim_steamid: Option<u64>,
+        attacker_steamid: Option<u64>,
+        // Weapon the attacker was holding when the kill was made
+        weapon_entity_id: Option<i32>,
+    },
+    RoundEnd {
+        tick: i32,
+    },
+    FreezeEnd {
+        tick: i32,
+    },
+}
+
+impl WeaponInput {
+    fn tick(&self) -> i32 {
+        match self {
+            WeaponInput::Created { tick, .. } => *tick,
+            WeaponInput::Deleted { tick, .. } => *tick,
+            WeaponInput::Observation(observation) => observation.tick,
+            WeaponInput::Purchase { tick, .. } => *tick,
+            WeaponInput::Death { tick, .. } => *tick,
+            WeaponInput::RoundEnd { tick } => *tick,
+            WeaponInput::FreezeEnd { tick } => *tick,
+        }
+    }
+    // A weapon has to exist and have its owner before kills on the same tick are counted
+    fn order(&self) -> i32 {
+        match self {
+            WeaponInput::Created { .. } => 0,
+            WeaponInput::Observation(_) => 1,
+            WeaponInput::Death { .. } => 2,
+            WeaponInput::Deleted { .. } => 3,
+            _ => 4,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WeaponOwnership {
+    pub tick: i32,
+    // purchase, spawn or existing for the first entry, then pickup, drop or death_drop
+    pub reason: String,
+    // None while the weapon is lying on the ground
+    pub steamid: Option<u64>,
+    pub name: Option<String>,
+    pub position: Option<[f32; 3]>,
+    // Kills made with the weapon while this owner had it
+    pub kills: i32,
+}
+
+#[derive(Debug, Clone)]
+pub struct WeaponProvenanceRecord {
+    pub entity_id: i32,
+    pub weapon: String,
+    pub round: Option<i32>,
+    // None if the weapon already existed when the demo (or the recording) started
+    pub created_tick: Option<i32>,
+    pub deleted_tick: Option<i32>,
+    pub purchaser_steamid: Option<u64>,
+    pub purchaser_name: Option<String>,
+    pub purchase_tick: Option<i32>,
+    pub original_owner_steamid: Option<u64>,
+    pub owners: Vec<WeaponOwnership>,
+    pub kills: i32,
+    // Whether the weapon still existed when the next round went live, None if the demo ends before
+    pub survived_round: Option<bool>,
+}
+
+#[derive(Debug, Clone)]
+pub struct GroundWeaponRecord {
+    pub tick: i32,
+    pub entity_id: i32,
+    pub weapon: String,
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub original_owner_steamid: Option<u64>,
+}
+
+impl<'a> SecondPassParser<'a> {
+    pub fn collect_weapon_owners(&mut self) {
+        let round = match self.find_current_round() {
+            Some(Variant::I32(round)) => Some(round),
+            _ => None,
+        };
+        let mut observations = vec![];
+        for entity_id in &self.weapons {
+            let weapon = match self.find_weapon_entity_name(entity_id) {
+                Some(weapon) => weapon,
+                None => continue,
+            };
+            let owner_entity_id = self.find_weapon_owner(entity_id);
+            let owner = owner_entity_id.and_then(|id| self.players.get(&id));
+            let owner_steamid = owner.and_then(|player| player.steamid);
+            if self.weapon_owners.get(entity_id) == Some(&owner_steamid) {
+                continue;
+            }
+            observations.push(WeaponObservation {
+                tick: self.tick,
+                round,
+                entity_id: *entity_id,
+                weapon,
+                owner_steamid,
+                owner_name: owner.and_then(|player| player.name.clone()),
+                original_owner_steamid: self.find_original_owner_steamid(entity_id),
+                position: match owner_entity_id {
+                    Some(owner_entity_id) if owner.is_some() => self.find_position(&owner_entity_id),
+                    _ => self.find_weapon_position(entity_id),
+                },
+            });
+        }
+        for observation in observations {
+            self.weapon_owners.insert(observation.entity_id, observation.owner_steamid);
+            self.weapon_inputs.push(WeaponInput::Observation(observation));
+        }
+    }
+    pub fn collect_ground_weapons(&mut self) {
+        let mut records = vec![];
+        for entity_id in &self.weapons {
+            if self.find_weapon_owner(entity_id).is_some_and(|id| self.players.contains_key(&id)) {
+                continue;
+            }
+            if let (Some(weapon), Some([x, y, z])) = (self.find_weapon_entity_name(entity_id), self.find_weapon_position(entity_id)) {
+                records.push(GroundWeaponRecord {
+                    tick: self.tick,
+                    entity_id: *entity_id,
+                    weapon,
+                    x,
+                    y,
+                    z,
+                    original_owner_steamid: self.find_original_owner_steamid(entity_id),
+                });
+            }
+        }
+        self.ground_weapons.extend(records);
+    }
+    pub fn collect_weapon_event(&mut self, event: &DecodedGameEvent) -> Result<(), DemoParserError> {
+        let find_entity_id = |name: &str| match event.key(name) {
+            Some(Variant::I32(userid)) => self.entity_id_from_userid(userid),
+            _ => None,
+        };
+        let input = match event.name() {
+            "item_purchase" => {
+                let player = find_entity_id("userid").and_then(|id| self.players.get(&id));
+                match (player.and_then(|p| p.steamid), event.key("weapon")) {
+                    // Armor and kits are bought too but never become weapon entities
+                    (Some(steamid), Some(Variant::String(weapon))) if !weapon.starts_with("item_") => WeaponInput::Purchase {
+                        tick: self.tick,
+                        steamid,
+                        name: player.and_then(|p| p.name.clone()),
+                    },
+                    _ => return Ok(()),
+                }
+            }
+            "player_death" => {
+                let attacker = find_entity_id("attacker");
+                let weapon_entity_id = match event.key("weapon") {
+                    Some(Variant::String(weapon)) if INDIRECT_KILL_WEAPONS.contains(&weapon.as_str()) => None,
+                    _ => attacker.and_then(|id| self.find_active_weapon_entity_id(&id)),
+                };
+                WeaponInput::Death {
+                    tick: self.tick,
+                    victim_steamid: find_entity_id("userid").and_then(|id| self.players.get(&id)).and_then(|p| p.steamid),
+                    attacker_steamid: attacker.and_then(|id| self.players.get(&id)).and_then(|p| p.steamid),
+                    weapon_entity_id,
+                }
+            }
+            "round_end" => WeaponInput::RoundEnd { tick: self.tick },
+            "round_freeze_end" => WeaponInput::FreezeEnd { tick: self.tick },
+            _ => return Ok(()),
+        };
+        self.weapon_inputs.push(input);
+        Ok(())
+    }
+    pub fn find_weapon_entity_name(&self, entity_id: &i32) -> Option<String> {
+        let item_def_id = self.prop_controller.special_ids.item_def?;
+        match self.get_prop_from_ent(&item_def_id, entity_id) {
+            Ok(Variant::U32(def_idx)) => WEAPINDICIES.get(&def_idx).map(|name| name.to_string()),
+            _ => None,
+        }
+    }
+    // Player pawn entity id from m_hOwnerEntity, None when nobody holds the weapon
+    fn find_weapon_owner(&self, entity_id: &i32) -> Option<i32> {
+        let prop_id = self.prop_controller.special_ids.h_owner_entity?;
+        match self.get_prop_from_ent(&prop_id, entity_id) {
+            Ok(Variant::U32(handle)) if handle & 0x7FF != 0x7FF => Some((handle & 0x7FF) as i32),
+            _ => None,
+        }
+    }
+    fn find_active_weapon_entity_id(&self, player_entity_id: &i32) -> Option<i32> {
+        let prop_id = self.prop_controller.special_ids.active_weapon?;
+        match self.get_prop_from_ent(&prop_id, player_entity_id) {
+            Ok(Variant::U32(handle)) if handle & 0x7FF != 0x7FF => Some((handle & 0x7FF) as i32),
+            _ => None,
+        }
+    }
+    // Same as find_weapon_original_owner but read from the weapon entity instead of the active weapon
+    fn find_original_owner_steamid(&self, entity_id: &i32) -> Option<u64> {
+        let low_id = self.prop_controller.special_ids.orig_own_low?;
+        let high_id = self.prop_controller.special_ids.orig_own_high?;
+        match (self.get_prop_from_ent(&low_id, entity_id), self.get_prop_from_ent(&high_id, entity_id)) {
+            (Ok(Variant::U32(low)), Ok(Variant::U32(high))) if low != 0 || high != 0 => Some((high as u64) << 32 | low as u64),
+            _ => None,
+        }
+    }
+    fn find_weapon_position(&self, entity_id: &i32) -> Option<[f32; 3]> {
+        match (
+            self.collect_cell_coordinate_grenade(CoordinateAxis::X, entity_id),
+            self.collect_cell_coordinate_grenade(CoordinateAxis::Y, entity_id),
+            self.collect_cell_coordinate_grenade(CoordinateAxis::Z, entity_id),
+        ) {
+            (Ok(Variant::F32(x)), Ok(Variant::F32(y)), Ok(Variant::F32(z))) => Some([x, y, z]),
+            _ => None,
+        }
+    }
+}
+
+pub fn create_weapon_provenance(mut inputs: Vec<WeaponInput>) -> Vec<WeaponProvenanceRecord> {
+    inputs.sort_by_key(|input| (input.tick(), input.order()));
+    let mut deaths = vec![];
+    let mut purchases = vec![];
+    let mut round_ends = vec![];
+    let mut freeze_ends = vec![];
+    for input in &inputs {
+        match input {
+            WeaponInput::Death {
+                tick,
+                victim_steamid: Some(victim),
+                ..
+            } => deaths.push((*tick, *victim)),
+            WeaponInput::Purchase { tick, steamid, name } => purchases.push((*tick, *steamid, name.clone(), false)),
+            WeaponInput::RoundEnd { tick } => round_ends.push(*tick),
+            WeaponInput::FreezeEnd { tick } => freeze_ends.push(*tick),
+            _ => {}
+        }
+    }
+    let mut open: AHashMap<i32, WeaponProvenanceRecord> = AHashMap::default();
+    let mut records = vec![];
+    for input in inputs {
+        match input {
+            WeaponInput::Created { tick, entity_id } => {
+                if let Some(mut record) = open.remove(&entity_id) {
+                    record.deleted_tick = Some(tick);
+                    records.push(record);
+                }
+                open.insert(entity_id, new_record(entity_id, Some(tick)));
+            }
+            WeaponInput::Deleted { tick, entity_id } => {
+                if let Some(mut record) = open.remove(&entity_id) {
+                    record.deleted_tick = Some(tick);
+                    records.push(record);
+                }
+            }
+            WeaponInput::Observation(observation) => {
+                // Entity id reused without us seeing the delete (between chunks)
+                if open
+                    .get(&observation.entity_id)
+                    .is_some_and(|r| !r.weapon.is_empty() && r.weapon != observation.weapon)
+                {
+                    if let Some(record) = open.remove(&observation.entity_id) {
+                        records.push(record);
+                    }
+                }
+                let record = open.entry(observation.entity_id).or_insert_with(|| new_record(observation.entity_id, None));
+                if record.weapon.is_empty() {
+                    record.weapon = observation.weapon.clone();
+                    record.round = observation.round;
+                }
+                if record.original_owner_steamid.is_none() {
+                    record.original_owner_steamid = observation.original_owner_steamid;
+                }
+                let reason = match record.owners.last() {
+                    Some(last) if last.steamid == observation.owner_steamid => continue,
+                    None if record.created_tick.is_some() => "spawn",
+                    None => "existing",
+                    Some(last) => match (last.steamid, observation.owner_steamid) {
+                        (Some(previous), None)
+                            if deaths
+                                .iter()
+                                .any(|(t, s)| *s == previous && (t - observation.tick).abs() <= DEATH_DROP_WINDOW_TICKS) =>
+                        {
+                            "death_drop"
+                        }
+                        (Some(_), None) => "drop",
+                        _ => "pickup",
+                    },
+                };
+                record.owners.push(WeaponOwnership {
+                    tick: observation.tick,
+                    reason: reason.to_string(),
+                    steamid: observation.owner_steamid,
+                    name: observation.owner_name,
+                    position: observation.position,
+                    kills: 0,
+                });
+            }
+            WeaponInput::Death {
+                attacker_steamid: Some(attacker),
+                weapon_entity_id: Some(entity_id),
+                ..
+            } => {
+                if let Some(record) = open.get_mut(&entity_id) {
+                    if let Some(owner) = record.owners.last_mut().filter(|owner| owner.steamid == Some(attacker)) {
+                        owner.kills += 1;
+                        record.kills += 1;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    records.extend(open.into_values());
+    records.sort_by_key(|r| (r.created_tick.or(r.owners.first().map(|o| o.tick)), r.entity_id));
+    for record in &mut records {
+        if let (Some(created), Some(original_owner)) = (record.created_tick, record.original_owner_steamid) {
+            let purchase = purchases
+                .iter_mut()
+                .filter(|(tick, steamid, _, used)| !used && *steamid == original_owner && (tick - created).abs() <= PURCHASE_WINDOW_TICKS)
+                .min_by_key(|(tick, _, _, _)| (tick - created).abs());
+            if let Some((tick, steamid, name, used)) = purchase {
+                *used = true;
+                record.purchaser_steamid = Some(*steamid);
+                record.purchaser_name = name.clone();
+                record.purchase_tick = Some(*tick);
+                if let Some(first) = record.owners.first_mut() {
+                    first.reason = "purchase".to_string();
+                }
+            }
+        }
+        let start = match record.created_tick.or(record.owners.first().map(|o| o.tick)) {
+            Some(start) => start,
+            None => continue,
+        };
+        // Weapons left on the ground are removed when the next round starts, held ones are kept
+        let next_round_live = round_ends
+            .iter()
+            .find(|tick| **tick >= start)
+            .and_then(|round_end| freeze_ends.iter().find(|tick| *tick > round_end));
+        record.survived_round = match (next_round_live, record.deleted_tick) {
+            (Some(live), deleted) => Some(deleted.is_none_or(|deleted| deleted > *live)),
+            (None, Some(_)) => Some(false),
+            (None, None) => None,
+        };
+    }
+    records
+}
+
+fn new_record(entity_id: i32, created_tick: Option<i32>) -> WeaponProvenanceRecord {
+    WeaponProvenanceRecord {
+        entity_id,
+        weapon: String::new(),
+        round: None,
+        created_tick,
+        deleted_tick: None,
+        purchaser_steamid: None,
+        purchaser_name: None,
+        purchase_tick: None,
+        original_owner_steamid: None,
+        owners: vec![],
+        kills: 0,
+        survived_round: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn observation(tick: i32, owner: Option<u64>) -> WeaponInput {
+        WeaponInput::Observation(WeaponObservation {
+            tick,
+            round: Some(1),
+            entity_id: 100,
+            weapon: "AWP".to_string(),
+            owner_steamid: owner,
+            owner_name: None,
+            original_owner_steamid: Some(1),
+            position: Some([0.0, 0.0, 0.0]),
+        })
+    }
+
+    #[test]
+    fn test_weapon_provenance() {
+        let death = |tick, victim, attacker| WeaponInput::Death {
+            tick,
+            victim_steamid: Some(victim),
+            attacker_steamid: Some(attacker),
+            weapon_entity_id: Some(100),
+        };
+        let inputs = vec![
+            WeaponInput::Purchase {
+                tick: 10,
+                steamid: 1,
+                name: Some("buyer".to_string()),
+            },
+            WeaponInput::Created { tick: 11, entity_id: 100 },
+            observation(11, Some(1)),
+            WeaponInput::FreezeEnd { tick: 20 },
+            death(30, 5, 1),
+            death(40, 1, 5),
+            observation(40, None),
+            // Start of the next chunk sees the same owner again
+            observation(41, None),
+            observation(50, Some(2)),
+            death(60, 6, 2),
+            WeaponInput::RoundEnd { tick: 70 },
+            WeaponInput::FreezeEnd { tick: 90 },
+        ];
+        let records = create_weapon_provenance(inputs);
+        assert_eq!(records.len(), 1);
+        let record = &records[0];
+        assert_eq!(record.purchaser_steamid, Some(1));
+        assert_eq!(record.purchase_tick, Some(10));
+        assert_eq!(record.kills, 2);
+        assert_eq!(record.survived_round, Some(true));
+        let owners: Vec<(&str, Option<u64>, i32)> = record.owners.iter().map(|o| (o.reason.as_str(), o.steamid, o.kills)).collect();
+        assert_eq!(owners, vec![("purchase", Some(1), 1), ("death_drop", None, 0), ("pickup", Some(2), 1)]);
+    }
+}
@@ -1,11 +1,56 @@
 import pandas as pd
-from typing import Dict, Sequence, Optional, List, Tuple
+from typing import Any, Dict, Sequence, Optional, List, Tuple, Union
+
+class ChangeLog:
+    def changes(self) -> pd.DataFrame: ...
+    def to_dense(self, ticks: Optional[Sequence[int]] = None) -> pd.DataFrame: ...
 
 class DemoParser:
-    def __init__(self, path: str) -> None: ...
+    def __init__(
+        self,
+        path: str,
+        *,
+        threads: Optional[int] = None,
+        chunks: Optional[int] = None,
+        chunk_size: Optional[int] = None,
+        fallback_timeout: Optional[float] = None,
+    ) -> None: ...
     def parse_header(self) -> Dict[str, str]: ...
     def list_game_events(self) -> List[str]: ...
     def parse_grenades(self) -> pd.DataFrame: ...
+    def parse_money_ledger(self) -> pd.DataFrame: ...
+    def parse_grenade_lifecycles(self, *, trajectory_step: Optional[int] = None) -> pd.DataFrame: ...
+    def parse_utility_effects(self) -> pd.DataFrame: ...
+    def parse_infernos(self) -> pd.DataFrame: ...
+    def parse_bomb(self) -> pd.DataFrame: ...
+    def parse_spotting(self) -> Tuple[pd.DataFrame, pd.DataFrame]: ...
+    def parse_fov(
+        self,
+        ticks: Optional[Sequence[int]] = None,
+        fov: Optional[float] = None,
+    ) -> Tuple[pd.DataFrame, pd.DataFrame]: ...
+    def parse_shots(self) -> Tuple[pd.DataFrame, pd.DataFrame]: ...
+    def parse_sprays(self) -> Tuple[pd.DataFrame, pd.DataFrame]: ...
+    def parse_usercmds(self) -> pd.DataFrame: ...
+    def parse_movement(self) -> pd.DataFrame: ...
+    def parse_entities(
+        self,
+        class_names: Sequence[str],
+        props: Sequence[str],
+        *,
+        ticks: Optional[Sequence[int]] = None,
+    ) -> pd.DataFrame: ...
+    def parse_weapons(
+        self,
+        *,
+        ticks: Optional[Sequence[int]] = None,
+    ) -> Tuple[pd.DataFrame, pd.DataFrame, pd.DataFrame]: ...
+    def parse_visibility(
+        self,
+        geometry_path: str,
+        pairs: Optional[Sequence[Tuple[int, int]]] = None,
+        ticks: Optional[Sequence[int]] = None,
+    ) -> pd.DataFrame: ...
     def parse_player_info(self) -> pd.DataFrame: ...
     def parse_item_drops(self) -> pd.DataFrame: ...
     def parse_skins(self) -> pd.DataFrame: ...
@@ -14,12 +59,20 @@ class DemoParser:
         event_name: str,
         player: Optional[Sequence[str]] = None,
         other: Optional[Sequence[str]] = None,
+        nav_path: Optional[str] = None,
+        kill_context: bool = False,
+        entity_classes: Optional[Sequence[str]] = None,
+        prop_changes: Optional[Sequence[str]] = None,
     ) -> pd.DataFrame: ...
     def parse_events(
         self,
         event_name: Sequence[str],
         player: Optional[Sequence[str]] = None,
         other: Optional[Sequence[str]] = None,
+        nav_path: Optional[str] = None,
+        kill_context: bool = False,
+        entity_classes: Optional[Sequence[str]] = None,
+        prop_changes: Optional[Sequence[str]] = None,
     ) -> List[Tuple[str, pd.DataFrame]]: ...
     def parse_voice(self) -> Dict[str, bytes]: ...
     def parse_ticks(
@@ -27,7 +80,11 @@ class DemoParser:
         wanted_props: Sequence[str],
         players: Optional[Sequence[int]] = None,
         ticks: Optional[Sequence[int]] = None,
-    ) -> pd.DataFrame:
+        prop_states: Optional[Union[str, Sequence[Any]]] = None,
+        overview_path: Optional[str] = None,
+        nav_path: Optional[str] = None,
+        change_log: bool = False,
+    ) -> Union[pd.DataFrame, ChangeLog]:
         """Parse the specified props.
 
         Args:
@@ -36,6 +93,18 @@ class DemoParser:
                 `None` or an empty Sequence means all players. Defaults to `None`.
             ticks (Optional[Sequence[int]]): Sequence of ticks to parse.
                 `None` or an empty Sequence means all ticks. Defaults to `None`.
+            prop_states (Optional[Union[str, Sequence[Any]]]): Only keep rows matching a filter
+                expression like `"is_alive == true AND health < 30"`, or a sequence of objects
+                with `prop` and `state` attributes that all have to match. Rows are filtered per
+                player, other players on the same tick are kept. Defaults to `None`.
+            overview_path (Optional[str]): Path to a map overview `.txt` used for the radar_x,
+                radar_y and radar_level props. Only needed for maps without built-in
+                overview metadata. Defaults to `None`.
+            nav_path (Optional[str]): Path to the map's `.nav` file, needed for the nav_area_id
+                prop. CS2 nav files have no place names so nav_place is always `None`, use
+                last_place_name for the game's own place. Defaults to `None`.
+            change_log (bool): Return a `ChangeLog` that only keeps the ticks a value
+                changes on instead of the dense DataFrame. Defaults to `False`.
 
         Returns:
             pd.DataFrame: Dataframe of all the parsed props for each player at each tick.
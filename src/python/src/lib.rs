@@ -2,14 +2,26 @@ use ahash::AHashMap;
 use itertools::Itertools;
 use memmap2::Mmap;
 use parser::first_pass::parser_settings::create_mmap;
-use parser::first_pass::parser_settings::rm_map_user_friendly_names;
+use parser::first_pass::parser_settings::rm_filter_user_friendly_names;
 use parser::first_pass::parser_settings::rm_user_friendly_names;
+use parser::first_pass::parser_settings::Chunking;
+use parser::first_pass::parser_settings::ParallelismSettings;
 use parser::first_pass::parser_settings::ParserInputs;
+use parser::first_pass::prop_controller::PropInfo;
+use parser::first_pass::prop_filter::PropFilter;
 use parser::first_pass::read_bits::DemoParserError;
+use parser::maps::geometry::TriangleMesh;
+use parser::maps::nav::NavMesh;
+use parser::maps::overview::MapOverview;
 use parser::parse_demo::Parser;
+use parser::second_pass::fov::DEFAULT_FOV;
 use parser::second_pass::game_events::EventField;
 use parser::second_pass::game_events::GameEvent;
 use parser::second_pass::parser_settings::create_huffman_lookup_table;
+use parser::second_pass::variants::InputHistory;
+use parser::second_pass::variants::InterpolationInfo;
+use parser::second_pass::variants::PropColumn;
+use parser::second_pass::variants::SubtickMove;
 use parser::second_pass::variants::VarVec;
 use parser::second_pass::variants::Variant;
 #[cfg(feature = "voice")]
@@ -32,6 +44,7 @@ use pyo3::types::PyList;
 use pyo3::Python;
 use pyo3::{PyAny, PyObject, PyResult};
 use std::sync::Arc;
+use std::time::Duration;
 
 use pyo3::create_exception;
 create_exception!(DemoParser, Exception, pyo3::exceptions::PyException);
@@ -70,32 +83,1639 @@ struct WantedPropState {
 
 #[pymethods]
 impl DemoParser {
+    /// threads limits the parse to its own pool of that many threads instead of sharing
+    /// the global one. chunks or chunk_size (bytes) control how the file is split while
+    /// looking for fullpackets and fallback_timeout (seconds) how long to wait for the
+    /// next chunk before falling back to a full first pass.
     #[new]
-    pub fn py_new(demo_path: String) -> PyResult<Self> {
+    #[pyo3(signature = (demo_path, *, threads=None, chunks=None, chunk_size=None, fallback_timeout=None))]
+    pub fn py_new(
+        demo_path: String,
+        threads: Option<usize>,
+        chunks: Option<usize>,
+        chunk_size: Option<usize>,
+        fallback_timeout: Option<f64>,
+    ) -> PyResult<Self> {
         let mmap = match create_mmap(demo_path.clone()) {
             Ok(mmap) => mmap,
             Err(e) => return Err(Exception::new_err(format!("{e}. File name: {demo_path}"))),
         };
         let huf = create_huffman_lookup_table();
-        Ok(Self { mmap, huf })
+        let mut parallelism = ParallelismSettings::default();
+        parallelism.num_threads = threads;
+        match (chunks, chunk_size) {
+            (Some(_), Some(_)) => return Err(PyValueError::new_err("chunks and chunk_size can not both be set")),
+            (Some(n), None) => parallelism.chunking = Chunking::Count(n),
+            (None, Some(size)) => parallelism.chunking = Chunking::TargetSize(size),
+            (None, None) => {}
+        }
+        if let Some(seconds) = fallback_timeout {
+            parallelism.fallback_timeout = match Duration::try_from_secs_f64(seconds) {
+                Ok(timeout) => timeout,
+                Err(e) => return Err(PyValueError::new_err(format!("invalid fallback_timeout: {e}"))),
+            };
+        }
+        Ok(Self { mmap, huf, parallelism })
+    }
+
+    /// Parses header message (different from the first 16 bytes of the file)
+    /// Should have the following fields:
+    ///
+    /// "addons", "server_name", "demo_file_stamp", "network_protocol",
+    /// "map_name", "fullpackets_version", "allow_clientside_entities",
+    /// "allow_clientside_particles", "demo_version_name", "demo_version_guid",
+    /// "client_name", "game_directory"
+    pub fn parse_header(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        let settings = ParserInputs {
+            real_name_to_og_name: AHashMap::default(),
+            wanted_players: vec![],
+            wanted_player_props: vec![],
+            wanted_other_props: vec![],
+            wanted_prop_states: PropFilter::default(),
+            wanted_events: vec![],
+            parse_ents: false,
+            wanted_ticks: vec![],
+            parse_projectiles: false,
+            only_header: true,
+            count_props: false,
+            only_convars: false,
+            huffman_lookup_table: &self.huf,
+            order_by_steamid: false,
+            change_log: false,
+            parse_money_ledger: false,
+            parse_bomb: false,
+            parse_spotting: false,
+            parse_kill_context: false,
+            parse_shots: false,
+            parse_sprays: false,
+            parse_usercmds: false,
+            parse_movement: false,
+            parse_weapons: false,
+            wanted_entity_classes: vec![],
+            wanted_entity_props: vec![],
+            entity_event_classes: vec![],
+            wanted_prop_changes: vec![],
+            map_overview: None,
+            nav_mesh: None,
+            map_geometry: None,
+            visibility_pairs: None,
+            fov: None,
+            parallelism: self.parallelism.clone(),
+        };
+        let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
+        let output = match parser.parse_demo(&self.mmap) {
+            Ok(output) => output,
+            Err(e) => return Err(Exception::new_err(format!("{e}"))),
+        };
+        Ok(output
+            .header
+            .unwrap_or_else(AHashMap::default)
+            .to_object(py))
+    }
+    /// Returns the names of game events present in the demo
+    pub fn list_game_events(&self, _py: Python<'_>) -> PyResult<Py<PyAny>> {
+        let settings = ParserInputs {
+            real_name_to_og_name: AHashMap::default(),
+            wanted_players: vec![],
+            wanted_player_props: vec![],
+            wanted_other_props: vec![],
+            wanted_prop_states: PropFilter::default(),
+            wanted_events: vec!["all".to_string()],
+            parse_ents: false,
+            wanted_ticks: vec![],
+            parse_projectiles: false,
+            only_header: true,
+            count_props: false,
+            only_convars: false,
+            huffman_lookup_table: &self.huf,
+            order_by_steamid: false,
+            change_log: false,
+            parse_money_ledger: false,
+            parse_bomb: false,
+            parse_spotting: false,
+            parse_kill_context: false,
+            parse_shots: false,
+            parse_sprays: false,
+            parse_usercmds: false,
+            parse_movement: false,
+            parse_weapons: false,
+            wanted_entity_classes: vec![],
+            wanted_entity_props: vec![],
+            entity_event_classes: vec![],
+            wanted_prop_changes: vec![],
+            map_overview: None,
+            nav_mesh: None,
+            map_geometry: None,
+            visibility_pairs: None,
+            fov: None,
+            parallelism: self.parallelism.clone(),
+        };
+        let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
+        let output = match parser.parse_demo(&self.mmap) {
+            Ok(output) => output,
+            Err(e) => return Err(Exception::new_err(format!("{e}"))),
+        };
+        let as_vec = output.game_events_counter.iter().collect_vec();
+        let ge = pyo3::Python::with_gil(|py| as_vec.to_object(py));
+        Ok(ge)
+    }
+
+    /// Returns all coordinates of all grenades along with info about thrower.
+    ///
+    /// Example:
+    ///          X           Y       Z  tick  thrower_steamid grenade_type
+    /// 0 -388.875  1295.46875 -5120.0   982              NaN    HeGrenade
+    /// 1 -388.875  1295.46875 -5120.0   983              NaN    HeGrenade
+    /// 2 -388.875  1295.46875 -5120.0   983              NaN    HeGrenade
+
+    pub fn parse_grenades(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        let settings = ParserInputs {
+            real_name_to_og_name: AHashMap::default(),
+            wanted_players: vec![],
+            wanted_player_props: vec![],
+            wanted_other_props: vec![],
+            wanted_prop_states: PropFilter::default(),
+            wanted_events: vec![],
+            parse_ents: true,
+            wanted_ticks: vec![],
+            parse_projectiles: true,
+            only_header: true,
+            count_props: false,
+            only_convars: false,
+            huffman_lookup_table: &self.huf,
+            order_by_steamid: false,
+            change_log: false,
+            parse_money_ledger: false,
+            parse_bomb: false,
+            parse_spotting: false,
+            parse_kill_context: false,
+            parse_shots: false,
+            parse_sprays: false,
+            parse_usercmds: false,
+            parse_movement: false,
+            parse_weapons: false,
+            wanted_entity_classes: vec![],
+            wanted_entity_props: vec![],
+            entity_event_classes: vec![],
+            wanted_prop_changes: vec![],
+            map_overview: None,
+            nav_mesh: None,
+            map_geometry: None,
+            visibility_pairs: None,
+            fov: None,
+            parallelism: self.parallelism.clone(),
+        };
+        let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
+        let output = match parser.parse_demo(&self.mmap) {
+            Ok(output) => output,
+            Err(e) => return Err(Exception::new_err(format!("{e}"))),
+        };
+
+        let entity_id: Vec<Option<i32>> = output.projectiles.iter().map(|s| s.entity_id).collect();
+        let xs: Vec<Option<f32>> = output.projectiles.iter().map(|s| s.x).collect();
+        let ys: Vec<Option<f32>> = output.projectiles.iter().map(|s| s.y).collect();
+        let zs: Vec<Option<f32>> = output.projectiles.iter().map(|s| s.z).collect();
+
+        let ticks: Vec<Option<i32>> = output.projectiles.iter().map(|s| s.tick).collect();
+        let steamid: Vec<Option<u64>> = output.projectiles.iter().map(|s| s.steamid).collect();
+        let name: Vec<Option<String>> = output.projectiles.iter().map(|s| s.name.clone()).collect();
+        let grenade_type: Vec<Option<String>> = output
+            .projectiles
+            .iter()
+            .map(|s| s.grenade_type.clone())
+            .collect();
+
+        // SoA form
+        let xs = arr_to_py(Box::new(Float32Array::from(xs))).unwrap();
+        let ys = arr_to_py(Box::new(Float32Array::from(ys))).unwrap();
+        let zs = arr_to_py(Box::new(Float32Array::from(zs))).unwrap();
+        // Actually not sure about Z coordinate. Leave out for now.
+        let ticks = arr_to_py(Box::new(Int32Array::from(ticks))).unwrap();
+        let grenade_type = arr_to_py(Box::new(Utf8Array::<i32>::from(grenade_type))).unwrap();
+        let name = arr_to_py(Box::new(Utf8Array::<i32>::from(name))).unwrap();
+        let steamids = arr_to_py(Box::new(UInt64Array::from(steamid))).unwrap();
+        let entity_ids = arr_to_py(Box::new(Int32Array::from(entity_id))).unwrap();
+
+        let polars = py.import_bound("polars")?;
+        let all_series_py =
+            [xs, ys, zs, ticks, steamids, name, grenade_type, entity_ids].to_object(py);
+        Python::with_gil(|py| {
+            let df = polars.call_method1("DataFrame", (all_series_py,))?;
+            // Set column names
+            let column_names = [
+                "X",
+                "Y",
+                "Z",
+                "tick",
+                "thrower_steamid",
+                "name",
+                "grenade_type",
+                "entity_id",
+            ];
+            df.setattr("columns", column_names.to_object(py)).unwrap();
+            // Call to_pandas with use_pyarrow_extension_array = true
+            let kwargs = vec![("use_pyarrow_extension_array", true)].into_py_dict_bound(py);
+            let pandas_df = df.call_method("to_pandas", (), Some(&kwargs)).unwrap();
+            Ok(pandas_df.to_object(py))
+        })
+    }
+    /// Returns every change of a players balance along with what caused it.
+    /// Changes that could not be matched to a cause have reason "unexplained".
+    ///
+    /// Example:
+    ///     tick           steamid   name  delta  balance       reason  explained
+    /// 0   2410  7656119xxxxxxxxxx  Player   -200      600     purchase       True
+    /// 1   3022  7656119xxxxxxxxxx  Player    300      900  kill_reward       True
+    pub fn parse_money_ledger(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        let settings = ParserInputs {
+            real_name_to_og_name: AHashMap::default(),
+            wanted_players: vec![],
+            wanted_player_props: vec![],
+            wanted_other_props: vec![],
+            wanted_prop_states: PropFilter::default(),
+            wanted_events: vec![],
+            parse_ents: true,
+            wanted_ticks: vec![],
+            parse_projectiles: false,
+            only_header: true,
+            count_props: false,
+            only_convars: false,
+            huffman_lookup_table: &self.huf,
+            order_by_steamid: false,
+            change_log: false,
+            parse_money_ledger: true,
+            parse_bomb: false,
+            parse_spotting: false,
+            parse_kill_context: false,
+            parse_shots: false,
+            parse_sprays: false,
+            parse_usercmds: false,
+            parse_movement: false,
+            parse_weapons: false,
+            wanted_entity_classes: vec![],
+            wanted_entity_props: vec![],
+            entity_event_classes: vec![],
+            wanted_prop_changes: vec![],
+            map_overview: None,
+            nav_mesh: None,
+            map_geometry: None,
+            visibility_pairs: None,
+            fov: None,
+            parallelism: self.parallelism.clone(),
+        };
+        let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
+        let output = match parser.parse_demo(&self.mmap) {
+            Ok(output) => output,
+            Err(e) => return Err(Exception::new_err(format!("{e}"))),
+        };
+
+        let ticks: Vec<Option<i32>> = output.money_ledger.iter().map(|x| x.tick).collect();
+        let steamids: Vec<Option<u64>> = output.money_ledger.iter().map(|x| x.steamid).collect();
+        let names: Vec<Option<String>> = output.money_ledger.iter().map(|x| x.name.clone()).collect();
+        let deltas: Vec<Option<i32>> = output.money_ledger.iter().map(|x| x.delta).collect();
+        let balances: Vec<Option<i32>> = output.money_ledger.iter().map(|x| x.balance).collect();
+        let reasons: Vec<Option<String>> = output.money_ledger.iter().map(|x| x.reason.clone()).collect();
+        let explained: Vec<Option<bool>> = output.money_ledger.iter().map(|x| x.explained).collect();
+
+        // SoA form
+        let ticks = arr_to_py(Box::new(Int32Array::from(ticks)))?;
+        let steamids = arr_to_py(Box::new(UInt64Array::from(steamids)))?;
+        let names = arr_to_py(Box::new(Utf8Array::<i32>::from(names)))?;
+        let deltas = arr_to_py(Box::new(Int32Array::from(deltas)))?;
+        let balances = arr_to_py(Box::new(Int32Array::from(balances)))?;
+        let reasons = arr_to_py(Box::new(Utf8Array::<i32>::from(reasons)))?;
+        let explained = arr_to_py(Box::new(BooleanArray::from(explained)))?;
+
+        let polars = py.import_bound("polars")?;
+        let all_series_py = [ticks, steamids, names, deltas, balances, reasons, explained].to_object(py);
+        Python::with_gil(|py| {
+            let df = polars.call_method1("DataFrame", (all_series_py,))?;
+            // Set column names
+            let column_names = ["tick", "steamid", "name", "delta", "balance", "reason", "explained"];
+            df.setattr("columns", column_names.to_object(py))?;
+            // Call to_pandas with use_pyarrow_extension_array = true
+            let kwargs = vec![("use_pyarrow_extension_array", true)].into_py_dict_bound(py);
+            let pandas_df = df.call_method("to_pandas", (), Some(&kwargs))?;
+            Ok(pandas_df.to_object(py))
+        })
+    }
+    /// Returns one row per thrown grenade, from throw to detonation and expiry.
+    /// The trajectory column holds a list of {tick, x, y, z} dicts, every nth point if trajectory_step is given.
+    #[pyo3(signature = (*, trajectory_step=None))]
+    pub fn parse_grenade_lifecycles(&self, py: Python<'_>, trajectory_step: Option<usize>) -> PyResult<Py<PyAny>> {
+        let settings = ParserInputs {
+            real_name_to_og_name: AHashMap::default(),
+            wanted_players: vec![],
+            wanted_player_props: vec![],
+            wanted_other_props: vec![],
+            wanted_prop_states: PropFilter::default(),
+            wanted_events: vec![],
+            parse_ents: true,
+            wanted_ticks: vec![],
+            parse_projectiles: true,
+            only_header: true,
+            count_props: false,
+            only_convars: false,
+            huffman_lookup_table: &self.huf,
+            order_by_steamid: false,
+            change_log: false,
+            parse_money_ledger: false,
+            parse_bomb: false,
+            parse_spotting: false,
+            parse_kill_context: false,
+            parse_shots: false,
+            parse_sprays: false,
+            parse_usercmds: false,
+            parse_movement: false,
+            parse_weapons: false,
+            wanted_entity_classes: vec![],
+            wanted_entity_props: vec![],
+            entity_event_classes: vec![],
+            wanted_prop_changes: vec![],
+            map_overview: None,
+            nav_mesh: None,
+            map_geometry: None,
+            visibility_pairs: None,
+            fov: None,
+            parallelism: self.parallelism.clone(),
+        };
+        let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
+        let output = match parser.parse_demo(&self.mmap) {
+            Ok(output) => output,
+            Err(e) => return Err(Exception::new_err(format!("{e}"))),
+        };
+        let grenades = &output.grenade_lifecycles;
+
+        let entity_ids: Vec<Option<i32>> = grenades.iter().map(|g| g.entity_id).collect();
+        let grenade_types: Vec<Option<String>> = grenades.iter().map(|g| g.grenade_type.clone()).collect();
+        let steamids: Vec<Option<u64>> = grenades.iter().map(|g| g.thrower_steamid).collect();
+        let names: Vec<Option<String>> = grenades.iter().map(|g| g.thrower_name.clone()).collect();
+        let throw_ticks: Vec<Option<i32>> = grenades.iter().map(|g| g.throw_tick).collect();
+        let throw_xs: Vec<Option<f32>> = grenades.iter().map(|g| g.throw_x).collect();
+        let throw_ys: Vec<Option<f32>> = grenades.iter().map(|g| g.throw_y).collect();
+        let throw_zs: Vec<Option<f32>> = grenades.iter().map(|g| g.throw_z).collect();
+        let throw_pitches: Vec<Option<f32>> = grenades.iter().map(|g| g.throw_pitch).collect();
+        let throw_yaws: Vec<Option<f32>> = grenades.iter().map(|g| g.throw_yaw).collect();
+        let bounces: Vec<Option<i32>> = grenades.iter().map(|g| g.bounces).collect();
+        let detonate_ticks: Vec<Option<i32>> = grenades.iter().map(|g| g.detonate_tick).collect();
+        let detonate_xs: Vec<Option<f32>> = grenades.iter().map(|g| g.detonate_x).collect();
+        let detonate_ys: Vec<Option<f32>> = grenades.iter().map(|g| g.detonate_y).collect();
+        let detonate_zs: Vec<Option<f32>> = grenades.iter().map(|g| g.detonate_z).collect();
+        let expire_ticks: Vec<Option<i32>> = grenades.iter().map(|g| g.expire_tick).collect();
+
+        let mut trajectories = vec![];
+        for grenade in grenades {
+            let mut v = vec![];
+            for point in grenade.downsampled_trajectory(trajectory_step.unwrap_or(1)) {
+                let dict = PyDict::new_bound(py);
+                dict.set_item("tick", point.tick.to_object(py))?;
+                dict.set_item("x", point.x.to_object(py))?;
+                dict.set_item("y", point.y.to_object(py))?;
+                dict.set_item("z", point.z.to_object(py))?;
+                v.push(dict);
+            }
+            trajectories.push(v);
+        }
+
+        // SoA form
+        let entity_ids = arr_to_py(Box::new(Int32Array::from(entity_ids)))?;
+        let grenade_types = arr_to_py(Box::new(Utf8Array::<i32>::from(grenade_types)))?;
+        let steamids = arr_to_py(Box::new(UInt64Array::from(steamids)))?;
+        let names = arr_to_py(Box::new(Utf8Array::<i32>::from(names)))?;
+        let throw_ticks = arr_to_py(Box::new(Int32Array::from(throw_ticks)))?;
+        let throw_xs = arr_to_py(Box::new(Float32Array::from(throw_xs)))?;
+        let throw_ys = arr_to_py(Box::new(Float32Array::from(throw_ys)))?;
+        let throw_zs = arr_to_py(Box::new(Float32Array::from(throw_zs)))?;
+        let throw_pitches = arr_to_py(Box::new(Float32Array::from(throw_pitches)))?;
+        let throw_yaws = arr_to_py(Box::new(Float32Array::from(throw_yaws)))?;
+        let bounces = arr_to_py(Box::new(Int32Array::from(bounces)))?;
+        let detonate_ticks = arr_to_py(Box::new(Int32Array::from(detonate_ticks)))?;
+        let detonate_xs = arr_to_py(Box::new(Float32Array::from(detonate_xs)))?;
+        let detonate_ys = arr_to_py(Box::new(Float32Array::from(detonate_ys)))?;
+        let detonate_zs = arr_to_py(Box::new(Float32Array::from(detonate_zs)))?;
+        let expire_ticks = arr_to_py(Box::new(Int32Array::from(expire_ticks)))?;
+
+        let polars = py.import_bound("polars")?;
+        let all_series_py = [
+            entity_ids,
+            grenade_types,
+            steamids,
+            names,
+            throw_ticks,
+            throw_xs,
+            throw_ys,
+            throw_zs,
+            throw_pitches,
+            throw_yaws,
+            bounces,
+            detonate_ticks,
+            detonate_xs,
+            detonate_ys,
+            detonate_zs,
+            expire_ticks,
+        ]
+        .to_object(py);
+        Python::with_gil(|py| {
+            let df = polars.call_method1("DataFrame", (all_series_py,))?;
+            // Set column names
+            let column_names = [
+                "entity_id",
+                "grenade_type",
+                "thrower_steamid",
+                "thrower_name",
+                "throw_tick",
+                "throw_x",
+                "throw_y",
+                "throw_z",
+                "throw_pitch",
+                "throw_yaw",
+                "bounces",
+                "detonate_tick",
+                "detonate_x",
+                "detonate_y",
+                "detonate_z",
+                "expire_tick",
+            ];
+            df.setattr("columns", column_names.to_object(py))?;
+            // Call to_pandas with use_pyarrow_extension_array = true
+            let kwargs = vec![("use_pyarrow_extension_array", true)].into_py_dict_bound(py);
+            let pandas_df = df.call_method("to_pandas", (), Some(&kwargs))?;
+            // Lists of dicts don't map to arrow so add trajectory directly to pandas
+            pandas_df.call_method1("insert", (10, "trajectory", trajectories.to_object(py)))?;
+            Ok(pandas_df.to_object(py))
+        })
+    }
+    /// Returns one row per thrown grenade with the damage, flashes and flash assisted kills it caused.
+    /// The flash_victims column holds a list of {steamid, name, blind_duration, is_teammate, tick} dicts.
+    pub fn parse_utility_effects(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        let settings = ParserInputs {
+            real_name_to_og_name: AHashMap::default(),
+            wanted_players: vec![],
+            wanted_player_props: vec![],
+            wanted_other_props: vec![],
+            wanted_prop_states: PropFilter::default(),
+            wanted_events: vec![],
+            parse_ents: true,
+            wanted_ticks: vec![],
+            parse_projectiles: true,
+            only_header: true,
+            count_props: false,
+            only_convars: false,
+            huffman_lookup_table: &self.huf,
+            order_by_steamid: false,
+            change_log: false,
+            parse_money_ledger: false,
+            parse_bomb: false,
+            parse_spotting: false,
+            parse_kill_context: false,
+            parse_shots: false,
+            parse_sprays: false,
+            parse_usercmds: false,
+            parse_movement: false,
+            parse_weapons: false,
+            wanted_entity_classes: vec![],
+            wanted_entity_props: vec![],
+            entity_event_classes: vec![],
+            wanted_prop_changes: vec![],
+            map_overview: None,
+            nav_mesh: None,
+            map_geometry: None,
+            visibility_pairs: None,
+            fov: None,
+            parallelism: self.parallelism.clone(),
+        };
+        let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
+        let output = match parser.parse_demo(&self.mmap) {
+            Ok(output) => output,
+            Err(e) => return Err(Exception::new_err(format!("{e}"))),
+        };
+        let effects = &output.utility_effects;
+
+        let entity_ids: Vec<Option<i32>> = effects.iter().map(|e| e.entity_id).collect();
+        let grenade_types: Vec<Option<String>> = effects.iter().map(|e| e.grenade_type.clone()).collect();
+        let steamids: Vec<Option<u64>> = effects.iter().map(|e| e.thrower_steamid).collect();
+        let names: Vec<Option<String>> = effects.iter().map(|e| e.thrower_name.clone()).collect();
+        let throw_ticks: Vec<Option<i32>> = effects.iter().map(|e| e.throw_tick).collect();
+        let detonate_ticks: Vec<Option<i32>> = effects.iter().map(|e| e.detonate_tick).collect();
+        let enemy_damage: Vec<Option<i32>> = effects.iter().map(|e| e.enemy_damage).collect();
+        let team_damage: Vec<Option<i32>> = effects.iter().map(|e| e.team_damage).collect();
+        let enemies_hurt: Vec<Option<i32>> = effects.iter().map(|e| e.enemies_hurt).collect();
+        let teammates_hurt: Vec<Option<i32>> = effects.iter().map(|e| e.teammates_hurt).collect();
+        let enemies_flashed: Vec<Option<i32>> = effects.iter().map(|e| e.enemies_flashed).collect();
+        let teammates_flashed: Vec<Option<i32>> = effects.iter().map(|e| e.teammates_flashed).collect();
+        let enemy_blind_duration: Vec<Option<f32>> = effects.iter().map(|e| e.enemy_blind_duration).collect();
+        let team_blind_duration: Vec<Option<f32>> = effects.iter().map(|e| e.team_blind_duration).collect();
+        let kills_on_flashed: Vec<Option<i32>> = effects.iter().map(|e| e.kills_on_flashed).collect();
+
+        let mut flash_victims = vec![];
+        for effect in effects {
+            let mut v = vec![];
+            for victim in &effect.flash_victims {
+                let dict = PyDict::new_bound(py);
+                dict.set_item("steamid", victim.steamid.to_object(py))?;
+                dict.set_item("name", victim.name.to_object(py))?;
+                dict.set_item("blind_duration", victim.blind_duration.to_object(py))?;
+                dict.set_item("is_teammate", victim.is_teammate.to_object(py))?;
+                dict.set_item("tick", victim.tick.to_object(py))?;
+                v.push(dict);
+            }
+            flash_victims.push(v);
+        }
+
+        // SoA form
+        let entity_ids = arr_to_py(Box::new(Int32Array::from(entity_ids)))?;
+        let grenade_types = arr_to_py(Box::new(Utf8Array::<i32>::from(grenade_types)))?;
+        let steamids = arr_to_py(Box::new(UInt64Array::from(steamids)))?;
+        let names = arr_to_py(Box::new(Utf8Array::<i32>::from(names)))?;
+        let throw_ticks = arr_to_py(Box::new(Int32Array::from(throw_ticks)))?;
+        let detonate_ticks = arr_to_py(Box::new(Int32Array::from(detonate_ticks)))?;
+        let enemy_damage = arr_to_py(Box::new(Int32Array::from(enemy_damage)))?;
+        let team_damage = arr_to_py(Box::new(Int32Array::from(team_damage)))?;
+        let enemies_hurt = arr_to_py(Box::new(Int32Array::from(enemies_hurt)))?;
+        let teammates_hurt = arr_to_py(Box::new(Int32Array::from(teammates_hurt)))?;
+        let enemies_flashed = arr_to_py(Box::new(Int32Array::from(enemies_flashed)))?;
+        let teammates_flashed = arr_to_py(Box::new(Int32Array::from(teammates_flashed)))?;
+        let enemy_blind_duration = arr_to_py(Box::new(Float32Array::from(enemy_blind_duration)))?;
+        let team_blind_duration = arr_to_py(Box::new(Float32Array::from(team_blind_duration)))?;
+        let kills_on_flashed = arr_to_py(Box::new(Int32Array::from(kills_on_flashed)))?;
+
+        let polars = py.import_bound("polars")?;
+        let all_series_py = [
+            entity_ids,
+            grenade_types,
+            steamids,
+            names,
+            throw_ticks,
+            detonate_ticks,
+            enemy_damage,
+            team_damage,
+            enemies_hurt,
+            teammates_hurt,
+            enemies_flashed,
+            teammates_flashed,
+            enemy_blind_duration,
+            team_blind_duration,
+            kills_on_flashed,
+        ]
+        .to_object(py);
+        Python::with_gil(|py| {
+            let df = polars.call_method1("DataFrame", (all_series_py,))?;
+            // Set column names
+            let column_names = [
+                "entity_id",
+                "grenade_type",
+                "thrower_steamid",
+                "thrower_name",
+                "throw_tick",
+                "detonate_tick",
+                "enemy_damage",
+                "team_damage",
+                "enemies_hurt",
+                "teammates_hurt",
+                "enemies_flashed",
+                "teammates_flashed",
+                "enemy_blind_duration",
+                "team_blind_duration",
+                "kills_on_flashed",
+            ];
+            df.setattr("columns", column_names.to_object(py))?;
+            // Call to_pandas with use_pyarrow_extension_array = true
+            let kwargs = vec![("use_pyarrow_extension_array", true)].into_py_dict_bound(py);
+            let pandas_df = df.call_method("to_pandas", (), Some(&kwargs))?;
+            // Lists of dicts don't map to arrow so add flash victims directly to pandas
+            pandas_df.call_method1("insert", (column_names.len(), "flash_victims", flash_victims.to_object(py)))?;
+            Ok(pandas_df.to_object(py))
+        })
+    }
+    /// Returns the burning fire cells of every inferno (molotov/incendiary) at every tick.
+    /// The fires column holds a list of {x, y, z} dicts in world coordinates and area is the convex hull area of them on the xy-plane.
+    pub fn parse_infernos(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        let settings = ParserInputs {
+            real_name_to_og_name: AHashMap::default(),
+            wanted_players: vec![],
+            wanted_player_props: vec![],
+            wanted_other_props: vec![],
+            wanted_prop_states: PropFilter::default(),
+            wanted_events: vec![],
+            parse_ents: true,
+            wanted_ticks: vec![],
+            parse_projectiles: true,
+            only_header: true,
+            count_props: false,
+            only_convars: false,
+            huffman_lookup_table: &self.huf,
+            order_by_steamid: false,
+            change_log: false,
+            parse_money_ledger: false,
+            parse_bomb: false,
+            parse_spotting: false,
+            parse_kill_context: false,
+            parse_shots: false,
+            parse_sprays: false,
+            parse_usercmds: false,
+            parse_movement: false,
+            parse_weapons: false,
+            wanted_entity_classes: vec![],
+            wanted_entity_props: vec![],
+            entity_event_classes: vec![],
+            wanted_prop_changes: vec![],
+            map_overview: None,
+            nav_mesh: None,
+            map_geometry: None,
+            visibility_pairs: None,
+            fov: None,
+            parallelism: self.parallelism.clone(),
+        };
+        let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
+        let output = match parser.parse_demo(&self.mmap) {
+            Ok(output) => output,
+            Err(e) => return Err(Exception::new_err(format!("{e}"))),
+        };
+        let infernos = &output.inferno_fires;
+
+        let ticks: Vec<Option<i32>> = infernos.iter().map(|i| i.tick).collect();
+        let entity_ids: Vec<Option<i32>> = infernos.iter().map(|i| i.entity_id).collect();
+        let steamids: Vec<Option<u64>> = infernos.iter().map(|i| i.thrower_steamid).collect();
+        let names: Vec<Option<String>> = infernos.iter().map(|i| i.thrower_name.clone()).collect();
+        let fire_counts: Vec<Option<i32>> = infernos.iter().map(|i| i.fire_count).collect();
+        let areas: Vec<Option<f32>> = infernos.iter().map(|i| i.area).collect();
+
+        let mut fires = vec![];
+        for inferno in infernos {
+            let mut v = vec![];
+            for fire in &inferno.fires {
+                let dict = PyDict::new_bound(py);
+                dict.set_item("x", fire.x.to_object(py))?;
+                dict.set_item("y", fire.y.to_object(py))?;
+                dict.set_item("z", fire.z.to_object(py))?;
+                v.push(dict);
+            }
+            fires.push(v);
+        }
+
+        // SoA form
+        let ticks = arr_to_py(Box::new(Int32Array::from(ticks)))?;
+        let entity_ids = arr_to_py(Box::new(Int32Array::from(entity_ids)))?;
+        let steamids = arr_to_py(Box::new(UInt64Array::from(steamids)))?;
+        let names = arr_to_py(Box::new(Utf8Array::<i32>::from(names)))?;
+        let fire_counts = arr_to_py(Box::new(Int32Array::from(fire_counts)))?;
+        let areas = arr_to_py(Box::new(Float32Array::from(areas)))?;
+
+        let polars = py.import_bound("polars")?;
+        let all_series_py = [ticks, entity_ids, steamids, names, fire_counts, areas].to_object(py);
+        Python::with_gil(|py| {
+            let df = polars.call_method1("DataFrame", (all_series_py,))?;
+            // Set column names
+            let column_names = ["tick", "entity_id", "thrower_steamid", "thrower_name", "fire_count", "area"];
+            df.setattr("columns", column_names.to_object(py))?;
+            // Call to_pandas with use_pyarrow_extension_array = true
+            let kwargs = vec![("use_pyarrow_extension_array", true)].into_py_dict_bound(py);
+            let pandas_df = df.call_method("to_pandas", (), Some(&kwargs))?;
+            // Lists of dicts don't map to arrow so add fires directly to pandas
+            pandas_df.call_method1("insert", (column_names.len(), "fires", fires.to_object(py)))?;
+            Ok(pandas_df.to_object(py))
+        })
+    }
+    /// Returns every bomb event of every round: pickups, drops, plant and defuse attempts, plants, defuses and explosions.
+    /// Each row carries the round and its final outcome (exploded, defused, planted, time_ran_out or no_plant).
+    pub fn parse_bomb(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        let settings = ParserInputs {
+            real_name_to_og_name: AHashMap::default(),
+            wanted_players: vec![],
+            wanted_player_props: vec![],
+            wanted_other_props: vec![],
+            wanted_prop_states: PropFilter::default(),
+            wanted_events: vec![],
+            parse_ents: true,
+            wanted_ticks: vec![],
+            parse_projectiles: false,
+            only_header: true,
+            count_props: false,
+            only_convars: false,
+            huffman_lookup_table: &self.huf,
+            order_by_steamid: false,
+            change_log: false,
+            parse_money_ledger: false,
+            parse_bomb: true,
+            parse_spotting: false,
+            parse_kill_context: false,
+            parse_shots: false,
+            parse_sprays: false,
+            parse_usercmds: false,
+            parse_movement: false,
+            parse_weapons: false,
+            wanted_entity_classes: vec![],
+            wanted_entity_props: vec![],
+            entity_event_classes: vec![],
+            wanted_prop_changes: vec![],
+            map_overview: None,
+            nav_mesh: None,
+            map_geometry: None,
+            visibility_pairs: None,
+            fov: None,
+            parallelism: self.parallelism.clone(),
+        };
+        let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
+        let output = match parser.parse_demo(&self.mmap) {
+            Ok(output) => output,
+            Err(e) => return Err(Exception::new_err(format!("{e}"))),
+        };
+        let rows: Vec<_> = output.bomb_timelines.iter().flat_map(|t| t.events.iter().map(move |e| (t, e))).collect();
+
+        let rounds: Vec<Option<i32>> = rows.iter().map(|(t, _)| t.round).collect();
+        let outcomes: Vec<Option<String>> = rows.iter().map(|(t, _)| t.outcome.clone()).collect();
+        let ticks: Vec<Option<i32>> = rows.iter().map(|(_, e)| Some(e.tick)).collect();
+        let events: Vec<Option<String>> = rows.iter().map(|(_, e)| Some(e.kind.as_str().to_string())).collect();
+        let steamids: Vec<Option<u64>> = rows.iter().map(|(_, e)| e.steamid).collect();
+        let names: Vec<Option<String>> = rows.iter().map(|(_, e)| e.name.clone()).collect();
+        let xs: Vec<Option<f32>> = rows.iter().map(|(_, e)| e.x).collect();
+        let ys: Vec<Option<f32>> = rows.iter().map(|(_, e)| e.y).collect();
+        let zs: Vec<Option<f32>> = rows.iter().map(|(_, e)| e.z).collect();
+        let sites: Vec<Option<String>> = rows.iter().map(|(_, e)| e.site.clone()).collect();
+        let has_kits: Vec<Option<bool>> = rows.iter().map(|(_, e)| e.has_kit).collect();
+        let bomb_times: Vec<Option<f32>> = rows.iter().map(|(_, e)| e.bomb_time_remaining).collect();
+        let defuse_times: Vec<Option<f32>> = rows.iter().map(|(_, e)| e.defuse_time_remaining).collect();
+
+        // SoA form
+        let rounds = arr_to_py(Box::new(Int32Array::from(rounds)))?;
+        let outcomes = arr_to_py(Box::new(Utf8Array::<i32>::from(outcomes)))?;
+        let ticks = arr_to_py(Box::new(Int32Array::from(ticks)))?;
+        let events = arr_to_py(Box::new(Utf8Array::<i32>::from(events)))?;
+        let steamids = arr_to_py(Box::new(UInt64Array::from(steamids)))?;
+        let names = arr_to_py(Box::new(Utf8Array::<i32>::from(names)))?;
+        let xs = arr_to_py(Box::new(Float32Array::from(xs)))?;
+        let ys = arr_to_py(Box::new(Float32Array::from(ys)))?;
+        let zs = arr_to_py(Box::new(Float32Array::from(zs)))?;
+        let sites = arr_to_py(Box::new(Utf8Array::<i32>::from(sites)))?;
+        let has_kits = arr_to_py(Box::new(BooleanArray::from(has_kits)))?;
+        let bomb_times = arr_to_py(Box::new(Float32Array::from(bomb_times)))?;
+        let defuse_times = arr_to_py(Box::new(Float32Array::from(defuse_times)))?;
+
+        let polars = py.import_bound("polars")?;
+        let all_series_py = [
+            rounds,
+            outcomes,
+            ticks,
+            events,
+            steamids,
+            names,
+            xs,
+            ys,
+            zs,
+            sites,
+            has_kits,
+            bomb_times,
+            defuse_times,
+        ]
+        .to_object(py);
+        Python::with_gil(|py| {
+            let df = polars.call_method1("DataFrame", (all_series_py,))?;
+            // Set column names
+            let column_names = [
+                "round",
+                "outcome",
+                "tick",
+                "event",
+                "steamid",
+                "name",
+                "X",
+                "Y",
+                "Z",
+                "site",
+                "has_kit",
+                "bomb_time_remaining",
+                "defuse_time_remaining",
+            ];
+            df.setattr("columns", column_names.to_object(py))?;
+            // Call to_pandas with use_pyarrow_extension_array = true
+            let kwargs = vec![("use_pyarrow_extension_array", true)].into_py_dict_bound(py);
+            let pandas_df = df.call_method("to_pandas", (), Some(&kwargs))?;
+            Ok(pandas_df.to_object(py))
+        })
+    }
+    /// Returns line of sight between players at the wanted ticks, ray cast from eye to eye against the map geometry.
+    /// geometry_path is an .obj or a raw triangle soup (9 little endian f32 per triangle) of the map.
+    /// pairs is a list of (observer_steamid, target_steamid), all pairs of alive players if not given.
+    #[pyo3(signature = (geometry_path, *, pairs=None, ticks=None))]
+    pub fn parse_visibility(
+        &self,
+        py: Python<'_>,
+        geometry_path: String,
+        pairs: Option<Vec<(u64, u64)>>,
+        ticks: Option<Vec<i32>>,
+    ) -> PyResult<Py<PyAny>> {
+        let geometry = match TriangleMesh::from_file(&geometry_path) {
+            Ok(geometry) => geometry,
+            Err(e) => return Err(Exception::new_err(format!("{e}"))),
+        };
+        let settings = ParserInputs {
+            real_name_to_og_name: AHashMap::default(),
+            wanted_players: vec![],
+            wanted_player_props: vec![],
+            wanted_other_props: vec![],
+            wanted_prop_states: PropFilter::default(),
+            wanted_events: vec![],
+            parse_ents: true,
+            wanted_ticks: ticks.unwrap_or_default(),
+            parse_projectiles: false,
+            only_header: true,
+            count_props: false,
+            only_convars: false,
+            huffman_lookup_table: &self.huf,
+            order_by_steamid: false,
+            change_log: false,
+            parse_money_ledger: false,
+            parse_bomb: false,
+            parse_spotting: false,
+            parse_kill_context: false,
+            parse_shots: false,
+            parse_sprays: false,
+            parse_usercmds: false,
+            parse_movement: false,
+            parse_weapons: false,
+            wanted_entity_classes: vec![],
+            wanted_entity_props: vec![],
+            entity_event_classes: vec![],
+            wanted_prop_changes: vec![],
+            map_overview: None,
+            nav_mesh: None,
+            map_geometry: Some(Arc::new(geometry)),
+            visibility_pairs: Some(pairs.unwrap_or_default()),
+            fov: None,
+            parallelism: self.parallelism.clone(),
+        };
+        let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
+        let output = match parser.parse_demo(&self.mmap) {
+            Ok(output) => output,
+            Err(e) => return Err(Exception::new_err(format!("{e}"))),
+        };
+        let visibility = &output.visibility;
+
+        let ticks: Vec<Option<i32>> = visibility.iter().map(|v| Some(v.tick)).collect();
+        let observer_steamids: Vec<Option<u64>> = visibility.iter().map(|v| v.observer_steamid).collect();
+        let observer_names: Vec<Option<String>> = visibility.iter().map(|v| v.observer_name.clone()).collect();
+        let target_steamids: Vec<Option<u64>> = visibility.iter().map(|v| v.target_steamid).collect();
+        let target_names: Vec<Option<String>> = visibility.iter().map(|v| v.target_name.clone()).collect();
+        let line_of_sight: Vec<Option<bool>> = visibility.iter().map(|v| Some(v.line_of_sight)).collect();
+        let angles: Vec<Option<f32>> = visibility.iter().map(|v| v.angle).collect();
+        let distances: Vec<Option<f32>> = visibility.iter().map(|v| Some(v.distance)).collect();
+
+        // SoA form
+        let ticks = arr_to_py(Box::new(Int32Array::from(ticks)))?;
+        let observer_steamids = arr_to_py(Box::new(UInt64Array::from(observer_steamids)))?;
+        let observer_names = arr_to_py(Box::new(Utf8Array::<i32>::from(observer_names)))?;
+        let target_steamids = arr_to_py(Box::new(UInt64Array::from(target_steamids)))?;
+        let target_names = arr_to_py(Box::new(Utf8Array::<i32>::from(target_names)))?;
+        let line_of_sight = arr_to_py(Box::new(BooleanArray::from(line_of_sight)))?;
+        let angles = arr_to_py(Box::new(Float32Array::from(angles)))?;
+        let distances = arr_to_py(Box::new(Float32Array::from(distances)))?;
+
+        let polars = py.import_bound("polars")?;
+        let all_series_py = [
+            ticks,
+            observer_steamids,
+            observer_names,
+            target_steamids,
+            target_names,
+            line_of_sight,
+            angles,
+            distances,
+        ]
+        .to_object(py);
+        Python::with_gil(|py| {
+            let df = polars.call_method1("DataFrame", (all_series_py,))?;
+            // Set column names
+            let column_names = [
+                "tick",
+                "observer_steamid",
+                "observer_name",
+                "target_steamid",
+                "target_name",
+                "line_of_sight",
+                "angle",
+                "distance",
+            ];
+            df.setattr("columns", column_names.to_object(py))?;
+            // Call to_pandas with use_pyarrow_extension_array = true
+            let kwargs = vec![("use_pyarrow_extension_array", true)].into_py_dict_bound(py);
+            let pandas_df = df.call_method("to_pandas", (), Some(&kwargs))?;
+            Ok(pandas_df.to_object(py))
+        })
+    }
+    /// Returns two dataframes built from m_bSpottedByMask.
+    /// The first has a row every time a player starts or stops being spotted by another player (event is started/ended),
+    /// started rows also say whether the line between their eyes went through a smoke.
+    /// The second has a row per round and spotter/spotted pair with how long they were spotted, the first tick they were
+    /// spotted and whether the spotter saw the other player before being seen by them.
+    pub fn parse_spotting(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        let settings = ParserInputs {
+            real_name_to_og_name: AHashMap::default(),
+            wanted_players: vec![],
+            wanted_player_props: vec![],
+            wanted_other_props: vec![],
+            wanted_prop_states: PropFilter::default(),
+            wanted_events: vec![],
+            parse_ents: true,
+            wanted_ticks: vec![],
+            parse_projectiles: false,
+            only_header: true,
+            count_props: false,
+            only_convars: false,
+            huffman_lookup_table: &self.huf,
+            order_by_steamid: false,
+            change_log: false,
+            parse_money_ledger: false,
+            parse_bomb: false,
+            parse_spotting: true,
+            parse_kill_context: false,
+            parse_shots: false,
+            parse_sprays: false,
+            parse_usercmds: false,
+            parse_movement: false,
+            parse_weapons: false,
+            wanted_entity_classes: vec![],
+            wanted_entity_props: vec![],
+            entity_event_classes: vec![],
+            wanted_prop_changes: vec![],
+            map_overview: None,
+            nav_mesh: None,
+            map_geometry: None,
+            visibility_pairs: None,
+            fov: None,
+            parallelism: self.parallelism.clone(),
+        };
+        let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
+        let output = match parser.parse_demo(&self.mmap) {
+            Ok(output) => output,
+            Err(e) => return Err(Exception::new_err(format!("{e}"))),
+        };
+        let edges = &output.spotting_edges;
+        let rounds = &output.spotting_rounds;
+
+        let ticks: Vec<Option<i32>> = edges.iter().map(|e| Some(e.tick)).collect();
+        let edge_rounds: Vec<Option<i32>> = edges.iter().map(|e| e.round).collect();
+        let spotter_steamids: Vec<Option<u64>> = edges.iter().map(|e| Some(e.spotter_steamid)).collect();
+        let spotter_names: Vec<Option<String>> = edges.iter().map(|e| e.spotter_name.clone()).collect();
+        let spotted_steamids: Vec<Option<u64>> = edges.iter().map(|e| Some(e.spotted_steamid)).collect();
+        let spotted_names: Vec<Option<String>> = edges.iter().map(|e| e.spotted_name.clone()).collect();
+        let events: Vec<Option<&str>> = edges.iter().map(|e| Some(if e.started { "started" } else { "ended" })).collect();
+        let through_smoke: Vec<Option<bool>> = edges.iter().map(|e| e.through_smoke).collect();
+
+        let round_numbers: Vec<Option<i32>> = rounds.iter().map(|r| r.round).collect();
+        let round_spotter_steamids: Vec<Option<u64>> = rounds.iter().map(|r| Some(r.spotter_steamid)).collect();
+        let round_spotter_names: Vec<Option<String>> = rounds.iter().map(|r| r.spotter_name.clone()).collect();
+        let round_spotted_steamids: Vec<Option<u64>> = rounds.iter().map(|r| Some(r.spotted_steamid)).collect();
+        let round_spotted_names: Vec<Option<String>> = rounds.iter().map(|r| r.spotted_name.clone()).collect();
+        let ticks_spotted: Vec<Option<i32>> = rounds.iter().map(|r| Some(r.ticks_spotted)).collect();
+        let time_spotted: Vec<Option<f32>> = rounds.iter().map(|r| Some(r.time_spotted)).collect();
+        let first_spot_ticks: Vec<Option<i32>> = rounds.iter().map(|r| Some(r.first_spot_tick)).collect();
+        let spotted_first: Vec<Option<bool>> = rounds.iter().map(|r| Some(r.spotted_first)).collect();
+
+        // SoA form
+        let ticks = arr_to_py(Box::new(Int32Array::from(ticks)))?;
+        let edge_rounds = arr_to_py(Box::new(Int32Array::from(edge_rounds)))?;
+        let spotter_steamids = arr_to_py(Box::new(UInt64Array::from(spotter_steamids)))?;
+        let spotter_names = arr_to_py(Box::new(Utf8Array::<i32>::from(spotter_names)))?;
+        let spotted_steamids = arr_to_py(Box::new(UInt64Array::from(spotted_steamids)))?;
+        let spotted_names = arr_to_py(Box::new(Utf8Array::<i32>::from(spotted_names)))?;
+        let events = arr_to_py(Box::new(Utf8Array::<i32>::from(events)))?;
+        let through_smoke = arr_to_py(Box::new(BooleanArray::from(through_smoke)))?;
+
+        let round_numbers = arr_to_py(Box::new(Int32Array::from(round_numbers)))?;
+        let round_spotter_steamids = arr_to_py(Box::new(UInt64Array::from(round_spotter_steamids)))?;
+        let round_spotter_names = arr_to_py(Box::new(Utf8Array::<i32>::from(round_spotter_names)))?;
+        let round_spotted_steamids = arr_to_py(Box::new(UInt64Array::from(round_spotted_steamids)))?;
+        let round_spotted_names = arr_to_py(Box::new(Utf8Array::<i32>::from(round_spotted_names)))?;
+        let ticks_spotted = arr_to_py(Box::new(Int32Array::from(ticks_spotted)))?;
+        let time_spotted = arr_to_py(Box::new(Float32Array::from(time_spotted)))?;
+        let first_spot_ticks = arr_to_py(Box::new(Int32Array::from(first_spot_ticks)))?;
+        let spotted_first = arr_to_py(Box::new(BooleanArray::from(spotted_first)))?;
+
+        let polars = py.import_bound("polars")?;
+        let edge_series_py = [
+            ticks,
+            edge_rounds,
+            spotter_steamids,
+            spotter_names,
+            spotted_steamids,
+            spotted_names,
+            events,
+            through_smoke,
+        ]
+        .to_object(py);
+        let round_series_py = [
+            round_numbers,
+            round_spotter_steamids,
+            round_spotter_names,
+            round_spotted_steamids,
+            round_spotted_names,
+            ticks_spotted,
+            time_spotted,
+            first_spot_ticks,
+            spotted_first,
+        ]
+        .to_object(py);
+        Python::with_gil(|py| {
+            let kwargs = vec![("use_pyarrow_extension_array", true)].into_py_dict_bound(py);
+
+            let edges_df = polars.call_method1("DataFrame", (edge_series_py,))?;
+            let edge_column_names = [
+                "tick",
+                "round",
+                "spotter_steamid",
+                "spotter_name",
+                "spotted_steamid",
+                "spotted_name",
+                "event",
+                "through_smoke",
+            ];
+            edges_df.setattr("columns", edge_column_names.to_object(py))?;
+            let edges_pandas = edges_df.call_method("to_pandas", (), Some(&kwargs))?;
+
+            let rounds_df = polars.call_method1("DataFrame", (round_series_py,))?;
+            let round_column_names = [
+                "round",
+                "spotter_steamid",
+                "spotter_name",
+                "spotted_steamid",
+                "spotted_name",
+                "ticks_spotted",
+                "time_spotted",
+                "first_spot_tick",
+                "spotted_first",
+            ];
+            rounds_df.setattr("columns", round_column_names.to_object(py))?;
+            let rounds_pandas = rounds_df.call_method("to_pandas", (), Some(&kwargs))?;
+            Ok((edges_pandas, rounds_pandas).to_object(py))
+        })
+    }
+    /// Returns two dataframes about where players aim relative to each other.
+    /// The first has a row per alive player pair at the wanted ticks with the angle from the observer's crosshair to
+    /// the head and body of the target, the yaw/pitch needed to reach the head, distance and whether the target is on screen.
+    /// The second has a row per duel (attacker, victim, round) with the crosshair placement error before the first damage,
+    /// measured when the attacker spotted the victim or at the damage tick if that is not known.
+    /// fov is the horizontal field of view in degrees on a 16:9 screen, defaults to the CS2 fov.
+    #[pyo3(signature = (*, ticks=None, fov=None))]
+    pub fn parse_fov(&self, py: Python<'_>, ticks: Option<Vec<i32>>, fov: Option<f32>) -> PyResult<Py<PyAny>> {
+        let settings = ParserInputs {
+            real_name_to_og_name: AHashMap::default(),
+            wanted_players: vec![],
+            wanted_player_props: vec![],
+            wanted_other_props: vec![],
+            wanted_prop_states: PropFilter::default(),
+            wanted_events: vec![],
+            parse_ents: true,
+            wanted_ticks: ticks.unwrap_or_default(),
+            parse_projectiles: false,
+            only_header: true,
+            count_props: false,
+            only_convars: false,
+            huffman_lookup_table: &self.huf,
+            order_by_steamid: false,
+            change_log: false,
+            parse_money_ledger: false,
+            parse_bomb: false,
+            parse_spotting: false,
+            parse_kill_context: false,
+            parse_shots: false,
+            parse_sprays: false,
+            parse_usercmds: false,
+            parse_movement: false,
+            parse_weapons: false,
+            wanted_entity_classes: vec![],
+            wanted_entity_props: vec![],
+            entity_event_classes: vec![],
+            wanted_prop_changes: vec![],
+            map_overview: None,
+            nav_mesh: None,
+            map_geometry: None,
+            visibility_pairs: None,
+            fov: Some(fov.unwrap_or(DEFAULT_FOV)),
+            parallelism: self.parallelism.clone(),
+        };
+        let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
+        let output = match parser.parse_demo(&self.mmap) {
+            Ok(output) => output,
+            Err(e) => return Err(Exception::new_err(format!("{e}"))),
+        };
+        let fov = &output.fov;
+        let placement = &output.crosshair_placement;
+
+        let ticks: Vec<Option<i32>> = fov.iter().map(|f| Some(f.tick)).collect();
+        let observer_steamids: Vec<Option<u64>> = fov.iter().map(|f| f.observer_steamid).collect();
+        let observer_names: Vec<Option<String>> = fov.iter().map(|f| f.observer_name.clone()).collect();
+        let target_steamids: Vec<Option<u64>> = fov.iter().map(|f| f.target_steamid).collect();
+        let target_names: Vec<Option<String>> = fov.iter().map(|f| f.target_name.clone()).collect();
+        let head_angles: Vec<Option<f32>> = fov.iter().map(|f| Some(f.head_angle)).collect();
+        let body_angles: Vec<Option<f32>> = fov.iter().map(|f| Some(f.body_angle)).collect();
+        let yaw_errors: Vec<Option<f32>> = fov.iter().map(|f| Some(f.yaw_error)).collect();
+        let pitch_errors: Vec<Option<f32>> = fov.iter().map(|f| Some(f.pitch_error)).collect();
+        let distances: Vec<Option<f32>> = fov.iter().map(|f| Some(f.distance)).collect();
+        let in_fov: Vec<Option<bool>> = fov.iter().map(|f| Some(f.in_fov)).collect();
+
+        let rounds: Vec<Option<i32>> = placement.iter().map(|p| p.round).collect();
+        let damage_ticks: Vec<Option<i32>> = placement.iter().map(|p| Some(p.tick)).collect();
+        let attacker_steamids: Vec<Option<u64>> = placement.iter().map(|p| Some(p.attacker_steamid)).collect();
+        let attacker_names: Vec<Option<String>> = placement.iter().map(|p| p.attacker_name.clone()).collect();
+        let victim_steamids: Vec<Option<u64>> = placement.iter().map(|p| Some(p.victim_steamid)).collect();
+        let victim_names: Vec<Option<String>> = placement.iter().map(|p| p.victim_name.clone()).collect();
+        let spotted_ticks: Vec<Option<i32>> = placement.iter().map(|p| p.spotted_tick).collect();
+        let angle_errors: Vec<Option<f32>> = placement.iter().map(|p| Some(p.angle_error)).collect();
+        let placement_yaw_errors: Vec<Option<f32>> = placement.iter().map(|p| Some(p.yaw_error)).collect();
+        let placement_pitch_errors: Vec<Option<f32>> = placement.iter().map(|p| Some(p.pitch_error)).collect();
+        let placement_distances: Vec<Option<f32>> = placement.iter().map(|p| Some(p.distance)).collect();
+
+        // SoA form
+        let ticks = arr_to_py(Box::new(Int32Array::from(ticks)))?;
+        let observer_steamids = arr_to_py(Box::new(UInt64Array::from(observer_steamids)))?;
+        let observer_names = arr_to_py(Box::new(Utf8Array::<i32>::from(observer_names)))?;
+        let target_steamids = arr_to_py(Box::new(UInt64Array::from(target_steamids)))?;
+        let target_names = arr_to_py(Box::new(Utf8Array::<i32>::from(target_names)))?;
+        let head_angles = arr_to_py(Box::new(Float32Array::from(head_angles)))?;
+        let body_angles = arr_to_py(Box::new(Float32Array::from(body_angles)))?;
+        let yaw_errors = arr_to_py(Box::new(Float32Array::from(yaw_errors)))?;
+        let pitch_errors = arr_to_py(Box::new(Float32Array::from(pitch_errors)))?;
+        let distances = arr_to_py(Box::new(Float32Array::from(distances)))?;
+        let in_fov = arr_to_py(Box::new(BooleanArray::from(in_fov)))?;
+
+        let rounds = arr_to_py(Box::new(Int32Array::from(rounds)))?;
+        let damage_ticks = arr_to_py(Box::new(Int32Array::from(damage_ticks)))?;
+        let attacker_steamids = arr_to_py(Box::new(UInt64Array::from(attacker_steamids)))?;
+        let attacker_names = arr_to_py(Box::new(Utf8Array::<i32>::from(attacker_names)))?;
+        let victim_steamids = arr_to_py(Box::new(UInt64Array::from(victim_steamids)))?;
+        let victim_names = arr_to_py(Box::new(Utf8Array::<i32>::from(victim_names)))?;
+        let spotted_ticks = arr_to_py(Box::new(Int32Array::from(spotted_ticks)))?;
+        let angle_errors = arr_to_py(Box::new(Float32Array::from(angle_errors)))?;
+        let placement_yaw_errors = arr_to_py(Box::new(Float32Array::from(placement_yaw_errors)))?;
+        let placement_pitch_errors = arr_to_py(Box::new(Float32Array::from(placement_pitch_errors)))?;
+        let placement_distances = arr_to_py(Box::new(Float32Array::from(placement_distances)))?;
+
+        let polars = py.import_bound("polars")?;
+        let fov_series_py = [
+            ticks,
+            observer_steamids,
+            observer_names,
+            target_steamids,
+            target_names,
+            head_angles,
+            body_angles,
+            yaw_errors,
+            pitch_errors,
+            distances,
+            in_fov,
+        ]
+        .to_object(py);
+        let placement_series_py = [
+            rounds,
+            damage_ticks,
+            attacker_steamids,
+            attacker_names,
+            victim_steamids,
+            victim_names,
+            spotted_ticks,
+            angle_errors,
+            placement_yaw_errors,
+            placement_pitch_errors,
+            placement_distances,
+        ]
+        .to_object(py);
+        Python::with_gil(|py| {
+            let kwargs = vec![("use_pyarrow_extension_array", true)].into_py_dict_bound(py);
+
+            let fov_df = polars.call_method1("DataFrame", (fov_series_py,))?;
+            let fov_column_names = [
+                "tick",
+                "observer_steamid",
+                "observer_name",
+                "target_steamid",
+                "target_name",
+                "head_angle",
+                "body_angle",
+                "yaw_error",
+                "pitch_error",
+                "distance",
+                "in_fov",
+            ];
+            fov_df.setattr("columns", fov_column_names.to_object(py))?;
+            let fov_pandas = fov_df.call_method("to_pandas", (), Some(&kwargs))?;
+
+            let placement_df = polars.call_method1("DataFrame", (placement_series_py,))?;
+            let placement_column_names = [
+                "round",
+                "tick",
+                "attacker_steamid",
+                "attacker_name",
+                "victim_steamid",
+                "victim_name",
+                "spotted_tick",
+                "angle_error",
+                "yaw_error",
+                "pitch_error",
+                "distance",
+            ];
+            placement_df.setattr("columns", placement_column_names.to_object(py))?;
+            let placement_pandas = placement_df.call_method("to_pandas", (), Some(&kwargs))?;
+            Ok((fov_pandas, placement_pandas).to_object(py))
+        })
+    }
+    /// Returns two dataframes. The first has a row per weapon_fire with the state of the shooter and the player_hurt
+    /// it caused if any (same attacker and weapon within a few ticks). bullet_impact and CCSUsrMsg_ShootInfo are
+    /// attached when the demo has them. The second has accuracy per player and weapon, split into first bullets and sprays.
+    pub fn parse_shots(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        let settings = ParserInputs {
+            real_name_to_og_name: AHashMap::default(),
+            wanted_players: vec![],
+            wanted_player_props: vec![],
+            wanted_other_props: vec![],
+            wanted_prop_states: PropFilter::default(),
+            wanted_events: vec![],
+            parse_ents: true,
+            wanted_ticks: vec![],
+            parse_projectiles: false,
+            only_header: true,
+            count_props: false,
+            only_convars: false,
+            huffman_lookup_table: &self.huf,
+            order_by_steamid: false,
+            change_log: false,
+            parse_money_ledger: false,
+            parse_bomb: false,
+            parse_spotting: false,
+            parse_kill_context: false,
+            parse_shots: true,
+            parse_sprays: false,
+            parse_usercmds: false,
+            parse_movement: false,
+            parse_weapons: false,
+            wanted_entity_classes: vec![],
+            wanted_entity_props: vec![],
+            entity_event_classes: vec![],
+            wanted_prop_changes: vec![],
+            map_overview: None,
+            nav_mesh: None,
+            map_geometry: None,
+            visibility_pairs: None,
+            fov: None,
+            parallelism: self.parallelism.clone(),
+        };
+        let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
+        let output = match parser.parse_demo(&self.mmap) {
+            Ok(output) => output,
+            Err(e) => return Err(Exception::new_err(format!("{e}"))),
+        };
+        let shots = &output.shots;
+        let accuracy = &output.accuracy;
+
+        let ticks: Vec<Option<i32>> = shots.iter().map(|s| Some(s.tick)).collect();
+        let rounds: Vec<Option<i32>> = shots.iter().map(|s| s.round).collect();
+        let steamids: Vec<Option<u64>> = shots.iter().map(|s| Some(s.steamid)).collect();
+        let names: Vec<Option<String>> = shots.iter().map(|s| s.name.clone()).collect();
+        let weapons: Vec<Option<String>> = shots.iter().map(|s| Some(s.weapon.clone())).collect();
+        let recoil_indices: Vec<Option<f32>> = shots.iter().map(|s| s.recoil_index).collect();
+        let inaccuracies: Vec<Option<f32>> = shots.iter().map(|s| s.inaccuracy).collect();
+        let speeds: Vec<Option<f32>> = shots.iter().map(|s| s.speed).collect();
+        let movements: Vec<Option<String>> = shots.iter().map(|s| Some(s.movement.clone())).collect();
+        let burst_indices: Vec<Option<i32>> = shots.iter().map(|s| Some(s.burst_index)).collect();
+        let hits: Vec<Option<bool>> = shots.iter().map(|s| Some(s.hit)).collect();
+        let victim_steamids: Vec<Option<u64>> = shots.iter().map(|s| s.victim_steamid).collect();
+        let victim_names: Vec<Option<String>> = shots.iter().map(|s| s.victim_name.clone()).collect();
+        let hitgroups: Vec<Option<String>> = shots.iter().map(|s| s.hitgroup.clone()).collect();
+        let damages: Vec<Option<i32>> = shots.iter().map(|s| Some(s.damage)).collect();
+        let players_hit: Vec<Option<i32>> = shots.iter().map(|s| Some(s.players_hit)).collect();
+        let impact_xs: Vec<Option<f32>> = shots.iter().map(|s| s.impact.map(|p| p[0])).collect();
+        let impact_ys: Vec<Option<f32>> = shots.iter().map(|s| s.impact.map(|p| p[1])).collect();
+        let impact_zs: Vec<Option<f32>> = shots.iter().map(|s| s.impact.map(|p| p[2])).collect();
+        let shoot_pitches: Vec<Option<f32>> = shots.iter().map(|s| s.shoot_pitch).collect();
+        let shoot_yaws: Vec<Option<f32>> = shots.iter().map(|s| s.shoot_yaw).collect();
+
+        let acc_steamids: Vec<Option<u64>> = accuracy.iter().map(|a| Some(a.steamid)).collect();
+        let acc_names: Vec<Option<String>> = accuracy.iter().map(|a| a.name.clone()).collect();
+        let acc_weapons: Vec<Option<String>> = accuracy.iter().map(|a| Some(a.weapon.clone())).collect();
+        let acc_shots: Vec<Option<i32>> = accuracy.iter().map(|a| Some(a.shots)).collect();
+        let acc_hits: Vec<Option<i32>> = accuracy.iter().map(|a| Some(a.hits)).collect();
+        let accuracies: Vec<Option<f32>> = accuracy.iter().map(|a| a.accuracy).collect();
+        let first_bullet_shots: Vec<Option<i32>> = accuracy.iter().map(|a| Some(a.first_bullet_shots)).collect();
+        let first_bullet_hits: Vec<Option<i32>> = accuracy.iter().map(|a| Some(a.first_bullet_hits)).collect();
+        let first_bullet_accuracies: Vec<Option<f32>> = accuracy.iter().map(|a| a.first_bullet_accuracy).collect();
+        let spray_shots: Vec<Option<i32>> = accuracy.iter().map(|a| Some(a.spray_shots)).collect();
+        let spray_hits: Vec<Option<i32>> = accuracy.iter().map(|a| Some(a.spray_hits)).collect();
+        let spray_accuracies: Vec<Option<f32>> = accuracy.iter().map(|a| a.spray_accuracy).collect();
+
+        // SoA form
+        let ticks = arr_to_py(Box::new(Int32Array::from(ticks)))?;
+        let rounds = arr_to_py(Box::new(Int32Array::from(rounds)))?;
+        let steamids = arr_to_py(Box::new(UInt64Array::from(steamids)))?;
+        let names = arr_to_py(Box::new(Utf8Array::<i32>::from(names)))?;
+        let weapons = arr_to_py(Box::new(Utf8Array::<i32>::from(weapons)))?;
+        let recoil_indices = arr_to_py(Box::new(Float32Array::from(recoil_indices)))?;
+        let inaccuracies = arr_to_py(Box::new(Float32Array::from(inaccuracies)))?;
+        let speeds = arr_to_py(Box::new(Float32Array::from(speeds)))?;
+        let movements = arr_to_py(Box::new(Utf8Array::<i32>::from(movements)))?;
+        let burst_indices = arr_to_py(Box::new(Int32Array::from(burst_indices)))?;
+        let hits = arr_to_py(Box::new(BooleanArray::from(hits)))?;
+        let victim_steamids = arr_to_py(Box::new(UInt64Array::from(victim_steamids)))?;
+        let victim_names = arr_to_py(Box::new(Utf8Array::<i32>::from(victim_names)))?;
+        let hitgroups = arr_to_py(Box::new(Utf8Array::<i32>::from(hitgroups)))?;
+        let damages = arr_to_py(Box::new(Int32Array::from(damages)))?;
+        let players_hit = arr_to_py(Box::new(Int32Array::from(players_hit)))?;
+        let impact_xs = arr_to_py(Box::new(Float32Array::from(impact_xs)))?;
+        let impact_ys = arr_to_py(Box::new(Float32Array::from(impact_ys)))?;
+        let impact_zs = arr_to_py(Box::new(Float32Array::from(impact_zs)))?;
+        let shoot_pitches = arr_to_py(Box::new(Float32Array::from(shoot_pitches)))?;
+        let shoot_yaws = arr_to_py(Box::new(Float32Array::from(shoot_yaws)))?;
+
+        let acc_steamids = arr_to_py(Box::new(UInt64Array::from(acc_steamids)))?;
+        let acc_names = arr_to_py(Box::new(Utf8Array::<i32>::from(acc_names)))?;
+        let acc_weapons = arr_to_py(Box::new(Utf8Array::<i32>::from(acc_weapons)))?;
+        let acc_shots = arr_to_py(Box::new(Int32Array::from(acc_shots)))?;
+        let acc_hits = arr_to_py(Box::new(Int32Array::from(acc_hits)))?;
+        let accuracies = arr_to_py(Box::new(Float32Array::from(accuracies)))?;
+        let first_bullet_shots = arr_to_py(Box::new(Int32Array::from(first_bullet_shots)))?;
+        let first_bullet_hits = arr_to_py(Box::new(Int32Array::from(first_bullet_hits)))?;
+        let first_bullet_accuracies = arr_to_py(Box::new(Float32Array::from(first_bullet_accuracies)))?;
+        let spray_shots = arr_to_py(Box::new(Int32Array::from(spray_shots)))?;
+        let spray_hits = arr_to_py(Box::new(Int32Array::from(spray_hits)))?;
+        let spray_accuracies = arr_to_py(Box::new(Float32Array::from(spray_accuracies)))?;
+
+        let polars = py.import_bound("polars")?;
+        let shot_series_py = [
+            ticks,
+            rounds,
+            steamids,
+            names,
+            weapons,
+            recoil_indices,
+            inaccuracies,
+            speeds,
+            movements,
+            burst_indices,
+            hits,
+            victim_steamids,
+            victim_names,
+            hitgroups,
+            damages,
+            players_hit,
+            impact_xs,
+            impact_ys,
+            impact_zs,
+            shoot_pitches,
+            shoot_yaws,
+        ]
+        .to_object(py);
+        let accuracy_series_py = [
+            acc_steamids,
+            acc_names,
+            acc_weapons,
+            acc_shots,
+            acc_hits,
+            accuracies,
+            first_bullet_shots,
+            first_bullet_hits,
+            first_bullet_accuracies,
+            spray_shots,
+            spray_hits,
+            spray_accuracies,
+        ]
+        .to_object(py);
+        Python::with_gil(|py| {
+            let kwargs = vec![("use_pyarrow_extension_array", true)].into_py_dict_bound(py);
+
+            let shots_df = polars.call_method1("DataFrame", (shot_series_py,))?;
+            let shot_column_names = [
+                "tick",
+                "round",
+                "steamid",
+                "name",
+                "weapon",
+                "recoil_index",
+                "inaccuracy",
+                "speed",
+                "movement",
+                "burst_index",
+                "hit",
+                "victim_steamid",
+                "victim_name",
+                "hitgroup",
+                "damage",
+                "players_hit",
+                "impact_x",
+                "impact_y",
+                "impact_z",
+                "shoot_pitch",
+                "shoot_yaw",
+            ];
+            shots_df.setattr("columns", shot_column_names.to_object(py))?;
+            let shots_pandas = shots_df.call_method("to_pandas", (), Some(&kwargs))?;
+
+            let accuracy_df = polars.call_method1("DataFrame", (accuracy_series_py,))?;
+            let accuracy_column_names = [
+                "steamid",
+                "name",
+                "weapon",
+                "shots",
+                "hits",
+                "accuracy",
+                "first_bullet_shots",
+                "first_bullet_hits",
+                "first_bullet_accuracy",
+                "spray_shots",
+                "spray_hits",
+                "spray_accuracy",
+            ];
+            accuracy_df.setattr("columns", accuracy_column_names.to_object(py))?;
+            let accuracy_pandas = accuracy_df.call_method("to_pandas", (), Some(&kwargs))?;
+            Ok((shots_pandas, accuracy_pandas).to_object(py))
+        })
     }
+    /// Returns two dataframes. The first has a row per rifle spray (3+ bullets with an increasing recoil index) scored by
+    /// how far the bullets went from where the first bullet went, in degrees. The second has a row per bullet with the view
+    /// angles and aim punch relative to the first bullet and the view change that would have cancelled the recoil exactly.
+    pub fn parse_sprays(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        let settings = ParserInputs {
+            real_name_to_og_name: AHashMap::default(),
+            wanted_players: vec![],
+            wanted_player_props: vec![],
+            wanted_other_props: vec![],
+            wanted_prop_states: PropFilter::default(),
+            wanted_events: vec![],
+            parse_ents: true,
+            wanted_ticks: vec![],
+            parse_projectiles: false,
+            only_header: true,
+            count_props: false,
+            only_convars: false,
+            huffman_lookup_table: &self.huf,
+            order_by_steamid: false,
+            change_log: false,
+            parse_money_ledger: false,
+            parse_bomb: false,
+            parse_spotting: false,
+            parse_kill_context: false,
+            parse_shots: false,
+            parse_sprays: true,
+            parse_usercmds: false,
+            parse_movement: false,
+            parse_weapons: false,
+            wanted_entity_classes: vec![],
+            wanted_entity_props: vec![],
+            entity_event_classes: vec![],
+            wanted_prop_changes: vec![],
+            map_overview: None,
+            nav_mesh: None,
+            map_geometry: None,
+            visibility_pairs: None,
+            fov: None,
+            parallelism: self.parallelism.clone(),
+        };
+        let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
+        let output = match parser.parse_demo(&self.mmap) {
+            Ok(output) => output,
+            Err(e) => return Err(Exception::new_err(format!("{e}"))),
+        };
+        let sprays = &output.sprays;
+        let spray_bullets = &output.spray_bullets;
+
+        let spray_ids: Vec<Option<i32>> = sprays.iter().map(|s| Some(s.spray_id)).collect();
+        let steamids: Vec<Option<u64>> = sprays.iter().map(|s| Some(s.steamid)).collect();
+        let names: Vec<Option<String>> = sprays.iter().map(|s| s.name.clone()).collect();
+        let weapons: Vec<Option<String>> = sprays.iter().map(|s| Some(s.weapon.clone())).collect();
+        let rounds: Vec<Option<i32>> = sprays.iter().map(|s| s.round).collect();
+        let start_ticks: Vec<Option<i32>> = sprays.iter().map(|s| Some(s.start_tick)).collect();
+        let end_ticks: Vec<Option<i32>> = sprays.iter().map(|s| Some(s.end_tick)).collect();
+        let bullets: Vec<Option<i32>> = sprays.iter().map(|s| Some(s.bullets)).collect();
+        let hits: Vec<Option<i32>> = sprays.iter().map(|s| Some(s.hits)).collect();
+        let angle_sources: Vec<Option<String>> = sprays.iter().map(|s| Some(s.angle_source.clone())).collect();
+        let mean_errors: Vec<Option<f32>> = sprays.iter().map(|s| s.mean_error).collect();
+        let max_errors: Vec<Option<f32>> = sprays.iter().map(|s| s.max_error).collect();
+
+        let bullet_spray_ids: Vec<Option<i32>> = spray_bullets.iter().map(|b| Some(b.spray_id)).collect();
+        let bullet_steamids: Vec<Option<u64>> = spray_bullets.iter().map(|b| Some(b.steamid)).collect();
+        let bullet_ticks: Vec<Option<i32>> = spray_bullets.iter().map(|b| Some(b.tick)).collect();
+        let bullet_indices: Vec<Option<i32>> = spray_bullets.iter().map(|b| Some(b.bullet)).collect();
+        let recoil_indices: Vec<Option<f32>> = spray_bullets.iter().map(|b| b.recoil_index).collect();
+        let view_pitches: Vec<Option<f32>> = spray_bullets.iter().map(|b| b.view_pitch).collect();
+        let view_yaws: Vec<Option<f32>> = spray_bullets.iter().map(|b| b.view_yaw).collect();
+        let aim_punch_pitches: Vec<Option<f32>> = spray_bullets.iter().map(|b| b.aim_punch_pitch).collect();
+        let aim_punch_yaws: Vec<Option<f32>> = spray_bullets.iter().map(|b| b.aim_punch_yaw).collect();
+        let ideal_pitches: Vec<Option<f32>> = spray_bullets.iter().map(|b| b.ideal_pitch).collect();
+        let ideal_yaws: Vec<Option<f32>> = spray_bullets.iter().map(|b| b.ideal_yaw).collect();
+        let errors: Vec<Option<f32>> = spray_bullets.iter().map(|b| b.error).collect();
+        let bullet_hits: Vec<Option<bool>> = spray_bullets.iter().map(|b| Some(b.hit)).collect();
+
+        // SoA form
+        let spray_ids = arr_to_py(Box::new(Int32Array::from(spray_ids)))?;
+        let steamids = arr_to_py(Box::new(UInt64Array::from(steamids)))?;
+        let names = arr_to_py(Box::new(Utf8Array::<i32>::from(names)))?;
+        let weapons = arr_to_py(Box::new(Utf8Array::<i32>::from(weapons)))?;
+        let rounds = arr_to_py(Box::new(Int32Array::from(rounds)))?;
+        let start_ticks = arr_to_py(Box::new(Int32Array::from(start_ticks)))?;
+        let end_ticks = arr_to_py(Box::new(Int32Array::from(end_ticks)))?;
+        let bullets = arr_to_py(Box::new(Int32Array::from(bullets)))?;
+        let hits = arr_to_py(Box::new(Int32Array::from(hits)))?;
+        let angle_sources = arr_to_py(Box::new(Utf8Array::<i32>::from(angle_sources)))?;
+        let mean_errors = arr_to_py(Box::new(Float32Array::from(mean_errors)))?;
+        let max_errors = arr_to_py(Box::new(Float32Array::from(max_errors)))?;
+
+        let bullet_spray_ids = arr_to_py(Box::new(Int32Array::from(bullet_spray_ids)))?;
+        let bullet_steamids = arr_to_py(Box::new(UInt64Array::from(bullet_steamids)))?;
+        let bullet_ticks = arr_to_py(Box::new(Int32Array::from(bullet_ticks)))?;
+        let bullet_indices = arr_to_py(Box::new(Int32Array::from(bullet_indices)))?;
+        let recoil_indices = arr_to_py(Box::new(Float32Array::from(recoil_indices)))?;
+        let view_pitches = arr_to_py(Box::new(Float32Array::from(view_pitches)))?;
+        let view_yaws = arr_to_py(Box::new(Float32Array::from(view_yaws)))?;
+        let aim_punch_pitches = arr_to_py(Box::new(Float32Array::from(aim_punch_pitches)))?;
+        let aim_punch_yaws = arr_to_py(Box::new(Float32Array::from(aim_punch_yaws)))?;
+        let ideal_pitches = arr_to_py(Box::new(Float32Array::from(ideal_pitches)))?;
+        let ideal_yaws = arr_to_py(Box::new(Float32Array::from(ideal_yaws)))?;
+        let errors = arr_to_py(Box::new(Float32Array::from(errors)))?;
+        let bullet_hits = arr_to_py(Box::new(BooleanArray::from(bullet_hits)))?;
+
+        let polars = py.import_bound("polars")?;
+        let spray_series_py = [
+            spray_ids,
+            steamids,
+            names,
+            weapons,
+            rounds,
+            start_ticks,
+            end_ticks,
+            bullets,
+            hits,
+            angle_sources,
+            mean_errors,
+            max_errors,
+        ]
+        .to_object(py);
+        let bullet_series_py = [
+            bullet_spray_ids,
+            bullet_steamids,
+            bullet_ticks,
+            bullet_indices,
+            recoil_indices,
+            view_pitches,
+            view_yaws,
+            aim_punch_pitches,
+            aim_punch_yaws,
+            ideal_pitches,
+            ideal_yaws,
+            errors,
+            bullet_hits,
+        ]
+        .to_object(py);
+        Python::with_gil(|py| {
+            let kwargs = vec![("use_pyarrow_extension_array", true)].into_py_dict_bound(py);
+
+            let sprays_df = polars.call_method1("DataFrame", (spray_series_py,))?;
+            let spray_column_names = [
+                "spray_id",
+                "steamid",
+                "name",
+                "weapon",
+                "round",
+                "start_tick",
+                "end_tick",
+                "bullets",
+                "hits",
+                "angle_source",
+                "mean_error",
+                "max_error",
+            ];
+            sprays_df.setattr("columns", spray_column_names.to_object(py))?;
+            let sprays_pandas = sprays_df.call_method("to_pandas", (), Some(&kwargs))?;
 
-    /// Parses header message (different from the first 16 bytes of the file)
-    /// Should have the following fields:
-    ///
-    /// "addons", "server_name", "demo_file_stamp", "network_protocol",
-    /// "map_name", "fullpackets_version", "allow_clientside_entities",
-    /// "allow_clientside_particles", "demo_version_name", "demo_version_guid",
-    /// "client_name", "game_directory"
-    pub fn parse_header(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+            let bullets_df = polars.call_method1("DataFrame", (bullet_series_py,))?;
+            let bullet_column_names = [
+                "spray_id",
+                "steamid",
+                "tick",
+                "bullet",
+                "recoil_index",
+                "view_pitch",
+                "view_yaw",
+                "aim_punch_pitch",
+                "aim_punch_yaw",
+                "ideal_pitch",
+                "ideal_yaw",
+                "error",
+                "hit",
+            ];
+            bullets_df.setattr("columns", bullet_column_names.to_object(py))?;
+            let bullets_pandas = bullets_df.call_method("to_pandas", (), Some(&kwargs))?;
+            Ok((sprays_pandas, bullets_pandas).to_object(py))
+        })
+    }
+    /// Every usercmd in the demo, one row per command. parse_ticks only sees the latest command of
+    /// each player at the wanted ticks, this keeps all of them. subtick_moves and input_history are
+    /// lists of dicts.
+    pub fn parse_usercmds(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
         let settings = ParserInputs {
             real_name_to_og_name: AHashMap::default(),
             wanted_players: vec![],
             wanted_player_props: vec![],
             wanted_other_props: vec![],
-            wanted_prop_states: AHashMap::default(),
+            wanted_prop_states: PropFilter::default(),
             wanted_events: vec![],
-            parse_ents: false,
+            parse_ents: true,
             wanted_ticks: vec![],
             parse_projectiles: false,
             only_header: true,
@@ -103,27 +1723,184 @@ impl DemoParser {
             only_convars: false,
             huffman_lookup_table: &self.huf,
             order_by_steamid: false,
+            change_log: false,
+            parse_money_ledger: false,
+            parse_bomb: false,
+            parse_spotting: false,
+            parse_kill_context: false,
+            parse_shots: false,
+            parse_sprays: false,
+            parse_usercmds: true,
+            parse_movement: false,
+            parse_weapons: false,
+            wanted_entity_classes: vec![],
+            wanted_entity_props: vec![],
+            entity_event_classes: vec![],
+            wanted_prop_changes: vec![],
+            map_overview: None,
+            nav_mesh: None,
+            map_geometry: None,
+            visibility_pairs: None,
+            fov: None,
+            parallelism: self.parallelism.clone(),
         };
         let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
         let output = match parser.parse_demo(&self.mmap) {
             Ok(output) => output,
             Err(e) => return Err(Exception::new_err(format!("{e}"))),
         };
-        Ok(output
-            .header
-            .unwrap_or_else(AHashMap::default)
-            .to_object(py))
+        let usercmds = &output.usercmds;
+
+        let ticks: Vec<Option<i32>> = usercmds.iter().map(|c| Some(c.tick)).collect();
+        let steamids: Vec<Option<u64>> = usercmds.iter().map(|c| c.steamid).collect();
+        let names: Vec<Option<String>> = usercmds.iter().map(|c| c.name.clone()).collect();
+        let entity_ids: Vec<Option<i32>> = usercmds.iter().map(|c| Some(c.entity_id)).collect();
+        let legacy_command_numbers: Vec<Option<i32>> = usercmds.iter().map(|c| Some(c.legacy_command_number)).collect();
+        let client_ticks: Vec<Option<i32>> = usercmds.iter().map(|c| Some(c.client_tick)).collect();
+        let buttonstates_1: Vec<Option<u64>> = usercmds.iter().map(|c| Some(c.buttonstate_1)).collect();
+        let buttonstates_2: Vec<Option<u64>> = usercmds.iter().map(|c| Some(c.buttonstate_2)).collect();
+        let buttonstates_3: Vec<Option<u64>> = usercmds.iter().map(|c| Some(c.buttonstate_3)).collect();
+        let viewangles_x: Vec<Option<f32>> = usercmds.iter().map(|c| Some(c.viewangle_x)).collect();
+        let viewangles_y: Vec<Option<f32>> = usercmds.iter().map(|c| Some(c.viewangle_y)).collect();
+        let viewangles_z: Vec<Option<f32>> = usercmds.iter().map(|c| Some(c.viewangle_z)).collect();
+        let forward_moves: Vec<Option<f32>> = usercmds.iter().map(|c| Some(c.forward_move)).collect();
+        let left_moves: Vec<Option<f32>> = usercmds.iter().map(|c| Some(c.left_move)).collect();
+        let up_moves: Vec<Option<f32>> = usercmds.iter().map(|c| Some(c.up_move)).collect();
+        let impulses: Vec<Option<i32>> = usercmds.iter().map(|c| Some(c.impulse)).collect();
+        let weapon_selects: Vec<Option<i32>> = usercmds.iter().map(|c| Some(c.weapon_select)).collect();
+        let random_seeds: Vec<Option<i32>> = usercmds.iter().map(|c| Some(c.random_seed)).collect();
+        let mouse_dxs: Vec<Option<i32>> = usercmds.iter().map(|c| Some(c.mouse_dx)).collect();
+        let mouse_dys: Vec<Option<i32>> = usercmds.iter().map(|c| Some(c.mouse_dy)).collect();
+        let consumed_server_angle_changes: Vec<Option<u32>> = usercmds.iter().map(|c| Some(c.consumed_server_angle_changes)).collect();
+        let cmd_flags: Vec<Option<i32>> = usercmds.iter().map(|c| Some(c.cmd_flags)).collect();
+        let move_crcs: Vec<Option<String>> = usercmds.iter().map(|c| Some(c.move_crc.clone())).collect();
+        let attack_start_history_indices_1: Vec<Option<i32>> = usercmds.iter().map(|c| Some(c.attack_start_history_index_1)).collect();
+        let attack_start_history_indices_2: Vec<Option<i32>> = usercmds.iter().map(|c| Some(c.attack_start_history_index_2)).collect();
+        let attack_start_history_indices_3: Vec<Option<i32>> = usercmds.iter().map(|c| Some(c.attack_start_history_index_3)).collect();
+        let left_hands_desired: Vec<Option<bool>> = usercmds.iter().map(|c| Some(c.left_hand_desired)).collect();
+
+        let mut subtick_moves = vec![];
+        let mut input_histories = vec![];
+        for usercmd in usercmds {
+            subtick_moves.push(subtick_moves_to_py(py, &usercmd.subtick_moves)?);
+            input_histories.push(input_history_to_py(py, &usercmd.input_history)?);
+        }
+        // SoA form
+        let ticks = arr_to_py(Box::new(Int32Array::from(ticks)))?;
+        let steamids = arr_to_py(Box::new(UInt64Array::from(steamids)))?;
+        let names = arr_to_py(Box::new(Utf8Array::<i32>::from(names)))?;
+        let entity_ids = arr_to_py(Box::new(Int32Array::from(entity_ids)))?;
+        let legacy_command_numbers = arr_to_py(Box::new(Int32Array::from(legacy_command_numbers)))?;
+        let client_ticks = arr_to_py(Box::new(Int32Array::from(client_ticks)))?;
+        let buttonstates_1 = arr_to_py(Box::new(UInt64Array::from(buttonstates_1)))?;
+        let buttonstates_2 = arr_to_py(Box::new(UInt64Array::from(buttonstates_2)))?;
+        let buttonstates_3 = arr_to_py(Box::new(UInt64Array::from(buttonstates_3)))?;
+        let viewangles_x = arr_to_py(Box::new(Float32Array::from(viewangles_x)))?;
+        let viewangles_y = arr_to_py(Box::new(Float32Array::from(viewangles_y)))?;
+        let viewangles_z = arr_to_py(Box::new(Float32Array::from(viewangles_z)))?;
+        let forward_moves = arr_to_py(Box::new(Float32Array::from(forward_moves)))?;
+        let left_moves = arr_to_py(Box::new(Float32Array::from(left_moves)))?;
+        let up_moves = arr_to_py(Box::new(Float32Array::from(up_moves)))?;
+        let impulses = arr_to_py(Box::new(Int32Array::from(impulses)))?;
+        let weapon_selects = arr_to_py(Box::new(Int32Array::from(weapon_selects)))?;
+        let random_seeds = arr_to_py(Box::new(Int32Array::from(random_seeds)))?;
+        let mouse_dxs = arr_to_py(Box::new(Int32Array::from(mouse_dxs)))?;
+        let mouse_dys = arr_to_py(Box::new(Int32Array::from(mouse_dys)))?;
+        let consumed_server_angle_changes = arr_to_py(Box::new(UInt32Array::from(consumed_server_angle_changes)))?;
+        let cmd_flags = arr_to_py(Box::new(Int32Array::from(cmd_flags)))?;
+        let move_crcs = arr_to_py(Box::new(Utf8Array::<i32>::from(move_crcs)))?;
+        let attack_start_history_indices_1 = arr_to_py(Box::new(Int32Array::from(attack_start_history_indices_1)))?;
+        let attack_start_history_indices_2 = arr_to_py(Box::new(Int32Array::from(attack_start_history_indices_2)))?;
+        let attack_start_history_indices_3 = arr_to_py(Box::new(Int32Array::from(attack_start_history_indices_3)))?;
+        let left_hands_desired = arr_to_py(Box::new(BooleanArray::from(left_hands_desired)))?;
+
+        let polars = py.import_bound("polars")?;
+        let all_series_py = [
+            ticks,
+            steamids,
+            names,
+            entity_ids,
+            legacy_command_numbers,
+            client_ticks,
+            buttonstates_1,
+            buttonstates_2,
+            buttonstates_3,
+            viewangles_x,
+            viewangles_y,
+            viewangles_z,
+            forward_moves,
+            left_moves,
+            up_moves,
+            impulses,
+            weapon_selects,
+            random_seeds,
+            mouse_dxs,
+            mouse_dys,
+            consumed_server_angle_changes,
+            cmd_flags,
+            move_crcs,
+            attack_start_history_indices_1,
+            attack_start_history_indices_2,
+            attack_start_history_indices_3,
+            left_hands_desired,
+        ]
+        .to_object(py);
+        Python::with_gil(|py| {
+            let df = polars.call_method1("DataFrame", (all_series_py,))?;
+            let column_names = [
+                "tick",
+                "steamid",
+                "name",
+                "entity_id",
+                "legacy_command_number",
+                "client_tick",
+                "buttonstate_1",
+                "buttonstate_2",
+                "buttonstate_3",
+                "viewangle_x",
+                "viewangle_y",
+                "viewangle_z",
+                "forward_move",
+                "left_move",
+                "up_move",
+                "impulse",
+                "weapon_select",
+                "random_seed",
+                "mouse_dx",
+                "mouse_dy",
+                "consumed_server_angle_changes",
+                "cmd_flags",
+                "move_crc",
+                "attack_start_history_index_1",
+                "attack_start_history_index_2",
+                "attack_start_history_index_3",
+                "left_hand_desired",
+            ];
+            df.setattr("columns", column_names.to_object(py))?;
+            let kwargs = vec![("use_pyarrow_extension_array", true)].into_py_dict_bound(py);
+            let pandas_df = df.call_method("to_pandas", (), Some(&kwargs))?;
+            pandas_df.call_method1(
+                "insert",
+                (column_names.len(), "subtick_moves", subtick_moves.to_object(py)),
+            )?;
+            pandas_df.call_method1(
+                "insert",
+                (column_names.len() + 1, "input_history", input_histories.to_object(py)),
+            )?;
+            Ok(pandas_df.to_object(py))
+        })
     }
-    /// Returns the names of game events present in the demo
-    pub fn list_game_events(&self, _py: Python<'_>) -> PyResult<Py<PyAny>> {
+    /// Jumps, landings, bunny hops, counter-strafes before shots, crouch-peeks, ducks and ladder use.
+    /// counter_strafe_feedback rows come from the game's own counter-strafe message and only exist in POV demos.
+    pub fn parse_movement(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
         let settings = ParserInputs {
             real_name_to_og_name: AHashMap::default(),
             wanted_players: vec![],
             wanted_player_props: vec![],
             wanted_other_props: vec![],
-            wanted_prop_states: AHashMap::default(),
-            wanted_events: vec!["all".to_string()],
-            parse_ents: false,
+            wanted_prop_states: PropFilter::default(),
+            wanted_events: vec![],
+            parse_ents: true,
             wanted_ticks: vec![],
             parse_projectiles: false,
             only_header: true,
@@ -131,94 +1908,364 @@ impl DemoParser {
             only_convars: false,
             huffman_lookup_table: &self.huf,
             order_by_steamid: false,
+            change_log: false,
+            parse_money_ledger: false,
+            parse_bomb: false,
+            parse_spotting: false,
+            parse_kill_context: false,
+            parse_shots: false,
+            parse_sprays: false,
+            parse_usercmds: false,
+            parse_movement: true,
+            parse_weapons: false,
+            wanted_entity_classes: vec![],
+            wanted_entity_props: vec![],
+            entity_event_classes: vec![],
+            wanted_prop_changes: vec![],
+            map_overview: None,
+            nav_mesh: None,
+            map_geometry: None,
+            visibility_pairs: None,
+            fov: None,
+            parallelism: self.parallelism.clone(),
         };
         let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
         let output = match parser.parse_demo(&self.mmap) {
             Ok(output) => output,
             Err(e) => return Err(Exception::new_err(format!("{e}"))),
         };
-        let as_vec = output.game_events_counter.iter().collect_vec();
-        let ge = pyo3::Python::with_gil(|py| as_vec.to_object(py));
-        Ok(ge)
-    }
+        let events = &output.movement;
 
-    /// Returns all coordinates of all grenades along with info about thrower.
-    ///
-    /// Example:
-    ///          X           Y       Z  tick  thrower_steamid grenade_type
-    /// 0 -388.875  1295.46875 -5120.0   982              NaN    HeGrenade
-    /// 1 -388.875  1295.46875 -5120.0   983              NaN    HeGrenade
-    /// 2 -388.875  1295.46875 -5120.0   983              NaN    HeGrenade
+        let ticks: Vec<Option<i32>> = events.iter().map(|e| Some(e.tick)).collect();
+        let rounds: Vec<Option<i32>> = events.iter().map(|e| e.round).collect();
+        let steamids: Vec<Option<u64>> = events.iter().map(|e| Some(e.steamid)).collect();
+        let names: Vec<Option<String>> = events.iter().map(|e| e.name.clone()).collect();
+        let event_types: Vec<Option<String>> = events.iter().map(|e| Some(e.event_type.clone())).collect();
+        let start_ticks: Vec<Option<i32>> = events.iter().map(|e| Some(e.start_tick)).collect();
+        let durations: Vec<Option<f32>> = events.iter().map(|e| e.duration).collect();
+        let speeds: Vec<Option<f32>> = events.iter().map(|e| e.speed).collect();
+        let weapons: Vec<Option<String>> = events.iter().map(|e| e.weapon.clone()).collect();
+        let opposing_keys: Vec<Option<bool>> = events.iter().map(|e| e.opposing_key).collect();
+        let keys_down: Vec<Option<i32>> = events.iter().map(|e| e.keys_down).collect();
 
-    pub fn parse_grenades(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        // SoA form
+        let ticks = arr_to_py(Box::new(Int32Array::from(ticks)))?;
+        let rounds = arr_to_py(Box::new(Int32Array::from(rounds)))?;
+        let steamids = arr_to_py(Box::new(UInt64Array::from(steamids)))?;
+        let names = arr_to_py(Box::new(Utf8Array::<i32>::from(names)))?;
+        let event_types = arr_to_py(Box::new(Utf8Array::<i32>::from(event_types)))?;
+        let start_ticks = arr_to_py(Box::new(Int32Array::from(start_ticks)))?;
+        let durations = arr_to_py(Box::new(Float32Array::from(durations)))?;
+        let speeds = arr_to_py(Box::new(Float32Array::from(speeds)))?;
+        let weapons = arr_to_py(Box::new(Utf8Array::<i32>::from(weapons)))?;
+        let opposing_keys = arr_to_py(Box::new(BooleanArray::from(opposing_keys)))?;
+        let keys_down = arr_to_py(Box::new(Int32Array::from(keys_down)))?;
+
+        let polars = py.import_bound("polars")?;
+        let all_series_py = [
+            ticks,
+            rounds,
+            steamids,
+            names,
+            event_types,
+            start_ticks,
+            durations,
+            speeds,
+            weapons,
+            opposing_keys,
+            keys_down,
+        ]
+        .to_object(py);
+        Python::with_gil(|py| {
+            let df = polars.call_method1("DataFrame", (all_series_py,))?;
+            let column_names = [
+                "tick",
+                "round",
+                "steamid",
+                "name",
+                "event_type",
+                "start_tick",
+                "duration",
+                "speed",
+                "weapon",
+                "opposing_key",
+                "keys_down",
+            ];
+            df.setattr("columns", column_names.to_object(py))?;
+            let kwargs = vec![("use_pyarrow_extension_array", true)].into_py_dict_bound(py);
+            let pandas_df = df.call_method("to_pandas", (), Some(&kwargs))?;
+            Ok(pandas_df.to_object(py))
+        })
+    }
+    /// One row per tick for every entity of the given classes, including the ones parse_ticks
+    /// does not cover (CChicken, CHostage, weapons on the ground, CPlantedC4, CBaseDoor...).
+    /// Props are relative to the class ("m_iHealth") or full names, "X", "Y" and "Z" give the position.
+    #[pyo3(signature = (class_names, props, *, ticks=None))]
+    pub fn parse_entities(
+        &self,
+        py: Python<'_>,
+        class_names: Vec<String>,
+        props: Vec<String>,
+        ticks: Option<Vec<i32>>,
+    ) -> PyResult<Py<PyAny>> {
         let settings = ParserInputs {
             real_name_to_og_name: AHashMap::default(),
             wanted_players: vec![],
             wanted_player_props: vec![],
             wanted_other_props: vec![],
-            wanted_prop_states: AHashMap::default(),
+            wanted_prop_states: PropFilter::default(),
             wanted_events: vec![],
             parse_ents: true,
-            wanted_ticks: vec![],
-            parse_projectiles: true,
+            wanted_ticks: ticks.unwrap_or_default(),
+            parse_projectiles: false,
             only_header: true,
             count_props: false,
             only_convars: false,
             huffman_lookup_table: &self.huf,
             order_by_steamid: false,
+            change_log: false,
+            parse_money_ledger: false,
+            parse_bomb: false,
+            parse_spotting: false,
+            parse_kill_context: false,
+            parse_shots: false,
+            parse_sprays: false,
+            parse_usercmds: false,
+            parse_movement: false,
+            parse_weapons: false,
+            wanted_entity_classes: class_names,
+            wanted_entity_props: props,
+            entity_event_classes: vec![],
+            wanted_prop_changes: vec![],
+            map_overview: None,
+            nav_mesh: None,
+            map_geometry: None,
+            visibility_pairs: None,
+            fov: None,
+            parallelism: self.parallelism.clone(),
         };
         let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
         let output = match parser.parse_demo(&self.mmap) {
             Ok(output) => output,
             Err(e) => return Err(Exception::new_err(format!("{e}"))),
         };
-
-        let entity_id: Vec<Option<i32>> = output.projectiles.iter().map(|s| s.entity_id).collect();
-        let xs: Vec<Option<f32>> = output.projectiles.iter().map(|s| s.x).collect();
-        let ys: Vec<Option<f32>> = output.projectiles.iter().map(|s| s.y).collect();
-        let zs: Vec<Option<f32>> = output.projectiles.iter().map(|s| s.z).collect();
-
-        let ticks: Vec<Option<i32>> = output.projectiles.iter().map(|s| s.tick).collect();
-        let steamid: Vec<Option<u64>> = output.projectiles.iter().map(|s| s.steamid).collect();
-        let name: Vec<Option<String>> = output.projectiles.iter().map(|s| s.name.clone()).collect();
-        let grenade_type: Vec<Option<String>> = output
-            .projectiles
+        // Same shape as events so the columns can be built the same way
+        let rows: Vec<GameEvent> = output
+            .entities
             .iter()
-            .map(|s| s.grenade_type.clone())
+            .map(|record| {
+                let mut fields = vec![
+                    EventField {
+                        name: "tick".to_string(),
+                        data: Some(Variant::I32(record.tick)),
+                    },
+                    EventField {
+                        name: "entity_id".to_string(),
+                        data: Some(Variant::I32(record.entity_id)),
+                    },
+                    EventField {
+                        name: "class_name".to_string(),
+                        data: Some(Variant::String(record.class_name.clone())),
+                    },
+                ];
+                fields.extend(record.props.iter().cloned());
+                GameEvent {
+                    name: "entity".to_string(),
+                    fields,
+                    tick: record.tick,
+                }
+            })
             .collect();
+        match series_from_event(&rows, py) {
+            Ok(df) => Ok(df),
+            Err(_e) => Ok(PyList::empty_bound(py).into()),
+        }
+    }
+    /// Where every weapon came from: one row per weapon entity with its purchaser, kills and whether
+    /// it survived into the next round, one row per owner change (purchase, pickup, drop, death_drop)
+    /// and one row per weapon lying on the ground at every tick (or the wanted ticks).
+    #[pyo3(signature = (*, ticks=None))]
+    pub fn parse_weapons(&self, py: Python<'_>, ticks: Option<Vec<i32>>) -> PyResult<Py<PyAny>> {
+        let settings = ParserInputs {
+            real_name_to_og_name: AHashMap::default(),
+            wanted_players: vec![],
+            wanted_player_props: vec![],
+            wanted_other_props: vec![],
+            wanted_prop_states: PropFilter::default(),
+            wanted_events: vec![],
+            parse_ents: true,
+            wanted_ticks: ticks.unwrap_or_default(),
+            parse_projectiles: false,
+            only_header: true,
+            count_props: false,
+            only_convars: false,
+            huffman_lookup_table: &self.huf,
+            order_by_steamid: false,
+            change_log: false,
+            parse_money_ledger: false,
+            parse_bomb: false,
+            parse_spotting: false,
+            parse_kill_context: false,
+            parse_shots: false,
+            parse_sprays: false,
+            parse_usercmds: false,
+            parse_movement: false,
+            parse_weapons: true,
+            wanted_entity_classes: vec![],
+            wanted_entity_props: vec![],
+            entity_event_classes: vec![],
+            wanted_prop_changes: vec![],
+            map_overview: None,
+            nav_mesh: None,
+            map_geometry: None,
+            visibility_pairs: None,
+            fov: None,
+            parallelism: self.parallelism.clone(),
+        };
+        let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
+        let output = match parser.parse_demo(&self.mmap) {
+            Ok(output) => output,
+            Err(e) => return Err(Exception::new_err(format!("{e}"))),
+        };
+        let weapons = &output.weapons;
+        let ground = &output.ground_weapons;
+
+        let entity_ids: Vec<Option<i32>> = weapons.iter().map(|w| Some(w.entity_id)).collect();
+        let weapon_names: Vec<Option<String>> = weapons.iter().map(|w| Some(w.weapon.clone())).collect();
+        let rounds: Vec<Option<i32>> = weapons.iter().map(|w| w.round).collect();
+        let created_ticks: Vec<Option<i32>> = weapons.iter().map(|w| w.created_tick).collect();
+        let deleted_ticks: Vec<Option<i32>> = weapons.iter().map(|w| w.deleted_tick).collect();
+        let purchaser_steamids: Vec<Option<u64>> = weapons.iter().map(|w| w.purchaser_steamid).collect();
+        let purchaser_names: Vec<Option<String>> = weapons.iter().map(|w| w.purchaser_name.clone()).collect();
+        let purchase_ticks: Vec<Option<i32>> = weapons.iter().map(|w| w.purchase_tick).collect();
+        let original_owners: Vec<Option<u64>> = weapons.iter().map(|w| w.original_owner_steamid).collect();
+        let owner_counts: Vec<Option<i32>> = weapons.iter().map(|w| Some(w.owners.len() as i32)).collect();
+        let kills: Vec<Option<i32>> = weapons.iter().map(|w| Some(w.kills)).collect();
+        let survived: Vec<Option<bool>> = weapons.iter().map(|w| w.survived_round).collect();
+
+        let owners = weapons.iter().flat_map(|w| w.owners.iter().map(move |o| (w, o))).collect_vec();
+        let owner_entity_ids: Vec<Option<i32>> = owners.iter().map(|(w, _)| Some(w.entity_id)).collect();
+        let owner_weapons: Vec<Option<String>> = owners.iter().map(|(w, _)| Some(w.weapon.clone())).collect();
+        let owner_ticks: Vec<Option<i32>> = owners.iter().map(|(_, o)| Some(o.tick)).collect();
+        let reasons: Vec<Option<String>> = owners.iter().map(|(_, o)| Some(o.reason.clone())).collect();
+        let owner_steamids: Vec<Option<u64>> = owners.iter().map(|(_, o)| o.steamid).collect();
+        let owner_names: Vec<Option<String>> = owners.iter().map(|(_, o)| o.name.clone()).collect();
+        let owner_xs: Vec<Option<f32>> = owners.iter().map(|(_, o)| o.position.map(|p| p[0])).collect();
+        let owner_ys: Vec<Option<f32>> = owners.iter().map(|(_, o)| o.position.map(|p| p[1])).collect();
+        let owner_zs: Vec<Option<f32>> = owners.iter().map(|(_, o)| o.position.map(|p| p[2])).collect();
+        let owner_kills: Vec<Option<i32>> = owners.iter().map(|(_, o)| Some(o.kills)).collect();
+
+        let ground_ticks: Vec<Option<i32>> = ground.iter().map(|g| Some(g.tick)).collect();
+        let ground_entity_ids: Vec<Option<i32>> = ground.iter().map(|g| Some(g.entity_id)).collect();
+        let ground_weapons: Vec<Option<String>> = ground.iter().map(|g| Some(g.weapon.clone())).collect();
+        let ground_xs: Vec<Option<f32>> = ground.iter().map(|g| Some(g.x)).collect();
+        let ground_ys: Vec<Option<f32>> = ground.iter().map(|g| Some(g.y)).collect();
+        let ground_zs: Vec<Option<f32>> = ground.iter().map(|g| Some(g.z)).collect();
+        let ground_original_owners: Vec<Option<u64>> = ground.iter().map(|g| g.original_owner_steamid).collect();
 
         // SoA form
-        let xs = arr_to_py(Box::new(Float32Array::from(xs))).unwrap();
-        let ys = arr_to_py(Box::new(Float32Array::from(ys))).unwrap();
-        let zs = arr_to_py(Box::new(Float32Array::from(zs))).unwrap();
-        // Actually not sure about Z coordinate. Leave out for now.
-        let ticks = arr_to_py(Box::new(Int32Array::from(ticks))).unwrap();
-        let grenade_type = arr_to_py(Box::new(Utf8Array::<i32>::from(grenade_type))).unwrap();
-        let name = arr_to_py(Box::new(Utf8Array::<i32>::from(name))).unwrap();
-        let steamids = arr_to_py(Box::new(UInt64Array::from(steamid))).unwrap();
-        let entity_ids = arr_to_py(Box::new(Int32Array::from(entity_id))).unwrap();
+        let entity_ids = arr_to_py(Box::new(Int32Array::from(entity_ids)))?;
+        let weapon_names = arr_to_py(Box::new(Utf8Array::<i32>::from(weapon_names)))?;
+        let rounds = arr_to_py(Box::new(Int32Array::from(rounds)))?;
+        let created_ticks = arr_to_py(Box::new(Int32Array::from(created_ticks)))?;
+        let deleted_ticks = arr_to_py(Box::new(Int32Array::from(deleted_ticks)))?;
+        let purchaser_steamids = arr_to_py(Box::new(UInt64Array::from(purchaser_steamids)))?;
+        let purchaser_names = arr_to_py(Box::new(Utf8Array::<i32>::from(purchaser_names)))?;
+        let purchase_ticks = arr_to_py(Box::new(Int32Array::from(purchase_ticks)))?;
+        let original_owners = arr_to_py(Box::new(UInt64Array::from(original_owners)))?;
+        let owner_counts = arr_to_py(Box::new(Int32Array::from(owner_counts)))?;
+        let kills = arr_to_py(Box::new(Int32Array::from(kills)))?;
+        let survived = arr_to_py(Box::new(BooleanArray::from(survived)))?;
+
+        let owner_entity_ids = arr_to_py(Box::new(Int32Array::from(owner_entity_ids)))?;
+        let owner_weapons = arr_to_py(Box::new(Utf8Array::<i32>::from(owner_weapons)))?;
+        let owner_ticks = arr_to_py(Box::new(Int32Array::from(owner_ticks)))?;
+        let reasons = arr_to_py(Box::new(Utf8Array::<i32>::from(reasons)))?;
+        let owner_steamids = arr_to_py(Box::new(UInt64Array::from(owner_steamids)))?;
+        let owner_names = arr_to_py(Box::new(Utf8Array::<i32>::from(owner_names)))?;
+        let owner_xs = arr_to_py(Box::new(Float32Array::from(owner_xs)))?;
+        let owner_ys = arr_to_py(Box::new(Float32Array::from(owner_ys)))?;
+        let owner_zs = arr_to_py(Box::new(Float32Array::from(owner_zs)))?;
+        let owner_kills = arr_to_py(Box::new(Int32Array::from(owner_kills)))?;
+
+        let ground_ticks = arr_to_py(Box::new(Int32Array::from(ground_ticks)))?;
+        let ground_entity_ids = arr_to_py(Box::new(Int32Array::from(ground_entity_ids)))?;
+        let ground_weapons = arr_to_py(Box::new(Utf8Array::<i32>::from(ground_weapons)))?;
+        let ground_xs = arr_to_py(Box::new(Float32Array::from(ground_xs)))?;
+        let ground_ys = arr_to_py(Box::new(Float32Array::from(ground_ys)))?;
+        let ground_zs = arr_to_py(Box::new(Float32Array::from(ground_zs)))?;
+        let ground_original_owners = arr_to_py(Box::new(UInt64Array::from(ground_original_owners)))?;
 
         let polars = py.import_bound("polars")?;
-        let all_series_py =
-            [xs, ys, zs, ticks, steamids, name, grenade_type, entity_ids].to_object(py);
+        let weapon_series_py = [
+            entity_ids,
+            weapon_names,
+            rounds,
+            created_ticks,
+            deleted_ticks,
+            purchaser_steamids,
+            purchaser_names,
+            purchase_ticks,
+            original_owners,
+            owner_counts,
+            kills,
+            survived,
+        ]
+        .to_object(py);
+        let owner_series_py = [
+            owner_entity_ids,
+            owner_weapons,
+            owner_ticks,
+            reasons,
+            owner_steamids,
+            owner_names,
+            owner_xs,
+            owner_ys,
+            owner_zs,
+            owner_kills,
+        ]
+        .to_object(py);
+        let ground_series_py = [
+            ground_ticks,
+            ground_entity_ids,
+            ground_weapons,
+            ground_xs,
+            ground_ys,
+            ground_zs,
+            ground_original_owners,
+        ]
+        .to_object(py);
         Python::with_gil(|py| {
-            let df = polars.call_method1("DataFrame", (all_series_py,))?;
-            // Set column names
-            let column_names = [
-                "X",
-                "Y",
-                "Z",
-                "tick",
-                "thrower_steamid",
-                "name",
-                "grenade_type",
+            let kwargs = vec![("use_pyarrow_extension_array", true)].into_py_dict_bound(py);
+
+            let weapons_df = polars.call_method1("DataFrame", (weapon_series_py,))?;
+            let weapon_column_names = [
                 "entity_id",
+                "weapon",
+                "round",
+                "created_tick",
+                "deleted_tick",
+                "purchaser_steamid",
+                "purchaser_name",
+                "purchase_tick",
+                "original_owner_steamid",
+                "owners",
+                "kills",
+                "survived_round",
             ];
-            df.setattr("columns", column_names.to_object(py)).unwrap();
-            // Call to_pandas with use_pyarrow_extension_array = true
-            let kwargs = vec![("use_pyarrow_extension_array", true)].into_py_dict_bound(py);
-            let pandas_df = df.call_method("to_pandas", (), Some(&kwargs)).unwrap();
-            Ok(pandas_df.to_object(py))
+            weapons_df.setattr("columns", weapon_column_names.to_object(py))?;
+            let weapons_pandas = weapons_df.call_method("to_pandas", (), Some(&kwargs))?;
+
+            let owners_df = polars.call_method1("DataFrame", (owner_series_py,))?;
+            let owner_column_names = ["entity_id", "weapon", "tick", "reason", "steamid", "name", "X", "Y", "Z", "kills"];
+            owners_df.setattr("columns", owner_column_names.to_object(py))?;
+            let owners_pandas = owners_df.call_method("to_pandas", (), Some(&kwargs))?;
+
+            let ground_df = polars.call_method1("DataFrame", (ground_series_py,))?;
+            let ground_column_names = ["tick", "entity_id", "weapon", "X", "Y", "Z", "original_owner_steamid"];
+            ground_df.setattr("columns", ground_column_names.to_object(py))?;
+            let ground_pandas = ground_df.call_method("to_pandas", (), Some(&kwargs))?;
+            Ok((weapons_pandas, owners_pandas, ground_pandas).to_object(py))
         })
     }
     pub fn parse_player_info(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
@@ -227,7 +2274,7 @@ impl DemoParser {
             wanted_players: vec![],
             wanted_player_props: vec![],
             wanted_other_props: vec![],
-            wanted_prop_states: AHashMap::default(),
+            wanted_prop_states: PropFilter::default(),
             wanted_events: vec![],
             parse_ents: false,
             wanted_ticks: vec![],
@@ -237,6 +2284,26 @@ impl DemoParser {
             only_convars: false,
             huffman_lookup_table: &self.huf,
             order_by_steamid: false,
+            change_log: false,
+            parse_money_ledger: false,
+            parse_bomb: false,
+            parse_spotting: false,
+            parse_kill_context: false,
+            parse_shots: false,
+            parse_sprays: false,
+            parse_usercmds: false,
+            parse_movement: false,
+            parse_weapons: false,
+            wanted_entity_classes: vec![],
+            wanted_entity_props: vec![],
+            entity_event_classes: vec![],
+            wanted_prop_changes: vec![],
+            map_overview: None,
+            nav_mesh: None,
+            map_geometry: None,
+            visibility_pairs: None,
+            fov: None,
+            parallelism: self.parallelism.clone(),
         };
         let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
         let output = match parser.parse_demo(&self.mmap) {
@@ -272,7 +2339,7 @@ impl DemoParser {
             wanted_players: vec![],
             wanted_player_props: vec![],
             wanted_other_props: vec![],
-            wanted_prop_states: AHashMap::default(),
+            wanted_prop_states: PropFilter::default(),
             wanted_events: vec![],
             parse_ents: false,
             wanted_ticks: vec![],
@@ -282,6 +2349,26 @@ impl DemoParser {
             only_convars: false,
             huffman_lookup_table: &self.huf,
             order_by_steamid: false,
+            change_log: false,
+            parse_money_ledger: false,
+            parse_bomb: false,
+            parse_spotting: false,
+            parse_kill_context: false,
+            parse_shots: false,
+            parse_sprays: false,
+            parse_usercmds: false,
+            parse_movement: false,
+            parse_weapons: false,
+            wanted_entity_classes: vec![],
+            wanted_entity_props: vec![],
+            entity_event_classes: vec![],
+            wanted_prop_changes: vec![],
+            map_overview: None,
+            nav_mesh: None,
+            map_geometry: None,
+            visibility_pairs: None,
+            fov: None,
+            parallelism: self.parallelism.clone(),
         };
         let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
         let output = match parser.parse_demo(&self.mmap) {
@@ -353,7 +2440,7 @@ impl DemoParser {
             wanted_players: vec![],
             wanted_player_props: vec![],
             wanted_other_props: vec![],
-            wanted_prop_states: AHashMap::default(),
+            wanted_prop_states: PropFilter::default(),
             wanted_events: vec![],
             parse_ents: false,
             wanted_ticks: vec![],
@@ -363,6 +2450,26 @@ impl DemoParser {
             only_convars: false,
             huffman_lookup_table: &self.huf,
             order_by_steamid: false,
+            change_log: false,
+            parse_money_ledger: false,
+            parse_bomb: false,
+            parse_spotting: false,
+            parse_kill_context: false,
+            parse_shots: false,
+            parse_sprays: false,
+            parse_usercmds: false,
+            parse_movement: false,
+            parse_weapons: false,
+            wanted_entity_classes: vec![],
+            wanted_entity_props: vec![],
+            entity_event_classes: vec![],
+            wanted_prop_changes: vec![],
+            map_overview: None,
+            nav_mesh: None,
+            map_geometry: None,
+            visibility_pairs: None,
+            fov: None,
+            parallelism: self.parallelism.clone(),
         };
         let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
         let output = match parser.parse_demo(&self.mmap) {
@@ -418,13 +2525,17 @@ impl DemoParser {
         })
     }
 
-    #[pyo3(signature = (event_name, *, player=None, other=None))]
+    #[pyo3(signature = (event_name, *, player=None, other=None, nav_path=None, kill_context=false, entity_classes=None, prop_changes=None))]
     pub fn parse_event(
         &self,
         py: Python<'_>,
         event_name: String,
         player: Option<Vec<String>>,
         other: Option<Vec<String>>,
+        nav_path: Option<String>,
+        kill_context: bool,
+        entity_classes: Option<Vec<String>>,
+        prop_changes: Option<Vec<String>>,
     ) -> PyResult<Py<PyAny>> {
         let wanted_player_props = player.unwrap_or_default();
         let wanted_other_props = other.unwrap_or_default();
@@ -449,13 +2560,18 @@ impl DemoParser {
             real_name_to_og_name.insert(real_name.clone(), user_friendly_name.clone());
         }
 
+        let nav_mesh = match nav_path.map(|path| NavMesh::from_nav_file(&path)).transpose() {
+            Ok(nav_mesh) => nav_mesh.map(Arc::new),
+            Err(e) => return Err(Exception::new_err(format!("{e}"))),
+        };
+
         let settings = ParserInputs {
             real_name_to_og_name,
             wanted_players: vec![],
             wanted_player_props: real_player_props,
             wanted_other_props: real_other_props,
             wanted_events: vec![event_name],
-            wanted_prop_states: AHashMap::default(),
+            wanted_prop_states: PropFilter::default(),
             parse_ents: true,
             wanted_ticks: vec![],
             parse_projectiles: false,
@@ -464,6 +2580,26 @@ impl DemoParser {
             only_convars: false,
             huffman_lookup_table: &self.huf,
             order_by_steamid: false,
+            change_log: false,
+            parse_money_ledger: false,
+            parse_bomb: false,
+            parse_spotting: false,
+            parse_kill_context: kill_context,
+            parse_shots: false,
+            parse_sprays: false,
+            parse_usercmds: false,
+            parse_movement: false,
+            parse_weapons: false,
+            wanted_entity_classes: vec![],
+            wanted_entity_props: vec![],
+            entity_event_classes: entity_classes.unwrap_or_default(),
+            wanted_prop_changes: prop_changes.unwrap_or_default(),
+            map_overview: None,
+            nav_mesh,
+            map_geometry: None,
+            visibility_pairs: None,
+            fov: None,
+            parallelism: self.parallelism.clone(),
         };
         let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
         let output = match parser.parse_demo(&self.mmap) {
@@ -477,13 +2613,17 @@ impl DemoParser {
         Ok(event_series)
     }
 
-    #[pyo3(signature = (event_name, *, player=None, other=None))]
+    #[pyo3(signature = (event_name, *, player=None, other=None, nav_path=None, kill_context=false, entity_classes=None, prop_changes=None))]
     pub fn parse_events(
         &self,
         py: Python<'_>,
         event_name: Vec<String>,
         player: Option<Vec<String>>,
         other: Option<Vec<String>>,
+        nav_path: Option<String>,
+        kill_context: bool,
+        entity_classes: Option<Vec<String>>,
+        prop_changes: Option<Vec<String>>,
     ) -> PyResult<Py<PyAny>> {
         let wanted_player_props = player.unwrap_or_default();
         let wanted_other_props = other.unwrap_or_default();
@@ -508,13 +2648,18 @@ impl DemoParser {
             real_name_to_og_name.insert(real_name.clone(), user_friendly_name.clone());
         }
 
+        let nav_mesh = match nav_path.map(|path| NavMesh::from_nav_file(&path)).transpose() {
+            Ok(nav_mesh) => nav_mesh.map(Arc::new),
+            Err(e) => return Err(Exception::new_err(format!("{e}"))),
+        };
+
         let settings = ParserInputs {
             real_name_to_og_name,
             wanted_players: vec![],
             wanted_player_props: real_player_props,
             wanted_other_props: real_other_props,
             wanted_events: event_name,
-            wanted_prop_states: AHashMap::default(),
+            wanted_prop_states: PropFilter::default(),
             parse_ents: true,
             wanted_ticks: vec![],
             parse_projectiles: false,
@@ -523,6 +2668,26 @@ impl DemoParser {
             only_convars: false,
             huffman_lookup_table: &self.huf,
             order_by_steamid: false,
+            change_log: false,
+            parse_money_ledger: false,
+            parse_bomb: false,
+            parse_spotting: false,
+            parse_kill_context: kill_context,
+            parse_shots: false,
+            parse_sprays: false,
+            parse_usercmds: false,
+            parse_movement: false,
+            parse_weapons: false,
+            wanted_entity_classes: vec![],
+            wanted_entity_props: vec![],
+            entity_event_classes: entity_classes.unwrap_or_default(),
+            wanted_prop_changes: prop_changes.unwrap_or_default(),
+            map_overview: None,
+            nav_mesh,
+            map_geometry: None,
+            visibility_pairs: None,
+            fov: None,
+            parallelism: self.parallelism.clone(),
         };
         let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
         let output = match parser.parse_demo(&self.mmap) {
@@ -541,7 +2706,7 @@ impl DemoParser {
             wanted_players: vec![],
             wanted_player_props: vec![],
             wanted_other_props: vec![],
-            wanted_prop_states: AHashMap::default(),
+            wanted_prop_states: PropFilter::default(),
             wanted_events: vec![],
             wanted_ticks: vec![],
             real_name_to_og_name: AHashMap::default(),
@@ -552,6 +2717,26 @@ impl DemoParser {
             only_convars: false,
             huffman_lookup_table: &vec![],
             order_by_steamid: false,
+            change_log: false,
+            parse_money_ledger: false,
+            parse_bomb: false,
+            parse_spotting: false,
+            parse_kill_context: false,
+            parse_shots: false,
+            parse_sprays: false,
+            parse_usercmds: false,
+            parse_movement: false,
+            parse_weapons: false,
+            wanted_entity_classes: vec![],
+            wanted_entity_props: vec![],
+            entity_event_classes: vec![],
+            wanted_prop_changes: vec![],
+            map_overview: None,
+            nav_mesh: None,
+            map_geometry: None,
+            visibility_pairs: None,
+            fov: None,
+            parallelism: self.parallelism.clone(),
         };
         let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
         let output = match parser.parse_demo(&self.mmap) {
@@ -567,24 +2752,39 @@ impl DemoParser {
         Ok(out_hm.to_object(py))
     }
 
-    #[pyo3(signature = (wanted_props, *, players=None, ticks=None, prop_states=None))]
+    #[pyo3(signature = (wanted_props, *, players=None, ticks=None, prop_states=None, overview_path=None, nav_path=None, change_log=false))]
     pub fn parse_ticks(
         &self,
         py: Python,
         wanted_props: Vec<String>,
         players: Option<Vec<u64>>,
         ticks: Option<Vec<i32>>,
-        prop_states: Option<Vec<WantedPropState>>,
+        prop_states: Option<PyObject>,
+        overview_path: Option<String>,
+        nav_path: Option<String>,
+        change_log: bool,
     ) -> PyResult<PyObject> {
         let wanted_players = players.unwrap_or_default();
         let wanted_ticks = ticks.unwrap_or_default();
-        let wanted_prop_states = prop_states
-            .unwrap_or_default()
-            .into_iter()
-            .map(|prop| (prop.prop, prop.state.0))
-            .collect();
+        // Either a filter expression or the old list of props that have to equal their state
+        let wanted_prop_states = match prop_states.as_ref().map(|states| states.bind(py)) {
+            None => PropFilter::default(),
+            Some(states) => match states.extract::<String>() {
+                Ok(expression) => match PropFilter::parse(&expression) {
+                    Ok(filter) => filter,
+                    Err(e) => return Err(Exception::new_err(format!("{e}"))),
+                },
+                Err(_) => PropFilter::from(
+                    states
+                        .extract::<Vec<WantedPropState>>()?
+                        .into_iter()
+                        .map(|prop| (prop.prop, prop.state.0))
+                        .collect::<AHashMap<String, Variant>>(),
+                ),
+            },
+        };
         let real_props = rm_user_friendly_names(&wanted_props);
-        let real_wanted_prop_states = rm_map_user_friendly_names(&wanted_prop_states);
+        let real_wanted_prop_states = rm_filter_user_friendly_names(&wanted_prop_states);
 
         let real_props = match real_props {
             Ok(real_props) => real_props,
@@ -595,16 +2795,30 @@ impl DemoParser {
             Err(e) => return Err(Exception::new_err(format!("{e}"))),
         };
 
+        let map_overview = match overview_path {
+            Some(path) => match MapOverview::from_overview_file(&path) {
+                Ok(overview) => Some(overview),
+                Err(e) => return Err(Exception::new_err(format!("{e}"))),
+            },
+            None => None,
+        };
+
+        let nav_mesh = match nav_path.map(|path| NavMesh::from_nav_file(&path)).transpose() {
+            Ok(nav_mesh) => nav_mesh.map(Arc::new),
+            Err(e) => return Err(Exception::new_err(format!("{e}"))),
+        };
+
         let arc_huf = Arc::new(&self.huf);
         let mut real_name_to_og_name = AHashMap::default();
         for (real_name, user_friendly_name) in real_props.iter().zip(&wanted_props) {
             real_name_to_og_name.insert(real_name.clone(), user_friendly_name.clone());
         }
         for (real_name, user_friendly_name) in real_wanted_prop_states
-            .keys()
-            .zip(wanted_prop_states.keys())
+            .prop_names()
+            .into_iter()
+            .zip(wanted_prop_states.prop_names())
         {
-            real_name_to_og_name.insert(real_name.clone(), user_friendly_name.clone());
+            real_name_to_og_name.insert(real_name.to_string(), user_friendly_name.to_string());
         }
 
         let settings = ParserInputs {
@@ -622,136 +2836,152 @@ impl DemoParser {
             only_convars: false,
             huffman_lookup_table: &arc_huf,
             order_by_steamid: false,
+            change_log,
+            parse_money_ledger: false,
+            parse_bomb: false,
+            parse_spotting: false,
+            parse_kill_context: false,
+            parse_shots: false,
+            parse_sprays: false,
+            parse_usercmds: false,
+            parse_movement: false,
+            parse_weapons: false,
+            wanted_entity_classes: vec![],
+            wanted_entity_props: vec![],
+            entity_event_classes: vec![],
+            wanted_prop_changes: vec![],
+            map_overview,
+            nav_mesh,
+            map_geometry: None,
+            visibility_pairs: None,
+            fov: None,
+            parallelism: self.parallelism.clone(),
         };
         let mut parser = Parser::new(settings, parser::parse_demo::ParsingMode::Normal);
         let output = match parser.parse_demo(&self.mmap) {
             Ok(output) => output,
             Err(e) => return Err(Exception::new_err(format!("{e}"))),
         };
-        let mut all_series = vec![];
-        let mut all_pyobjects = vec![];
-        let prop_infos = output.prop_controller.prop_infos;
-        let mut df_column_names_arrow = vec![];
-        let mut df_column_names_py = vec![];
-
-        for prop_info in prop_infos {
-            if output.df.contains_key(&prop_info.id) {
-                match &output.df[&prop_info.id].data {
-                    Some(VarVec::F32(data)) => {
-                        df_column_names_arrow.push(prop_info.prop_friendly_name);
-                        all_series.push(arr_to_py(Box::new(Float32Array::from(data)))?);
-                    }
-                    Some(VarVec::I32(data)) => {
-                        df_column_names_arrow.push(prop_info.prop_friendly_name);
-                        all_series.push(arr_to_py(Box::new(Int32Array::from(data)))?);
-                    }
-                    Some(VarVec::U64(data)) => {
-                        df_column_names_arrow.push(prop_info.prop_friendly_name);
-                        all_series.push(arr_to_py(Box::new(UInt64Array::from(data)))?);
-                    }
-                    Some(VarVec::U32(data)) => {
-                        df_column_names_arrow.push(prop_info.prop_friendly_name);
-                        all_series.push(arr_to_py(Box::new(UInt32Array::from(data)))?);
-                    }
-                    Some(VarVec::Bool(data)) => {
-                        df_column_names_arrow.push(prop_info.prop_friendly_name);
-                        all_series.push(arr_to_py(Box::new(BooleanArray::from(data)))?);
-                    }
-                    Some(VarVec::String(data)) => {
-                        df_column_names_arrow.push(prop_info.prop_friendly_name.clone());
-                        let s = Series::new(&prop_info.prop_friendly_name.clone(), data);
-                        let py_series = rust_series_to_py_series(&s)?;
-                        all_series.push(py_series);
-                    }
-                    Some(VarVec::StringVec(data)) => {
-                        df_column_names_py.push(prop_info.prop_friendly_name);
-                        all_pyobjects.push(data.to_object(py));
-                    }
-                    Some(VarVec::U64Vec(data)) => {
-                        df_column_names_py.push(prop_info.prop_friendly_name);
-                        all_pyobjects.push(data.to_object(py));
-                    }
-                    Some(VarVec::XYZVec(data)) => {
-                        df_column_names_py.push(prop_info.prop_friendly_name);
-                        all_pyobjects.push(data.to_object(py));
-                    }
-                    Some(VarVec::U32Vec(data)) => {
-                        df_column_names_py.push(prop_info.prop_friendly_name);
-                        all_pyobjects.push(data.to_object(py));
-                    }
+        if change_log {
+            let change_log = ChangeLog {
+                inner: output.change_log.unwrap_or_default(),
+                prop_infos: output.prop_controller.prop_infos,
+            };
+            return Ok(Py::new(py, change_log)?.to_object(py));
+        }
+        df_to_pandas(py, &output.df, &output.prop_controller.prop_infos)
+    }
+}
+
+// Builds the parse_ticks DataFrame out of prop columns
+fn df_to_pandas(py: Python, df: &AHashMap<u32, PropColumn>, prop_infos: &[PropInfo]) -> PyResult<PyObject> {
+    let mut all_series = vec![];
+    let mut all_pyobjects = vec![];
+    let mut df_column_names_arrow = vec![];
+    let mut df_column_names_py = vec![];
 
-                    Some(VarVec::Stickers(data)) => {
-                        let mut dicts = vec![];
-                        for weapon in data {
-                            let mut v = vec![];
-                            for sticker in weapon {
-                                let dict = PyDict::new_bound(py);
-                                dict.set_item("id", sticker.id.to_object(py))?;
-                                dict.set_item("name", sticker.name.to_object(py))?;
-                                dict.set_item("wear", sticker.wear.to_object(py))?;
-                                dict.set_item("x", sticker.x.to_object(py))?;
-                                dict.set_item("y", sticker.y.to_object(py))?;
-                                v.push(dict);
-                            }
-                            dicts.push(v);
+    for prop_info in prop_infos {
+        let prop_friendly_name = prop_info.prop_friendly_name.clone();
+        if df.contains_key(&prop_info.id) {
+            match &df[&prop_info.id].data {
+                Some(VarVec::F32(data)) => {
+                    df_column_names_arrow.push(prop_friendly_name);
+                    all_series.push(arr_to_py(Box::new(Float32Array::from(data)))?);
+                }
+                Some(VarVec::I32(data)) => {
+                    df_column_names_arrow.push(prop_friendly_name);
+                    all_series.push(arr_to_py(Box::new(Int32Array::from(data)))?);
+                }
+                Some(VarVec::U64(data)) => {
+                    df_column_names_arrow.push(prop_friendly_name);
+                    all_series.push(arr_to_py(Box::new(UInt64Array::from(data)))?);
+                }
+                Some(VarVec::U32(data)) => {
+                    df_column_names_arrow.push(prop_friendly_name);
+                    all_series.push(arr_to_py(Box::new(UInt32Array::from(data)))?);
+                }
+                Some(VarVec::Bool(data)) => {
+                    df_column_names_arrow.push(prop_friendly_name);
+                    all_series.push(arr_to_py(Box::new(BooleanArray::from(data)))?);
+                }
+                Some(VarVec::String(data)) => {
+                    df_column_names_arrow.push(prop_friendly_name.clone());
+                    let s = Series::new(&prop_friendly_name.clone(), data);
+                    let py_series = rust_series_to_py_series(&s)?;
+                    all_series.push(py_series);
+                }
+                Some(VarVec::StringVec(data)) => {
+                    df_column_names_py.push(prop_friendly_name);
+                    all_pyobjects.push(data.to_object(py));
+                }
+                Some(VarVec::U64Vec(data)) => {
+                    df_column_names_py.push(prop_friendly_name);
+                    all_pyobjects.push(data.to_object(py));
+                }
+                Some(VarVec::XYZVec(data)) => {
+                    df_column_names_py.push(prop_friendly_name);
+                    all_pyobjects.push(data.to_object(py));
+                }
+                Some(VarVec::U32Vec(data)) => {
+                    df_column_names_py.push(prop_friendly_name);
+                    all_pyobjects.push(data.to_object(py));
+                }
+
+                Some(VarVec::Stickers(data)) => {
+                    let mut dicts = vec![];
+                    for weapon in data {
+                        let mut v = vec![];
+                        for sticker in weapon {
+                            let dict = PyDict::new_bound(py);
+                            dict.set_item("id", sticker.id.to_object(py))?;
+                            dict.set_item("name", sticker.name.to_object(py))?;
+                            dict.set_item("wear", sticker.wear.to_object(py))?;
+                            dict.set_item("x", sticker.x.to_object(py))?;
+                            dict.set_item("y", sticker.y.to_object(py))?;
+                            v.push(dict);
                         }
-                        df_column_names_py.push(prop_info.prop_friendly_name);
-                        all_pyobjects.push(dicts.to_object(py));
+                        dicts.push(v);
                     }
+                    df_column_names_py.push(prop_friendly_name);
+                    all_pyobjects.push(dicts.to_object(py));
+                }
 
-                    Some(VarVec::InputHistory(data)) => {
-                        let mut dicts = vec![];
-                        for input in data {
-                            let mut v = vec![];
-                            for sticker in input {
-                                let dict = PyDict::new_bound(py);
-                                dict.set_item("x", sticker.x.to_object(py))?;
-                                dict.set_item("y", sticker.y.to_object(py))?;
-                                dict.set_item("z", sticker.z.to_object(py))?;
-                                dict.set_item(
-                                    "render_tick_count",
-                                    sticker.render_tick_count.to_object(py),
-                                )?;
-                                dict.set_item(
-                                    "render_tick_fraction",
-                                    sticker.render_tick_fraction.to_object(py),
-                                )?;
-                                dict.set_item(
-                                    "player_tick_count",
-                                    sticker.player_tick_count.to_object(py),
-                                )?;
-                                dict.set_item(
-                                    "player_tick_fraction",
-                                    sticker.player_tick_fraction.to_object(py),
-                                )?;
-                                v.push(dict);
-                            }
-                            dicts.push(v);
-                        }
-                        df_column_names_py.push(prop_info.prop_friendly_name);
-                        all_pyobjects.push(dicts.to_object(py));
+                Some(VarVec::InputHistory(data)) => {
+                    let mut dicts = vec![];
+                    for input in data {
+                        dicts.push(input_history_to_py(py, &input)?);
+                    }
+                    df_column_names_py.push(prop_friendly_name);
+                    all_pyobjects.push(dicts.to_object(py));
+                }
+                Some(VarVec::SubtickMoves(data)) => {
+                    let mut dicts = vec![];
+                    for moves in data {
+                        dicts.push(subtick_moves_to_py(py, &moves)?);
                     }
-                    _ => {}
+                    df_column_names_py.push(prop_friendly_name);
+                    all_pyobjects.push(dicts.to_object(py));
                 }
+                _ => {}
             }
         }
-        Python::with_gil(|py| {
-            let polars = py.import_bound("polars")?;
-            let all_series_py = all_series.to_object(py);
-            let df = polars.call_method1("DataFrame", (all_series_py,))?;
-            df.setattr("columns", df_column_names_arrow.to_object(py))?;
-            let pandas_df = df.call_method0("to_pandas")?;
-            for (pyobj, col_name) in all_pyobjects.iter().zip(&df_column_names_py) {
-                pandas_df.call_method1("insert", (0, col_name, pyobj))?;
-            }
-            df_column_names_arrow.extend(df_column_names_py);
-            df_column_names_arrow.sort();
-            let kwargs = vec![("axis", 1)].into_py_dict_bound(py);
-            let args = (df_column_names_arrow,);
-            pandas_df.call_method("reindex", args, Some(&kwargs))?;
-            Ok(pandas_df.to_object(py))
-        })
     }
+    Python::with_gil(|py| {
+        let polars = py.import_bound("polars")?;
+        let all_series_py = all_series.to_object(py);
+        let df = polars.call_method1("DataFrame", (all_series_py,))?;
+        df.setattr("columns", df_column_names_arrow.to_object(py))?;
+        let pandas_df = df.call_method0("to_pandas")?;
+        for (pyobj, col_name) in all_pyobjects.iter().zip(&df_column_names_py) {
+            pandas_df.call_method1("insert", (0, col_name, pyobj))?;
+        }
+        df_column_names_arrow.extend(df_column_names_py);
+        df_column_names_arrow.sort();
+        let kwargs = vec![("axis", 1)].into_py_dict_bound(py);
+        let args = (df_column_names_arrow,);
+        pandas_df.call_method("reindex", args, Some(&kwargs))?;
+        Ok(pandas_df.to_object(py))
+    })
 }
 
 /// <https://github.com/pola-rs/polars/blob/master/examples/python_rust_compiled_function/src/ffi.rs>
@@ -810,10 +3040,138 @@ pub fn arr_to_py(array: Box<dyn Array>) -> PyResult<PyObject> {
         Ok(out.to_object(py))
     })
 }
+/// Sparse parse_ticks output from parse_ticks(..., change_log=True). Every prop of every
+/// player is run-length encoded so values are only kept on the ticks they change.
+#[pyclass]
+struct ChangeLog {
+    inner: parser::second_pass::change_log::ChangeLog,
+    prop_infos: Vec<PropInfo>,
+}
+
+#[pymethods]
+impl ChangeLog {
+    /// One row per value change: tick, steamid, prop and the new value
+    pub fn changes(&self, py: Python) -> PyResult<PyObject> {
+        let names: AHashMap<u32, String> = self
+            .prop_infos
+            .iter()
+            .map(|prop_info| (prop_info.id, prop_info.prop_friendly_name.clone()))
+            .collect();
+        let changes = self.inner.changes();
+        let ticks = changes.iter().map(|change| Some(change.tick)).collect_vec();
+        let steamids = changes.iter().map(|change| Some(change.steamid)).collect_vec();
+        let props = changes
+            .iter()
+            .map(|change| names.get(&change.prop_id).cloned())
+            .collect_vec();
+        let values = changes
+            .iter()
+            .map(|change| variant_to_py(&change.value, py))
+            .collect_vec();
+
+        let polars = py.import_bound("polars")?;
+        let all_series = vec![
+            arr_to_py(Box::new(Int32Array::from(ticks)))?,
+            arr_to_py(Box::new(UInt64Array::from(steamids)))?,
+            rust_series_to_py_series(&Series::new("prop", props))?,
+        ];
+        let df = polars.call_method1("DataFrame", (all_series.to_object(py),))?;
+        df.setattr("columns", vec!["tick", "steamid", "prop"].to_object(py))?;
+        let pandas_df = df.call_method0("to_pandas")?;
+        pandas_df.call_method1("insert", (3, "value", values.to_object(py)))?;
+        Ok(pandas_df.to_object(py))
+    }
+    /// Rebuilds the dense parse_ticks DataFrame, for every tick or only the given ones
+    #[pyo3(signature = (ticks=None))]
+    pub fn to_dense(&self, py: Python, ticks: Option<Vec<i32>>) -> PyResult<PyObject> {
+        let df = self.inner.to_df(&ticks.unwrap_or_default());
+        df_to_pandas(py, &df, &self.prop_infos)
+    }
+}
+
 #[pyclass]
 struct DemoParser {
     mmap: Mmap,
     huf: Vec<(u8, u8)>,
+    parallelism: ParallelismSettings,
+}
+
+fn interpolation_to_py(py: Python, info: &Option<InterpolationInfo>) -> PyResult<PyObject> {
+    match info {
+        Some(info) => {
+            let dict = PyDict::new_bound(py);
+            dict.set_item("src_tick", info.src_tick.to_object(py))?;
+            dict.set_item("dst_tick", info.dst_tick.to_object(py))?;
+            dict.set_item("frac", info.frac.to_object(py))?;
+            Ok(dict.to_object(py))
+        }
+        None => Ok(py.None()),
+    }
+}
+fn input_history_to_py<'py>(
+    py: Python<'py>,
+    history: &[InputHistory],
+) -> PyResult<Vec<Bound<'py, PyDict>>> {
+    let mut v = vec![];
+    for input in history {
+        let dict = PyDict::new_bound(py);
+        dict.set_item("x", input.x.to_object(py))?;
+        dict.set_item("y", input.y.to_object(py))?;
+        dict.set_item("z", input.z.to_object(py))?;
+        dict.set_item("render_tick_count", input.render_tick_count.to_object(py))?;
+        dict.set_item(
+            "render_tick_fraction",
+            input.render_tick_fraction.to_object(py),
+        )?;
+        dict.set_item("player_tick_count", input.player_tick_count.to_object(py))?;
+        dict.set_item(
+            "player_tick_fraction",
+            input.player_tick_fraction.to_object(py),
+        )?;
+        dict.set_item("cl_interp", interpolation_to_py(py, &input.cl_interp)?)?;
+        dict.set_item("sv_interp0", interpolation_to_py(py, &input.sv_interp0)?)?;
+        dict.set_item("sv_interp1", interpolation_to_py(py, &input.sv_interp1)?)?;
+        dict.set_item(
+            "player_interp",
+            interpolation_to_py(py, &input.player_interp)?,
+        )?;
+        dict.set_item("frame_number", input.frame_number.to_object(py))?;
+        dict.set_item("target_ent_index", input.target_ent_index.to_object(py))?;
+        dict.set_item("shoot_position", input.shoot_position.to_object(py))?;
+        dict.set_item(
+            "target_head_pos_check",
+            input.target_head_pos_check.to_object(py),
+        )?;
+        dict.set_item(
+            "target_abs_pos_check",
+            input.target_abs_pos_check.to_object(py),
+        )?;
+        dict.set_item(
+            "target_abs_ang_check",
+            input.target_abs_ang_check.to_object(py),
+        )?;
+        v.push(dict);
+    }
+    Ok(v)
+}
+fn subtick_moves_to_py<'py>(
+    py: Python<'py>,
+    moves: &[SubtickMove],
+) -> PyResult<Vec<Bound<'py, PyDict>>> {
+    let mut v = vec![];
+    for step in moves {
+        let dict = PyDict::new_bound(py);
+        dict.set_item("button", step.button.to_object(py))?;
+        dict.set_item("pressed", step.pressed.to_object(py))?;
+        dict.set_item("when", step.when.to_object(py))?;
+        dict.set_item(
+            "analog_forward_delta",
+            step.analog_forward_delta.to_object(py),
+        )?;
+        dict.set_item("analog_left_delta", step.analog_left_delta.to_object(py))?;
+        v.push(dict);
+    }
+    Ok(v)
 }
 
 pub fn series_from_multiple_events(
@@ -992,6 +3350,17 @@ fn to_py_string_col(pairs: &Vec<&EventField>, _name: &str, py: Python) -> DataFr
     }
     DataFrameColumn::Pyany(v.to_object(py))
 }
+fn to_py_vec_col(pairs: &Vec<&EventField>, _name: &str, py: Python) -> DataFrameColumn {
+    let mut v = vec![];
+    for pair in pairs {
+        match &pair.data {
+            Some(Variant::VecXY(val)) => v.push(Some(val.to_vec())),
+            Some(Variant::VecXYZ(val)) => v.push(Some(val.to_vec())),
+            _ => v.push(None),
+        }
+    }
+    DataFrameColumn::Pyany(v.to_object(py))
+}
 fn to_py_u64_col(pairs: &Vec<&EventField>, _name: &str, py: Python) -> DataFrameColumn {
     let mut v = vec![];
     for pair in pairs {
@@ -1071,6 +3440,10 @@ pub fn column_from_pairs(
     py: Python,
 ) -> Result<DataFrameColumn, DemoParserError> {
     let field_type = find_type_of_vals(pairs)?;
+    // prop_changed events put values of every watched prop in the same columns
+    if has_mixed_types(pairs) {
+        return Ok(to_py_mixed_col(pairs, py));
+    }
 
     let s = match field_type {
         None => to_null_series(pairs, name),
@@ -1084,10 +3457,38 @@ pub fn column_from_pairs(
         Some(Variant::U64Vec(_)) => to_py_u64_col(pairs, name, py),
         Some(Variant::U32Vec(_)) => to_py_u32_col(pairs, name, py),
         Some(Variant::Stickers(_)) => to_py_sticker_col(pairs, name, py),
+        Some(Variant::VecXY(_)) | Some(Variant::VecXYZ(_)) => to_py_vec_col(pairs, name, py),
         _ => panic!("unkown ge key: {field_type:?}"),
     };
     Ok(s)
 }
+fn has_mixed_types(pairs: &Vec<&EventField>) -> bool {
+    let mut types = pairs.iter().filter_map(|pair| pair.data.as_ref().map(std::mem::discriminant));
+    match types.next() {
+        Some(first) => types.any(|t| t != first),
+        None => false,
+    }
+}
+fn to_py_mixed_col(pairs: &Vec<&EventField>, py: Python) -> DataFrameColumn {
+    let v = pairs.iter().map(|pair| variant_to_py(&pair.data, py)).collect_vec();
+    DataFrameColumn::Pyany(v.to_object(py))
+}
+fn variant_to_py(val: &Option<Variant>, py: Python) -> PyObject {
+    match val {
+        Some(Variant::Bool(val)) => val.to_object(py),
+        Some(Variant::I32(val)) => val.to_object(py),
+        Some(Variant::U32(val)) => val.to_object(py),
+        Some(Variant::U64(val)) => val.to_object(py),
+        Some(Variant::F32(val)) => val.to_object(py),
+        Some(Variant::String(val)) => val.to_object(py),
+        Some(Variant::VecXY(val)) => val.to_vec().to_object(py),
+        Some(Variant::VecXYZ(val)) => val.to_vec().to_object(py),
+        Some(Variant::StringVec(val)) => val.to_object(py),
+        Some(Variant::U64Vec(val)) => val.to_object(py),
+        Some(Variant::U32Vec(val)) => val.to_object(py),
+        _ => py.None(),
+    }
+}
 fn find_type_of_vals(pairs: &Vec<&EventField>) -> Result<Option<Variant>, DemoParserError> {
     // Need to find the correct type for outgoing series,
     let mut all_types = vec![];
@@ -1103,6 +3504,8 @@ fn find_type_of_vals(pairs: &Vec<&EventField>) -> Result<Option<Variant>, DemoPa
             Some(Variant::U64Vec(_u)) => Some(Variant::U64Vec(vec![])),
             Some(Variant::U32Vec(_u)) => Some(Variant::U64Vec(vec![])),
             Some(Variant::Stickers(_u)) => Some(Variant::Stickers(vec![])),
+            Some(Variant::VecXY(v)) => Some(Variant::VecXY(*v)),
+            Some(Variant::VecXYZ(v)) => Some(Variant::VecXYZ(*v)),
             None => None,
             _ => {
                 return Err(DemoParserError::UnknownGameEventVariant(pair.name.clone()));
@@ -1120,5 +3523,6 @@ fn find_type_of_vals(pairs: &Vec<&EventField>) -> Result<Option<Variant>, DemoPa
 #[pymodule]
 fn demoparser2(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<DemoParser>()?;
+    m.add_class::<ChangeLog>()?;
     Ok(())
 }
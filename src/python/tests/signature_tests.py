@@ -135,6 +135,7 @@ class SignatureTest(TestCase):
         parser.parse_ticks(["X", "Y"], players=None, ticks=None)
         parser.parse_ticks(["X", "Y"], players=[], ticks=[])
         parser.parse_ticks(["X", "Y"], prop_states=[WantedPropState("is_alive", True), WantedPropState("is_bomb_planted", True)])
+        parser.parse_ticks(["X", "Y"], prop_states="is_alive == true AND health BETWEEN 1 AND 30 OR is_bomb_planted == true")
 
         with self.assertRaises(TypeError):
             parser.parse_ticks(["X", "Y"], players=5, ticks=None)
@@ -1,5 +1,8 @@
+use parser::first_pass::parser_settings::rm_filter_user_friendly_names;
 use parser::first_pass::parser_settings::rm_user_friendly_names;
+use parser::first_pass::parser_settings::ParallelismSettings;
 use parser::first_pass::parser_settings::ParserInputs;
+use parser::first_pass::prop_filter::PropFilter;
 use parser::parse_demo::Parser;
 use parser::parse_demo::ParsingMode::ForceSingleThreaded;
 use parser::second_pass::parser_settings::create_huffman_lookup_table;
@@ -17,6 +20,7 @@ pub fn parseEvent(
     event_name: Option<String>,
     wanted_player_props: Option<Vec<JsValue>>,
     wanted_other_props: Option<Vec<JsValue>>,
+    kill_context: Option<bool>,
 ) -> Result<JsValue, JsError> {
     let player_props = match wanted_player_props {
         Some(p) => p.iter().map(|s| s.as_string().unwrap()).collect::<Vec<_>>(),
@@ -51,12 +55,33 @@ pub fn parseEvent(
         wanted_events: vec![event_name.unwrap_or("none".to_string())],
         parse_ents: true,
         wanted_ticks: vec![],
+        wanted_prop_states: PropFilter::default(),
         parse_projectiles: false,
         only_header: false,
         count_props: false,
         only_convars: false,
         huffman_lookup_table: &arc_huf,
         order_by_steamid: false,
+        change_log: false,
+        parse_money_ledger: false,
+        parse_bomb: false,
+        parse_spotting: false,
+        parse_kill_context: kill_context.unwrap_or(false),
+        parse_shots: false,
+        parse_sprays: false,
+        parse_usercmds: false,
+        parse_movement: false,
+        parse_weapons: false,
+        wanted_entity_classes: vec![],
+        wanted_entity_props: vec![],
+        entity_event_classes: vec![],
+        wanted_prop_changes: vec![],
+        map_overview: None,
+        nav_mesh: None,
+        map_geometry: None,
+        visibility_pairs: None,
+        fov: None,
+        parallelism: ParallelismSettings::default(),
     };
     let mut parser = Parser::new(settings, ForceSingleThreaded);
 
@@ -75,6 +100,7 @@ pub fn parseEvents(
     event_names: Option<Vec<JsValue>>,
     wanted_player_props: Option<Vec<JsValue>>,
     wanted_other_props: Option<Vec<JsValue>>,
+    kill_context: Option<bool>,
 ) -> Result<JsValue, JsError> {
     let event_names = match event_names {
         Some(p) => p.iter().map(|s| s.as_string().unwrap()).collect::<Vec<_>>(),
@@ -113,12 +139,33 @@ pub fn parseEvents(
         wanted_events: event_names,
         parse_ents: true,
         wanted_ticks: vec![],
+        wanted_prop_states: PropFilter::default(),
         parse_projectiles: false,
         only_header: false,
         count_props: false,
         only_convars: false,
         huffman_lookup_table: &arc_huf,
         order_by_steamid: false,
+        change_log: false,
+        parse_money_ledger: false,
+        parse_bomb: false,
+        parse_spotting: false,
+        parse_kill_context: kill_context.unwrap_or(false),
+        parse_shots: false,
+        parse_sprays: false,
+        parse_usercmds: false,
+        parse_movement: false,
+        parse_weapons: false,
+        wanted_entity_classes: vec![],
+        wanted_entity_props: vec![],
+        entity_event_classes: vec![],
+        wanted_prop_changes: vec![],
+        map_overview: None,
+        nav_mesh: None,
+        map_geometry: None,
+        visibility_pairs: None,
+        fov: None,
+        parallelism: ParallelismSettings::default(),
     };
     let mut parser = Parser::new(settings, ForceSingleThreaded);
 
@@ -143,12 +190,33 @@ pub fn listGameEvents(fileBytes: Vec<u8>) -> Result<JsValue, JsError> {
         wanted_events: vec!["all".to_string()],
         parse_ents: false,
         wanted_ticks: vec![],
+        wanted_prop_states: PropFilter::default(),
         parse_projectiles: false,
         only_header: false,
         count_props: false,
         only_convars: false,
         huffman_lookup_table: &arc_huf.clone(),
         order_by_steamid: false,
+        change_log: false,
+        parse_money_ledger: false,
+        parse_bomb: false,
+        parse_spotting: false,
+        parse_kill_context: false,
+        parse_shots: false,
+        parse_sprays: false,
+        parse_usercmds: false,
+        parse_movement: false,
+        parse_weapons: false,
+        wanted_entity_classes: vec![],
+        wanted_entity_props: vec![],
+        entity_event_classes: vec![],
+        wanted_prop_changes: vec![],
+        map_overview: None,
+        nav_mesh: None,
+        map_geometry: None,
+        visibility_pairs: None,
+        fov: None,
+        parallelism: ParallelismSettings::default(),
     };
     let mut parser = Parser::new(settings, ForceSingleThreaded);
 
@@ -170,6 +238,7 @@ pub fn parseTicks(
     wanted_ticks: Option<Vec<i32>>,
     wanted_players: Option<Vec<JsValue>>,
     struct_of_arrays: Option<bool>,
+    prop_states: Option<String>,
 ) -> Result<JsValue, JsError> {
     let wanted_props = match wanted_props {
         Some(p) => p.iter().map(|s| s.as_string().unwrap()).collect::<Vec<_>>(),
@@ -191,6 +260,20 @@ pub fn parseTicks(
     for (real_name, user_friendly_name) in real_names.iter().zip(&wanted_props) {
         real_name_to_og_name.insert(real_name.clone(), user_friendly_name.clone());
     }
+    let wanted_prop_states = match prop_states {
+        Some(expression) => match PropFilter::parse(&expression) {
+            Ok(filter) => filter,
+            Err(e) => return Err(JsError::new(&format!("{}", e))),
+        },
+        None => PropFilter::default(),
+    };
+    let real_wanted_prop_states = match rm_filter_user_friendly_names(&wanted_prop_states) {
+        Ok(filter) => filter,
+        Err(e) => return Err(JsError::new(&format!("{}", e))),
+    };
+    for (real_name, user_friendly_name) in real_wanted_prop_states.prop_names().into_iter().zip(wanted_prop_states.prop_names()) {
+        real_name_to_og_name.insert(real_name.to_string(), user_friendly_name.to_string());
+    }
     let wanted_ticks = match wanted_ticks {
         Some(t) => t,
         None => vec![],
@@ -203,12 +286,33 @@ pub fn parseTicks(
         wanted_events: vec![],
         parse_ents: true,
         wanted_ticks: wanted_ticks,
+        wanted_prop_states: real_wanted_prop_states,
         parse_projectiles: false,
         only_header: false,
         count_props: false,
         only_convars: false,
         huffman_lookup_table: &arc_huf.clone(),
         order_by_steamid: false,
+        change_log: false,
+        parse_money_ledger: false,
+        parse_bomb: false,
+        parse_spotting: false,
+        parse_kill_context: false,
+        parse_shots: false,
+        parse_sprays: false,
+        parse_usercmds: false,
+        parse_movement: false,
+        parse_weapons: false,
+        wanted_entity_classes: vec![],
+        wanted_entity_props: vec![],
+        entity_event_classes: vec![],
+        wanted_prop_changes: vec![],
+        map_overview: None,
+        nav_mesh: None,
+        map_geometry: None,
+        visibility_pairs: None,
+        fov: None,
+        parallelism: ParallelismSettings::default(),
     };
     let mut parser = Parser::new(settings, ForceSingleThreaded);
 
@@ -257,12 +361,33 @@ pub fn parseGrenades(file: Vec<u8>) -> Result<JsValue, JsError> {
         wanted_events: vec![],
         parse_ents: true,
         wanted_ticks: vec![],
+        wanted_prop_states: PropFilter::default(),
         parse_projectiles: true,
         only_header: true,
         count_props: false,
         only_convars: false,
         huffman_lookup_table: &arc_huf.clone(),
         order_by_steamid: false,
+        change_log: false,
+        parse_money_ledger: false,
+        parse_bomb: false,
+        parse_spotting: false,
+        parse_kill_context: false,
+        parse_shots: false,
+        parse_sprays: false,
+        parse_usercmds: false,
+        parse_movement: false,
+        parse_weapons: false,
+        wanted_entity_classes: vec![],
+        wanted_entity_props: vec![],
+        entity_event_classes: vec![],
+        wanted_prop_changes: vec![],
+        map_overview: None,
+        nav_mesh: None,
+        map_geometry: None,
+        visibility_pairs: None,
+        fov: None,
+        parallelism: ParallelismSettings::default(),
     };
     let mut parser = Parser::new(settings, ForceSingleThreaded);
 
@@ -289,12 +414,33 @@ pub fn parseHeader(file: Vec<u8>) -> Result<JsValue, JsError> {
         wanted_events: vec![],
         parse_ents: false,
         wanted_ticks: vec![],
+        wanted_prop_states: PropFilter::default(),
         parse_projectiles: true,
         only_header: true,
         count_props: false,
         only_convars: false,
         huffman_lookup_table: &arc_huf.clone(),
         order_by_steamid: false,
+        change_log: false,
+        parse_money_ledger: false,
+        parse_bomb: false,
+        parse_spotting: false,
+        parse_kill_context: false,
+        parse_shots: false,
+        parse_sprays: false,
+        parse_usercmds: false,
+        parse_movement: false,
+        parse_weapons: false,
+        wanted_entity_classes: vec![],
+        wanted_entity_props: vec![],
+        entity_event_classes: vec![],
+        wanted_prop_changes: vec![],
+        map_overview: None,
+        nav_mesh: None,
+        map_geometry: None,
+        visibility_pairs: None,
+        fov: None,
+        parallelism: ParallelismSettings::default(),
     };
     let mut parser = Parser::new(settings, ForceSingleThreaded);
     let output = match parser.parse_demo(&file) {